@@ -16,6 +16,14 @@ complete -c hx -l hsplit -d "Splits all given files horizontally"
 complete -c hx -s c -l config -r -d "Specifies a file to use for config"
 complete -c hx -l log -r -d "Specifies a file to use for logging"
 complete -c hx -s w -l working-dir -d "Specify initial working directory" -xa "(__fish_complete_directories)"
+complete -c hx -l project -r -d "Open a registered project by name or path"
+complete -c hx -l list-projects -d "Print the registered projects"
+complete -c hx -l format -x -a "tsv json" -d "Output format of --list-projects"
+complete -c hx -l scan-projects -d "Register the git repositories within a directory" -xa "(__fish_complete_directories)"
+complete -c hx -l max-depth -x -d "Directory depth of --scan-projects"
+complete -c hx -l exclude -x -d "Directory name skipped by --scan-projects"
+complete -c hx -l max-repos -x -d "Maximum number of repositories found by --scan-projects"
+complete -c hx -l dry-run -d "Print what --scan-projects would register"
 
 function __hx_langs_ops
     hx --health all-languages | tail -n '+2' | string replace -fr '^(\S+) .*' '$1'
@@ -6,7 +6,7 @@
 use crate::{
     chars::{char_is_line_ending, char_is_whitespace},
     graphemes::{grapheme_width, tab_width_at},
-    syntax::{self, config::IndentationHeuristic},
+    syntax::{self, config::IndentationHeuristic, QueryDeadline},
     tree_sitter::{
         self,
         query::{InvalidPredicateError, UserPredicate},
@@ -645,8 +645,12 @@ fn query_indents<'a>(
         RopeInput::new(text),
     );
 
+    let mut deadline = QueryDeadline::new("indents");
     // Iterate over all captures from the query
-    while let Some(m) = cursor.next_match() {
+    while let Some(m) = deadline.time(|| cursor.next_match()) {
+        if deadline.is_exceeded() {
+            break;
+        }
         // Skip matches where not all custom predicates are fulfilled
         if query
             .predicates
@@ -112,6 +112,61 @@ pub fn from_rope_slice(g: &RopeSlice) -> Option<LineEnding> {
             None
         }
     }
+
+    /// Detects the line ending used by most of the first 1000 lines of the passed document.
+    /// Only CRLF, LF and CR are counted as the other line endings are special-use only.
+    /// Returns `None` if none of these line endings are found.
+    pub fn detect(doc: &Rope) -> Option<DetectedLineEnding> {
+        const SAMPLE_SIZE: usize = 1000;
+
+        let mut counts = [
+            (LineEnding::Crlf, 0),
+            (LineEnding::LF, 0),
+            #[cfg(feature = "unicode-lines")]
+            (LineEnding::CR, 0),
+        ];
+        let mut sampled = 0;
+        for line in doc.lines() {
+            let Some(ending) = get_line_ending(&line) else {
+                continue;
+            };
+            if let Some((_, count)) = counts.iter_mut().find(|(le, _)| *le == ending) {
+                *count += 1;
+                sampled += 1;
+                if sampled == SAMPLE_SIZE {
+                    break;
+                }
+            }
+        }
+
+        let (majority, max) = counts.into_iter().max_by_key(|(_, count)| *count)?;
+        if max == 0 {
+            None
+        } else if counts.iter().filter(|(_, count)| *count == max).count() > 1 {
+            Some(DetectedLineEnding::Mixed)
+        } else {
+            Some(DetectedLineEnding::Majority(majority))
+        }
+    }
+}
+
+/// The result of [`LineEnding::detect`].
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DetectedLineEnding {
+    /// The line ending used by most lines.
+    Majority(LineEnding),
+    /// Several line endings are used equally often.
+    Mixed,
+}
+
+impl DetectedLineEnding {
+    /// Returns the majority line ending, if there is one.
+    pub fn majority(self) -> Option<LineEnding> {
+        match self {
+            Self::Majority(line_ending) => Some(line_ending),
+            Self::Mixed => None,
+        }
+    }
 }
 
 #[inline]
@@ -260,6 +315,59 @@ fn line_ending_autodetect() {
         assert_eq!(auto_detect_line_ending(&Rope::from_str("a formfeed\u{000C} with a\u{000C} carriage return linefeed\u{000D}\u{000A} and a linefeed\u{000A}")), Some(LineEnding::Crlf));
     }
 
+    #[test]
+    fn line_ending_detect() {
+        let detect = |text: &str| LineEnding::detect(&Rope::from_str(text));
+
+        assert_eq!(detect(""), None);
+        assert_eq!(detect("hello"), None);
+        assert_eq!(detect("a formfeed\u{000C}"), None);
+        assert_eq!(
+            detect("\n"),
+            Some(DetectedLineEnding::Majority(LineEnding::LF))
+        );
+        assert_eq!(
+            detect("\r\n"),
+            Some(DetectedLineEnding::Majority(LineEnding::Crlf))
+        );
+        // The first line ending doesn't decide the result.
+        assert_eq!(
+            detect("a\r\nb\nc\nd\r\ne\n"),
+            Some(DetectedLineEnding::Majority(LineEnding::LF))
+        );
+        assert_eq!(
+            detect("a\nb\r\nc\r\nd\ne\r\n"),
+            Some(DetectedLineEnding::Majority(LineEnding::Crlf))
+        );
+        assert_eq!(detect("hello\nhelix\r\n"), Some(DetectedLineEnding::Mixed));
+        assert_eq!(
+            detect("a\u{000C}b\u{000C}c\r\n"),
+            Some(DetectedLineEnding::Majority(LineEnding::Crlf))
+        );
+        #[cfg(feature = "unicode-lines")]
+        {
+            assert_eq!(
+                detect("a\rb\rc\n"),
+                Some(DetectedLineEnding::Majority(LineEnding::CR))
+            );
+            assert_eq!(detect("a\rb\nc\r\n"), Some(DetectedLineEnding::Mixed));
+        }
+
+        // Only the first 1000 line endings are sampled.
+        let text = format!("{}{}", "a\r\n".repeat(1000), "b\n".repeat(2000));
+        assert_eq!(
+            detect(&text),
+            Some(DetectedLineEnding::Majority(LineEnding::Crlf))
+        );
+        let text = format!("{}{}", "a\r\n".repeat(499), "b\n".repeat(2000));
+        assert_eq!(
+            detect(&text),
+            Some(DetectedLineEnding::Majority(LineEnding::LF))
+        );
+        let text = format!("{}{}", "a\r\n".repeat(500), "b\n".repeat(2000));
+        assert_eq!(detect(&text), Some(DetectedLineEnding::Mixed));
+    }
+
     #[test]
     fn str_to_line_ending() {
         #[cfg(feature = "unicode-lines")]
@@ -1,5 +1,6 @@
 use std::{borrow::Cow, cmp::Reverse, iter};
 
+use imara_diff::Hunk;
 use ropey::iter::Chars;
 
 use crate::{
@@ -693,6 +694,39 @@ pub fn move_parent_node_end(
     })
 }
 
+/// Returns the start of the first line changed by the next hunk after the line of `pos`.
+///
+/// `hunks` are the hunks of a diff against the document `text`, ordered by their position in
+/// `text`. If there is no hunk after `pos`, the first hunk is used when `wrap_around` is set and
+/// `pos` is returned unchanged otherwise.
+pub fn goto_next_change(text: RopeSlice, hunks: &[Hunk], pos: usize, wrap_around: bool) -> usize {
+    let line = text.char_to_line(pos) as u32;
+    let hunk = hunks
+        .iter()
+        .find(|hunk| hunk.after.start > line)
+        .or_else(|| hunks.first().filter(|_| wrap_around));
+    hunk.map_or(pos, |hunk| text.line_to_char(hunk.after.start as usize))
+}
+
+/// Returns the start of the first line changed by the previous hunk before the line of `pos`,
+/// see [goto_next_change]. The last hunk is used when there is none and `wrap_around` is set.
+pub fn goto_prev_change(text: RopeSlice, hunks: &[Hunk], pos: usize, wrap_around: bool) -> usize {
+    let line = text.char_to_line(pos) as u32;
+    let hunk = hunks
+        .iter()
+        .rev()
+        .find(|hunk| {
+            // Removals are empty but shown on the line after them, which they are part of.
+            if hunk.after.is_empty() {
+                hunk.after.start < line
+            } else {
+                hunk.after.end <= line
+            }
+        })
+        .or_else(|| hunks.last().filter(|_| wrap_around));
+    hunk.map_or(pos, |hunk| text.line_to_char(hunk.after.start as usize))
+}
+
 #[cfg(test)]
 mod test {
     use ropey::Rope;
@@ -835,6 +869,39 @@ fn selection_extending_moves_in_single_line_text() {
         }
     }
 
+    #[test]
+    fn vertical_moves_through_soft_wrapped_lines() {
+        let text = Rope::from("alpha beta gamma delta\nshort\n");
+        let slice = text.slice(..);
+        let text_fmt = TextFormat {
+            soft_wrap: true,
+            viewport_width: 12,
+            wrap_indicator: "".into(),
+            ..TextFormat::default()
+        };
+        // The first line wraps after "alpha beta ", so "gamma delta" starts at column 11.
+        let mut range = Range::point(pos_at_coords(slice, (0, 2).into(), true));
+        let moves_and_expected_coordinates = [
+            ((Direction::Forward, 1), (0, 13)), // ga|mma delta
+            ((Direction::Forward, 1), (1, 2)),  // sh|ort
+            ((Direction::Backward, 2), (0, 2)), // al|pha beta
+            ((Direction::Forward, 2), (1, 2)),  // sh|ort
+            ((Direction::Backward, 1), (0, 13)),
+        ];
+        for ((direction, count), coordinates) in moves_and_expected_coordinates {
+            range = move_vertically_visual(
+                slice,
+                range,
+                direction,
+                count,
+                Movement::Move,
+                &text_fmt,
+                &mut TextAnnotations::default(),
+            );
+            assert_eq!(coords_at_pos(slice, range.head), coordinates.into());
+        }
+    }
+
     #[test]
     fn vertical_moves_in_single_column() {
         let text = Rope::from(MULTILINE_SAMPLE);
@@ -2194,4 +2261,71 @@ fn test_behaviour_when_moving_to_next_paragraph_extend() {
             assert_eq!(actual, expected, "\nbefore: `{:?}`", before);
         }
     }
+
+    fn hunk(before: std::ops::Range<u32>, after: std::ops::Range<u32>) -> Hunk {
+        Hunk { before, after }
+    }
+
+    #[test]
+    fn test_goto_change() {
+        let text = Rope::from(
+            "zero
+one
+two
+three
+four
+five
+six
+",
+        );
+        let text = text.slice(..);
+        // A modification of line 1, a removal before line 3 and an addition of lines 4 and 5.
+        let hunks = [hunk(1..2, 1..2), hunk(3..4, 3..3), hunk(4..4, 4..6)];
+        let line = |line| text.line_to_char(line);
+
+        assert_eq!(goto_next_change(text, &hunks, 0, false), line(1));
+        assert_eq!(goto_next_change(text, &hunks, line(1) + 2, false), line(3));
+        assert_eq!(goto_next_change(text, &hunks, line(3), false), line(4));
+        assert_eq!(goto_next_change(text, &hunks, line(4), false), line(4));
+        assert_eq!(goto_next_change(text, &hunks, line(6), false), line(6));
+
+        assert_eq!(goto_prev_change(text, &hunks, line(6), false), line(4));
+        // Inside the addition, and on the line of the removal.
+        assert_eq!(goto_prev_change(text, &hunks, line(5), false), line(3));
+        assert_eq!(goto_prev_change(text, &hunks, line(3), false), line(1));
+        assert_eq!(goto_prev_change(text, &hunks, line(2), false), line(1));
+        assert_eq!(
+            goto_prev_change(text, &hunks, line(1) + 1, false),
+            line(1) + 1
+        );
+        assert_eq!(goto_prev_change(text, &hunks, 0, false), 0);
+    }
+
+    #[test]
+    fn test_goto_change_wrap_around() {
+        let text = Rope::from(
+            "zero
+one
+two
+three
+four
+five
+six
+",
+        );
+        let text = text.slice(..);
+        let hunks = [hunk(1..2, 1..2), hunk(3..4, 3..3), hunk(4..4, 4..6)];
+        let line = |line| text.line_to_char(line);
+
+        assert_eq!(goto_next_change(text, &hunks, line(4), true), line(1));
+        assert_eq!(goto_next_change(text, &hunks, line(6), true), line(1));
+        assert_eq!(goto_next_change(text, &hunks, 0, true), line(1));
+        assert_eq!(goto_prev_change(text, &hunks, line(1), true), line(4));
+        assert_eq!(goto_prev_change(text, &hunks, 0, true), line(4));
+        assert_eq!(goto_prev_change(text, &hunks, line(6), true), line(4));
+
+        // Without hunks there is nowhere to go.
+        assert_eq!(goto_next_change(text, &[], line(2), true), line(2));
+        assert_eq!(goto_prev_change(text, &[], line(2), true), line(2));
+    }
 }
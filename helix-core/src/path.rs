@@ -0,0 +1,79 @@
+//! Functions for displaying paths compactly.
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf, MAIN_SEPARATOR_STR},
+};
+
+use helix_stdx::{env::current_working_dir, path::normalize};
+
+/// Replaces the longest prefix of `path` which is the directory of one of `aliases` with the
+/// alias, for example `~` for the home directory. Paths without a matching prefix are returned
+/// unchanged.
+///
+/// Prefixes are compared by whole path components, so an alias for `/src/helix` doesn't
+/// apply to `/src/helix-plugins`.
+pub fn shorten_path<S: AsRef<str>>(path: &Path, aliases: &[(PathBuf, S)]) -> String {
+    let alias = aliases
+        .iter()
+        .filter_map(|(dir, alias)| Some((path.strip_prefix(dir).ok()?, dir, alias)))
+        .max_by_key(|(_, dir, _)| dir.components().count());
+    let Some((rest, _, alias)) = alias else {
+        return path.to_string_lossy().into_owned();
+    };
+
+    let mut shortened = OsString::from(alias.as_ref());
+    if !rest.as_os_str().is_empty() {
+        shortened.push(MAIN_SEPARATOR_STR);
+        shortened.push(rest);
+    }
+    shortened.to_string_lossy().into_owned()
+}
+
+/// Returns `path` relative to the current working directory, or shortened by [shorten_path] if
+/// it is outside of it.
+pub fn display_path<S: AsRef<str>>(path: &Path, aliases: &[(PathBuf, S)]) -> String {
+    if path.is_absolute() {
+        let cwd = normalize(current_working_dir());
+        if let Ok(relative) = normalize(path).strip_prefix(cwd) {
+            return relative.to_string_lossy().into_owned();
+        }
+        return shorten_path(path, aliases);
+    }
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shorten_paths_with_aliases() {
+        let home = PathBuf::from("/home/user");
+        let aliases = [
+            (home.clone(), "~"),
+            (home.join("src/work"), "@work"),
+            (PathBuf::from("/mnt/data"), "@data"),
+        ];
+        let shorten = |path: &str| PathBuf::from(shorten_path(Path::new(path), &aliases));
+
+        assert_eq!(shorten("/home/user/notes.md"), Path::new("~/notes.md"));
+        assert_eq!(shorten("/home/user"), Path::new("~"));
+        // The longest matching prefix wins.
+        assert_eq!(
+            shorten("/home/user/src/work/helix/README.md"),
+            Path::new("@work/helix/README.md")
+        );
+        assert_eq!(shorten("/mnt/data/a.csv"), Path::new("@data/a.csv"));
+        // Only whole path components match.
+        assert_eq!(
+            shorten("/mnt/database/a.csv"),
+            Path::new("/mnt/database/a.csv")
+        );
+        assert_eq!(shorten("/etc/hosts"), Path::new("/etc/hosts"));
+        assert_eq!(
+            shorten_path::<&str>(Path::new("relative/path"), &[]),
+            "relative/path"
+        );
+    }
+}
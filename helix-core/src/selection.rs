@@ -7,10 +7,10 @@
         ensure_grapheme_boundary_next, ensure_grapheme_boundary_prev, next_grapheme_boundary,
         prev_grapheme_boundary,
     },
-    line_ending::get_line_ending,
+    line_ending::{get_line_ending, line_end_char_index},
     movement::Direction,
     tree_sitter::Node,
-    Assoc, ChangeSet, RopeSlice,
+    Assoc, ChangeSet, Position, RopeSlice,
 };
 use helix_stdx::range::is_subset;
 use helix_stdx::rope::{self, RopeSliceExt};
@@ -700,6 +700,58 @@ pub fn len(&self) -> usize {
     pub fn contains(&self, other: &Selection) -> bool {
         is_subset::<true>(self.range_bounds(), other.range_bounds())
     }
+
+    /// Splits each range into one range per line that it spans.
+    ///
+    /// Line endings (both LF and CRLF) are excluded from the resulting ranges, which keep the
+    /// direction of the range they were split from. Empty lines within a range become zero-width
+    /// ranges. The primary range becomes the piece containing its head.
+    pub fn split_at_newlines(&self, text: RopeSlice) -> Self {
+        let mut ranges = SmallVec::with_capacity(self.len());
+        let mut primary_index = 0;
+
+        for (i, range) in self.iter().enumerate() {
+            let first_piece = ranges.len();
+
+            if range.is_empty() {
+                ranges.push(*range);
+            } else {
+                let (from, to) = (range.from(), range.to());
+                let first_line = text.char_to_line(from);
+                let last_line = text.char_to_line(to);
+
+                for line in first_line..=last_line {
+                    let line_start = text.line_to_char(line);
+                    // The range ends right after the previous line's line ending.
+                    if line > first_line && line_start >= to {
+                        break;
+                    }
+                    let start = from.max(line_start);
+                    let end = to.min(line_end_char_index(&text, line));
+                    // The range starts within this line's line ending.
+                    if start > end {
+                        continue;
+                    }
+                    ranges.push(Range::new(start, end).with_direction(range.direction()));
+                }
+
+                // The range only covered a line ending.
+                if ranges.len() == first_piece {
+                    let pos = from.min(line_end_char_index(&text, first_line));
+                    ranges.push(Range::point(pos));
+                }
+            }
+
+            if i == self.primary_index {
+                primary_index = match range.direction() {
+                    Direction::Forward => ranges.len() - 1,
+                    Direction::Backward => first_piece,
+                };
+            }
+        }
+
+        Selection::new(ranges, primary_index)
+    }
 }
 
 impl<'a> IntoIterator for &'a Selection {
@@ -879,6 +931,45 @@ pub fn split_on_matches(text: RopeSlice, selection: &Selection, regex: &rope::Re
     Selection::new(result, 0)
 }
 
+/// The column [align_cursors] moves the cursors to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignTo {
+    /// The leftmost cursor column.
+    Min,
+    /// The rightmost cursor column.
+    Max,
+    /// A fixed column, counting from zero.
+    Column(usize),
+}
+
+/// Moves the cursor of every range to the same visual column of its line, collapsing the
+/// ranges. Tabs count as `tab_width` columns and cursors on lines that are too short end up at
+/// the end of the line.
+#[allow(deprecated)]
+pub fn align_cursors(
+    text: RopeSlice,
+    selection: Selection,
+    align_to: AlignTo,
+    tab_width: usize,
+) -> Selection {
+    let coords: SmallVec<[Position; 1]> = selection
+        .iter()
+        .map(|range| crate::visual_coords_at_pos(text, range.cursor(text), tab_width))
+        .collect();
+    let col = match align_to {
+        AlignTo::Min => coords.iter().map(|coords| coords.col).min().unwrap_or(0),
+        AlignTo::Max => coords.iter().map(|coords| coords.col).max().unwrap_or(0),
+        AlignTo::Column(col) => col,
+    };
+
+    let mut coords = coords.into_iter();
+    selection.transform(|_| {
+        let row = coords.next().unwrap().row;
+        let pos = crate::pos_at_visual_coords(text, Position::new(row, col), tab_width);
+        Range::point(pos)
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1276,6 +1367,67 @@ fn test_put_cursor() {
         assert_eq!(Range::new(6, 3).put_cursor(s, 8, true), Range::new(4, 9));
     }
 
+    #[test]
+    fn test_align_cursors() {
+        let text = Rope::from("a\tbc\nabcdefg\n\tx\nab\n");
+        let text = text.slice(..);
+        let line = |line: usize, col: usize| text.line_to_char(line) + col;
+        // Cursors at the visual columns 4, 5, 4 and 1.
+        let selection = Selection::new(
+            smallvec![
+                Range::point(line(0, 2)),
+                Range::new(line(1, 2), line(1, 6)),
+                Range::point(line(2, 1)),
+                Range::point(line(3, 1)),
+            ],
+            1,
+        );
+        let cursors = |selection: Selection| -> Vec<_> {
+            selection
+                .iter()
+                .map(|range| {
+                    assert_eq!(range.anchor, range.head);
+                    let line = text.char_to_line(range.head);
+                    (line, range.head - text.line_to_char(line))
+                })
+                .collect()
+        };
+
+        // Columns within a tab snap to its start.
+        assert_eq!(
+            cursors(align_cursors(text, selection.clone(), AlignTo::Min, 4)),
+            [(0, 1), (1, 1), (2, 0), (3, 1)]
+        );
+        // Short lines clamp to their end.
+        assert_eq!(
+            cursors(align_cursors(text, selection.clone(), AlignTo::Max, 4)),
+            [(0, 3), (1, 5), (2, 2), (3, 2)]
+        );
+        assert_eq!(
+            cursors(align_cursors(
+                text,
+                selection.clone(),
+                AlignTo::Column(2),
+                4
+            )),
+            [(0, 1), (1, 2), (2, 0), (3, 2)]
+        );
+        // The tab width is taken into account.
+        assert_eq!(
+            cursors(align_cursors(
+                text,
+                selection.clone(),
+                AlignTo::Column(2),
+                2
+            )),
+            [(0, 2), (1, 2), (2, 1), (3, 2)]
+        );
+        assert_eq!(
+            align_cursors(text, selection, AlignTo::Max, 4).primary_index(),
+            1
+        );
+    }
+
     #[test]
     fn test_split_on_matches() {
         let text = Rope::from(" abcd efg wrs   xyz 123 456");
@@ -1316,6 +1468,87 @@ fn test_split_on_matches() {
         );
     }
 
+    #[test]
+    fn test_split_at_newlines() {
+        let text = Rope::from("abc\ndef\r\n\nghi\n");
+        let text = text.slice(..);
+
+        let selection = Selection::new(smallvec![Range::new(1, 14), Range::new(14, 14)], 0)
+            .split_at_newlines(text);
+        assert_eq!(
+            selection.ranges(),
+            &[
+                Range::new(1, 3),
+                Range::new(4, 7),
+                Range::new(9, 9),
+                Range::new(10, 13),
+                Range::new(14, 14),
+            ]
+        );
+        assert_eq!(
+            selection.fragments(text).collect::<Vec<_>>(),
+            &["bc", "def", "", "ghi", ""]
+        );
+        // The head of the primary range is on the last line.
+        assert_eq!(selection.primary_index(), 3);
+
+        // Direction is kept and the head of a backward range is on the first line.
+        let selection = Selection::single(8, 2).split_at_newlines(text);
+        assert_eq!(selection.ranges(), &[Range::new(3, 2), Range::new(7, 4)]);
+        assert_eq!(selection.primary_index(), 0);
+
+        // A range covering only a line ending becomes a point before it.
+        let selection = Selection::single(7, 9).split_at_newlines(text);
+        assert_eq!(selection.ranges(), &[Range::point(7)]);
+        let selection = Selection::single(8, 9).split_at_newlines(text);
+        assert_eq!(selection.ranges(), &[Range::point(7)]);
+    }
+
+    quickcheck::quickcheck! {
+        fn split_at_newlines_covers_selection(text: Vec<u8>, ranges: Vec<(usize, usize)>) -> bool {
+            // Restrict the text to a few characters so that line endings are common.
+            let text: String = text
+                .into_iter()
+                .map(|b| ['a', 'b', ' ', '\r', '\n'][b as usize % 5])
+                .collect();
+            let text = Rope::from(text);
+            let text = text.slice(..);
+            let len = text.len_chars() + 1;
+            if ranges.is_empty() {
+                return true;
+            }
+            let selection = Selection::new(
+                ranges
+                    .into_iter()
+                    .map(|(anchor, head)| Range::new(anchor % len, head % len))
+                    .collect(),
+                0,
+            );
+            let split = selection.split_at_newlines(text);
+
+            // The new ranges are disjoint...
+            let disjoint = split
+                .ranges()
+                .windows(2)
+                .all(|pair| pair[0].to() <= pair[1].from() && !pair[0].overlaps(&pair[1]));
+
+            // ...and cover exactly the selected characters which are not part of a line ending.
+            let is_line_ending =
+                |idx: usize| idx >= line_end_char_index(&text, text.char_to_line(idx));
+            let expected: Vec<usize> = selection
+                .iter()
+                .flat_map(|range| range.from()..range.to())
+                .filter(|&idx| !is_line_ending(idx))
+                .collect();
+            let covered: Vec<usize> = split
+                .iter()
+                .flat_map(|range| range.from()..range.to())
+                .collect();
+
+            disjoint && expected == covered
+        }
+    }
+
     #[test]
     fn test_merge_consecutive_ranges() {
         let selection = Selection::new(
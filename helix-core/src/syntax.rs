@@ -1,4 +1,5 @@
 pub mod config;
+mod scope_selector;
 
 use std::{
     borrow::Cow,
@@ -6,8 +7,11 @@
     fmt, iter,
     ops::{self, RangeBounds},
     path::Path,
-    sync::Arc,
-    time::Duration,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result};
@@ -30,12 +34,91 @@
 
 use crate::{indent::IndentQuery, tree_sitter, ChangeSet, Language};
 
+pub use scope_selector::{ScopeError, ScopeSelector};
 pub use tree_house::{
     highlighter::{Highlight, HighlightEvent},
     query_iter::QueryIterEvent,
     Error as HighlighterError, LanguageLoader, TreeCursor, TREE_SITTER_MATCH_LIMIT,
 };
 
+/// The default of [set_query_timeout].
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_millis(50);
+
+static QUERY_TIMEOUT_MICROS: AtomicU64 = AtomicU64::new(DEFAULT_QUERY_TIMEOUT.as_micros() as u64);
+
+/// Sets how long the highlight, indent and text object queries may run before they are
+/// cancelled. A zero timeout disables cancellation.
+///
+/// The query cursors of the tree-sitter bindings can't be given a timeout, so queries are
+/// cancelled between two matches: a single slow match may still exceed the timeout.
+pub fn set_query_timeout(timeout: Duration) {
+    let micros = timeout.as_micros().try_into().unwrap_or(u64::MAX);
+    QUERY_TIMEOUT_MICROS.store(micros, Ordering::Relaxed);
+}
+
+pub fn query_timeout() -> Duration {
+    Duration::from_micros(QUERY_TIMEOUT_MICROS.load(Ordering::Relaxed))
+}
+
+/// The time a query may run before it is cancelled, see [set_query_timeout].
+///
+/// Only the time spent in the steps of the query passed to [QueryDeadline::time] counts, so
+/// the work done with the matches in between, like rendering highlights, doesn't cancel it.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryDeadline {
+    query: &'static str,
+    timeout: Duration,
+    elapsed: Duration,
+}
+
+impl QueryDeadline {
+    /// Creates the deadline of a query. `query` names the kind of query, like `"highlights"`, in
+    /// the warning logged when it is cancelled.
+    pub fn new(query: &'static str) -> Self {
+        Self::with_timeout(query, query_timeout())
+    }
+
+    fn with_timeout(query: &'static str, timeout: Duration) -> Self {
+        Self {
+            query,
+            timeout,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Runs `step` of the query, counting the time it takes towards the timeout.
+    pub fn time<T>(&mut self, step: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = step();
+        self.elapsed += start.elapsed();
+        result
+    }
+
+    /// Whether the query should be cancelled. Logs a warning if so.
+    pub fn is_exceeded(&self) -> bool {
+        let exceeded = !self.timeout.is_zero() && self.elapsed > self.timeout;
+        if exceeded {
+            log::warn!(
+                "Cancelled the {} query after {:?} (query-timeout is {:?})",
+                self.query,
+                self.elapsed,
+                self.timeout
+            );
+        }
+        exceeded
+    }
+
+    /// Ends `matches` once the time spent finding them exceeds the deadline.
+    pub fn limit<I: Iterator>(mut self, mut matches: I) -> impl Iterator<Item = I::Item> {
+        iter::from_fn(move || {
+            if self.is_exceeded() {
+                return None;
+            }
+            self.time(|| matches.next())
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct LanguageData {
     config: Arc<LanguageConfiguration>,
@@ -626,10 +709,47 @@ pub fn tags<'a>(
         )
     }
 
+    /// Labels of the nodes captured as `@scope.symbol` by the tags queries which contain
+    /// the byte `pos`, from the outermost to the innermost node.
+    ///
+    /// Labels are the node kind without suffixes like `_item`, followed by the text of the
+    /// node's `name` or `type` field if it has one, for example `impl Foo` or `function bar`.
+    pub fn symbol_path(&self, source: RopeSlice, loader: &Loader, pos: u32) -> Vec<String> {
+        let mut nodes = Vec::new();
+        let mut tags = self.tags(source, loader, pos..pos + 1);
+        while let Some(event) = tags.next() {
+            let QueryIterEvent::Match(mat) = event else {
+                continue;
+            };
+            let Some(query) = loader.tag_query(tags.current_language()) else {
+                continue;
+            };
+            let range = mat.node.byte_range();
+            if query.query.capture_name(mat.capture) == "scope.symbol"
+                && range.contains(&pos)
+                && !nodes.contains(&mat.node)
+            {
+                nodes.push(mat.node);
+            }
+        }
+        // Outer nodes start first and end last.
+        nodes.sort_by_key(|node| (node.start_byte(), std::cmp::Reverse(node.end_byte())));
+        nodes
+            .iter()
+            .map(|node| symbol_label(node, source))
+            .collect()
+    }
+
+    /// Computes rainbow bracket highlights within `range`.
+    ///
+    /// Brackets are colored by the depth of their enclosing `@rainbow.scope`. Brackets
+    /// without a partner (inside an error node or next to a missing node) use the
+    /// `unmatched` highlight instead, when one is given.
     pub fn rainbow_highlights(
         &self,
         source: RopeSlice,
         rainbow_length: usize,
+        unmatched: Option<Highlight>,
         loader: &Loader,
         range: impl RangeBounds<u32>,
     ) -> OverlayHighlights {
@@ -680,16 +800,20 @@ struct RainbowScope<'tree> {
                     highlight: Highlight::new((scope_stack.len() % rainbow_length) as u32),
                 });
             } else if capture == rainbow_query.bracket_capture {
-                if let Some(scope) = scope_stack.last() {
+                if mat.node.is_missing() {
+                    continue;
+                }
+                let start =
+                    source.byte_to_char(source.floor_char_boundary(byte_range.start as usize));
+                let end = source.byte_to_char(source.ceil_char_boundary(byte_range.end as usize));
+                if let Some(highlight) = unmatched.filter(|_| is_unmatched_bracket(&mat.node)) {
+                    highlights.push((highlight, start..end));
+                } else if let Some(scope) = scope_stack.last() {
                     if !scope
                         .node
                         .as_ref()
                         .is_some_and(|node| mat.node.parent().as_ref() != Some(node))
                     {
-                        let start = source
-                            .byte_to_char(source.floor_char_boundary(byte_range.start as usize));
-                        let end =
-                            source.byte_to_char(source.ceil_char_boundary(byte_range.end as usize));
                         highlights.push((scope.highlight, start..end));
                     }
                 }
@@ -700,6 +824,14 @@ struct RainbowScope<'tree> {
     }
 }
 
+/// Whether a bracket has no partner: the parser either gave up on its parent
+/// (an `ERROR` node) or had to insert the other half as a missing node.
+fn is_unmatched_bracket(node: &Node) -> bool {
+    node.parent().is_some_and(|parent| {
+        parent.kind() == "ERROR" || parent.children().any(|child| child.is_missing())
+    })
+}
+
 pub type Highlighter<'a> = highlighter::Highlighter<'a, 'a, Loader>;
 
 fn generate_edits(old_text: RopeSlice, changeset: &ChangeSet) -> Vec<InputEdit> {
@@ -1053,17 +1185,20 @@ pub fn capture_nodes_any<'a>(
 
         let mut cursor = InactiveQueryCursor::new(0..u32::MAX, TREE_SITTER_MATCH_LIMIT)
             .execute_query(&self.query, node, RopeInput::new(slice));
-        let capture_node = iter::from_fn(move || {
+        let matches = iter::from_fn(move || {
             let mat = cursor.next_match()?;
             Some(mat.nodes_for_capture(capture).cloned().collect())
-        })
-        .filter_map(move |nodes: Vec<_>| {
-            if nodes.len() > 1 {
-                Some(CapturedNode::Grouped(nodes))
-            } else {
-                nodes.into_iter().map(CapturedNode::Single).next()
-            }
         });
+        let capture_node =
+            QueryDeadline::new("textobjects")
+                .limit(matches)
+                .filter_map(move |nodes: Vec<_>| {
+                    if nodes.len() > 1 {
+                        Some(CapturedNode::Grouped(nodes))
+                    } else {
+                        nodes.into_iter().map(CapturedNode::Single).next()
+                    }
+                });
         Some(capture_node)
     }
 }
@@ -1073,6 +1208,48 @@ pub struct TagQuery {
     pub query: Query,
 }
 
+fn symbol_label(node: &Node, source: RopeSlice) -> String {
+    const SUFFIXES: &[&str] = &[
+        "_item",
+        "_expression",
+        "_statement",
+        "_declaration",
+        "_definition",
+    ];
+    let kind = node.kind();
+    let kind = SUFFIXES
+        .iter()
+        .find_map(|suffix| kind.strip_suffix(suffix))
+        .unwrap_or(kind);
+
+    let mut cursor = node.walk();
+    let mut name = None;
+    if cursor.goto_first_child() {
+        loop {
+            match cursor.field_name() {
+                Some("name") => {
+                    name = Some(cursor.node());
+                    break;
+                }
+                Some("type") if name.is_none() => name = Some(cursor.node()),
+                _ => (),
+            }
+            if !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+
+    match name {
+        Some(name) => {
+            let range = name.byte_range();
+            let name = source.byte_slice(range.start as usize..range.end as usize);
+            format!("{kind} {name}")
+        }
+        None => kind.to_string(),
+    }
+}
+
 pub fn pretty_print_tree<W: fmt::Write>(fmt: &mut W, node: Node) -> fmt::Result {
     if node.child_count() == 0 {
         if node_is_visible(&node) {
@@ -1204,6 +1381,155 @@ mod test {
 
     static LOADER: Lazy<Loader> = Lazy::new(crate::config::default_lang_loader);
 
+    fn validate(languages: &str) -> Vec<config::ValidationError> {
+        let loader = Loader::new(toml::from_str(languages).unwrap()).unwrap();
+        let config = loader.language_configs().next().unwrap();
+        config.validate(&loader)
+    }
+
+    #[test]
+    fn validate_default_languages() {
+        for config in LOADER.language_configs() {
+            let errors = config.validate(&LOADER);
+            assert!(errors.is_empty(), "{}: {errors:?}", config.language_id);
+        }
+    }
+
+    #[test]
+    fn validate_language_servers() {
+        let errors = validate(
+            r#"
+            [[language]]
+            name = "test"
+            scope = "source.test"
+            file-types = []
+            language-servers = ["test-ls", "tset-ls"]
+
+            [language-server.test-ls]
+            command = "test-ls"
+            "#,
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "language-servers");
+        assert_eq!(errors[0].message, "unknown language server 'tset-ls'");
+    }
+
+    #[test]
+    fn validate_auto_format() {
+        let language = |extra: &str| {
+            format!(
+                r#"
+                [[language]]
+                name = "test"
+                scope = "source.test"
+                file-types = []
+                auto-format = true
+                {extra}
+
+                [language-server.test-ls]
+                command = "test-ls"
+                "#
+            )
+        };
+        let errors = validate(&language(""));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "auto-format");
+        let errors = validate(&language(
+            r#"language-servers = [{ name = "test-ls", except-features = ["format"] }]"#,
+        ));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "auto-format");
+
+        assert!(validate(&language(r#"formatter = { command = "fmt" }"#)).is_empty());
+        assert!(validate(&language(r#"language-servers = ["test-ls"]"#)).is_empty());
+    }
+
+    #[test]
+    fn validate_injection_languages() {
+        // The Rust queries inject Rust and a few other languages, which are missing here.
+        let errors = validate(
+            r#"
+            [[language]]
+            name = "rust"
+            scope = "source.rust"
+            grammar = "not-installed"
+            file-types = []
+            "#,
+        );
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|err| err.field == "queries"));
+        let message = |name| format!("injections.scm injects the unknown language '{name}'");
+        assert!(errors.iter().any(|err| err.message == message("comment")));
+        assert!(!errors.iter().any(|err| err.message == message("rust")));
+    }
+
+    #[test]
+    fn validate_queries() {
+        // The Rust queries don't compile for the JSON grammar.
+        let errors = validate(
+            r#"
+            [[language]]
+            name = "rust"
+            scope = "source.rust"
+            grammar = "json"
+            file-types = []
+            "#,
+        );
+        let query_errors: Vec<_> = errors
+            .iter()
+            .filter(|err| err.field == "queries" && !err.message.contains("injects"))
+            .collect();
+        assert!(!query_errors.is_empty(), "{errors:?}");
+        assert!(query_errors[0]
+            .message
+            .contains("Failed to compile highlights for 'rust'"));
+    }
+
+    #[test]
+    fn cancel_queries_after_timeout() {
+        // Stands in for a query over a deeply nested tree that takes a while for every match.
+        fn slow_matches(depth: u32) -> Box<dyn Iterator<Item = u32>> {
+            if depth == 0 {
+                return Box::new(iter::empty());
+            }
+            let mat = iter::once_with(move || {
+                std::thread::sleep(Duration::from_millis(1));
+                depth
+            });
+            Box::new(mat.chain(iter::once_with(move || slow_matches(depth - 1)).flatten()))
+        }
+
+        let timeout = DEFAULT_QUERY_TIMEOUT;
+        let start = Instant::now();
+        let matches = QueryDeadline::with_timeout("test", timeout)
+            .limit(slow_matches(1000))
+            .count();
+        let elapsed = start.elapsed();
+        assert!(matches < 1000);
+        assert!(elapsed < timeout * 2, "cancelled after {elapsed:?}");
+
+        let mut deadline = QueryDeadline::with_timeout("test", Duration::ZERO);
+        deadline.time(|| std::thread::sleep(Duration::from_millis(1)));
+        assert!(!deadline.is_exceeded());
+    }
+
+    #[test]
+    fn only_count_query_time() {
+        // The time spent with each match, like rendering it, doesn't count towards the timeout.
+        let timeout = Duration::from_millis(5);
+        let matches = QueryDeadline::with_timeout("test", timeout)
+            .limit(0..10)
+            .inspect(|_| std::thread::sleep(Duration::from_millis(1)))
+            .count();
+        assert_eq!(matches, 10);
+
+        let mut deadline = QueryDeadline::with_timeout("test", timeout);
+        std::thread::sleep(timeout * 2);
+        assert!(!deadline.is_exceeded());
+        deadline.time(|| std::thread::sleep(timeout * 2));
+        assert!(deadline.is_exceeded());
+    }
+
     #[test]
     fn test_textobject_queries() {
         let query_str = r#"
@@ -1251,6 +1577,76 @@ fn test_textobject_queries() {
         // test("multiple_nodes_grouped", 1..37);
     }
 
+    #[test]
+    fn test_symbol_path() {
+        let source = Rope::from_str(
+            r#"
+mod outer {
+    struct Foo;
+
+    impl Foo {
+        fn bar(x: bool) {
+            if x {
+                let y = 1;
+            }
+        }
+    }
+}
+"#,
+        );
+        let language = LOADER.language_for_name("rust").unwrap();
+        let syntax = Syntax::new(source.slice(..), language, &LOADER).unwrap();
+        let path = |needle: &str| {
+            let pos = source.to_string().find(needle).unwrap();
+            syntax.symbol_path(source.slice(..), &LOADER, pos as u32)
+        };
+
+        assert_eq!(path("mod outer"), ["mod outer"]);
+        assert_eq!(path("Foo;"), ["mod outer", "struct Foo"]);
+        assert_eq!(path("impl Foo"), ["mod outer", "impl Foo"]);
+        assert_eq!(path("(x: bool)"), ["mod outer", "impl Foo", "function bar"]);
+        assert_eq!(
+            path("let y"),
+            ["mod outer", "impl Foo", "function bar", "if"]
+        );
+        assert!(syntax.symbol_path(source.slice(..), &LOADER, 0).is_empty());
+    }
+
+    #[test]
+    fn test_rainbow_highlights() {
+        let rainbow = |text: &str| {
+            let source = Rope::from_str(text);
+            let language = LOADER.language_for_name("rust").unwrap();
+            let syntax = Syntax::new(source.slice(..), language, &LOADER).unwrap();
+            let unmatched = Highlight::new(100);
+            let OverlayHighlights::Heterogenous { highlights } =
+                syntax.rainbow_highlights(source.slice(..), 6, Some(unmatched), &LOADER, ..)
+            else {
+                unreachable!()
+            };
+            highlights
+                .into_iter()
+                .map(|(highlight, range)| (highlight.idx(), text[range].to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        // Each nested pair gets the next depth color.
+        let highlights = rainbow("const X: i32 = ((1));");
+        assert_eq!(
+            highlights,
+            [
+                (0, "(".to_string()),
+                (1, "(".to_string()),
+                (1, ")".to_string()),
+                (0, ")".to_string()),
+            ]
+        );
+
+        // A bracket without a partner uses the unmatched highlight.
+        let highlights = rainbow("fn f() { g(1; }");
+        assert!(highlights.contains(&(100, "(".to_string())));
+    }
+
     #[test]
     fn test_input_edits() {
         use tree_sitter::{InputEdit, Point};
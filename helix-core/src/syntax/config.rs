@@ -1,6 +1,12 @@
-use crate::{auto_pairs::AutoPairs, diagnostic::Severity, Language};
+use crate::{
+    auto_pairs::AutoPairs,
+    diagnostic::Severity,
+    syntax::{read_query, LanguageData, Loader},
+    Language,
+};
 
 use globset::GlobSet;
+use helix_loader::grammar::get_language;
 use helix_stdx::rope;
 use serde::{ser::SerializeSeq as _, Deserialize, Serialize};
 
@@ -110,6 +116,122 @@ pub fn language(&self) -> Language {
         // This value must be set by `super::Loader::new`.
         self.language.unwrap()
     }
+
+    /// Checks the configuration for mistakes which otherwise only show up as missing features
+    /// later on: queries which fail to compile, injections of unknown languages, `auto-format`
+    /// without a formatter and unknown language servers.
+    ///
+    /// Queries are only compiled if the grammar is installed. `loader` is the loader the
+    /// configuration belongs to, which knows the other languages and the language servers.
+    pub fn validate(&self, loader: &Loader) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        for server in &self.language_servers {
+            if !loader.language_server_configs().contains_key(&server.name) {
+                errors.push(ValidationError::new(
+                    "language-servers",
+                    format!("unknown language server '{}'", server.name),
+                ));
+            }
+        }
+
+        let formats_with_server = self
+            .language_servers
+            .iter()
+            .any(|server| server.has_feature(LanguageServerFeature::Format));
+        if self.auto_format && self.formatter.is_none() && !formats_with_server {
+            errors.push(ValidationError::new(
+                "auto-format",
+                "enabled without a formatter or a language server to format with",
+            ));
+        }
+
+        let injections = read_query(&self.language_id, "injections.scm");
+        for name in injection_language_names(&injections) {
+            if loader.language_for_name(name).is_none() {
+                errors.push(ValidationError::new(
+                    "queries",
+                    format!("injections.scm injects the unknown language '{name}'"),
+                ));
+            }
+        }
+
+        let grammar = match get_language(self.grammar.as_deref().unwrap_or(&self.language_id)) {
+            Ok(Some(grammar)) => grammar,
+            Ok(None) => return errors,
+            Err(err) => {
+                errors.push(ValidationError::new("grammar", format!("{err:#}")));
+                return errors;
+            }
+        };
+        let query_errors = [
+            LanguageData::compile_syntax_config(self, loader).err(),
+            LanguageData::compile_indent_query(grammar, self).err(),
+            LanguageData::compile_textobject_query(grammar, self).err(),
+            LanguageData::compile_tag_query(grammar, self).err(),
+            LanguageData::compile_rainbow_query(grammar, self).err(),
+        ];
+        for err in query_errors.into_iter().flatten() {
+            errors.push(ValidationError::new("queries", format!("{err:#}")));
+        }
+
+        errors
+    }
+}
+
+/// The distinct language names set with `(#set! injection.language "name")` in an injections
+/// query. Names taken from the document with an `@injection.language` capture can't be known.
+fn injection_language_names(query: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    for line in query.lines() {
+        if line.trim_start().starts_with(';') {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(idx) = rest.find("#set!") {
+            rest = &rest[idx + "#set!".len()..];
+            let property = rest.trim_start();
+            // The property name may be quoted.
+            let Some(value) = property
+                .strip_prefix("injection.language")
+                .or_else(|| property.strip_prefix("\"injection.language\""))
+            else {
+                continue;
+            };
+            let Some(quoted) = value.trim_start().strip_prefix('"') else {
+                continue;
+            };
+            if let Some(end) = quoted.find('"') {
+                names.push(&quoted[..end]);
+            }
+        }
+    }
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// A mistake in a [LanguageConfiguration] found by [LanguageConfiguration::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The key in languages.toml the mistake is in, or `queries` for the query files.
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -254,6 +376,7 @@ enum BlockCommentTokens {
 #[serde(rename_all = "kebab-case")]
 pub enum LanguageServerFeature {
     Format,
+    OnTypeFormatting,
     GotoDeclaration,
     GotoDefinition,
     GotoTypeDefinition,
@@ -274,6 +397,10 @@ pub enum LanguageServerFeature {
     RenameSymbol,
     InlayHints,
     DocumentColors,
+    CallHierarchy,
+    TypeHierarchy,
+    LinkedEditingRange,
+    CodeLens,
 }
 
 impl Display for LanguageServerFeature {
@@ -281,6 +408,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use LanguageServerFeature::*;
         let feature = match self {
             Format => "format",
+            OnTypeFormatting => "on-type-formatting",
             GotoDeclaration => "goto-declaration",
             GotoDefinition => "goto-definition",
             GotoTypeDefinition => "goto-type-definition",
@@ -299,6 +427,10 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             RenameSymbol => "rename-symbol",
             InlayHints => "inlay-hints",
             DocumentColors => "document-colors",
+            CallHierarchy => "call-hierarchy",
+            TypeHierarchy => "type-hierarchy",
+            LinkedEditingRange => "linked-editing-range",
+            CodeLens => "code-lens",
         };
         write!(f, "{feature}",)
     }
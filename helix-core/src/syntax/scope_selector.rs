@@ -0,0 +1,340 @@
+//! TextMate style scope selectors.
+//!
+//! A selector is made up of scope paths like `source.rust entity.name`, where each scope of the
+//! path matches a scope of the stack (or any of its descendant scopes, so `entity.name` matches
+//! `entity.name.function`) and the scopes must appear in the stack in order. Paths may be
+//! combined with `,` or `|` (union), `&` (intersection) and `-` (exclusion), negated with a
+//! leading `-` and grouped with parentheses. `,` binds loosest, the other operators are left
+//! associative.
+
+use std::fmt;
+
+use super::Highlight;
+
+/// The error returned when parsing an invalid [ScopeSelector].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeError {
+    /// The selector (or a group or operand within it) is empty.
+    Empty,
+    /// A character which can't appear at this position.
+    UnexpectedChar(char),
+    /// A `(` without a matching `)`.
+    UnclosedGroup,
+}
+
+impl fmt::Display for ScopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "expected a scope"),
+            Self::UnexpectedChar(ch) => write!(f, "unexpected '{ch}' in scope selector"),
+            Self::UnclosedGroup => write!(f, "unclosed '(' in scope selector"),
+        }
+    }
+}
+
+impl std::error::Error for ScopeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    /// Scopes which must appear in the stack in order.
+    Path(Vec<Box<str>>),
+    Not(Box<Expr>),
+    Union(Vec<Expr>),
+    Intersection(Box<Expr>, Box<Expr>),
+    Difference(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn matches(&self, stack: &[&str]) -> bool {
+        match self {
+            Self::Path(path) => {
+                let mut stack = stack.iter();
+                path.iter()
+                    .all(|scope| stack.any(|candidate| scope_matches(scope, candidate)))
+            }
+            Self::Not(expr) => !expr.matches(stack),
+            Self::Union(exprs) => exprs.iter().any(|expr| expr.matches(stack)),
+            Self::Intersection(lhs, rhs) => lhs.matches(stack) && rhs.matches(stack),
+            Self::Difference(lhs, rhs) => lhs.matches(stack) && !rhs.matches(stack),
+        }
+    }
+}
+
+/// Whether `scope` is `candidate` or one of its ancestors, for example `entity.name` for
+/// `entity.name.function`.
+fn scope_matches(scope: &str, candidate: &str) -> bool {
+    candidate
+        .strip_prefix(scope)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// A parsed TextMate scope selector, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeSelector(Expr);
+
+impl ScopeSelector {
+    pub fn parse(s: &str) -> Result<Self, ScopeError> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let expr = parser.selector()?;
+        match parser.peek() {
+            None => Ok(Self(expr)),
+            Some(ch) => Err(ScopeError::UnexpectedChar(ch)),
+        }
+    }
+
+    /// Whether the selector matches the stack of highlights, ordered from the outermost to the
+    /// innermost highlight.
+    ///
+    /// `scopes` are the highlight names that the highlights index into, see
+    /// [Loader::scopes](super::Loader::scopes).
+    pub fn matches(&self, scope_stack: &[Highlight], scopes: &[String]) -> bool {
+        let stack: Vec<&str> = scope_stack
+            .iter()
+            .filter_map(|highlight| scopes.get(highlight.idx()).map(String::as_str))
+            .collect();
+        self.matches_names(&stack)
+    }
+
+    /// Whether the selector matches the stack of scope names, ordered from the outermost to the
+    /// innermost scope.
+    pub fn matches_names(&self, scope_stack: &[&str]) -> bool {
+        self.0.matches(scope_stack)
+    }
+}
+
+impl std::str::FromStr for ScopeSelector {
+    type Err = ScopeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Skips whitespace and returns the next character.
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.input[self.pos..];
+        let trimmed = rest.trim_start();
+        self.pos += rest.len() - trimmed.len();
+        trimmed.chars().next()
+    }
+
+    fn bump(&mut self) {
+        if let Some(ch) = self.peek() {
+            self.pos += ch.len_utf8();
+        }
+    }
+
+    /// `composite (',' composite)*`
+    fn selector(&mut self) -> Result<Expr, ScopeError> {
+        let mut exprs = vec![self.composite()?];
+        while self.peek() == Some(',') {
+            self.bump();
+            exprs.push(self.composite()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            Expr::Union(exprs)
+        })
+    }
+
+    /// `unary (('|' | '&' | '-') unary)*`
+    fn composite(&mut self) -> Result<Expr, ScopeError> {
+        let mut expr = self.unary()?;
+        loop {
+            expr = match self.peek() {
+                Some('|') => {
+                    self.bump();
+                    Expr::Union(vec![expr, self.unary()?])
+                }
+                Some('&') => {
+                    self.bump();
+                    Expr::Intersection(Box::new(expr), Box::new(self.unary()?))
+                }
+                Some('-') => {
+                    self.bump();
+                    Expr::Difference(Box::new(expr), Box::new(self.unary()?))
+                }
+                _ => return Ok(expr),
+            };
+        }
+    }
+
+    /// `'-' unary | '(' selector ')' | path`
+    fn unary(&mut self) -> Result<Expr, ScopeError> {
+        match self.peek() {
+            Some('-') => {
+                self.bump();
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some('(') => {
+                self.bump();
+                let expr = self.selector()?;
+                match self.peek() {
+                    Some(')') => {
+                        self.bump();
+                        Ok(expr)
+                    }
+                    Some(ch) => Err(ScopeError::UnexpectedChar(ch)),
+                    None => Err(ScopeError::UnclosedGroup),
+                }
+            }
+            _ => self.path(),
+        }
+    }
+
+    /// One or more whitespace separated scopes.
+    fn path(&mut self) -> Result<Expr, ScopeError> {
+        let mut path = Vec::new();
+        while let Some(scope) = self.scope()? {
+            path.push(scope.into());
+        }
+        if path.is_empty() {
+            return match self.peek() {
+                Some(ch) => Err(ScopeError::UnexpectedChar(ch)),
+                None => Err(ScopeError::Empty),
+            };
+        }
+        Ok(Expr::Path(path))
+    }
+
+    fn scope(&mut self) -> Result<Option<&str>, ScopeError> {
+        match self.peek() {
+            Some(ch) if is_scope_char(ch) && ch != '-' => (),
+            _ => return Ok(None),
+        }
+        let rest = &self.input[self.pos..];
+        let len = rest.find(|ch| !is_scope_char(ch)).unwrap_or(rest.len());
+        let scope = &rest[..len];
+        self.pos += len;
+        if scope.starts_with('.') || scope.ends_with('.') || scope.contains("..") {
+            return Err(ScopeError::UnexpectedChar('.'));
+        }
+        Ok(Some(scope))
+    }
+}
+
+fn is_scope_char(ch: char) -> bool {
+    !ch.is_whitespace() && !matches!(ch, ',' | '|' | '&' | '(' | ')')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[track_caller]
+    fn matches(selector: &str, stack: &[&str]) -> bool {
+        ScopeSelector::parse(selector).unwrap().matches_names(stack)
+    }
+
+    #[test]
+    fn prefix_match() {
+        assert!(matches("entity", &["entity.name.function.rust"]));
+        assert!(matches("entity.name", &["entity.name.function.rust"]));
+        assert!(matches(
+            "entity.name.function.rust",
+            &["entity.name.function.rust"]
+        ));
+        assert!(!matches("entity.names", &["entity.name.function"]));
+        assert!(!matches("entity.name.function.rust", &["entity.name"]));
+        assert!(!matches("name", &["entity.name"]));
+    }
+
+    #[test]
+    fn ancestor_match() {
+        let stack = ["source.rust", "meta.function", "entity.name.function"];
+        assert!(matches("source.rust entity.name", &stack));
+        assert!(matches("source meta entity", &stack));
+        // Scopes don't need to be adjacent.
+        assert!(matches("source.rust entity.name.function", &stack));
+        // Any scope of the stack may match, not only the innermost one.
+        assert!(matches("meta.function", &stack));
+        // Scopes must be in order.
+        assert!(!matches("entity.name source.rust", &stack));
+        assert!(!matches("source.python entity.name", &stack));
+        // Each scope of the path matches a distinct scope of the stack.
+        assert!(!matches("source source", &stack));
+        assert!(matches("source source", &["source.rust", "source.sql"]));
+    }
+
+    #[test]
+    fn union() {
+        let stack = ["source.rust", "string.quoted.double"];
+        assert!(matches("string, comment", &stack));
+        assert!(matches("comment, string", &stack));
+        assert!(matches("comment | string", &stack));
+        assert!(matches("comment|source.rust string", &stack));
+        assert!(!matches("comment, keyword | constant", &stack));
+    }
+
+    #[test]
+    fn exclusion() {
+        let stack = ["source.rust", "string.quoted.double"];
+        assert!(matches("string - comment", &stack));
+        assert!(!matches("string - string.quoted", &stack));
+        assert!(!matches("source - source.rust string", &stack));
+        assert!(matches("-comment", &stack));
+        assert!(!matches("-string", &stack));
+        assert!(matches("- (comment | keyword)", &stack));
+        assert!(matches("source - comment - keyword", &stack));
+        assert!(!matches("source - comment - string", &stack));
+        // Exclusion binds tighter than `,`.
+        assert!(matches("string - string, source", &stack));
+        assert!(!matches("string - (string, source)", &stack));
+        // `-` within a scope name is not an operator.
+        assert!(matches("meta.tag-name", &["meta.tag-name.html"]));
+    }
+
+    #[test]
+    fn intersection() {
+        let stack = ["source.rust", "string.quoted.double"];
+        assert!(matches("source & string", &stack));
+        assert!(!matches("source & comment", &stack));
+        assert!(matches("(comment | string) & source", &stack));
+    }
+
+    #[test]
+    fn highlights() {
+        let scopes = ["keyword", "function.method", "string"].map(String::from);
+        let selector = ScopeSelector::parse("function - keyword").unwrap();
+        assert!(selector.matches(&[Highlight::new(1)], &scopes));
+        assert!(!selector.matches(&[Highlight::new(0), Highlight::new(1)], &scopes));
+        assert!(!selector.matches(&[Highlight::new(2)], &scopes));
+        // Unknown highlights are ignored.
+        assert!(selector.matches(&[Highlight::new(5), Highlight::new(1)], &scopes));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(ScopeSelector::parse(""), Err(ScopeError::Empty));
+        assert_eq!(ScopeSelector::parse("  "), Err(ScopeError::Empty));
+        assert_eq!(ScopeSelector::parse("string,"), Err(ScopeError::Empty));
+        assert_eq!(ScopeSelector::parse("string -"), Err(ScopeError::Empty));
+        assert_eq!(
+            ScopeSelector::parse("(string"),
+            Err(ScopeError::UnclosedGroup)
+        );
+        assert_eq!(
+            ScopeSelector::parse("string)"),
+            Err(ScopeError::UnexpectedChar(')'))
+        );
+        assert_eq!(
+            ScopeSelector::parse("| string"),
+            Err(ScopeError::UnexpectedChar('|'))
+        );
+        assert_eq!(
+            ScopeSelector::parse("entity..name"),
+            Err(ScopeError::UnexpectedChar('.'))
+        );
+        assert_eq!(
+            ScopeSelector::parse("()"),
+            Err(ScopeError::UnexpectedChar(')'))
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use std::fmt::Display;
 
+use helix_stdx::rope::RopeSliceExt;
 use ropey::RopeSlice;
 
-use crate::chars::{categorize_char, char_is_whitespace, CharCategory};
+use crate::chars::{categorize_char, char_is_line_ending, char_is_whitespace, CharCategory};
 use crate::graphemes::{next_grapheme_boundary, prev_grapheme_boundary};
-use crate::line_ending::rope_is_line_ending;
+use crate::line_ending::{line_end_char_index, rope_is_line_ending};
 use crate::movement::Direction;
 use crate::syntax;
 use crate::Range;
@@ -197,6 +198,236 @@ pub fn textobject_paragraph(
     Range::new(anchor, head)
 }
 
+fn char_is_sentence_space(ch: char) -> bool {
+    char_is_whitespace(ch) || char_is_line_ending(ch)
+}
+
+/// Whether the run of whitespace `start..end` separates two sentences: either it contains an
+/// empty line, or the text before it ends with a sentence terminator (`.`, `!` or `?`, possibly
+/// followed by closing quotes or brackets).
+///
+/// A single `.` after a lone uppercase letter is taken for an initial or abbreviation
+/// ("J. R. R. Tolkien") and an ellipsis followed by a lowercase letter continues the sentence.
+fn is_sentence_boundary(slice: RopeSlice, start: usize, end: usize) -> bool {
+    if start == 0 || end == slice.len_chars() {
+        return true;
+    }
+    if slice.char_to_line(end) - slice.char_to_line(start) >= 2 {
+        return true;
+    }
+
+    let mut pos = start;
+    while pos > 0 && matches!(slice.char(pos - 1), '"' | '\'' | ')' | ']' | '”' | '’') {
+        pos -= 1;
+    }
+    let terminators_end = pos;
+    while pos > 0 && matches!(slice.char(pos - 1), '.' | '!' | '?') {
+        pos -= 1;
+    }
+    let terminators = slice.slice(pos..terminators_end);
+    if terminators.len_chars() == 0 {
+        return false;
+    }
+    if terminators.chars().all(|ch| ch == '.') {
+        if terminators.len_chars() == 1 {
+            let is_initial = pos > 0
+                && slice.char(pos - 1).is_uppercase()
+                && (pos == 1 || !slice.char(pos - 2).is_alphanumeric());
+            return !is_initial;
+        }
+        return !slice.char(end).is_lowercase();
+    }
+    true
+}
+
+/// The start of the sentence containing `pos`, which must not be whitespace.
+fn sentence_start(slice: RopeSlice, mut pos: usize) -> usize {
+    loop {
+        while pos > 0 && !char_is_sentence_space(slice.char(pos - 1)) {
+            pos -= 1;
+        }
+        let word_start = pos;
+        while pos > 0 && char_is_sentence_space(slice.char(pos - 1)) {
+            pos -= 1;
+        }
+        if is_sentence_boundary(slice, pos, word_start) {
+            return word_start;
+        }
+    }
+}
+
+/// The end of the sentence containing `pos`, which must not be whitespace.
+fn sentence_end(slice: RopeSlice, mut pos: usize) -> usize {
+    let len = slice.len_chars();
+    loop {
+        while pos < len && !char_is_sentence_space(slice.char(pos)) {
+            pos += 1;
+        }
+        let word_end = pos;
+        while pos < len && char_is_sentence_space(slice.char(pos)) {
+            pos += 1;
+        }
+        if is_sentence_boundary(slice, word_end, pos) {
+            return word_end;
+        }
+    }
+}
+
+/// Selects the sentence under the cursor. Sentences end at `.`, `!` or `?` followed by
+/// whitespace and at empty lines. A cursor on the whitespace between two sentences selects
+/// the sentence before it.
+///
+/// `Around` includes the whitespace up to the next sentence of the paragraph or, for the last
+/// sentence of a paragraph, the whitespace before the sentence.
+pub fn textobject_sentence(slice: RopeSlice, range: Range, textobject: TextObject) -> Range {
+    let len = slice.len_chars();
+    let cursor = range.cursor(slice);
+    let pos = match slice.get_char(cursor) {
+        Some(ch) if !char_is_sentence_space(ch) => cursor,
+        _ => {
+            // Find the text around the whitespace under the cursor.
+            let mut before = cursor.min(len);
+            while before > 0 && char_is_sentence_space(slice.char(before - 1)) {
+                before -= 1;
+            }
+            if before > 0 {
+                before - 1
+            } else {
+                match (cursor..len).find(|&pos| !char_is_sentence_space(slice.char(pos))) {
+                    Some(after) => after,
+                    None => return range,
+                }
+            }
+        }
+    };
+
+    let start = sentence_start(slice, pos);
+    let end = sentence_end(slice, pos);
+
+    match textobject {
+        TextObject::Inside => Range::new(start, end),
+        TextObject::Around => {
+            let mut next = end;
+            while next < len && char_is_sentence_space(slice.char(next)) {
+                next += 1;
+            }
+            let same_paragraph = slice.char_to_line(next) - slice.char_to_line(end) < 2;
+            if next < len && same_paragraph {
+                return Range::new(start, next);
+            }
+
+            let mut prev = start;
+            while prev > 0 && char_is_sentence_space(slice.char(prev - 1)) {
+                prev -= 1;
+            }
+            if slice.char_to_line(start) - slice.char_to_line(prev) < 2 {
+                Range::new(prev, end)
+            } else {
+                Range::new(start, end)
+            }
+        }
+        TextObject::Movement => unreachable!(),
+    }
+}
+
+/// The tokens which start a line comment, recognized by [textobject_comment] without a syntax
+/// tree. Longer tokens come first so that `///` isn't taken for `//`.
+const LINE_COMMENT_TOKENS: &[&str] = &["///", "//!", "//", "#", "--", "%"];
+/// The delimiters of block comments stripped by the inside comment textobject.
+const BLOCK_COMMENT_TOKENS: &[(&str, &str)] = &[("/*", "*/"), ("<!--", "-->")];
+
+/// The char range of the outermost of the nested comment nodes under the cursor, such as the
+/// `line_comment` around the `doc_comment` of a Rust doc comment.
+fn comment_node(slice: RopeSlice, range: Range, syntax: &Syntax) -> Option<(usize, usize)> {
+    let byte_pos = slice.char_to_byte(range.cursor(slice)) as u32;
+    let mut node = syntax.descendant_for_byte_range(byte_pos, byte_pos)?;
+    while !node.kind().contains("comment") {
+        node = node.parent()?;
+    }
+    while let Some(parent) = node
+        .parent()
+        .filter(|parent| parent.kind().contains("comment"))
+    {
+        node = parent;
+    }
+
+    let start = slice.byte_to_char(node.start_byte() as usize);
+    let mut end = slice.byte_to_char(node.end_byte() as usize);
+    // Some grammars include the line ending in line comments.
+    while end > start && char_is_line_ending(slice.char(end - 1)) {
+        end -= 1;
+    }
+    Some((start, end))
+}
+
+/// The char range of the comment on the cursor line, if the line is a line comment.
+fn line_comment(slice: RopeSlice, range: Range) -> Option<(usize, usize)> {
+    let line = range.cursor_line(slice);
+    let line_start = slice.line_to_char(line);
+    let text = slice.line(line);
+    let indent = text
+        .chars()
+        .take_while(|&ch| char_is_whitespace(ch))
+        .count();
+    let content = text.slice(indent..);
+    LINE_COMMENT_TOKENS
+        .iter()
+        .any(|token| content.starts_with(token))
+        .then(|| (line_start + indent, line_end_char_index(&slice, line)))
+}
+
+/// The content of the comment `start..end` without its delimiters and the surrounding
+/// whitespace.
+fn comment_inside(slice: RopeSlice, start: usize, end: usize) -> Range {
+    let comment = slice.slice(start..end);
+    let (mut inner_start, mut inner_end) = (start, end);
+    if let Some((open, close)) = BLOCK_COMMENT_TOKENS.iter().find(|(open, close)| {
+        comment.starts_with(open)
+            && comment.ends_with(close)
+            && comment.len_chars() >= open.len() + close.len()
+    }) {
+        inner_start += open.chars().count();
+        inner_end -= close.chars().count();
+    } else if let Some(token) = LINE_COMMENT_TOKENS
+        .iter()
+        .find(|token| comment.starts_with(token))
+    {
+        inner_start += token.chars().count();
+    }
+
+    while inner_start < inner_end && char_is_sentence_space(slice.char(inner_start)) {
+        inner_start += 1;
+    }
+    while inner_end > inner_start && char_is_sentence_space(slice.char(inner_end - 1)) {
+        inner_end -= 1;
+    }
+    Range::new(inner_start, inner_end)
+}
+
+/// Selects the comment under the cursor: the comment node of the syntax tree, or without a
+/// syntax tree a line comment starting the cursor line. `Inside` leaves out the comment
+/// delimiters and the whitespace around the content.
+pub fn textobject_comment(
+    slice: RopeSlice,
+    range: Range,
+    syntax: Option<&Syntax>,
+    textobject: TextObject,
+) -> Range {
+    let comment = match syntax {
+        Some(syntax) => comment_node(slice, range, syntax),
+        None => line_comment(slice, range),
+    };
+    let Some((start, end)) = comment else {
+        return range;
+    };
+
+    match textobject {
+        TextObject::Inside => comment_inside(slice, start, end),
+        TextObject::Around => Range::new(start, end),
+        TextObject::Movement => unreachable!(),
+    }
+}
+
 pub fn textobject_pair_surround(
     syntax: Option<&Syntax>,
     slice: RopeSlice,
@@ -289,6 +520,63 @@ pub fn textobject_treesitter(
     get_range().unwrap_or(range)
 }
 
+/// Selects the innermost function under the cursor from the `function.around` and
+/// `function.inside` captures of the language's textobject query. `Around` includes the
+/// signature, `Inside` only the body: without the braces and the whitespace inside them if the
+/// body is delimited by braces.
+///
+/// Returns `None` if the cursor isn't within a function, the body is empty or the language has no
+/// textobject query.
+pub fn textobject_function(
+    slice: RopeSlice,
+    range: Range,
+    syntax: &Syntax,
+    loader: &syntax::Loader,
+    textobject: TextObject,
+) -> Option<Range> {
+    let query = loader.textobject_query(syntax.root_language())?;
+    let root = syntax.tree().root_node();
+    let byte_pos = slice.char_to_byte(range.cursor(slice));
+    let function = query
+        .capture_nodes("function.around", &root, slice)?
+        .map(|node| node.byte_range())
+        .filter(|function| function.contains(&byte_pos))
+        .min_by_key(|function| function.len())?;
+
+    let (start, end) = match textobject {
+        TextObject::Around => (function.start, function.end),
+        TextObject::Inside => {
+            // The bodies of nested functions are within the function too, its own body is the
+            // outermost one.
+            let body = query
+                .capture_nodes("function.inside", &root, slice)?
+                .map(|node| node.byte_range())
+                .filter(|body| function.start <= body.start && body.end <= function.end)
+                .max_by_key(|body| body.len())?;
+            (body.start, body.end)
+        }
+        TextObject::Movement => unreachable!(),
+    };
+    if end > slice.len_bytes() {
+        return None;
+    }
+    let (start, end) = (slice.byte_to_char(start), slice.byte_to_char(end));
+    let braced = end - start >= 2 && slice.char(start) == '{' && slice.char(end - 1) == '}';
+    if textobject == TextObject::Around || !braced {
+        return Some(Range::new(start, end));
+    }
+
+    let (mut inner_start, mut inner_end) = (start + 1, end - 1);
+    while inner_start < inner_end && char_is_sentence_space(slice.char(inner_start)) {
+        inner_start += 1;
+    }
+    while inner_end > inner_start && char_is_sentence_space(slice.char(inner_end - 1)) {
+        inner_end -= 1;
+    }
+    // An empty body has nothing to select.
+    (inner_start < inner_end).then(|| Range::new(inner_start, inner_end))
+}
+
 #[cfg(test)]
 mod test {
     use super::TextObject::*;
@@ -497,6 +785,263 @@ fn test_textobject_paragraph_around_single() {
         }
     }
 
+    #[test]
+    fn test_textobject_sentence_inside() {
+        let tests = [
+            ("#[|]#", "#[|]#"),
+            (
+                "Hello #[w|]#orld. How are you?",
+                "#[Hello world.|]# How are you?",
+            ),
+            (
+                "Hello world. How #[a|]#re you?",
+                "Hello world. #[How are you?|]#",
+            ),
+            ("One.#[ |]#Two.", "#[One.|]# Two."),
+            ("#[ |]# One. Two.", "  #[One.|]# Two."),
+            ("One. Tw#[o|]#.   ", "One. #[Two.|]#   "),
+            ("Really?! Y#[e|]#s.", "Really?! #[Yes.|]#"),
+            // initials and abbreviations
+            (
+                "Written by J. R. R. Tolk#[i|]#en. The end.",
+                "#[Written by J. R. R. Tolkien.|]# The end.",
+            ),
+            (
+                "Pi is 3.14 r#[o|]#ughly. Ok.",
+                "#[Pi is 3.14 roughly.|]# Ok.",
+            ),
+            // an uppercase letter before the end of a sentence is indistinguishable from an initial
+            ("Vitamin #[C|]#. Done.", "#[Vitamin C. Done.|]#"),
+            // ellipses
+            (
+                "Wait... what #[h|]#appened? Nothing.",
+                "#[Wait... what happened?|]# Nothing.",
+            ),
+            ("I see... T#[h|]#at is odd.", "I see... #[That is odd.|]#"),
+            // quotes and brackets
+            (
+                "He said \"stop!\" Th#[e|]#n he left.",
+                "He said \"stop!\" #[Then he left.|]#",
+            ),
+            ("(Aside.) Ma#[i|]#n text.", "(Aside.) #[Main text.|]#"),
+            // lines and paragraphs
+            (
+                "First line\ncontin#[u|]#es here. Next.",
+                "#[First line\ncontinues here.|]# Next.",
+            ),
+            (
+                "No terminator\n\nNext p#[a|]#ragraph",
+                "No terminator\n\n#[Next paragraph|]#",
+            ),
+            ("One.\n#[\n|]#Two.", "#[One.|]#\n\nTwo."),
+        ];
+
+        for (before, expected) in tests {
+            let (s, selection) = crate::test::print(before);
+            let text = Rope::from(s.as_str());
+            let selection =
+                selection.transform(|r| textobject_sentence(text.slice(..), r, TextObject::Inside));
+            let actual = crate::test::plain(s.as_ref(), &selection);
+            assert_eq!(actual, expected, "\nbefore: `{:?}`", before);
+        }
+    }
+
+    #[test]
+    fn test_textobject_sentence_around() {
+        let tests = [
+            ("#[|]#", "#[|]#"),
+            (
+                "Hello #[w|]#orld. How are you?",
+                "#[Hello world. |]#How are you?",
+            ),
+            (
+                "Hello world. How #[a|]#re you?",
+                "Hello world.#[ How are you?|]#",
+            ),
+            ("One. Tw#[o|]#.   ", "One.#[ Two.|]#   "),
+            ("One.  Tw#[o|]#.\n\nThree.", "One.#[  Two.|]#\n\nThree."),
+            ("On#[e|]#.\n\nTwo.", "#[One.|]#\n\nTwo."),
+            ("One.\nT#[w|]#o.", "One.#[\nTwo.|]#"),
+            ("One.\n\nT#[w|]#o.", "One.\n\n#[Two.|]#"),
+            (
+                "Written by J. R. R. Tolk#[i|]#en. The end.",
+                "#[Written by J. R. R. Tolkien. |]#The end.",
+            ),
+        ];
+
+        for (before, expected) in tests {
+            let (s, selection) = crate::test::print(before);
+            let text = Rope::from(s.as_str());
+            let selection =
+                selection.transform(|r| textobject_sentence(text.slice(..), r, TextObject::Around));
+            let actual = crate::test::plain(s.as_ref(), &selection);
+            assert_eq!(actual, expected, "\nbefore: `{:?}`", before);
+        }
+    }
+
+    #[test]
+    fn test_textobject_comment_line() {
+        let tests = [
+            (
+                "fn a() {}\n// a #[c|]#omment \n",
+                "fn a() {}\n// #[a comment|]# \n",
+                "fn a() {}\n#[// a comment |]#\n",
+            ),
+            (
+                "    /// d#[o|]#c comment\n",
+                "    /// #[doc comment|]#\n",
+                "    #[/// doc comment|]#\n",
+            ),
+            (
+                "x = 1\n#[#|]# python\r\n",
+                "x = 1\n# #[python|]#\r\n",
+                "x = 1\n#[# python|]#\r\n",
+            ),
+            ("-- s#[q|]#l\n", "-- #[sql|]#\n", "#[-- sql|]#\n"),
+            ("% t#[e|]#x", "% #[tex|]#", "#[% tex|]#"),
+            ("#[/|]#/\n", "//#[|]#\n", "#[//|]#\n"),
+            // only whole lines are recognized as comments
+            (
+                "let #[x|]# = 1; // trailing\n",
+                "let #[x|]# = 1; // trailing\n",
+                "let #[x|]# = 1; // trailing\n",
+            ),
+            (
+                "x = 10 #[%|]# 3\n",
+                "x = 10 #[%|]# 3\n",
+                "x = 10 #[%|]# 3\n",
+            ),
+        ];
+
+        for (before, inside, around) in tests {
+            let (s, selection) = crate::test::print(before);
+            let text = Rope::from(s.as_str());
+            for (objtype, expected) in [(Inside, inside), (Around, around)] {
+                let selection = selection
+                    .clone()
+                    .transform(|r| textobject_comment(text.slice(..), r, None, objtype));
+                let actual = crate::test::plain(s.as_ref(), &selection);
+                assert_eq!(actual, expected, "\nbefore: `{:?}` ({objtype})", before);
+            }
+        }
+    }
+
+    #[test]
+    fn test_textobject_function() {
+        let loader = crate::config::default_lang_loader();
+        let tests = [
+            (
+                "rust",
+                "fn a(x: u8) {\n    let #[y|]# = x;\n    y\n}\n",
+                "fn a(x: u8) {\n    #[let y = x;\n    y|]#\n}\n",
+                "#[fn a(x: u8) {\n    let y = x;\n    y\n}|]#\n",
+            ),
+            (
+                // The signature selects the function as well.
+                "rust",
+                "fn #[a|]#() { b() }\n",
+                "fn a() { #[b()|]# }\n",
+                "#[fn a() { b() }|]#\n",
+            ),
+            (
+                // Nested functions select the innermost function.
+                "rust",
+                "fn a() {\n    fn b() { #[c|]#() }\n}\n",
+                "fn a() {\n    fn b() { #[c()|]# }\n}\n",
+                "fn a() {\n    #[fn b() { c() }|]#\n}\n",
+            ),
+            (
+                "rust",
+                "fn a() {#[}|]#\n",
+                "fn a() {#[}|]#\n",
+                "#[fn a() {}|]#\n",
+            ),
+            (
+                "python",
+                "def a(x):\n    y = #[x|]#\n    return y\n",
+                "def a(x):\n    #[y = x\n    return y|]#\n",
+                "#[def a(x):\n    y = x\n    return y|]#\n",
+            ),
+            (
+                "c",
+                "int a(int x) {\n    return #[x|]#;\n}\n",
+                "int a(int x) {\n    #[return x;|]#\n}\n",
+                "#[int a(int x) {\n    return x;\n}|]#\n",
+            ),
+            (
+                "c",
+                "int #[x|]# = 1;\n",
+                "int #[x|]# = 1;\n",
+                "int #[x|]# = 1;\n",
+            ),
+        ];
+
+        for (language, before, inside, around) in tests {
+            let (s, selection) = crate::test::print(before);
+            let text = Rope::from(s.as_str());
+            let language = loader.language_for_name(language).unwrap();
+            let syntax = Syntax::new(text.slice(..), language, &loader).unwrap();
+            for (objtype, expected) in [(Inside, inside), (Around, around)] {
+                let selection = selection.clone().transform(|r| {
+                    textobject_function(text.slice(..), r, &syntax, &loader, objtype).unwrap_or(r)
+                });
+                let actual = crate::test::plain(s.as_ref(), &selection);
+                assert_eq!(actual, expected, "\nbefore: `{:?}` ({objtype})", before);
+            }
+        }
+    }
+
+    #[test]
+    fn test_textobject_comment_treesitter() {
+        let loader = crate::config::default_lang_loader();
+        let tests = [
+            (
+                "rust",
+                "/* a b#[l|]#ock */\nfn a() {}\n",
+                "/* #[a block|]# */\nfn a() {}\n",
+                "#[/* a block */|]#\nfn a() {}\n",
+            ),
+            (
+                "rust",
+                "fn a() {} // tr#[a|]#iling\n",
+                "fn a() {} // #[trailing|]#\n",
+                "fn a() {} #[// trailing|]#\n",
+            ),
+            (
+                "rust",
+                "/// d#[o|]#c\nfn a() {}\n",
+                "/// #[doc|]#\nfn a() {}\n",
+                "#[/// doc|]#\nfn a() {}\n",
+            ),
+            (
+                "rust",
+                "let #[s|]# = \"// not a comment\";\n",
+                "let #[s|]# = \"// not a comment\";\n",
+                "let #[s|]# = \"// not a comment\";\n",
+            ),
+            (
+                "python",
+                "x = 1  # n#[o|]#te\n",
+                "x = 1  # #[note|]#\n",
+                "x = 1  #[# note|]#\n",
+            ),
+        ];
+
+        for (language, before, inside, around) in tests {
+            let (s, selection) = crate::test::print(before);
+            let text = Rope::from(s.as_str());
+            let language = loader.language_for_name(language).unwrap();
+            let syntax = Syntax::new(text.slice(..), language, &loader).unwrap();
+            for (objtype, expected) in [(Inside, inside), (Around, around)] {
+                let selection = selection
+                    .clone()
+                    .transform(|r| textobject_comment(text.slice(..), r, Some(&syntax), objtype));
+                let actual = crate::test::plain(s.as_ref(), &selection);
+                assert_eq!(actual, expected, "\nbefore: `{:?}` ({objtype})", before);
+            }
+        }
+    }
+
     #[test]
     fn test_textobject_surround() {
         // (text, [(cursor position, textobject, final range, surround char, count), ...])
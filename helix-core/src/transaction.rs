@@ -572,10 +572,17 @@ pub fn invert(&self, original: &Rope) -> Self {
         }
     }
 
+    /// Combines `self` and `other` into a single transaction which has the effect of applying
+    /// `self` and then `other`. `other` must apply to the document produced by `self`.
     pub fn compose(mut self, other: Self) -> Self {
+        self.selection = match other.selection {
+            // Other selection takes precedence
+            Some(selection) => Some(selection),
+            None => self
+                .selection
+                .map(|selection| selection.map(&other.changes)),
+        };
         self.changes = self.changes.compose(other.changes);
-        // Other selection takes precedence
-        self.selection = other.selection;
         self
     }
 
@@ -848,6 +855,44 @@ fn composition() {
         assert_eq!(text, "世orld! abc");
     }
 
+    #[test]
+    fn transaction_composition() {
+        use crate::history::History;
+
+        let original = Rope::from("hello world\n");
+        let a = Transaction::change(&original, [(0, 0, Some("> ".into()))].into_iter())
+            .with_selection(Selection::point(2));
+        let mut after_a = original.clone();
+        assert!(a.apply(&mut after_a));
+        // `b` is built against the document produced by `a`.
+        let b = Transaction::change(&after_a, [(13, 13, Some("!".into()))].into_iter());
+        let mut expected = after_a.clone();
+        assert!(b.apply(&mut expected));
+        assert_eq!(expected, "> hello world!\n");
+
+        let composed = a.clone().compose(b.clone());
+        let mut doc = original.clone();
+        assert!(composed.apply(&mut doc));
+        assert_eq!(doc, expected);
+        // The selection of `a` is mapped through `b`.
+        assert_eq!(composed.selection(), Some(&Selection::point(2)));
+        let c = Transaction::change(&doc, [(0, 0, Some("# ".into()))].into_iter());
+        let selection = composed.clone().compose(c).selection().cloned();
+        assert_eq!(selection, Some(Selection::point(4)));
+
+        // The composed transaction is undone as a single revision.
+        let mut history = History::default();
+        let state = State {
+            doc: original.clone(),
+            selection: Selection::point(0),
+        };
+        history.commit_revision(&composed, &state);
+        let undo = history.undo().unwrap();
+        assert!(undo.apply(&mut doc));
+        assert_eq!(doc, original);
+        assert!(history.at_root());
+    }
+
     #[test]
     fn invert() {
         use Operation::*;
@@ -0,0 +1,282 @@
+//! Named locations in each project, kept in the state directory.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The maximum number of bookmarks per project.
+pub const MAX_BOOKMARKS: usize = 100;
+
+/// A named location in a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    /// The path relative to the project root, so that bookmarks survive moving the project.
+    pub path: PathBuf,
+    /// The 1-based line.
+    pub line: usize,
+    /// The 1-based column, counted in graphemes.
+    pub column: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Bookmarks {
+    /// The project the bookmarks belong to, used to detect file name collisions.
+    project: PathBuf,
+    #[serde(default)]
+    bookmarks: Vec<Bookmark>,
+}
+
+fn bookmarks_dir() -> PathBuf {
+    crate::state_dir().join("bookmarks")
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn load(dir: &Path, project: &Path) -> Bookmarks {
+    let file_path = dir.join(crate::session::session_file_name(project));
+    let empty = || Bookmarks {
+        project: project.to_path_buf(),
+        bookmarks: Vec::new(),
+    };
+    let Ok(content) = std::fs::read_to_string(&file_path) else {
+        return empty();
+    };
+    match toml::from_str::<Bookmarks>(&content) {
+        Ok(mut bookmarks) if bookmarks.project == project => {
+            bookmarks.bookmarks.truncate(MAX_BOOKMARKS);
+            bookmarks
+        }
+        Ok(_) => empty(),
+        Err(err) => {
+            log::warn!("Failed to parse bookmarks {}: {err}", file_path.display());
+            empty()
+        }
+    }
+}
+
+fn save(dir: &Path, bookmarks: &Bookmarks) -> Result<()> {
+    let file_path = dir.join(crate::session::session_file_name(&bookmarks.project));
+    crate::ensure_parent_dir(&file_path);
+    let content = toml::to_string_pretty(bookmarks).context("Failed to serialize bookmarks")?;
+
+    let mut file = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temporary file in: {}", dir.display()))?;
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write bookmarks: {}", file_path.display()))?;
+    file.persist(&file_path)
+        .with_context(|| format!("Failed to write bookmarks: {}", file_path.display()))?;
+    Ok(())
+}
+
+/// The bookmarks of the project at `project`, ordered by name.
+pub fn list_bookmarks(project: &Path) -> Vec<Bookmark> {
+    list_bookmarks_in(&bookmarks_dir(), &canonicalize(project))
+}
+
+fn list_bookmarks_in(dir: &Path, project: &Path) -> Vec<Bookmark> {
+    let mut bookmarks = load(dir, project).bookmarks;
+    bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+    bookmarks
+}
+
+/// Bookmarks the 1-based `line` and `column` of the file at `path` as `name` in the project at
+/// `project`, replacing the bookmark with that name if there is one.
+///
+/// Fails if `path` is outside of the project or the project has [MAX_BOOKMARKS] already.
+pub fn add_bookmark(
+    project: &Path,
+    name: &str,
+    path: &Path,
+    line: usize,
+    column: usize,
+) -> Result<()> {
+    let project = canonicalize(project);
+    let path = canonicalize(path);
+    add_bookmark_in(&bookmarks_dir(), &project, name, &path, line, column)
+}
+
+fn add_bookmark_in(
+    dir: &Path,
+    project: &Path,
+    name: &str,
+    path: &Path,
+    line: usize,
+    column: usize,
+) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("Bookmark names can't be empty");
+    }
+    let relative = match path.strip_prefix(project) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative,
+        _ => bail!("{} is outside of the project", path.display()),
+    };
+    let bookmark = Bookmark {
+        name: name.to_string(),
+        path: relative.to_path_buf(),
+        line: line.max(1),
+        column: column.max(1),
+    };
+
+    let mut bookmarks = load(dir, project);
+    match bookmarks.bookmarks.iter().position(|b| b.name == name) {
+        Some(i) => bookmarks.bookmarks[i] = bookmark,
+        None if bookmarks.bookmarks.len() >= MAX_BOOKMARKS => {
+            bail!("Projects can't have more than {MAX_BOOKMARKS} bookmarks")
+        }
+        None => bookmarks.bookmarks.push(bookmark),
+    }
+    save(dir, &bookmarks)
+}
+
+/// Removes the bookmark `name` of the project at `project`. Returns `false` if there is no such
+/// bookmark.
+pub fn remove_bookmark(project: &Path, name: &str) -> Result<bool> {
+    remove_bookmark_in(&bookmarks_dir(), &canonicalize(project), name)
+}
+
+fn remove_bookmark_in(dir: &Path, project: &Path, name: &str) -> Result<bool> {
+    let mut bookmarks = load(dir, project);
+    let len = bookmarks.bookmarks.len();
+    bookmarks.bookmarks.retain(|bookmark| bookmark.name != name);
+    if bookmarks.bookmarks.len() == len {
+        return Ok(false);
+    }
+    save(dir, &bookmarks)?;
+    Ok(true)
+}
+
+/// Moves the bookmarks of the project at `from` to the project at `to`, after the project was
+/// moved. Bookmarks of `to` with the same names are kept.
+pub fn move_bookmarks(from: &Path, to: &Path) -> Result<()> {
+    move_bookmarks_in(&bookmarks_dir(), from, &canonicalize(to))
+}
+
+fn move_bookmarks_in(dir: &Path, from: &Path, to: &Path) -> Result<()> {
+    let moved = load(dir, from);
+    if moved.bookmarks.is_empty() {
+        return Ok(());
+    }
+    let mut bookmarks = load(dir, to);
+    for bookmark in moved.bookmarks {
+        if bookmarks.bookmarks.len() >= MAX_BOOKMARKS {
+            break;
+        }
+        if !bookmarks.bookmarks.iter().any(|b| b.name == bookmark.name) {
+            bookmarks.bookmarks.push(bookmark);
+        }
+    }
+    save(dir, &bookmarks)?;
+    let file_path = dir.join(crate::session::session_file_name(from));
+    std::fs::remove_file(&file_path)
+        .with_context(|| format!("Failed to remove bookmarks: {}", file_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_bookmarks() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = PathBuf::from("/src/game");
+        add_bookmark_in(dir.path(), &project, "todo", &project.join("TODO.md"), 3, 1).unwrap();
+        add_bookmark_in(
+            dir.path(),
+            &project,
+            "dispatch",
+            &project.join("src/main.rs"),
+            120,
+            5,
+        )
+        .unwrap();
+        let expected = vec![
+            Bookmark {
+                name: "dispatch".into(),
+                path: PathBuf::from("src/main.rs"),
+                line: 120,
+                column: 5,
+            },
+            Bookmark {
+                name: "todo".into(),
+                path: PathBuf::from("TODO.md"),
+                line: 3,
+                column: 1,
+            },
+        ];
+        assert_eq!(list_bookmarks_in(dir.path(), &project), expected);
+
+        // Paths are stored relative to the project.
+        let content =
+            std::fs::read_to_string(dir.path().join(crate::session::session_file_name(&project)))
+                .unwrap();
+        assert!(content.contains("path = \"src/main.rs\""), "{content}");
+
+        // Bookmarks are only loaded for the project they were saved for.
+        assert!(list_bookmarks_in(dir.path(), Path::new("/src/other")).is_empty());
+
+        // A corrupt file is ignored rather than failing.
+        std::fs::write(
+            dir.path().join(crate::session::session_file_name(&project)),
+            "bookmarks = 1\n",
+        )
+        .unwrap();
+        assert!(list_bookmarks_in(dir.path(), &project).is_empty());
+    }
+
+    #[test]
+    fn replace_and_remove_bookmarks() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = PathBuf::from("/src/game");
+        let main = project.join("src/main.rs");
+        add_bookmark_in(dir.path(), &project, "main", &main, 1, 1).unwrap();
+        add_bookmark_in(dir.path(), &project, " main ", &main, 10, 2).unwrap();
+        let bookmarks = list_bookmarks_in(dir.path(), &project);
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!((bookmarks[0].line, bookmarks[0].column), (10, 2));
+
+        assert!(add_bookmark_in(dir.path(), &project, "", &main, 1, 1).is_err());
+        assert!(
+            add_bookmark_in(dir.path(), &project, "etc", Path::new("/etc/hosts"), 1, 1).is_err()
+        );
+
+        assert!(remove_bookmark_in(dir.path(), &project, "main").unwrap());
+        assert!(!remove_bookmark_in(dir.path(), &project, "main").unwrap());
+        assert!(list_bookmarks_in(dir.path(), &project).is_empty());
+    }
+
+    #[test]
+    fn cap_bookmarks() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = PathBuf::from("/src/game");
+        let main = project.join("src/main.rs");
+        for i in 0..MAX_BOOKMARKS {
+            add_bookmark_in(dir.path(), &project, &i.to_string(), &main, i + 1, 1).unwrap();
+        }
+        assert!(add_bookmark_in(dir.path(), &project, "more", &main, 1, 1).is_err());
+        // Existing bookmarks can still be replaced.
+        add_bookmark_in(dir.path(), &project, "0", &main, 5, 1).unwrap();
+        assert_eq!(list_bookmarks_in(dir.path(), &project).len(), MAX_BOOKMARKS);
+    }
+
+    #[test]
+    fn move_bookmarks_with_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = PathBuf::from("/code/game");
+        let to = PathBuf::from("/src/game");
+        add_bookmark_in(dir.path(), &from, "main", &from.join("src/main.rs"), 2, 1).unwrap();
+        add_bookmark_in(dir.path(), &from, "todo", &from.join("TODO.md"), 1, 1).unwrap();
+        add_bookmark_in(dir.path(), &to, "todo", &to.join("NOTES.md"), 1, 1).unwrap();
+
+        move_bookmarks_in(dir.path(), &from, &to).unwrap();
+        assert!(list_bookmarks_in(dir.path(), &from).is_empty());
+        let moved = list_bookmarks_in(dir.path(), &to);
+        assert_eq!(moved.len(), 2);
+        assert_eq!(moved[0].path, Path::new("src/main.rs"));
+        assert_eq!(moved[1].path, Path::new("NOTES.md"));
+    }
+}
@@ -1,11 +1,18 @@
+pub mod bookmarks;
 pub mod config;
 pub mod grammar;
+pub mod project_state;
 pub mod projects;
+pub mod recent_files;
+pub mod session;
 
 use helix_stdx::{env::current_working_dir, path};
 
 use etcetera::base_strategy::{choose_base_strategy, BaseStrategy};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 pub const VERSION_AND_GIT_HASH: &str = env!("VERSION_AND_GIT_HASH");
 
@@ -133,6 +140,14 @@ pub fn cache_dir() -> PathBuf {
     path
 }
 
+pub fn state_dir() -> PathBuf {
+    let strategy = choose_base_strategy().expect("Unable to find the state directory!");
+    // Only some platforms have a dedicated state directory.
+    let mut path = strategy.state_dir().unwrap_or_else(|| strategy.data_dir());
+    path.push("helix");
+    path
+}
+
 pub fn config_file() -> PathBuf {
     CONFIG_FILE.get().map(|path| path.to_path_buf()).unwrap()
 }
@@ -141,8 +156,14 @@ pub fn log_file() -> PathBuf {
     LOG_FILE.get().map(|path| path.to_path_buf()).unwrap()
 }
 
+/// The `.helix/config.toml` of the workspace. This is the nearest one above the current working
+/// directory, which may be above the workspace root if it sets `helix.workspace-markers`.
 pub fn workspace_config_file() -> PathBuf {
-    find_workspace().0.join(".helix").join("config.toml")
+    let cwd = current_working_dir();
+    cwd.ancestors()
+        .map(|dir| dir.join(".helix").join("config.toml"))
+        .find(|file| file.is_file())
+        .unwrap_or_else(|| find_workspace().0.join(".helix").join("config.toml"))
 }
 
 pub fn lang_config_file() -> PathBuf {
@@ -240,7 +261,7 @@ fn get_name(v: &Value) -> Option<&str> {
 /// Used as a ceiling dir for LSP root resolution, the filepicker and potentially as a future filewatching root
 ///
 /// This function starts searching the FS upward from the CWD
-/// and returns the first directory that contains either `.git`, `.svn`, `.jj` or `.helix`.
+/// and returns the workspace root found by [find_workspace_root].
 /// If no workspace was found returns (CWD, true).
 /// Otherwise (workspace, false) is returned
 pub fn find_workspace() -> (PathBuf, bool) {
@@ -250,17 +271,121 @@ pub fn find_workspace() -> (PathBuf, bool) {
 
 pub fn find_workspace_in(dir: impl AsRef<Path>) -> (PathBuf, bool) {
     let dir = dir.as_ref();
-    for ancestor in dir.ancestors() {
-        if ancestor.join(".git").exists()
-            || ancestor.join(".svn").exists()
-            || ancestor.join(".jj").exists()
-            || ancestor.join(".helix").exists()
+    match find_workspace_marker(dir) {
+        Some(root) => (root.to_owned(), false),
+        None => (dir.to_owned(), true),
+    }
+}
+
+/// Returns the root of the workspace containing `start`, or `start` itself if there is none.
+///
+/// This is the closest ancestor of `start` containing one of these markers, which are checked
+/// in this order within each directory:
+///
+/// 1. a `.helix` directory, such as the one holding a `.helix/config.toml`
+/// 2. the version control directories `.git`, `.hg`, `.fossil` or `.fslckout`, `.jj` and `.svn`
+/// 3. a `Cargo.toml` with a `[workspace]` table
+/// 4. a `pyproject.toml` with a `[tool.hatch.workspace]` table
+/// 5. a file listed in the `helix.workspace-markers` of the closest `.helix/config.toml`
+///
+/// So a repository nested in another workspace is a root of its own, and the custom markers
+/// split the workspace of a `.helix/config.toml` into the directories below it.
+pub fn find_workspace_root(start: &Path) -> PathBuf {
+    find_workspace_marker(start).unwrap_or(start).to_owned()
+}
+
+/// The version control directories marking a workspace root, see [find_workspace_root].
+const VCS_MARKERS: &[&str] = &[".git", ".hg", ".fossil", ".fslckout", ".jj", ".svn"];
+
+fn find_workspace_marker(start: &Path) -> Option<&Path> {
+    // The markers only apply below the config, which is found as a root itself before any
+    // directory above it.
+    let custom_markers = start
+        .ancestors()
+        .map(|dir| dir.join(".helix").join("config.toml"))
+        .find(|config| config.is_file())
+        .map(|config| custom_workspace_markers(&config))
+        .unwrap_or_default();
+
+    start.ancestors().find(|dir| {
+        dir.join(".helix").is_dir()
+            || VCS_MARKERS.iter().any(|marker| dir.join(marker).exists())
+            || toml_has_table(&dir.join("Cargo.toml"), &["workspace"])
+            || toml_has_table(&dir.join("pyproject.toml"), &["tool", "hatch", "workspace"])
+            || custom_markers
+                .iter()
+                .any(|marker| dir.join(marker).exists())
+    })
+}
+
+/// Reads the `helix.workspace-markers` list of the workspace config file at `path`.
+fn custom_workspace_markers(path: &Path) -> Vec<String> {
+    let Some(config) = read_toml_cached(path) else {
+        return Vec::new();
+    };
+    config
+        .get("helix")
+        .and_then(|helix| helix.get("workspace-markers"))
+        .and_then(|markers| markers.as_array())
+        .map(|markers| {
+            markers
+                .iter()
+                .filter_map(|marker| marker.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the TOML file at `path` exists and contains the table at `keys`.
+fn toml_has_table(path: &Path, keys: &[&str]) -> bool {
+    let Some(value) = read_toml_cached(path) else {
+        return false;
+    };
+    keys.iter()
+        .try_fold(&*value, |value, key| value.get(key))
+        .is_some_and(toml::Value::is_table)
+}
+
+/// A TOML file read by [find_workspace_marker], along with the modification time and length it
+/// was read at.
+struct CachedToml {
+    modified: Option<SystemTime>,
+    len: u64,
+    value: Option<Arc<toml::Value>>,
+}
+
+/// The TOML files read by [find_workspace_marker]. The workspace is looked up for every opened
+/// document, so the files are only parsed again once they change.
+static WORKSPACE_TOML: once_cell::sync::Lazy<Mutex<HashMap<PathBuf, CachedToml>>> =
+    once_cell::sync::Lazy::new(Default::default);
+
+fn read_toml_cached(path: &Path) -> Option<Arc<toml::Value>> {
+    let metadata = std::fs::metadata(path).ok().filter(|meta| meta.is_file())?;
+    let modified = metadata.modified().ok();
+    let mut cache = WORKSPACE_TOML.lock().unwrap();
+    if let Some(cached) = cache.get(path) {
+        if cached.modified.is_some() && cached.modified == modified && cached.len == metadata.len()
         {
-            return (ancestor.to_owned(), false);
+            return cached.value.clone();
         }
     }
+    let value = read_toml(path).map(Arc::new);
+    cache.insert(
+        path.to_owned(),
+        CachedToml {
+            modified,
+            len: metadata.len(),
+            value: value.clone(),
+        },
+    );
+    value
+}
 
-    (dir.to_owned(), true)
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    toml::from_str(&content)
+        .map_err(|err| log::warn!("Failed to parse {}: {err}", path.display()))
+        .ok()
 }
 
 fn default_config_file() -> PathBuf {
@@ -347,3 +472,156 @@ fn language_toml_nested_array_merges() {
         )
     }
 }
+
+#[cfg(test)]
+mod workspace_tests {
+    use super::find_workspace_root;
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn no_markers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let start = tmp.path().join("a/b");
+        fs::create_dir_all(&start).unwrap();
+        assert_eq!(find_workspace_root(&start), start);
+    }
+
+    #[test]
+    fn vcs_markers() {
+        for marker in [".git", ".hg", ".fossil"] {
+            let tmp = tempfile::tempdir().unwrap();
+            let root = tmp.path().join("repo");
+            let start = root.join("src/nested");
+            fs::create_dir_all(&start).unwrap();
+            fs::create_dir(root.join(marker)).unwrap();
+            assert_eq!(find_workspace_root(&start), root, "{marker}");
+        }
+    }
+
+    #[test]
+    fn helix_config_marker() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(root, ".helix/config.toml", "");
+        let start = root.join("sub/src");
+        fs::create_dir_all(&start).unwrap();
+        assert_eq!(find_workspace_root(&start), root);
+
+        // A `.helix` directory without a config is a marker as well.
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join(".helix")).unwrap();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        assert_eq!(find_workspace_root(&root.join("sub")), root);
+    }
+
+    #[test]
+    fn nested_repository() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(root, ".helix/config.toml", "");
+        fs::create_dir_all(root.join("vendor/lib/.git")).unwrap();
+        let start = root.join("vendor/lib/src");
+        fs::create_dir_all(&start).unwrap();
+        // The closest marker wins, even if a marker further up comes first in the list.
+        assert_eq!(find_workspace_root(&start), root.join("vendor/lib"));
+        assert_eq!(find_workspace_root(&root.join("vendor")), root);
+    }
+
+    #[test]
+    fn cargo_workspace_marker() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        );
+        write(root, "crates/foo/Cargo.toml", "[package]\nname = \"foo\"\n");
+        let start = root.join("crates/foo");
+        assert_eq!(find_workspace_root(&start), root);
+
+        // A crate outside of a workspace isn't a root.
+        let tmp = tempfile::tempdir().unwrap();
+        write(tmp.path(), "foo/Cargo.toml", "[package]\nname = \"foo\"\n");
+        let start = tmp.path().join("foo");
+        assert_eq!(find_workspace_root(&start), start);
+    }
+
+    #[test]
+    fn hatch_workspace_marker() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(
+            root,
+            "pyproject.toml",
+            "[tool.hatch.workspace]\nmembers = [\"pkgs/*\"]\n",
+        );
+        write(
+            root,
+            "pkgs/foo/pyproject.toml",
+            "[project]\nname = \"foo\"\n",
+        );
+        let start = root.join("pkgs/foo");
+        assert_eq!(find_workspace_root(&start), root);
+    }
+
+    #[test]
+    fn closest_marker() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir(root.join(".git")).unwrap();
+        write(root, "crates/Cargo.toml", "[workspace]\n");
+        write(root, "crates/foo/Cargo.toml", "[package]\nname = \"foo\"\n");
+        assert_eq!(
+            find_workspace_root(&root.join("crates/foo")),
+            root.join("crates")
+        );
+        assert_eq!(find_workspace_root(root), root);
+    }
+
+    #[test]
+    fn custom_markers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(
+            root,
+            ".helix/config.toml",
+            "[helix]\nworkspace-markers = [\"package.json\"]\n",
+        );
+        write(root, "apps/web/package.json", "{}");
+        let start = root.join("apps/web/src");
+        fs::create_dir_all(&start).unwrap();
+        assert_eq!(find_workspace_root(&start), root.join("apps/web"));
+        assert_eq!(find_workspace_root(&root.join("apps")), root);
+
+        // A repository nested below a custom marker is its own root.
+        fs::create_dir_all(root.join("apps/web/vendor/.git")).unwrap();
+        let start = root.join("apps/web/vendor/src");
+        fs::create_dir_all(&start).unwrap();
+        assert_eq!(find_workspace_root(&start), root.join("apps/web/vendor"));
+
+        // The markers are read again once the config changes.
+        write(
+            root,
+            ".helix/config.toml",
+            "[helix]\nworkspace-markers = [\"deno.json\"]\n",
+        );
+        let config = fs::File::options()
+            .write(true)
+            .open(root.join(".helix/config.toml"))
+            .unwrap();
+        config
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(find_workspace_root(&root.join("apps/web/src")), root);
+    }
+}
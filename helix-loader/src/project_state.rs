@@ -0,0 +1,166 @@
+//! Small pieces of state remembered for each project, kept in the state directory.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The state remembered for a project.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectState {
+    /// The project the state belongs to, used to detect file name collisions.
+    pub project: PathBuf,
+    /// The file focused when last leaving the project, relative to the project root so that it
+    /// survives moving the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_file: Option<PathBuf>,
+    /// The 1-based line and column, counted in graphemes, of the cursor in `last_file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_position: Option<(usize, usize)>,
+}
+
+fn project_state_dir() -> PathBuf {
+    crate::state_dir().join("project-state")
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn load(dir: &Path, project: &Path) -> ProjectState {
+    let file_path = dir.join(crate::session::session_file_name(project));
+    let empty = || ProjectState {
+        project: project.to_path_buf(),
+        ..Default::default()
+    };
+    let Ok(content) = std::fs::read_to_string(&file_path) else {
+        return empty();
+    };
+    match toml::from_str::<ProjectState>(&content) {
+        Ok(state) if state.project == project => state,
+        Ok(_) => empty(),
+        Err(err) => {
+            log::warn!(
+                "Failed to parse project state {}: {err}",
+                file_path.display()
+            );
+            empty()
+        }
+    }
+}
+
+fn save(dir: &Path, state: &ProjectState) -> Result<()> {
+    let file_path = dir.join(crate::session::session_file_name(&state.project));
+    crate::ensure_parent_dir(&file_path);
+    let content = toml::to_string_pretty(state).context("Failed to serialize project state")?;
+
+    let mut file = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temporary file in: {}", dir.display()))?;
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write project state: {}", file_path.display()))?;
+    file.persist(&file_path)
+        .with_context(|| format!("Failed to write project state: {}", file_path.display()))?;
+    Ok(())
+}
+
+/// The state of the project at `project`.
+pub fn load_project_state(project: &Path) -> ProjectState {
+    load(&project_state_dir(), &canonicalize(project))
+}
+
+/// Remembers the file at `path` as the last file focused in the project at `project`, with the
+/// cursor at the 1-based `line` and `column`. Files outside of the project are ignored.
+pub fn save_last_file(project: &Path, path: &Path, line: usize, column: usize) -> Result<()> {
+    let project = canonicalize(project);
+    let path = canonicalize(path);
+    save_last_file_in(&project_state_dir(), &project, &path, line, column)
+}
+
+fn save_last_file_in(
+    dir: &Path,
+    project: &Path,
+    path: &Path,
+    line: usize,
+    column: usize,
+) -> Result<()> {
+    let relative = match path.strip_prefix(project) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative,
+        _ => return Ok(()),
+    };
+    let mut state = load(dir, project);
+    state.last_file = Some(relative.to_path_buf());
+    state.last_position = Some((line.max(1), column.max(1)));
+    save(dir, &state)
+}
+
+/// Moves the state of the project at `from` to the project at `to`, after the project was moved.
+/// The state of `to` is kept if it has any.
+pub fn move_project_state(from: &Path, to: &Path) -> Result<()> {
+    move_project_state_in(&project_state_dir(), from, &canonicalize(to))
+}
+
+fn move_project_state_in(dir: &Path, from: &Path, to: &Path) -> Result<()> {
+    let file_path = dir.join(crate::session::session_file_name(from));
+    if !file_path.exists() {
+        return Ok(());
+    }
+    if load(dir, to).last_file.is_none() {
+        let state = ProjectState {
+            project: to.to_path_buf(),
+            ..load(dir, from)
+        };
+        save(dir, &state)?;
+    }
+    std::fs::remove_file(&file_path)
+        .with_context(|| format!("Failed to remove project state: {}", file_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_last_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = PathBuf::from("/src/game");
+        assert_eq!(load(dir.path(), &project).last_file, None);
+
+        save_last_file_in(dir.path(), &project, &project.join("src/main.rs"), 12, 5).unwrap();
+        let state = load(dir.path(), &project);
+        assert_eq!(state.last_file.as_deref(), Some(Path::new("src/main.rs")));
+        assert_eq!(state.last_position, Some((12, 5)));
+
+        // Files outside of the project don't replace the last file.
+        save_last_file_in(dir.path(), &project, Path::new("/etc/hosts"), 1, 1).unwrap();
+        assert_eq!(load(dir.path(), &project), state);
+
+        // The state is only loaded for the project it was saved for.
+        assert_eq!(load(dir.path(), Path::new("/src/other")).last_file, None);
+
+        // A corrupt file is ignored rather than failing.
+        std::fs::write(
+            dir.path().join(crate::session::session_file_name(&project)),
+            "last_position = 1\n",
+        )
+        .unwrap();
+        assert_eq!(load(dir.path(), &project).last_file, None);
+    }
+
+    #[test]
+    fn move_project_state_with_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = PathBuf::from("/code/game");
+        let to = PathBuf::from("/src/game");
+        save_last_file_in(dir.path(), &from, &from.join("src/main.rs"), 3, 1).unwrap();
+
+        move_project_state_in(dir.path(), &from, &to).unwrap();
+        assert_eq!(load(dir.path(), &from).last_file, None);
+        let state = load(dir.path(), &to);
+        assert_eq!(state.project, to);
+        assert_eq!(state.last_file.as_deref(), Some(Path::new("src/main.rs")));
+        assert_eq!(state.last_position, Some((3, 1)));
+
+        // Nothing to move.
+        move_project_state_in(dir.path(), &from, &to).unwrap();
+    }
+}
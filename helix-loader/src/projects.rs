@@ -1,147 +1,2970 @@
-use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashSet;
-use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::ffi::{OsStr, OsString};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+mod ambient;
+mod edit;
+mod error;
+mod ignores;
+mod import;
+mod languages;
+mod readme;
+mod relocate;
+mod retention;
+mod store;
+mod templates;
+
+pub use ambient::{ambient_roots, find_ambient_project, parse_project_paths, PROJECT_PATHS_VAR};
+use error::BoxError;
+pub use error::{EntryError, ProjectsError};
+pub use ignores::project_ignore_matcher;
+pub use import::{
+    import_projects, merge_projects, vscode_storage_path, ImportSource, ImportedProjects,
+};
+pub use languages::{detect_project_languages, refresh_project_languages};
+pub use readme::find_readme;
+pub use relocate::{origin_remote, relocate_missing_projects, relocate_project, Relocation};
+pub use retention::stale_projects;
+pub use store::{ProjectsEvent, ProjectsStore, FLUSH_INTERVAL};
+pub use templates::{create_project_from_template, list_templates, templates_dir};
+
+type Result<T, E = ProjectsError> = std::result::Result<T, E>;
+
+/// How long to wait for another instance to release the projects file lock.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Lock files older than this are assumed to be left behind by a crashed instance.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10);
+/// How long [check_projects] waits for the directory of a project, so that an unresponsive
+/// network mount can't block the caller.
+const STATUS_TIMEOUT: Duration = Duration::from_millis(500);
+/// How far in the future, in seconds, an access time may be before it is taken for the time of
+/// a wrong clock, see [Project::last_accessed_at].
+const MAX_CLOCK_SKEW: u64 = 24 * 60 * 60;
+
+static FILE_ORDER: RwLock<ProjectsFileOrder> = RwLock::new(ProjectsFileOrder::Path);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Project {
+    #[serde(
+        serialize_with = "serialize_path",
+        deserialize_with = "deserialize_path"
+    )]
     pub path: PathBuf,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_accessed: Option<u64>,
+    /// How often the project was accessed, used together with `last_accessed` to rank projects.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub access_count: u32,
+    /// Archived projects are left out of listings and matching unless asked for, but keep their
+    /// metadata and are still found by [find_project_for_path].
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub archived: bool,
+    /// The main languages of the project, named as in `languages.toml`, see
+    /// [detect_project_languages].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// When `languages` were detected, used to detect them again after the project changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub languages_detected: Option<u64>,
+    /// A shell command run in the project root after switching to the project. Takes the place of
+    /// the `editor.project-hooks.on-open` default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_open: Option<String>,
+    /// A shell command run in the project root when switching away from the project. Takes the
+    /// place of the `editor.project-hooks.on-close` default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_close: Option<String>,
+    /// The URL of the `origin` remote of the repository, cached when the project is registered
+    /// so that [relocate_missing_projects] can find the project again after it was moved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// Gitignore-style patterns, relative to the project root, of the files that the file picker
+    /// and global search leave out, see [project_ignore_matcher].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// The theme used while the project is the current one, in place of the `theme` of the
+    /// config.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+}
+
+/// The stored form of a project path. Paths that aren't valid UTF-8 are stored as their raw
+/// bytes, or UTF-16 code units on Windows, so that they round-trip exactly.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredPath {
+    Utf8(String),
+    Raw(Vec<RawPathUnit>),
+}
+
+#[cfg(windows)]
+type RawPathUnit = u16;
+#[cfg(not(windows))]
+type RawPathUnit = u8;
+
+impl From<&Path> for StoredPath {
+    fn from(path: &Path) -> Self {
+        if let Some(path) = path.to_str() {
+            return Self::Utf8(path.to_string());
+        }
+        #[cfg(unix)]
+        let raw = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+        #[cfg(windows)]
+        let raw = std::os::windows::ffi::OsStrExt::encode_wide(path.as_os_str()).collect();
+        #[cfg(not(any(unix, windows)))]
+        let raw = path.to_string_lossy().into_owned().into_bytes();
+        Self::Raw(raw)
+    }
+}
+
+impl From<StoredPath> for PathBuf {
+    fn from(path: StoredPath) -> Self {
+        match path {
+            StoredPath::Utf8(path) => path.into(),
+            #[cfg(unix)]
+            StoredPath::Raw(raw) => {
+                <OsString as std::os::unix::ffi::OsStringExt>::from_vec(raw).into()
+            }
+            #[cfg(windows)]
+            StoredPath::Raw(raw) => {
+                <OsString as std::os::windows::ffi::OsStringExt>::from_wide(&raw).into()
+            }
+            #[cfg(not(any(unix, windows)))]
+            StoredPath::Raw(raw) => String::from_utf8_lossy(&raw).into_owned().into(),
+        }
+    }
+}
+
+fn serialize_path<S: serde::Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    StoredPath::from(simplify(&clean(path)).as_path()).serialize(serializer)
+}
+
+fn deserialize_path<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<PathBuf, D::Error> {
+    StoredPath::deserialize(deserializer).map(|path| clean(&PathBuf::from(path)))
+}
+
+fn is_zero(count: &u32) -> bool {
+    *count == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl Project {
+    /// Creates a project for `path`, named after its final path component, with the `origin`
+    /// remote of its repository if it is one.
+    pub fn new(path: PathBuf) -> Self {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|s| s.to_string());
+        Self {
+            name,
+            last_accessed: None,
+            access_count: 0,
+            archived: false,
+            languages: Vec::new(),
+            languages_detected: None,
+            on_open: None,
+            on_close: None,
+            remote: origin_remote(&path),
+            ignore: Vec::new(),
+            theme: None,
+            path,
+        }
+    }
+
+    /// When the project was last accessed, as seen at unix time `now`. This is the time used to
+    /// rank, sort and archive projects.
+    ///
+    /// A time of 0, which is recorded while the system clock is before the epoch, counts as
+    /// never accessed. A time more than a day after `now`, as recorded by a machine with a wrong
+    /// clock, counts as `now` so that the project doesn't stay on top of every recency sort.
+    pub fn last_accessed_at(&self, now: u64) -> Option<u64> {
+        match self.last_accessed? {
+            0 => None,
+            last_accessed if last_accessed > now.saturating_add(MAX_CLOCK_SKEW) => Some(now),
+            last_accessed => Some(last_accessed),
+        }
+    }
+
+    /// Ranks the project by how often and how recently it was accessed at unix time `now`.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let Some(last_accessed) = self.last_accessed_at(now) else {
+            return 0.0;
+        };
+        let age = now.saturating_sub(last_accessed);
+        let weight = match age {
+            0..=3_599 => 4.0,
+            3_600..=86_399 => 2.0,
+            86_400..=604_799 => 0.5,
+            _ => 0.25,
+        };
+        // Projects accessed before access counts were recorded count as accessed once.
+        f64::from(self.access_count.max(1)) * weight
+    }
+
+    /// The name of the project, falling back to its final path component.
+    pub fn display_name(&self) -> Option<&str> {
+        self.name
+            .as_deref()
+            .or_else(|| self.path.file_name().and_then(|n| n.to_str()))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ProjectsFile {
+    #[serde(default, deserialize_with = "deserialize_projects")]
     projects: Vec<Project>,
 }
 
+/// Deserializes the projects list, skipping entries that can't be decoded so that a single bad
+/// entry doesn't make the whole file unreadable.
+fn deserialize_projects<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Project>, D::Error> {
+    let entries = Vec::<toml::Value>::deserialize(deserializer)?;
+    let projects = entries
+        .into_iter()
+        .filter_map(|entry| match entry.try_into() {
+            Ok(project) => Some(project),
+            Err(err) => {
+                log::warn!("Skipping invalid entry in the projects file: {err}");
+                None
+            }
+        })
+        .collect();
+    Ok(projects)
+}
+
+/// The profile selected by the editor config, see [active_profile].
+static PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Selects the profile used by the functions without an explicit projects file, unless
+/// `$HELIX_PROJECT_PROFILE` is set. `None` selects the default profile.
+pub fn set_profile(profile: Option<String>) {
+    *PROFILE.write().unwrap() = profile;
+}
+
+/// The active profile: `$HELIX_PROJECT_PROFILE` if set, or the one selected by [set_profile].
+/// `None` is the default profile.
+pub fn active_profile() -> Option<String> {
+    choose_profile(
+        std::env::var("HELIX_PROJECT_PROFILE").ok(),
+        PROFILE.read().unwrap().clone(),
+    )
+}
+
+/// The profile `env`, the value of `$HELIX_PROJECT_PROFILE`, unless it's empty, or else the
+/// `selected` one, see [active_profile].
+fn choose_profile(env: Option<String>, selected: Option<String>) -> Option<String> {
+    match env {
+        Some(profile) if !profile.is_empty() => Some(profile),
+        _ => selected,
+    }
+    .filter(|profile| profile != DEFAULT_PROFILE)
+}
+
+/// The name of the profile stored in the projects file without a profile suffix.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The path of the projects file of the [active profile](active_profile), see
+/// [profile_file_path].
 pub fn projects_file_path() -> PathBuf {
-    crate::config_dir().join("projects.toml")
+    profile_file_path(active_profile().as_deref())
+}
+
+/// The path of the projects file of `profile`: `$HELIX_PROJECTS_FILE` if set, or
+/// `projects.toml` in the config directory, for the default profile. Other profiles are
+/// stored next to it, in `projects.<profile>.toml` for example.
+pub fn profile_file_path(profile: Option<&str>) -> PathBuf {
+    profile_file_path_from(std::env::var_os("HELIX_PROJECTS_FILE").as_deref(), profile)
+}
+
+/// Like [profile_file_path], with `env` in place of the value of `$HELIX_PROJECTS_FILE`.
+fn profile_file_path_from(env: Option<&OsStr>, profile: Option<&str>) -> PathBuf {
+    let default = match env {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => crate::config_dir().join("projects.toml"),
+    };
+    sibling_profile_file_path(&default, profile)
+}
+
+/// The path of the projects file of `profile`, next to the projects file of the default profile
+/// at `default`.
+fn sibling_profile_file_path(default: &Path, profile: Option<&str>) -> PathBuf {
+    match profile.filter(|profile| *profile != DEFAULT_PROFILE) {
+        Some(profile) => {
+            let mut name = default.file_stem().unwrap_or_default().to_owned();
+            name.push(format!(".{profile}"));
+            if let Some(extension) = default.extension() {
+                name.push(".");
+                name.push(extension);
+            }
+            default.with_file_name(name)
+        }
+        None => default.to_path_buf(),
+    }
 }
 
+/// The profiles with a projects file, starting with the default profile as `None` followed by
+/// the others sorted by name.
+pub fn list_profiles() -> Vec<Option<String>> {
+    list_profiles_in(&profile_file_path(None))
+}
+
+/// Like [list_profiles], for the profiles stored next to the projects file at `default`.
+fn list_profiles_in(default: &Path) -> Vec<Option<String>> {
+    let (Some(stem), Some(dir)) = (
+        default.file_stem().and_then(|stem| stem.to_str()),
+        default.parent(),
+    ) else {
+        return vec![None];
+    };
+    let extension = default
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| format!(".{extension}"))
+        .unwrap_or_default();
+    let mut profiles: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            let profile = name
+                .strip_prefix(stem)?
+                .strip_prefix('.')?
+                .strip_suffix(extension.as_str())?;
+            (!profile.is_empty() && profile != DEFAULT_PROFILE && !profile.contains('.'))
+                .then(|| profile.to_string())
+        })
+        .collect();
+    profiles.sort();
+    std::iter::once(None)
+        .chain(profiles.into_iter().map(Some))
+        .collect()
+}
+
+/// A project along with the profile it is registered in, see [load_all_profiles].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileProject {
+    /// `None` for the default profile.
+    pub profile: Option<String>,
+    pub project: Project,
+}
+
+/// Loads the projects of every profile, see [list_profiles]. A project registered in several
+/// profiles is listed once per profile.
+pub fn load_all_profiles() -> Result<Vec<ProfileProject>> {
+    load_all_profiles_in(&profile_file_path(None))
+}
+
+/// Like [load_all_profiles], for the profiles stored next to the projects file at `default`.
+fn load_all_profiles_in(default: &Path) -> Result<Vec<ProfileProject>> {
+    let mut projects = Vec::new();
+    for profile in list_profiles_in(default) {
+        let file_path = sibling_profile_file_path(default, profile.as_deref());
+        projects.extend(load_projects_from(&file_path)?.into_iter().map(|project| {
+            ProfileProject {
+                profile: profile.clone(),
+                project,
+            }
+        }));
+    }
+    Ok(projects)
+}
+
+/// Loads the registered projects of the [active profile](active_profile), along with the
+/// projects of the [system projects file](set_system_file) which aren't registered by the user.
 pub fn load_projects() -> Result<Vec<Project>> {
-    let file_path = projects_file_path();
+    let mut projects = load_projects_from(&projects_file_path())?;
+    merge_system_projects(&mut projects, system_projects());
+    Ok(projects)
+}
+
+/// The read-only projects file shared by all users, see [set_system_file].
+static SYSTEM_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the path of a read-only projects file, such as `/etc/helix/projects.toml`, whose
+/// projects are added to the ones loaded by [load_projects], [load_projects_with_warnings] and
+/// [list_projects]. `None` disables it.
+///
+/// The system file is never written. Changes to its projects, such as an access, are stored in
+/// the user's projects file, and an entry for the same path there takes precedence.
+pub fn set_system_file(path: Option<PathBuf>) {
+    *SYSTEM_FILE.write().unwrap() = path;
+}
+
+/// Whether the `on_open` and `on_close` hooks of the system projects file are kept, see
+/// [set_system_hooks_trusted].
+static SYSTEM_HOOKS_TRUSTED: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether the hooks of the [system projects file](set_system_file) are trusted. Untrusted
+/// hooks are dropped when the file is loaded, since they would run commands written by someone
+/// else. Hooks are not trusted by default.
+pub fn set_system_hooks_trusted(trusted: bool) {
+    SYSTEM_HOOKS_TRUSTED.store(trusted, atomic::Ordering::Relaxed);
+}
+
+/// The projects of the system projects file, or none if it isn't set or can't be read.
+fn system_projects() -> Vec<Project> {
+    let Some(file_path) = SYSTEM_FILE.read().unwrap().clone() else {
+        return Vec::new();
+    };
+    let mut projects = load_projects_from(&file_path).unwrap_or_else(|err| {
+        log::warn!("Failed to load system projects: {err}");
+        Vec::new()
+    });
+    if !SYSTEM_HOOKS_TRUSTED.load(atomic::Ordering::Relaxed) {
+        drop_hooks(&mut projects, &file_path);
+    }
+    projects
+}
+
+/// Removes the hooks of `projects`, which were loaded from the untrusted `file_path`.
+fn drop_hooks(projects: &mut [Project], file_path: &Path) {
+    for project in projects {
+        let on_open = project.on_open.take();
+        let on_close = project.on_close.take();
+        if on_open.is_some() || on_close.is_some() {
+            log::info!(
+                "Ignoring the hooks of {} in the untrusted projects file {}",
+                project.path.display(),
+                file_path.display()
+            );
+        }
+    }
+}
+
+/// Adds the `system` projects which aren't in `projects`.
+fn merge_system_projects(projects: &mut Vec<Project>, system: Vec<Project>) {
+    if system.is_empty() {
+        return;
+    }
+    let keys: HashSet<_> = projects.iter().map(|p| path_key(&p.path)).collect();
+    projects.extend(
+        system
+            .into_iter()
+            .filter(|project| !keys.contains(&path_key(&project.path))),
+    );
+}
+
+/// Leaves out the projects which are unchanged from their entry in `system`, so that only the
+/// user's own projects and their changes to system projects are saved.
+fn without_system_projects(projects: &[Project], system: &[Project]) -> Vec<Project> {
+    projects
+        .iter()
+        .filter(|project| !system.contains(project))
+        .cloned()
+        .collect()
+}
+
+/// Like [load_projects], for the projects file at `file_path`.
+///
+/// Invalid entries are skipped and logged, see [load_projects_with_warnings].
+pub fn load_projects_from(file_path: &Path) -> Result<Vec<Project>> {
+    let (projects, warnings) = load_projects_with_warnings_from(file_path)?;
+    for warning in warnings {
+        log::warn!("{warning}");
+    }
+    Ok(projects)
+}
+
+/// Loads the registered projects along with the entries that were skipped because they are
+/// invalid, such as a table with a value of the wrong type or a syntax error.
+///
+/// Only fails if the projects file can't be read or its structure outside of the
+/// `[[projects]]` tables is invalid.
+pub fn load_projects_with_warnings() -> Result<(Vec<Project>, Vec<EntryError>)> {
+    let (mut projects, warnings) = load_projects_with_warnings_from(&projects_file_path())?;
+    merge_system_projects(&mut projects, system_projects());
+    Ok((projects, warnings))
+}
+
+/// Like [load_projects_with_warnings], for the projects file at `file_path`.
+pub fn load_projects_with_warnings_from(
+    file_path: &Path,
+) -> Result<(Vec<Project>, Vec<EntryError>)> {
     if !file_path.exists() {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), Vec::new()));
     }
 
-    let content = std::fs::read_to_string(&file_path)
-        .with_context(|| format!("Failed to read projects file: {}", file_path.display()))?;
+    let content = std::fs::read_to_string(file_path)
+        .map_err(|err| ProjectsError::io("read projects file", file_path, err))?;
+
+    parse_projects(&content).map_err(|source| ProjectsError::Parse {
+        path: file_path.to_path_buf(),
+        source,
+    })
+}
 
-    let projects_file: ProjectsFile = toml::from_str(&content)
-        .with_context(|| format!("Failed to parse projects file: {}", file_path.display()))?;
+/// Parses the entries of a projects file one by one. If the file isn't valid TOML, each
+/// `[[projects]]` table is parsed on its own so that a syntax error only affects its entry.
+fn parse_projects(content: &str) -> Result<(Vec<Project>, Vec<EntryError>), toml::de::Error> {
+    let entries = match toml::from_str::<toml::Table>(content) {
+        Ok(mut table) => match table.remove("projects") {
+            Some(toml::Value::Array(entries)) => entries.into_iter().map(Ok).collect(),
+            Some(_) => {
+                return Err(serde::de::Error::custom(
+                    "invalid type for `projects`, expected an array of tables",
+                ))
+            }
+            None => Vec::new(),
+        },
+        Err(err) => {
+            let mut chunks = content.split("\n[[projects]]");
+            if !content.starts_with("[[projects]]") {
+                let preamble = chunks.next().unwrap_or_default();
+                if toml::from_str::<toml::Table>(preamble).is_err() {
+                    return Err(err);
+                }
+            }
+            chunks
+                .map(|chunk| {
+                    let chunk = chunk.strip_prefix("[[projects]]").unwrap_or(chunk);
+                    toml::from_str::<toml::Table>(chunk)
+                        .map(toml::Value::Table)
+                        .map_err(|err| (chunk_path(chunk), err))
+                })
+                .collect()
+        }
+    };
 
-    Ok(projects_file.projects)
+    let mut projects: Vec<Project> = Vec::with_capacity(entries.len());
+    let mut warnings = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let result = entry.and_then(|entry| {
+            let path = entry
+                .get("path")
+                .and_then(|path| path.as_str())
+                .map(String::from);
+            entry.try_into::<Project>().map_err(|err| (path, err))
+        });
+        match result {
+            // Entries which only differ in how their path is written are merged.
+            Ok(project) => match projects.iter_mut().find(|p| p.path == project.path) {
+                Some(registered) => {
+                    registered.access_count =
+                        registered.access_count.saturating_add(project.access_count);
+                    registered.last_accessed = registered.last_accessed.max(project.last_accessed);
+                }
+                None => projects.push(project),
+            },
+            Err((path, source)) => warnings.push(EntryError {
+                index,
+                path,
+                source,
+            }),
+        }
+    }
+    Ok((projects, warnings))
+}
+
+/// The value of the `path` key of an entry that isn't valid TOML, if it can be made out.
+fn chunk_path(chunk: &str) -> Option<String> {
+    chunk.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "path").then(|| value.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
+
+/// Loads the registered projects, leaving out archived ones unless `include_archived` is set.
+pub fn list_projects(include_archived: bool) -> Result<Vec<Project>> {
+    let mut projects = load_projects()?;
+    if !include_archived {
+        projects.retain(|project| !project.archived);
+    }
+    Ok(projects)
+}
+
+/// Like [list_projects], for the projects file at `file_path`.
+pub fn list_projects_from(file_path: &Path, include_archived: bool) -> Result<Vec<Project>> {
+    let mut projects = load_projects_from(file_path)?;
+    if !include_archived {
+        projects.retain(|project| !project.archived);
+    }
+    Ok(projects)
+}
+
+/// The order of the entries in the projects file, so that rewriting the file doesn't reorder
+/// them. The order of the lists returned by this module is not affected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProjectsFileOrder {
+    /// Alphabetically by path.
+    #[default]
+    Path,
+    /// Alphabetically by [display name](Project::display_name).
+    Name,
+    /// Most recently accessed first.
+    LastAccessed,
+}
+
+impl ProjectsFileOrder {
+    fn cmp(self, a: &Project, b: &Project) -> std::cmp::Ordering {
+        match self {
+            Self::Path => a.path.cmp(&b.path),
+            Self::Name => a.display_name().cmp(&b.display_name()),
+            Self::LastAccessed => b.last_accessed.cmp(&a.last_accessed),
+        }
+    }
+
+    /// Sorts `projects` in this order, keeping the order of equal entries.
+    fn sort(self, projects: &mut [Project]) {
+        projects.sort_by(|a, b| self.cmp(a, b));
+    }
 }
 
+/// Sets the order in which [save_projects] writes the projects file.
+pub fn set_file_order(order: ProjectsFileOrder) {
+    *FILE_ORDER.write().unwrap() = order;
+}
+
+/// Writes `projects` to the projects file, replacing it atomically.
+///
+/// An existing file is updated in place, keeping its comments, formatting and the order of its
+/// entries. New entries are put in the order set by [set_file_order].
+///
+/// This does not take the projects file lock. Use [update_projects] for
+/// read-modify-write cycles so that concurrent instances don't clobber each
+/// other's changes.
+///
+/// Projects of the [system projects file](set_system_file) are only saved if they were changed.
 pub fn save_projects(projects: &[Project]) -> Result<()> {
-    let file_path = projects_file_path();
-    crate::ensure_parent_dir(&file_path);
+    let projects = without_system_projects(projects, &system_projects());
+    save_projects_to(&projects_file_path(), &projects)
+}
+
+/// Like [save_projects], for the projects file at `file_path`.
+pub fn save_projects_to(file_path: &Path, projects: &[Project]) -> Result<()> {
+    crate::ensure_parent_dir(file_path);
+    let projects = &*clamp_access_times(projects, unix_now());
 
-    let projects_file = ProjectsFile {
-        projects: projects.to_vec(),
+    let order = *FILE_ORDER.read().unwrap();
+    let existing = std::fs::read_to_string(file_path).ok();
+    let content = match existing
+        .as_deref()
+        .and_then(|content| edit::update_projects_file(content, projects, order))
+    {
+        Some(content) => content?,
+        None => {
+            // Entries with syntax errors are skipped when loading and would be lost, so keep a
+            // copy of a file which can't be edited in place.
+            if existing.is_some_and(|content| !content.trim().is_empty()) {
+                let backup = file_path.with_extension("toml.bak");
+                log::warn!(
+                    "Rewriting invalid projects file, the previous contents are kept at {}",
+                    backup.display()
+                );
+                std::fs::copy(file_path, &backup)
+                    .map_err(|err| ProjectsError::io("back up projects file to", &backup, err))?;
+            }
+            serialize_projects(projects, order)?
+        }
     };
+    write_atomically(file_path, content.as_bytes())
+}
 
-    let content = toml::to_string_pretty(&projects_file)
-        .context("Failed to serialize projects to TOML")?;
+/// Replaces the access times of `projects` by [Project::last_accessed_at] so that times from a
+/// wrong clock don't persist.
+fn clamp_access_times(projects: &[Project], now: u64) -> Cow<'_, [Project]> {
+    if projects
+        .iter()
+        .all(|project| project.last_accessed == project.last_accessed_at(now))
+    {
+        return Cow::Borrowed(projects);
+    }
+    let projects = projects
+        .iter()
+        .map(|project| Project {
+            last_accessed: project.last_accessed_at(now),
+            ..project.clone()
+        })
+        .collect();
+    Cow::Owned(projects)
+}
+
+fn serialize_projects(projects: &[Project], order: ProjectsFileOrder) -> Result<String> {
+    let mut projects = projects.to_vec();
+    order.sort(&mut projects);
+    toml::to_string_pretty(&ProjectsFile { projects }).map_err(|err| ProjectsError::Serialize {
+        format: "TOML",
+        source: err.into(),
+    })
+}
 
-    std::fs::write(&file_path, content)
-        .with_context(|| format!("Failed to write projects file: {}", file_path.display()))?;
+/// Writes to a temporary file in the same directory and renames it over `file_path` so that
+/// readers never observe a partially written file.
+fn write_atomically(file_path: &Path, content: &[u8]) -> Result<()> {
+    let dir = file_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(dir)
+        .map_err(|err| ProjectsError::io("create temporary file in", dir, err))?;
+    file.write_all(content)
+        .map_err(|err| ProjectsError::io("write file", file_path, err))?;
+    file.persist(file_path)
+        .map_err(|err| ProjectsError::io("write file", file_path, err.error))?;
 
     Ok(())
 }
 
+/// An exclusive lock on the projects file, held for as long as the value lives.
+struct ProjectsLock {
+    path: PathBuf,
+}
+
+impl ProjectsLock {
+    fn acquire(file_path: &Path) -> Result<Self> {
+        crate::ensure_parent_dir(file_path);
+        let mut path = file_path.as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+
+        let start = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self { path }),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    let is_stale = std::fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > STALE_LOCK_AGE);
+                    if is_stale {
+                        log::warn!("Removing stale projects lock file: {}", path.display());
+                        let _ = std::fs::remove_file(&path);
+                        continue;
+                    }
+                    if start.elapsed() > LOCK_TIMEOUT {
+                        return Err(ProjectsError::Lock { path });
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(err) => return Err(ProjectsError::io("create projects lock", &path, err)),
+            }
+        }
+    }
+}
+
+impl Drop for ProjectsLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Loads the projects file, applies `f` and saves the result while holding
+/// the projects lock.
+///
+/// The file is only written if `f` returns `true` as the first element of
+/// its result, which signals that the list was modified.
+///
+/// The list includes the projects of the [system projects file](set_system_file), which are
+/// only saved if `f` changed them.
+pub fn update_projects<T>(f: impl FnOnce(&mut Vec<Project>) -> (bool, T)) -> Result<T> {
+    let system = system_projects();
+    if system.is_empty() {
+        return update_projects_in(&projects_file_path(), f);
+    }
+    update_projects_in(&projects_file_path(), |projects| {
+        merge_system_projects(projects, system.clone());
+        let (modified, result) = f(projects);
+        if modified {
+            *projects = without_system_projects(projects, &system);
+        }
+        (modified, result)
+    })
+}
+
+/// Like [update_projects], for the projects file at `file_path`.
+pub fn update_projects_in<T>(
+    file_path: &Path,
+    f: impl FnOnce(&mut Vec<Project>) -> (bool, T),
+) -> Result<T> {
+    let _lock = ProjectsLock::acquire(file_path)?;
+    let mut projects = load_projects_from(file_path)?;
+    let (modified, result) = f(&mut projects);
+    if modified {
+        save_projects_to(file_path, &projects)?;
+    }
+    Ok(result)
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    simplify(&path.canonicalize().unwrap_or_else(|_| clean(path)))
+}
+
+/// Lexically cleans up `path` without touching the file system: trailing separators and `.`
+/// components are removed and, on Windows, `/` separators become `\`. A path consisting of
+/// only `.` components is left as `.`.
+///
+/// Unlike [helix_stdx::path::normalize], `..` components are kept since `foo/..` only means
+/// the parent of `foo` if `foo` isn't a symlink.
+fn clean(path: &Path) -> PathBuf {
+    let cleaned: PathBuf = path
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect();
+    if cleaned.as_os_str().is_empty() && !path.as_os_str().is_empty() {
+        return PathBuf::from(".");
+    }
+    cleaned
+}
+
+/// Strips the `\\?\` prefix of the verbatim paths returned by [Path::canonicalize] on Windows
+/// if the path means the same without it: `\\?\C:\foo` becomes `C:\foo` and
+/// `\\?\UNC\server\share` becomes `\\server\share`. Other paths are returned unchanged.
+fn simplify(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::path::Prefix;
+
+        let mut components = path.components();
+        if let Some(Component::Prefix(prefix)) = components.next() {
+            if let Prefix::VerbatimUNC(server, share) = prefix.kind() {
+                // `dunce` only simplifies verbatim disk paths. Check whether the rest of the
+                // path could be written without the prefix by putting it on a verbatim disk.
+                let rest: PathBuf = components
+                    .skip_while(|c| *c == Component::RootDir)
+                    .collect();
+                let on_disk = Path::new(r"\\?\C:\").join(&rest);
+                if dunce::simplified(&on_disk) != on_disk {
+                    let mut simplified = PathBuf::from(r"\\");
+                    simplified.push(server);
+                    simplified.push(share);
+                    if !rest.as_os_str().is_empty() {
+                        simplified.push(rest);
+                    }
+                    return simplified;
+                }
+                return path.to_path_buf();
+            }
+        }
+    }
+    dunce::simplified(path).to_path_buf()
+}
+
+/// The form in which project paths are compared: the canonical path, or the [cleaned](clean) and
+/// lexically normalized path if it can't be canonicalized, with its case folded by [fold_case].
+fn path_key(path: &Path) -> PathBuf {
+    let path = path
+        .canonicalize()
+        .unwrap_or_else(|_| helix_stdx::path::normalize(clean(path)));
+    fold_case(&path)
+}
+
+/// Lowercases `path` and normalizes its separators on Windows and macOS, whose file systems
+/// are case-insensitive by default. Paths are left unchanged on other platforms.
+///
+/// Verbatim Windows paths are [simplified](simplify) first so that they compare equal to their
+/// regular form.
+fn fold_case(path: &Path) -> PathBuf {
+    let path = simplify(path);
+    if cfg!(windows) {
+        PathBuf::from(path.to_string_lossy().to_lowercase().replace('/', "\\"))
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path
+    }
+}
+
+/// Moves the project at `path` from the projects file of profile `from` to the one of `to`,
+/// keeping its metadata. If `to` already has the project, its entry there is kept.
+///
+/// Returns `false` if `from` has no project at `path`.
+pub fn move_project(path: &Path, from: Option<&str>, to: Option<&str>) -> Result<bool> {
+    move_project_in(&profile_file_path(from), &profile_file_path(to), path)
+}
+
+/// Like [move_project], between the projects files at `from` and `to`.
+///
+/// Both files are locked for the move and the project is added to `to` before it is removed from
+/// `from`. Two files can't be written atomically together though: if writing `from` fails the
+/// project ends up registered in both files, but it is never lost.
+pub fn move_project_in(from: &Path, to: &Path, path: &Path) -> Result<bool> {
+    if path_key(from) == path_key(to) {
+        return Ok(false);
+    }
+    // Lock in a fixed order so that opposite moves can't wait on each other.
+    let (first, second) = if from < to { (from, to) } else { (to, from) };
+    let _first = ProjectsLock::acquire(first)?;
+    let _second = ProjectsLock::acquire(second)?;
+
+    let key = path_key(path);
+    let mut source = load_projects_from(from)?;
+    let Some(index) = source.iter().position(|p| path_key(&p.path) == key) else {
+        return Ok(false);
+    };
+    let project = source.remove(index);
+    let mut target = load_projects_from(to)?;
+    if !target.iter().any(|p| path_key(&p.path) == key) {
+        target.push(project);
+        save_projects_to(to, &target)?;
+    }
+    save_projects_to(from, &source)?;
+    Ok(true)
+}
+
+/// Adds `path` to the projects list unless it is already registered.
+///
+/// Returns `false` if the project was already present.
+pub fn add_project(path: &Path) -> Result<bool> {
+    add_project_in(&projects_file_path(), path)
+}
+
+/// Like [add_project], for the projects file at `file_path`.
+pub fn add_project_in(file_path: &Path, path: &Path) -> Result<bool> {
+    let key = path_key(path);
+    let path = canonicalize(path);
+    update_projects_in(file_path, |projects| {
+        if projects.iter().any(|p| path_key(&p.path) == key) {
+            return (false, false);
+        }
+        projects.push(Project::new(path));
+        (true, true)
+    })
+}
+
+/// Records an access to the project at `path`, registering it first if needed.
+///
+/// Returns `true` if the project was newly registered.
+pub fn touch_project(path: &Path) -> Result<bool> {
+    touch_project_in(&projects_file_path(), path)
+}
+
+/// Like [touch_project], for the projects file at `file_path`.
+pub fn touch_project_in(file_path: &Path, path: &Path) -> Result<bool> {
+    let key = path_key(path);
+    let path = canonicalize(path);
+    update_projects_in(file_path, |projects| {
+        let added = !projects.iter().any(|p| path_key(&p.path) == key);
+        if added {
+            projects.push(Project::new(path.clone()));
+        }
+        update_project_last_accessed(projects, &path);
+        (true, added)
+    })
+}
+
+/// Removes `path` from the projects list.
+///
+/// Returns `false` if no project with that path was registered.
+pub fn remove_project(path: &Path) -> Result<bool> {
+    remove_project_in(&projects_file_path(), path)
+}
+
+/// Like [remove_project], for the projects file at `file_path`.
+pub fn remove_project_in(file_path: &Path, path: &Path) -> Result<bool> {
+    let key = path_key(path);
+    update_projects_in(file_path, |projects| {
+        let len = projects.len();
+        projects.retain(|p| path_key(&p.path) != key);
+        let removed = projects.len() != len;
+        (removed, removed)
+    })
+}
+
+/// Archives or unarchives the project at `path`.
+///
+/// Returns `false` if no project with that path was registered.
+pub fn set_archived(projects: &mut [Project], path: &Path, archived: bool) -> bool {
+    let key = path_key(path);
+    match projects.iter_mut().find(|p| path_key(&p.path) == key) {
+        Some(project) => {
+            project.archived = archived;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Sets or unsets the theme of the project at `path`.
+///
+/// Returns `false` if no project with that path was registered.
+pub fn set_theme(projects: &mut [Project], path: &Path, theme: Option<String>) -> bool {
+    let key = path_key(path);
+    match projects.iter_mut().find(|p| path_key(&p.path) == key) {
+        Some(project) => {
+            project.theme = theme;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Archives or unarchives the registered project at `path`, see [set_archived].
+pub fn set_project_archived(path: &Path, archived: bool) -> Result<bool> {
+    set_project_archived_in(&projects_file_path(), path, archived)
+}
+
+/// Like [set_project_archived], for the projects file at `file_path`.
+pub fn set_project_archived_in(file_path: &Path, path: &Path, archived: bool) -> Result<bool> {
+    update_projects_in(file_path, |projects| {
+        let found = set_archived(projects, path, archived);
+        (found, found)
+    })
+}
+
+/// Looks up a project by name, falling back to matching its path. Archived
+/// projects are skipped.
+///
+/// Relative paths and `~` are resolved against the current working directory
+/// and the home directory respectively.
+pub fn find_project<'a>(projects: &'a [Project], query: &str) -> Option<&'a Project> {
+    let mut projects = projects.iter().filter(|p| !p.archived);
+    if let Some(project) = projects.clone().find(|p| p.name.as_deref() == Some(query)) {
+        return Some(project);
+    }
+    let key = path_key(&helix_stdx::path::canonicalize(
+        helix_stdx::path::expand_tilde(Path::new(query)),
+    ));
+    projects.find(|p| path_key(&p.path) == key)
+}
+
+/// The result of [match_project].
+#[derive(Debug, PartialEq)]
+pub enum ProjectMatch<'a> {
+    Found(&'a Project),
+    /// Several projects match equally well.
+    Ambiguous(Vec<&'a Project>),
+    /// An unregistered directory under one of the ambient project roots, see
+    /// [match_project_in].
+    Ambient(PathBuf),
+    NotFound,
+}
+
+impl<'a> ProjectMatch<'a> {
+    /// The matching projects, several if the match is ambiguous.
+    pub fn into_candidates(self) -> Vec<&'a Project> {
+        match self {
+            Self::Found(project) => vec![project],
+            Self::Ambiguous(candidates) => candidates,
+            Self::Ambient(_) | Self::NotFound => Vec::new(),
+        }
+    }
+}
+
+/// The error message for a `query` of [match_project] matching several `candidates`, listing
+/// their full paths.
+pub fn ambiguous_project_message(query: &str, candidates: &[&Project]) -> String {
+    let candidates: Vec<_> = candidates
+        .iter()
+        .map(|project| match &project.name {
+            Some(name) => format!("  {} ({name})", project.path.display()),
+            None => format!("  {}", project.path.display()),
+        })
+        .collect();
+    format!(
+        "project '{query}' is ambiguous, candidates are:\n{}",
+        candidates.join("\n")
+    )
+}
+
+/// Looks up a project for a query typed by the user, trying in order:
+///
+/// 1. an exact name, then an exact path, see [find_project]
+/// 2. a name starting with `query`
+/// 3. a name containing the characters of `query` in order, ignoring case
+/// 4. a path ending with the path components of `query`, so `x/api` matches `/src/x/api` but
+///    not `/src/box/rapid`
+///
+/// If `query` contains a path separator, the path suffix is tried right after the exact matches
+/// instead, so that `client/api` picks out one of several projects named `api`.
+///
+/// The first step with any matches decides the result. Archived projects are skipped.
+pub fn match_project<'a>(projects: &'a [Project], query: &str) -> ProjectMatch<'a> {
+    if query.is_empty() {
+        return ProjectMatch::NotFound;
+    }
+    let mut named: Vec<_> = projects
+        .iter()
+        .filter(|p| !p.archived && p.name.as_deref() == Some(query))
+        .collect();
+    if named.len() > 1 {
+        return ProjectMatch::Ambiguous(named);
+    }
+    if let Some(project) = named.pop().or_else(|| find_project(projects, query)) {
+        return ProjectMatch::Found(project);
+    }
+
+    let query_lower = query.to_lowercase();
+    let is_subsequence = |name: &str| {
+        let mut name = name.chars().flat_map(char::to_lowercase);
+        query_lower.chars().all(|ch| name.any(|c| c == ch))
+    };
+    let suffix = Path::new(query.trim_end_matches(std::path::is_separator));
+    let has_prefix = |p: &Project| {
+        p.name
+            .as_deref()
+            .is_some_and(|name| name.starts_with(query))
+    };
+    let has_subsequence = |p: &Project| p.name.as_deref().is_some_and(is_subsequence);
+    let has_suffix = |p: &Project| suffix.components().next().is_some() && p.path.ends_with(suffix);
+    let steps: [&dyn Fn(&Project) -> bool; 3] = if query.contains(std::path::is_separator) {
+        [&has_suffix, &has_prefix, &has_subsequence]
+    } else {
+        [&has_prefix, &has_subsequence, &has_suffix]
+    };
+    for step in steps {
+        let mut matches: Vec<_> = projects.iter().filter(|p| !p.archived && step(p)).collect();
+        match matches.len() {
+            0 => continue,
+            1 => return ProjectMatch::Found(matches.pop().unwrap()),
+            _ => return ProjectMatch::Ambiguous(matches),
+        }
+    }
+    ProjectMatch::NotFound
+}
+
+/// Like [match_project], falling back to a directory named `query` directly under one of the
+/// ambient `roots`, see [ambient_roots]. Registered projects always take precedence, including
+/// partial matches.
+pub fn match_project_in<'a>(
+    projects: &'a [Project],
+    roots: &[PathBuf],
+    query: &str,
+) -> ProjectMatch<'a> {
+    match match_project(projects, query) {
+        ProjectMatch::NotFound => {
+            find_ambient_project(roots, query).map_or(ProjectMatch::NotFound, ProjectMatch::Ambient)
+        }
+        found => found,
+    }
+}
+
+/// Fuzzy matches `query` against the names and paths of `projects` with the matcher used by
+/// pickers, returning the matching projects with their scores.
+///
+/// Matches in the final path component rank highest, followed by matches in the display name
+/// and then anywhere in the path. Exact matches rank above partial ones. Results are sorted by
+/// score and then by frecency. An empty query matches every project with a score of zero.
+/// Archived projects are skipped.
+pub fn match_projects<'a>(projects: &'a [Project], query: &str) -> Vec<(&'a Project, u32)> {
+    use nucleo::pattern::{Atom, AtomKind, CaseMatching, Normalization};
+    use nucleo::{Config, Matcher, Utf32Str};
+
+    let now = unix_now();
+    let projects = projects.iter().filter(|project| !project.archived);
+    let mut matches: Vec<_> = if query.is_empty() {
+        projects.map(|project| (project, 0)).collect()
+    } else {
+        let atom = Atom::new(
+            query,
+            CaseMatching::Smart,
+            Normalization::Smart,
+            AtomKind::Fuzzy,
+            false,
+        );
+        let mut matcher = Matcher::new(Config::DEFAULT);
+        let mut path_matcher = Matcher::new(Config::DEFAULT.match_paths());
+        let mut buf = Vec::new();
+        let mut score = |haystack: &str, matcher: &mut Matcher, mut weight: u32| {
+            if haystack.eq_ignore_ascii_case(query) {
+                weight += 1;
+            }
+            atom.score(Utf32Str::new(haystack, &mut buf), matcher)
+                .map(|score| u32::from(score) * weight)
+        };
+        projects
+            .filter_map(|project| {
+                let path = project.path.to_string_lossy();
+                let basename = project.path.file_name().and_then(|name| name.to_str());
+                let scores = [
+                    basename.and_then(|name| score(name, &mut matcher, 3)),
+                    project
+                        .display_name()
+                        .and_then(|name| score(name, &mut matcher, 2)),
+                    score(&path, &mut path_matcher, 1),
+                ];
+                Some((project, scores.into_iter().flatten().max()?))
+            })
+            .collect()
+    };
+    matches.sort_by(|(a, a_score), (b, b_score)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| b.frecency(now).total_cmp(&a.frecency(now)))
+    });
+    matches
+}
+
+/// Returns the innermost project containing `path`.
+///
+/// Paths are not canonicalized, so `path` should be canonical like the paths of
+/// registered projects. Their case is folded by [fold_case]. Archived projects are
+/// included so that their access times keep being recorded.
+pub fn find_project_for_path<'a>(projects: &'a [Project], path: &Path) -> Option<&'a Project> {
+    let path = fold_case(&clean(path));
+    projects
+        .iter()
+        .filter(|p| path.starts_with(fold_case(&p.path)))
+        .max_by_key(|p| p.path.components().count())
+}
+
+/// Options of [scan_git_repositories_with] and [scan_and_register].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// How many directories deep below the root to look for repositories. The root itself is
+    /// at depth 0. Unlimited if `None`.
+    pub max_depth: Option<usize>,
+    /// Names of directories which are not descended into, such as `node_modules`.
+    pub exclude: Vec<String>,
+    /// How many repositories to find at most. The scan stops once it comes across another one,
+    /// see [RegisterReport::truncated]. Unlimited if `None`.
+    ///
+    /// Directories are scanned in order of their names, so the repositories which are kept are
+    /// the first ones by path.
+    pub max_repos: Option<usize>,
+}
+
+/// Finds the roots of the git repositories within `root`, see [scan_git_repositories_with].
 pub fn scan_git_repositories(root: &Path) -> Result<Vec<PathBuf>> {
-    let mut repositories = HashSet::new();
-    let root = root.canonicalize()
-        .with_context(|| format!("Failed to canonicalize root path: {}", root.display()))?;
-    
-    scan_directory(&root, &root, &mut repositories)?;
-    
-    let mut repos: Vec<PathBuf> = repositories.into_iter().collect();
+    scan_git_repositories_with(root, &ScanOptions::default())
+}
+
+/// Finds the roots of the git repositories within `root`, sorted by path. Repositories nested in
+/// other repositories and symlinked directories are skipped.
+///
+/// Fails on the first directory which can't be read, other than for a lack of permissions. With
+/// [ScanOptions::max_repos], only the first repositories are returned.
+pub fn scan_git_repositories_with(root: &Path, options: &ScanOptions) -> Result<Vec<PathBuf>> {
+    let scanned = scan(root, options)?;
+    match scanned.errors.into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(scanned.repos),
+    }
+}
+
+/// The result of [scan].
+struct Scanned {
+    /// The repositories which were found, sorted by path.
+    repos: Vec<PathBuf>,
+    /// The directories which couldn't be read.
+    errors: Vec<ProjectsError>,
+    /// Whether the scan stopped at [ScanOptions::max_repos].
+    truncated: bool,
+}
+
+/// Scans `root` like [scan_git_repositories_with], returning the directories which couldn't be
+/// read alongside the repositories instead of failing.
+fn scan(root: &Path, options: &ScanOptions) -> Result<Scanned> {
+    let root = root
+        .canonicalize()
+        .map_err(|err| ProjectsError::io("canonicalize root path", root, err))?;
+
+    let mut scan = Scan {
+        options,
+        repositories: Vec::new(),
+        errors: Vec::new(),
+        truncated: false,
+    };
+    scan.directory(&root, 0);
+
+    let mut repos = scan.repositories;
     repos.sort();
-    Ok(repos)
+    repos.dedup();
+    let mut errors = scan.errors;
+    errors.sort_by_cached_key(|err| err.to_string());
+    Ok(Scanned {
+        repos,
+        errors,
+        truncated: scan.truncated,
+    })
+}
+
+struct Scan<'a> {
+    options: &'a ScanOptions,
+    repositories: Vec<PathBuf>,
+    errors: Vec<ProjectsError>,
+    truncated: bool,
+}
+
+impl Scan<'_> {
+    fn directory(&mut self, current: &Path, depth: usize) {
+        if self.truncated {
+            return;
+        }
+        // Repositories nested in a repository aren't scanned for, so a repository root ends the
+        // scan of its directory.
+        if current.join(".git").is_dir() {
+            if self
+                .options
+                .max_repos
+                .is_some_and(|max| self.repositories.len() >= max)
+            {
+                self.truncated = true;
+            } else {
+                self.repositories.push(canonicalize(current));
+            }
+            return;
+        }
+
+        // Read directory entries
+        let entries = match std::fs::read_dir(current) {
+            Ok(entries) => entries,
+            Err(e) => {
+                // Skip directories we can't read (permission denied, etc.)
+                if e.kind() != ErrorKind::PermissionDenied {
+                    self.errors
+                        .push(ProjectsError::io("read directory", current, e));
+                }
+                return;
+            }
+        };
+
+        let mut entries: Vec<_> = entries
+            .filter_map(|entry| {
+                entry
+                    .map_err(|err| {
+                        self.errors
+                            .push(ProjectsError::io("read entry in", current, err));
+                    })
+                    .ok()
+            })
+            .collect();
+        // Directories are read in whatever order the file system returns them, sorting them
+        // keeps the repositories found before reaching `max_repos` the same between scans.
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+
+            // Skip if it's a symlink to avoid cycles (optional, but safer)
+            if path.is_symlink() || !path.is_dir() {
+                continue;
+            }
+            if self.options.max_depth.is_some_and(|max| depth >= max) {
+                continue;
+            }
+            if self
+                .options
+                .exclude
+                .iter()
+                .any(|name| entry.file_name() == name.as_str())
+            {
+                continue;
+            }
+            self.directory(&path, depth + 1);
+        }
+    }
+}
+
+/// The result of [scan_and_register].
+#[derive(Debug, Default)]
+pub struct RegisterReport {
+    /// Repositories which were registered, sorted by path.
+    pub added: Vec<PathBuf>,
+    /// Repositories which were already registered, sorted by path. Their entries are left
+    /// unchanged.
+    pub known: Vec<PathBuf>,
+    /// Directories which couldn't be scanned. The rest of the scan is registered anyway.
+    pub errors: Vec<ProjectsError>,
+    /// Whether the scan stopped at [ScanOptions::max_repos], so that there may be more
+    /// repositories than were reported.
+    pub truncated: bool,
 }
 
-fn scan_directory(
-    current: &Path,
+/// Scans `root` for git repositories and registers the ones which aren't registered yet, named
+/// after their directories.
+pub fn scan_and_register(root: &Path, options: &ScanOptions) -> Result<RegisterReport> {
+    scan_and_register_in(&projects_file_path(), root, options)
+}
+
+/// Like [scan_and_register], for the projects file at `file_path`.
+pub fn scan_and_register_in(
+    file_path: &Path,
     root: &Path,
-    repositories: &mut HashSet<PathBuf>,
-) -> Result<()> {
-    // Canonicalize current path once
-    let current_canonical = current.canonicalize()
-        .unwrap_or_else(|_| current.to_path_buf());
-    
-    // Check if current directory is a .git directory
-    if current.file_name()
-        .and_then(|n| n.to_str())
-        .map(|n| n == ".git")
-        .unwrap_or(false)
-        && current.is_dir()
+    options: &ScanOptions,
+) -> Result<RegisterReport> {
+    let scanned = scan(root, options)?;
+    update_projects_in(file_path, |projects| {
+        let report = register_repositories(projects, scanned);
+        (!report.added.is_empty(), report)
+    })
+}
+
+/// Scans `root` like [scan_and_register] without changing the projects file: the report lists
+/// the repositories which would be registered.
+pub fn scan_for_registration(root: &Path, options: &ScanOptions) -> Result<RegisterReport> {
+    scan_for_registration_in(&projects_file_path(), root, options)
+}
+
+/// Like [scan_for_registration], for the projects file at `file_path`.
+pub fn scan_for_registration_in(
+    file_path: &Path,
+    root: &Path,
+    options: &ScanOptions,
+) -> Result<RegisterReport> {
+    let scanned = scan(root, options)?;
+    let mut projects = load_projects_from(file_path)?;
+    Ok(register_repositories(&mut projects, scanned))
+}
+
+fn register_repositories(projects: &mut Vec<Project>, scanned: Scanned) -> RegisterReport {
+    let mut keys: HashSet<_> = projects.iter().map(|p| path_key(&p.path)).collect();
+    let mut report = RegisterReport {
+        errors: scanned.errors,
+        truncated: scanned.truncated,
+        ..RegisterReport::default()
+    };
+    for repo in scanned.repos {
+        if keys.insert(path_key(&repo)) {
+            projects.push(Project::new(repo.clone()));
+            report.added.push(repo);
+        } else {
+            report.known.push(repo);
+        }
+    }
+    report
+}
+
+/// Records an access to the project at `path`.
+///
+/// Returns `false` if no project with that path is registered. Only the first of several
+/// projects with the same path is updated.
+pub fn update_project_last_accessed(projects: &mut [Project], path: &Path) -> bool {
+    let key = path_key(path);
+    match projects
+        .iter_mut()
+        .find(|project| path_key(&project.path) == key)
     {
-        // Get the parent directory (the repository root)
-        if let Some(repo_root) = current.parent() {
-            let repo_root = repo_root.canonicalize()
-                .unwrap_or_else(|_| repo_root.to_path_buf());
-            repositories.insert(repo_root);
-            return Ok(()); // Don't scan inside .git directory
+        Some(project) => {
+            record_access(project, unix_now());
+            true
         }
+        None => false,
+    }
+}
+
+/// Like [update_project_last_accessed] for each of `paths`, returning whether each was found.
+///
+/// The paths of `projects` are canonicalized once rather than once per path. A path given
+/// several times records several accesses.
+pub fn update_many_last_accessed(projects: &mut [Project], paths: &[PathBuf]) -> Vec<bool> {
+    let now = unix_now();
+    let keys: Vec<_> = projects.iter().map(|p| path_key(&p.path)).collect();
+    paths
+        .iter()
+        .map(|path| {
+            let key = path_key(path);
+            let Some(idx) = keys.iter().position(|k| *k == key) else {
+                return false;
+            };
+            record_access(&mut projects[idx], now);
+            true
+        })
+        .collect()
+}
+
+fn record_access(project: &mut Project, now: u64) {
+    project.last_accessed = Some(now);
+    project.access_count = project.access_count.saturating_add(1);
+    // Projects registered before remotes were cached pick up theirs on their next access.
+    if project.remote.is_none() {
+        project.remote = origin_remote(&project.path);
     }
-    
-    // Check if current directory is inside any discovered repository
-    for repo_root in repositories.iter() {
-        if current_canonical.starts_with(repo_root) && current_canonical != *repo_root {
-            // This path is inside a repository, skip it
-            return Ok(());
+}
+
+/// Whether the directory of a project can currently be reached. This is never stored in the
+/// projects file since a project on an unmounted drive or network share comes back once it is
+/// mounted again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectStatus {
+    Available,
+    /// The directory doesn't exist or didn't respond in time.
+    Missing,
+}
+
+/// Checks whether the directories of `projects` exist, returning a status per project.
+///
+/// Each directory is checked on its own thread and counts as missing if it doesn't respond
+/// within a short timeout. Threads stuck on a dead mount are left to finish in the background.
+pub fn check_projects(projects: &[Project]) -> Vec<ProjectStatus> {
+    check_paths(projects.iter().map(|project| project.path.clone()))
+}
+
+/// Like [check_projects], for a single project directory.
+pub fn project_status(path: &Path) -> ProjectStatus {
+    check_paths(std::iter::once(path.to_path_buf()))[0]
+}
+
+fn check_paths(paths: impl Iterator<Item = PathBuf>) -> Vec<ProjectStatus> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut statuses = Vec::new();
+    for (i, path) in paths.enumerate() {
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send((i, path.is_dir()));
+        });
+        statuses.push(ProjectStatus::Missing);
+    }
+    drop(tx);
+
+    let deadline = Instant::now() + STATUS_TIMEOUT;
+    while let Ok((i, is_dir)) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+    {
+        if is_dir {
+            statuses[i] = ProjectStatus::Available;
         }
     }
-    
-    // Read directory entries
-    let entries = match std::fs::read_dir(current) {
-        Ok(entries) => entries,
-        Err(e) => {
-            // Skip directories we can't read (permission denied, etc.)
-            if e.kind() == std::io::ErrorKind::PermissionDenied {
-                return Ok(());
-            }
-            return Err(e).with_context(|| format!("Failed to read directory: {}", current.display()));
+    statuses
+}
+
+/// Sorts projects by [Project::frecency], most frequently and recently used first.
+pub fn sort_by_frecency(projects: &mut [Project]) {
+    let now = unix_now();
+    projects.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+}
+
+/// The file format of [export_projects] and [import_projects_file].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A JSON array of projects.
+    Json,
+    /// The format of the projects file.
+    Toml,
+}
+
+impl ExportFormat {
+    /// The format for `path`: TOML for a `.toml` extension and JSON otherwise.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// How [import_projects_file] combines the imported projects with the registered ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Replace the registered projects with the imported ones.
+    Replace,
+    /// Add missing projects and replace registered projects which were accessed less recently.
+    KeepNewest,
+    /// Only add projects which aren't registered yet.
+    AddMissingOnly,
+}
+
+impl std::str::FromStr for MergeStrategy {
+    type Err = ProjectsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "replace" => Ok(Self::Replace),
+            "keep-newest" => Ok(Self::KeepNewest),
+            "add-missing" => Ok(Self::AddMissingOnly),
+            _ => Err(ProjectsError::UnknownMergeStrategy(s.to_string())),
+        }
+    }
+}
+
+/// The result of [import_projects_file].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub added: usize,
+    /// Registered projects replaced by a more recently accessed import.
+    pub updated: usize,
+    /// Registered projects removed by [MergeStrategy::Replace].
+    pub removed: usize,
+    /// Imported projects whose path doesn't exist on this machine. They are imported anyway.
+    pub missing: usize,
+}
+
+/// Writes the registered projects to `writer`.
+pub fn export_projects(writer: impl std::io::Write, format: ExportFormat) -> Result<()> {
+    export_projects_from(&projects_file_path(), writer, format)
+}
+
+/// Like [export_projects], for the projects file at `file_path`.
+pub fn export_projects_from(
+    file_path: &Path,
+    writer: impl std::io::Write,
+    format: ExportFormat,
+) -> Result<()> {
+    write_projects(writer, &load_projects_from(file_path)?, format)
+}
+
+/// Writes the registered projects to the file at `path`, replacing it atomically.
+pub fn export_projects_file(path: &Path, format: ExportFormat) -> Result<()> {
+    export_projects_file_from(&projects_file_path(), path, format)
+}
+
+/// Like [export_projects_file], for the projects file at `file_path`.
+pub fn export_projects_file_from(
+    file_path: &Path,
+    path: &Path,
+    format: ExportFormat,
+) -> Result<()> {
+    let mut content = Vec::new();
+    export_projects_from(file_path, &mut content, format)?;
+    write_atomically(path, &content)
+}
+
+fn write_projects(
+    mut writer: impl std::io::Write,
+    projects: &[Project],
+    format: ExportFormat,
+) -> Result<()> {
+    let content = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(projects)
+            .map(|content| content + "\n")
+            .map_err(|err| ProjectsError::Serialize {
+                format: "JSON",
+                source: err.into(),
+            })?,
+        ExportFormat::Toml => toml::to_string_pretty(&ProjectsFile {
+            projects: projects.to_vec(),
+        })
+        .map_err(|err| ProjectsError::Serialize {
+            format: "TOML",
+            source: err.into(),
+        })?,
+    };
+    writer
+        .write_all(content.as_bytes())
+        .map_err(|source| ProjectsError::Stream {
+            action: "write projects",
+            source,
+        })
+}
+
+fn read_projects(mut reader: impl std::io::Read, format: ExportFormat) -> Result<Vec<Project>> {
+    let mut content = String::new();
+    reader
+        .read_to_string(&mut content)
+        .map_err(|source| ProjectsError::Stream {
+            action: "read projects",
+            source,
+        })?;
+    let invalid = |message: &str, err: BoxError| ProjectsError::InvalidImport {
+        message: message.to_string(),
+        source: Some(err),
+    };
+    let projects = match format {
+        ExportFormat::Json => serde_json::from_str(&content)
+            .map_err(|err| invalid("Invalid projects JSON", err.into()))?,
+        ExportFormat::Toml => {
+            toml::from_str::<ProjectsFile>(&content)
+                .map_err(|err| invalid("Invalid projects TOML", err.into()))?
+                .projects
         }
     };
-    
-    for entry in entries {
-        let entry = entry.with_context(|| format!("Failed to read entry in: {}", current.display()))?;
-        let path = entry.path();
-        
-        // Skip if it's a symlink to avoid cycles (optional, but safer)
-        if path.is_symlink() {
-            continue;
+    Ok(projects)
+}
+
+/// Imports projects exported by [export_projects] into the registered projects.
+///
+/// Imported paths are canonicalized and deduplicated. The projects file is left unchanged if
+/// the import can't be read.
+pub fn import_projects_file(
+    reader: impl std::io::Read,
+    format: ExportFormat,
+    strategy: MergeStrategy,
+) -> Result<ImportReport> {
+    import_projects_file_in(&projects_file_path(), reader, format, strategy)
+}
+
+/// Like [import_projects_file], for the projects file at `file_path`.
+pub fn import_projects_file_in(
+    file_path: &Path,
+    reader: impl std::io::Read,
+    format: ExportFormat,
+    strategy: MergeStrategy,
+) -> Result<ImportReport> {
+    let mut seen = HashSet::new();
+    let imported: Vec<_> = read_projects(reader, format)?
+        .into_iter()
+        .map(|project| Project {
+            path: canonicalize(&project.path),
+            ..project
+        })
+        .filter(|project| seen.insert(path_key(&project.path)))
+        .collect();
+
+    update_projects_in(file_path, |projects| {
+        let mut report = ImportReport {
+            missing: imported.iter().filter(|p| !p.path.exists()).count(),
+            ..ImportReport::default()
+        };
+        if strategy == MergeStrategy::Replace {
+            let paths: HashSet<_> = projects.iter().map(|p| path_key(&p.path)).collect();
+            report.added = imported
+                .iter()
+                .filter(|p| !paths.contains(&path_key(&p.path)))
+                .count();
+            report.removed = projects.len() + report.added - imported.len();
+            *projects = imported;
+            return (true, report);
         }
-        
-        if path.is_dir() {
-            scan_directory(&path, root, repositories)?;
+
+        for project in imported {
+            match projects
+                .iter_mut()
+                .find(|p| path_key(&p.path) == path_key(&project.path))
+            {
+                Some(registered)
+                    if strategy == MergeStrategy::KeepNewest
+                        && project.last_accessed > registered.last_accessed =>
+                {
+                    *registered = project;
+                    report.updated += 1;
+                }
+                Some(_) => (),
+                None => {
+                    projects.push(project);
+                    report.added += 1;
+                }
+            }
         }
+        (report.added + report.updated > 0, report)
+    })
+}
+
+/// Formats projects one per line as `name\tpath\tlast_accessed` for `hx --list-projects`.
+///
+/// Missing names fall back to [Project::display_name] and a missing access time is left empty.
+pub fn format_project_lines(projects: &[Project]) -> String {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    for project in projects {
+        let last_accessed = project
+            .last_accessed
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            output,
+            "{}\t{}\t{last_accessed}",
+            project.display_name().unwrap_or_default(),
+            project.path.display()
+        );
     }
-    
-    Ok(())
+    output
 }
 
-pub fn update_project_last_accessed(projects: &mut [Project], path: &Path) {
-    let now = SystemTime::now()
+fn unix_now() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_secs())
-        .unwrap_or(0);
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_remove_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+
+        assert!(add_project_in(&file_path, &project).unwrap());
+        assert!(!add_project_in(&file_path, &project).unwrap());
+        assert!(!add_project_in(&file_path, &project.join(".")).unwrap());
+
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name.as_deref(), Some("project"));
+
+        assert!(remove_project_in(&file_path, &project).unwrap());
+        assert!(!remove_project_in(&file_path, &project).unwrap());
+        assert!(load_projects_from(&file_path).unwrap().is_empty());
+        // The lock is released once the update is done.
+        assert!(!dir.path().join("projects.toml.lock").exists());
+    }
+
+    #[test]
+    fn compare_paths_lexically_if_missing() {
+        let mut projects = [Project::new(PathBuf::from("/missing/src/foo"))];
+        update_project_last_accessed(&mut projects, Path::new("/missing/src/../src/./foo"));
+        assert_eq!(projects[0].access_count, 1);
+        assert!(find_project(&projects, "/missing/src/bar/../foo").is_some());
+    }
+
+    #[test]
+    fn report_updated_projects() {
+        let mut projects = [
+            Project::new(PathBuf::from("/missing/foo")),
+            Project::new(PathBuf::from("/missing/bar")),
+            // A duplicate entry, which is left alone.
+            Project::new(PathBuf::from("/missing/./foo")),
+        ];
+        assert!(update_project_last_accessed(
+            &mut projects,
+            Path::new("/missing/foo/")
+        ));
+        assert!(!update_project_last_accessed(
+            &mut projects,
+            Path::new("/missing/baz")
+        ));
+        assert_eq!(projects[0].access_count, 1);
+        assert!(projects[0].last_accessed.is_some());
+        assert_eq!(projects[2].access_count, 0);
+
+        let paths = [
+            "/missing/bar",
+            "/missing/baz",
+            "/missing/foo",
+            "/missing/bar/",
+        ];
+        let paths: Vec<_> = paths.iter().map(PathBuf::from).collect();
+        assert_eq!(
+            update_many_last_accessed(&mut projects, &paths),
+            [true, false, true, true]
+        );
+        let counts: Vec<_> = projects.iter().map(|p| p.access_count).collect();
+        assert_eq!(counts, [2, 2, 0]);
+        assert_eq!(projects[1].last_accessed, projects[0].last_accessed);
+        assert!(update_many_last_accessed(&mut projects, &[]).is_empty());
+    }
+
+    #[test]
+    fn clean_messy_paths() {
+        let messy = [
+            "/home/me/code/foo",
+            "/home/me/code/foo/",
+            "/home/me/code/foo//",
+            "/home/me/code/./foo",
+            "/home/me/code//foo",
+            "/home/me/code/foo/.",
+            "/home/me/./code/./foo/./",
+        ];
+        for path in messy {
+            let cleaned = clean(Path::new(path));
+            assert_eq!(cleaned.to_str(), Some("/home/me/code/foo"), "{path}");
+
+            let mut projects = [Project::new(PathBuf::from("/home/me/code/foo"))];
+            update_project_last_accessed(&mut projects, Path::new(path));
+            assert_eq!(projects[0].access_count, 1, "{path}");
+            let project: Project = toml::from_str(&format!("path = '{path}'")).unwrap();
+            assert_eq!(project.path.to_str(), Some("/home/me/code/foo"), "{path}");
+            assert_eq!(project.name.as_deref(), None);
+        }
+        assert_eq!(clean(Path::new("/")), Path::new("/"));
+        assert_eq!(clean(Path::new("//.")).to_str(), Some("/"));
+        assert_eq!(clean(Path::new("./")).to_str(), Some("."));
+        assert_eq!(clean(Path::new("./foo/")).to_str(), Some("foo"));
+        // `..` is kept since the path before it may be a symlink.
+        assert_eq!(clean(Path::new("/a/b/../c/")).to_str(), Some("/a/b/../c"));
+    }
+
+    #[test]
+    fn clean_paths_in_projects_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        std::fs::write(
+            &file_path,
+            "[[projects]]\npath = '/missing/foo/'\naccess_count = 2\n\n\
+             [[projects]]\npath = '/missing/./foo'\naccess_count = 3\nlast_accessed = 10\n\n\
+             [[projects]]\npath = '/'\n",
+        )
+        .unwrap();
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].path.to_str(), Some("/missing/foo"));
+        assert_eq!(projects[0].access_count, 5);
+        assert_eq!(projects[0].last_accessed, Some(10));
+        assert_eq!(projects[1].path.to_str(), Some("/"));
+
+        assert!(!add_project_in(&file_path, Path::new("/missing/foo/.")).unwrap());
+        assert!(touch_project_in(&file_path, Path::new("/missing//bar/")).unwrap());
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert!(content.starts_with("[[projects]]\npath = \"/missing/bar\"\n"));
+        assert!(content.ends_with(
+            "[[projects]]\npath = \"/missing/foo\"\naccess_count = 5\nlast_accessed = 10\n\n\
+             [[projects]]\npath = '/'\n"
+        ));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn clean_windows_paths() {
+        for path in [
+            r"C:\src\helix",
+            r"C:\src\helix\",
+            r"C:/src/helix/",
+            r"C:\src\.\helix",
+        ] {
+            assert_eq!(
+                clean(Path::new(path)).to_str(),
+                Some(r"C:\src\helix"),
+                "{path}"
+            );
+        }
+        assert_eq!(clean(Path::new(r"C:\")).to_str(), Some(r"C:\"));
+        assert_eq!(clean(Path::new("C:/")).to_str(), Some(r"C:\"));
+        assert_eq!(
+            clean(Path::new(r"\\server\share\helix\")).to_str(),
+            Some(r"\\server\share\helix")
+        );
+    }
+
+    #[test]
+    #[cfg(any(windows, target_os = "macos"))]
+    fn compare_paths_ignoring_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let project = dir.path().join("Project");
+        std::fs::create_dir(&project).unwrap();
+        let other_case = dir.path().join("pROJECT");
+
+        assert!(add_project_in(&file_path, &project).unwrap());
+        assert!(!add_project_in(&file_path, &other_case).unwrap());
+        assert!(!touch_project_in(&file_path, &other_case).unwrap());
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].access_count, 1);
+
+        assert!(find_project(&projects, other_case.to_str().unwrap()).is_some());
+        let file = PathBuf::from(projects[0].path.to_string_lossy().to_uppercase()).join("a.rs");
+        assert!(find_project_for_path(&projects, &file).is_some());
+        // Lexical comparisons ignore case too.
+        let mut missing = [Project::new(PathBuf::from("/Missing/Foo"))];
+        update_project_last_accessed(&mut missing, Path::new("/missing/foo"));
+        assert_eq!(missing[0].access_count, 1);
+
+        assert!(remove_project_in(&file_path, &other_case).unwrap());
+        assert!(load_projects_from(&file_path).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(not(any(windows, target_os = "macos")))]
+    fn compare_paths_with_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let project = dir.path().join("Project");
+        let other_case = dir.path().join("pROJECT");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::create_dir(&other_case).unwrap();
+
+        assert!(add_project_in(&file_path, &project).unwrap());
+        assert!(add_project_in(&file_path, &other_case).unwrap());
+        let projects = load_projects_from(&file_path).unwrap();
+        assert!(
+            find_project_for_path(&projects, &canonicalize(dir.path()).join("PROJECT")).is_none()
+        );
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn simplify_verbatim_paths() {
+        let simplified = |path| simplify(Path::new(path));
+        assert_eq!(simplified(r"\\?\C:\src\helix"), Path::new(r"C:\src\helix"));
+        assert_eq!(simplified(r"\\?\C:\"), Path::new(r"C:\"));
+        // Reserved names and trailing dots are only valid in verbatim paths.
+        assert_eq!(simplified(r"\\?\C:\src\con"), Path::new(r"\\?\C:\src\con"));
+        assert_eq!(
+            simplified(r"\\?\C:\src\helix."),
+            Path::new(r"\\?\C:\src\helix.")
+        );
+        assert_eq!(
+            simplified(r"\\?\UNC\server\share\helix"),
+            Path::new(r"\\server\share\helix")
+        );
+        assert_eq!(
+            simplified(r"\\?\UNC\server\share"),
+            Path::new(r"\\server\share")
+        );
+        assert_eq!(
+            simplified(r"\\server\share\helix"),
+            Path::new(r"\\server\share\helix")
+        );
+        assert_eq!(simplified(r"C:src\helix"), Path::new(r"C:src\helix"));
+        assert_eq!(simplified(r"C:\src\helix"), Path::new(r"C:\src\helix"));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn compare_verbatim_paths() {
+        let projects = [
+            Project::new(PathBuf::from(r"C:\src\helix")),
+            Project::new(PathBuf::from(r"\\server\share\helix")),
+        ];
+        let found = find_project_for_path(&projects, Path::new(r"\\?\C:\src\helix\README.md"));
+        assert_eq!(found, Some(&projects[0]));
+        let found = find_project_for_path(&projects, Path::new(r"\\?\UNC\server\share\helix"));
+        assert_eq!(found, Some(&projects[1]));
+
+        let mut projects = [Project::new(PathBuf::from(r"\\?\C:\missing\helix"))];
+        update_project_last_accessed(&mut projects, Path::new(r"C:\missing\helix"));
+        assert_eq!(projects[0].access_count, 1);
+
+        let content = toml::to_string(&ProjectsFile {
+            projects: projects.to_vec(),
+        })
+        .unwrap();
+        assert!(
+            content.contains(r#"path = 'C:\missing\helix'"#),
+            "{content}"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn round_trip_non_utf8_paths() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let path = dir.path().join(OsStr::from_bytes(b"caf\xe9"));
+        let projects = [
+            Project::new(dir.path().join("cafe")),
+            Project::new(path.clone()),
+        ];
+
+        save_projects_to(&file_path, &projects).unwrap();
+        assert_eq!(load_projects_from(&file_path).unwrap(), projects);
+
+        let mut content = Vec::new();
+        write_projects(&mut content, &projects, ExportFormat::Json).unwrap();
+        let exported = read_projects(content.as_slice(), ExportFormat::Json).unwrap();
+        assert_eq!(exported, projects);
+    }
+
+    #[test]
+    fn skip_invalid_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        std::fs::write(
+            &file_path,
+            "[[projects]]\npath = 42\n\n[[projects]]\npath = '/src/helix'\n",
+        )
+        .unwrap();
+
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].path, Path::new("/src/helix"));
+    }
+
+    #[test]
+    fn report_invalid_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        std::fs::write(
+            &file_path,
+            "[[projects]]\npath = '/src/helix'\naccess_count = 'often'\n\n\
+             [[projects]]\npath = '/src/kakoune'\n",
+        )
+        .unwrap();
+        let (projects, warnings) = load_projects_with_warnings_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].path, Path::new("/src/kakoune"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].index, 0);
+        assert_eq!(warnings[0].path.as_deref(), Some("/src/helix"));
+        assert!(warnings[0]
+            .to_string()
+            .starts_with("Skipped entry 1 of the projects file (/src/helix): "));
+    }
+
+    #[test]
+    fn skip_entries_with_syntax_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let content = "[[projects]]\npath = '/src/helix'\n\n\
+             [[projects]]\npath = '/src/kakoune'\nname = 'kak\n\n\
+             [[projects]]\npath = '/src/vim'\n";
+        std::fs::write(&file_path, content).unwrap();
+        let (projects, warnings) = load_projects_with_warnings_from(&file_path).unwrap();
+        let paths: Vec<_> = projects.iter().map(|p| p.path.clone()).collect();
+        assert_eq!(paths, [Path::new("/src/helix"), Path::new("/src/vim")]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].index, 1);
+        assert_eq!(warnings[0].path.as_deref(), Some("/src/kakoune"));
+
+        // An invalid structure outside of the entries is still an error.
+        std::fs::write(&file_path, format!("version = \n{content}")).unwrap();
+        assert!(matches!(
+            load_projects_with_warnings_from(&file_path),
+            Err(ProjectsError::Parse { .. })
+        ));
+
+        // The entry which was skipped is kept in a backup when the file is rewritten.
+        std::fs::write(&file_path, content).unwrap();
+        assert!(add_project_in(&file_path, dir.path()).unwrap());
+        let backup = std::fs::read_to_string(dir.path().join("projects.toml.bak")).unwrap();
+        assert_eq!(backup, content);
+        assert_eq!(load_projects_from(&file_path).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn merge_system_projects_with_user_projects() {
+        let mut shared = Project::new(PathBuf::from("/opt/src/shared"));
+        shared.name = Some("Shared".to_string());
+        let system = vec![
+            shared.clone(),
+            Project::new(PathBuf::from("/opt/src/tools")),
+        ];
+        let mut tools = Project::new(PathBuf::from("/opt/src/tools/"));
+        tools.access_count = 2;
+        let mut projects = vec![Project::new(PathBuf::from("/home/me/src")), tools.clone()];
+
+        merge_system_projects(&mut projects, system.clone());
+        // The user's entry for a system project takes precedence.
+        assert_eq!(
+            projects,
+            [
+                Project::new(PathBuf::from("/home/me/src")),
+                tools.clone(),
+                shared.clone()
+            ]
+        );
+
+        // Only the user's projects and changed system projects are saved.
+        assert_eq!(
+            without_system_projects(&projects, &system),
+            [Project::new(PathBuf::from("/home/me/src")), tools]
+        );
+        projects[2].last_accessed = Some(10);
+        assert_eq!(without_system_projects(&projects, &system).len(), 3);
+    }
+
+    #[test]
+    fn drop_hooks_of_untrusted_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        std::fs::write(
+            &file_path,
+            "[[projects]]\npath = '/opt/src/shared'\non_open = 'direnv allow'\non_close = './leave.sh'\n",
+        )
+        .unwrap();
+        let mut projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects[0].on_open.as_deref(), Some("direnv allow"));
+        assert_eq!(projects[0].on_close.as_deref(), Some("./leave.sh"));
+
+        drop_hooks(&mut projects, &file_path);
+        assert_eq!(projects[0].on_open, None);
+        assert_eq!(projects[0].on_close, None);
+    }
+
+    #[test]
+    fn move_project_between_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let personal = dir.path().join("projects.toml");
+        let work = dir.path().join("projects.work.toml");
+        let mut project = Project::new(PathBuf::from("/src/client"));
+        project.access_count = 3;
+        save_projects_to(
+            &personal,
+            &[project.clone(), Project::new("/src/dotfiles".into())],
+        )
+        .unwrap();
+
+        assert!(move_project_in(&personal, &work, Path::new("/src/client/")).unwrap());
+        assert_eq!(load_projects_from(&work).unwrap(), [project.clone()]);
+        let remaining = load_projects_from(&personal).unwrap();
+        assert_eq!(remaining, [Project::new("/src/dotfiles".into())]);
+        assert!(!move_project_in(&personal, &work, Path::new("/src/client")).unwrap());
+        assert!(!dir.path().join("projects.toml.lock").exists());
+        assert!(!dir.path().join("projects.work.toml.lock").exists());
+
+        // The entry already in the target is kept.
+        let mut moved_back = project.clone();
+        moved_back.access_count = 1;
+        save_projects_to(&personal, &[moved_back]).unwrap();
+        assert!(move_project_in(&personal, &work, Path::new("/src/client")).unwrap());
+        assert_eq!(load_projects_from(&work).unwrap(), [project]);
+        assert!(load_projects_from(&personal).unwrap().is_empty());
+    }
+
+    #[test]
+    fn override_projects_file_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        assert_eq!(
+            profile_file_path_from(Some(file_path.as_os_str()), None),
+            file_path
+        );
+        assert_eq!(
+            profile_file_path_from(Some(OsStr::new("")), None),
+            crate::config_dir().join("projects.toml")
+        );
+        assert!(add_project_in(&file_path, dir.path()).unwrap());
+        assert_eq!(load_projects_from(&file_path).unwrap().len(), 1);
+
+        // Other profiles are stored next to the default projects file.
+        let work = dir.path().join("projects.work.toml");
+        assert_eq!(
+            profile_file_path_from(Some(file_path.as_os_str()), Some("work")),
+            work
+        );
+        assert_eq!(
+            profile_file_path_from(Some(file_path.as_os_str()), Some(DEFAULT_PROFILE)),
+            file_path
+        );
+        assert!(add_project_in(&work, &dir.path().join("client")).unwrap());
+        std::fs::write(dir.path().join("projects.work.toml.bak"), "").unwrap();
+        assert_eq!(
+            list_profiles_in(&file_path),
+            [None, Some("work".to_string())]
+        );
+        let all = load_all_profiles_in(&file_path).unwrap();
+        let profiles: Vec<_> = all.iter().map(|p| p.profile.as_deref()).collect();
+        assert_eq!(profiles, [None, Some("work")]);
+        assert!(all[1].project.path.ends_with("client"));
+    }
+
+    #[test]
+    fn override_active_profile() {
+        let selected = Some("home".to_string());
+        assert_eq!(
+            choose_profile(Some("work".into()), selected.clone()).as_deref(),
+            Some("work")
+        );
+        assert_eq!(
+            choose_profile(Some(String::new()), selected.clone()),
+            selected
+        );
+        assert_eq!(choose_profile(None, selected.clone()), selected);
+        assert_eq!(choose_profile(Some(DEFAULT_PROFILE.into()), selected), None);
+        assert_eq!(choose_profile(None, Some(DEFAULT_PROFILE.into())), None);
+    }
+
+    fn make_repos(root: &Path, repos: &[&str]) {
+        for repo in repos {
+            std::fs::create_dir_all(root.join(repo).join(".git")).unwrap();
+        }
+    }
+
+    #[test]
+    fn scan_with_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = canonicalize(dir.path());
+        make_repos(
+            &root,
+            &["a", "a/nested", "b/c", "b/node_modules/d", "e/f/g"],
+        );
+
+        let scan = |options: &ScanOptions| scan_git_repositories_with(&root, options).unwrap();
+        // Repositories nested in other repositories are skipped.
+        assert_eq!(
+            scan(&ScanOptions::default()),
+            [
+                root.join("a"),
+                root.join("b/c"),
+                root.join("b/node_modules/d"),
+                root.join("e/f/g"),
+            ]
+        );
+        let options = ScanOptions {
+            max_depth: Some(2),
+            exclude: vec!["node_modules".to_string()],
+            ..ScanOptions::default()
+        };
+        assert_eq!(scan(&options), [root.join("a"), root.join("b/c")]);
+        let options = ScanOptions {
+            max_depth: Some(0),
+            ..ScanOptions::default()
+        };
+        assert!(scan(&options).is_empty());
+        assert_eq!(
+            scan_git_repositories_with(&root.join("a"), &options).unwrap(),
+            [root.join("a")]
+        );
+    }
+
+    #[test]
+    fn scan_up_to_max_repos() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = canonicalize(dir.path()).join("src");
+        let file_path = dir.path().join("projects.toml");
+        make_repos(&root, &["d", "b/z", "b/a", "a/nested/x", "c"]);
+
+        let scan = |max_repos| {
+            let options = ScanOptions {
+                max_repos,
+                ..ScanOptions::default()
+            };
+            scan_for_registration_in(&file_path, &root, &options).unwrap()
+        };
+        // The first repositories by path are kept, whatever order the directories are read in.
+        let report = scan(Some(3));
+        assert!(report.truncated);
+        assert_eq!(
+            report.added,
+            [root.join("a/nested/x"), root.join("b/a"), root.join("b/z")]
+        );
+        let report = scan(Some(0));
+        assert!(report.truncated);
+        assert!(report.added.is_empty());
+        // Finding exactly `max_repos` repositories doesn't truncate the scan.
+        let report = scan(Some(5));
+        assert!(!report.truncated);
+        assert_eq!(report.added.len(), 5);
+        assert!(!scan(None).truncated);
+
+        let options = ScanOptions {
+            max_repos: Some(2),
+            ..ScanOptions::default()
+        };
+        let report = scan_and_register_in(&file_path, &root, &options).unwrap();
+        assert!(report.truncated);
+        assert_eq!(load_projects_from(&file_path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn scan_and_register_keeps_registered_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = canonicalize(dir.path()).join("src");
+        let file_path = dir.path().join("projects.toml");
+        make_repos(&root, &["helix", "kakoune", "vim"]);
+
+        let mut helix = Project::new(root.join("helix"));
+        helix.name = Some("hx".to_string());
+        helix.access_count = 7;
+        helix.archived = true;
+        let elsewhere = Project::new(PathBuf::from("/elsewhere"));
+        save_projects_to(&file_path, &[helix.clone(), elsewhere.clone()]).unwrap();
+
+        let report = scan_and_register_in(&file_path, &root, &ScanOptions::default()).unwrap();
+        assert_eq!(report.added, [root.join("kakoune"), root.join("vim")]);
+        assert_eq!(report.known, [root.join("helix")]);
+        assert!(report.errors.is_empty());
+
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 4);
+        assert!(projects.contains(&helix));
+        assert!(projects.contains(&elsewhere));
+        assert!(projects.contains(&Project::new(root.join("vim"))));
+
+        // Scanning again doesn't change anything.
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        let report = scan_and_register_in(&file_path, &root, &ScanOptions::default()).unwrap();
+        assert!(report.added.is_empty());
+        assert_eq!(report.known.len(), 3);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), content);
+    }
+
+    #[test]
+    fn scan_for_registration_leaves_projects_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = canonicalize(dir.path()).join("src");
+        let file_path = dir.path().join("projects.toml");
+        make_repos(&root, &["vim", "helix", "kakoune"]);
+        save_projects_to(&file_path, &[Project::new(root.join("kakoune"))]).unwrap();
+        let content = std::fs::read_to_string(&file_path).unwrap();
+
+        let report = scan_for_registration_in(&file_path, &root, &ScanOptions::default()).unwrap();
+        assert_eq!(report.added, [root.join("helix"), root.join("vim")]);
+        assert_eq!(report.known, [root.join("kakoune")]);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), content);
+
+        // Registering for real reports the same.
+        let registered = scan_and_register_in(&file_path, &root, &ScanOptions::default()).unwrap();
+        assert_eq!(registered.added, report.added);
+        assert_eq!(registered.known, report.known);
+    }
+
+    #[test]
+    fn report_error_kinds() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let missing = dir.path().join("missing");
+        let err = scan_git_repositories(&missing).unwrap_err();
+        assert!(matches!(err, ProjectsError::NotFound { ref path, .. } if *path == missing));
+        let file_path = dir.path().join("projects.toml");
+        let err = export_projects_file_from(
+            &file_path,
+            &missing.join("projects.json"),
+            ExportFormat::Json,
+        );
+        assert!(matches!(err, Err(ProjectsError::NotFound { .. })));
+
+        std::fs::write(&file_path, "projects = [").unwrap();
+        let err = load_projects_from(&file_path).unwrap_err();
+        assert!(matches!(err, ProjectsError::Parse { ref path, .. } if *path == file_path));
+        assert_eq!(
+            err.to_string(),
+            format!("Failed to parse projects file: {}", file_path.display())
+        );
+
+        let err = read_projects("[".as_bytes(), ExportFormat::Json).unwrap_err();
+        assert!(matches!(err, ProjectsError::InvalidImport { .. }));
+        let err = "newest".parse::<MergeStrategy>().unwrap_err();
+        assert!(matches!(err, ProjectsError::UnknownMergeStrategy(_)));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn report_read_only_target() {
+        use std::os::unix::fs::PermissionsExt;
 
-    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let dir = tempfile::tempdir().unwrap();
+        let read_only = dir.path().join("read-only");
+        std::fs::create_dir(&read_only).unwrap();
+        std::fs::set_permissions(&read_only, std::fs::Permissions::from_mode(0o555)).unwrap();
+        // Permissions don't apply to privileged users.
+        if std::fs::write(read_only.join("probe"), "").is_ok() {
+            return;
+        }
+
+        let file_path = read_only.join("projects.toml");
+        let err = save_projects_to(&file_path, &[]).unwrap_err();
+        assert!(matches!(
+            err,
+            ProjectsError::Io { ref source, .. } if source.kind() == ErrorKind::PermissionDenied
+        ));
+        assert!(matches!(
+            update_projects_in(&file_path, |_| (true, ())),
+            Err(ProjectsError::Io {
+                action: "create projects lock",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn find_project_by_name_or_path() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("foo")).unwrap();
+        std::fs::create_dir(dir.path().join("bar")).unwrap();
+        let foo = canonicalize(&dir.path().join("foo"));
+        let bar = canonicalize(&dir.path().join("bar"));
+        let mut named = Project::new(bar.clone());
+        // Names take precedence over paths.
+        named.name = Some(foo.to_string_lossy().into_owned());
+        let projects = [Project::new(foo.clone()), named];
+
+        let find = |query: &str| find_project(&projects, query).map(|p| p.path.clone());
+        assert_eq!(find("foo"), Some(foo.clone()));
+        assert_eq!(find(&foo.join(".").to_string_lossy()), Some(foo.clone()));
+        assert_eq!(find(&foo.to_string_lossy()), Some(bar.clone()));
+        assert_eq!(find(&bar.to_string_lossy()), Some(bar));
+        assert_eq!(find("baz"), None);
+    }
+
+    #[test]
+    fn match_project_by_query() {
+        let project = |path: &str| Project::new(PathBuf::from(path));
+        let projects = [
+            project("/src/helix"),
+            project("/src/helm"),
+            project("/work/tools/kakoune"),
+            project("/old/kakoune"),
+        ];
+        let found = |query: &str| match match_project(&projects, query) {
+            ProjectMatch::Found(project) => Some(project.path.to_string_lossy().into_owned()),
+            ProjectMatch::Ambiguous(_) | ProjectMatch::Ambient(_) | ProjectMatch::NotFound => None,
+        };
+
+        assert_eq!(found("helix").as_deref(), Some("/src/helix"));
+        assert_eq!(found("/src/helm").as_deref(), Some("/src/helm"));
+        // Unique prefix
+        assert_eq!(found("heli").as_deref(), Some("/src/helix"));
+        // Fuzzy match on the name
+        assert_eq!(found("HLX").as_deref(), Some("/src/helix"));
+        // Path suffix
+        assert_eq!(
+            found("tools/kakoune").as_deref(),
+            Some("/work/tools/kakoune")
+        );
+        assert_eq!(found("old/kakoune/").as_deref(), Some("/old/kakoune"));
+
+        let ambiguous = |query: &str| match match_project(&projects, query) {
+            ProjectMatch::Ambiguous(candidates) => candidates
+                .iter()
+                .map(|p| p.path.to_string_lossy().into_owned())
+                .collect(),
+            _ => Vec::new(),
+        };
+        assert_eq!(ambiguous("hel"), ["/src/helix", "/src/helm"]);
+        // Projects may share a name.
+        assert_eq!(
+            ambiguous("kakoune"),
+            ["/work/tools/kakoune", "/old/kakoune"]
+        );
+
+        assert_eq!(match_project(&projects, "vim"), ProjectMatch::NotFound);
+        assert_eq!(match_project(&projects, ""), ProjectMatch::NotFound);
+    }
+
+    #[test]
+    fn match_project_by_path_suffix() {
+        let project = |path: &str, name: &str| Project {
+            name: Some(name.to_string()),
+            ..Project::new(PathBuf::from(path))
+        };
+        let projects = [
+            project("/src/clientx/api", "api"),
+            project("/src/clienty/api", "api"),
+            project("/src/box/rapid", "rapid"),
+            // A name which contains the characters of `x/api` in order.
+            project("/src/other", "x/tapir"),
+        ];
+        let found = |query: &str| match match_project(&projects, query) {
+            ProjectMatch::Found(project) => Some(project.path.to_string_lossy().into_owned()),
+            ProjectMatch::Ambiguous(_) | ProjectMatch::Ambient(_) | ProjectMatch::NotFound => None,
+        };
+
+        assert_eq!(found("clientx/api").as_deref(), Some("/src/clientx/api"));
+        assert_eq!(
+            found("src/clienty/api/").as_deref(),
+            Some("/src/clienty/api")
+        );
+        // Path components are matched as a whole, so this falls back to matching names.
+        assert_eq!(found("x/api").as_deref(), Some("/src/other"));
+        assert_eq!(found("box/rapid").as_deref(), Some("/src/box/rapid"));
+
+        let candidates = match_project(&projects, "src/api").into_candidates();
+        assert!(candidates.is_empty());
+        let candidates = match_project(&projects, "api").into_candidates();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(
+            ambiguous_project_message("api", &candidates),
+            "project 'api' is ambiguous, candidates are:\n  /src/clientx/api (api)\n  /src/clienty/api (api)"
+        );
+    }
+
+    #[test]
+    fn archive_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let project = |path: &str| Project::new(PathBuf::from(path));
+        save_projects_to(&file_path, &[project("/src/helix"), project("/src/helm")]).unwrap();
+        // Unarchived projects don't store the field.
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert!(!content.contains("archived"), "{content}");
+
+        assert!(set_project_archived_in(&file_path, Path::new("/src/helm"), true).unwrap());
+        assert!(!set_project_archived_in(&file_path, Path::new("/src/vim"), true).unwrap());
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert!(content.contains("archived = true"), "{content}");
+
+        let names = |include_archived| -> Vec<_> {
+            list_projects_from(&file_path, include_archived)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.display_name().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(names(false), ["helix"]);
+        assert_eq!(names(true), ["helix", "helm"]);
+
+        let mut projects = load_projects_from(&file_path).unwrap();
+        assert!(find_project(&projects, "helm").is_none());
+        assert!(find_project(&projects, "/src/helm").is_none());
+        assert_eq!(
+            match_project(&projects, "hel"),
+            ProjectMatch::Found(&projects[0])
+        );
+        assert_eq!(match_projects(&projects, "").len(), 1);
+        // Archived projects still record accesses.
+        let found = find_project_for_path(&projects, Path::new("/src/helm/main.rs"));
+        assert_eq!(found, Some(&projects[1]));
+        update_project_last_accessed(&mut projects, Path::new("/src/helm"));
+        assert_eq!(projects[1].access_count, 1);
+
+        assert!(set_archived(&mut projects, Path::new("/src/helm"), false));
+        assert!(find_project(&projects, "helm").is_some());
+    }
+
+    #[test]
+    fn find_innermost_project_for_path() {
+        let outer = Project::new(PathBuf::from("/src/outer"));
+        let inner = Project::new(PathBuf::from("/src/outer/inner"));
+        let projects = [inner.clone(), outer];
+
+        let find = |path: &str| {
+            find_project_for_path(&projects, Path::new(path)).and_then(|p| p.display_name())
+        };
+        assert_eq!(find("/src/outer/inner/main.rs"), Some("inner"));
+        assert_eq!(find("/src/outer/main.rs"), Some("outer"));
+        assert_eq!(find("/src/outer"), Some("outer"));
+        // Only whole path components are matched.
+        assert_eq!(find("/src/outer-other/main.rs"), None);
+        assert_eq!(find("/elsewhere/main.rs"), None);
+
+        let mut named = inner;
+        named.name = Some("named".to_string());
+        let projects = [named];
+        assert_eq!(
+            find_project_for_path(&projects, Path::new("/src/outer/inner/main.rs"))
+                .and_then(|p| p.display_name()),
+            Some("named")
+        );
+    }
+
+    #[test]
+    fn match_projects_by_name_and_path() {
+        let now = unix_now();
+        let project = |path: &str, name: Option<&str>, access_count| Project {
+            name: name.map(String::from),
+            last_accessed: Some(now),
+            access_count,
+            ..Project::new(PathBuf::from(path))
+        };
+        let projects = [
+            project("/home/user/src/helix", None, 1),
+            project("/home/user/src/helix-plugins", None, 5),
+            project("/home/user/work/editor", Some("helix-fork"), 2),
+            project("/home/user/helix/docs", None, 3),
+            project("/home/user/src/kakoune", None, 4),
+        ];
+        let names = |query| -> Vec<_> {
+            match_projects(&projects, query)
+                .into_iter()
+                .filter_map(|(project, _)| project.display_name())
+                .collect()
+        };
+
+        // An empty query returns every project by frecency.
+        assert_eq!(
+            names(""),
+            ["helix-plugins", "kakoune", "docs", "helix-fork", "helix"]
+        );
+        // An exact match of the final path component ranks first, then other matches in final
+        // path components, then matches in the name and finally matches anywhere in the path.
+        assert_eq!(
+            names("helix"),
+            ["helix", "helix-plugins", "helix-fork", "docs"]
+        );
+        // Equal scores are ordered by frecency.
+        assert_eq!(
+            names("hx"),
+            ["helix-plugins", "helix", "helix-fork", "docs"]
+        );
+        assert_eq!(names("docs"), ["docs"]);
+        assert_eq!(names("fork"), ["helix-fork"]);
+        assert_eq!(names("src/k"), ["kakoune"]);
+        assert!(names("zzz").is_empty());
+    }
+
+    #[test]
+    fn sort_projects_by_frecency() {
+        let now = 10_000_000;
+        let project = |name: &str, last_accessed: Option<u64>, access_count| Project {
+            last_accessed,
+            access_count,
+            ..Project::new(PathBuf::from(name))
+        };
+        let mut projects = [
+            project("never", None, 0),
+            project("old-but-frequent", Some(now - 30 * 86_400), 40),
+            project("yesterday", Some(now - 86_400), 3),
+            project("just-now", Some(now - 60), 1),
+            project("legacy", Some(now - 60), 0),
+        ];
+        let scores: Vec<_> = projects.iter().map(|p| p.frecency(now)).collect();
+        assert_eq!(scores, [0.0, 10.0, 1.5, 4.0, 4.0]);
+
+        projects.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+        let names: Vec<_> = projects.iter().filter_map(|p| p.display_name()).collect();
+        assert_eq!(
+            names,
+            [
+                "old-but-frequent",
+                "just-now",
+                "legacy",
+                "yesterday",
+                "never"
+            ]
+        );
+    }
+
+    #[test]
+    fn sanitize_access_times() {
+        let now = unix_now();
+        let project = |name: &str, last_accessed: Option<u64>| Project {
+            last_accessed,
+            access_count: 1,
+            ..Project::new(PathBuf::from(name))
+        };
+        let mut projects = [
+            project("never", None),
+            project("future", Some(now + 365 * 86_400)),
+            project("epoch", Some(0)),
+            project("tomorrow", Some(now + 3_600)),
+            project("yesterday", Some(now - 86_400)),
+        ];
+        let times: Vec<_> = projects.iter().map(|p| p.last_accessed_at(now)).collect();
+        assert_eq!(
+            times,
+            [None, Some(now), None, Some(now + 3_600), Some(now - 86_400)]
+        );
 
-    for project in projects.iter_mut() {
-        let project_path = project.path.canonicalize()
-            .unwrap_or_else(|_| project.path.clone());
-        if project_path == path {
-            project.last_accessed = Some(now);
-            break;
+        // The future access ranks like one right now, and zero like none, in a stable order.
+        projects.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+        let names: Vec<_> = projects.iter().filter_map(|p| p.display_name()).collect();
+        assert_eq!(names, ["future", "tomorrow", "yesterday", "never", "epoch"]);
+
+        // Saving writes the sanitized times.
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        save_projects_to(&file_path, &projects).unwrap();
+        let saved = load_projects_from(&file_path).unwrap();
+        let saved_time = |name| {
+            let project = saved.iter().find(|p| p.display_name() == Some(name));
+            project.unwrap().last_accessed
+        };
+        assert_eq!(saved_time("epoch"), None);
+        assert!(saved_time("future").unwrap() <= unix_now());
+        assert_eq!(saved_time("tomorrow"), Some(now + 3_600));
+        assert_eq!(saved_time("yesterday"), Some(now - 86_400));
+        assert!(matches!(clamp_access_times(&saved, now), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn write_projects_file_in_order() {
+        let project = |path: &str, name: Option<&str>, last_accessed| Project {
+            name: name.map(String::from),
+            last_accessed,
+            ..Project::new(PathBuf::from(path))
+        };
+        let projects = [
+            project("/src/b", Some("z"), Some(1)),
+            project("/src/a", Some("y"), None),
+            project("/src/c", Some("y"), Some(3)),
+        ];
+        let order = |order| {
+            let content = serialize_projects(&projects, order).unwrap();
+            let projects: ProjectsFile = toml::from_str(&content).unwrap();
+            let paths: Vec<_> = projects.projects.into_iter().map(|p| p.path).collect();
+            paths
+        };
+        assert_eq!(
+            order(ProjectsFileOrder::Path),
+            ["/src/a", "/src/b", "/src/c"].map(PathBuf::from)
+        );
+        // Equal names keep their order.
+        assert_eq!(
+            order(ProjectsFileOrder::Name),
+            ["/src/a", "/src/c", "/src/b"].map(PathBuf::from)
+        );
+        assert_eq!(
+            order(ProjectsFileOrder::LastAccessed),
+            ["/src/c", "/src/b", "/src/a"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn rewrite_projects_file_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let projects = [
+            Project::new(dir.path().join("b")),
+            Project::new(dir.path().join("a")),
+        ];
+        save_projects_to(&file_path, &projects).unwrap();
+        let content = std::fs::read(&file_path).unwrap();
+
+        // The order in memory is left to the caller.
+        let mut loaded = load_projects_from(&file_path).unwrap();
+        loaded.reverse();
+        save_projects_to(&file_path, &loaded).unwrap();
+        assert_eq!(std::fs::read(&file_path).unwrap(), content);
+        update_projects_in(&file_path, |_| (true, ())).unwrap();
+        assert_eq!(std::fs::read(&file_path).unwrap(), content);
+    }
+
+    #[test]
+    fn touch_registers_and_bumps_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+
+        assert!(touch_project_in(&file_path, &project).unwrap());
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert!(projects[0].last_accessed.is_some());
+
+        update_projects_in(&file_path, |projects| {
+            projects[0].last_accessed = Some(0);
+            (true, ())
+        })
+        .unwrap();
+        assert!(!touch_project_in(&file_path, &project).unwrap());
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_ne!(projects[0].last_accessed, Some(0));
+    }
+
+    #[test]
+    fn remove_missing_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let missing = dir.path().join("missing");
+        save_projects_to(&file_path, &[Project::new(missing.clone())]).unwrap();
+
+        assert!(remove_project_in(&file_path, &missing).unwrap());
+        assert!(load_projects_from(&file_path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn check_project_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        let projects = [
+            Project::new(dir.path().to_path_buf()),
+            Project::new(dir.path().join("missing")),
+            Project::new(file),
+        ];
+
+        assert_eq!(
+            check_projects(&projects),
+            [
+                ProjectStatus::Available,
+                ProjectStatus::Missing,
+                ProjectStatus::Missing
+            ]
+        );
+        assert!(check_projects(&[]).is_empty());
+        assert_eq!(project_status(dir.path()), ProjectStatus::Available);
+    }
+
+    #[test]
+    fn format_projects_as_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        // A missing projects file is an empty list.
+        assert_eq!(
+            format_project_lines(&load_projects_from(&file_path).unwrap()),
+            ""
+        );
+
+        std::fs::write(
+            &file_path,
+            "[[projects]]\npath = \"/src/helix\"\nname = \"helix\"\nlast_accessed = 42\n\n\
+             [[projects]]\npath = \"/src/other\"\n",
+        )
+        .unwrap();
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(
+            format_project_lines(&projects),
+            "helix\t/src/helix\t42\nother\t/src/other\t\n"
+        );
+
+        std::fs::write(&file_path, "[[projects]\npath = 1\n").unwrap();
+        let err = load_projects_from(&file_path).unwrap_err();
+        assert!(format!("{err:#}").contains("Failed to parse projects file"));
+    }
+
+    #[test]
+    fn export_and_import_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = canonicalize(dir.path());
+        let file_path = dir_path.join("projects.toml");
+        let a = dir_path.join("a");
+        let b = dir_path.join("b");
+        std::fs::create_dir(&a).unwrap();
+        std::fs::create_dir(&b).unwrap();
+        let project = |path: &Path, last_accessed| Project {
+            last_accessed: Some(last_accessed),
+            ..Project::new(path.to_path_buf())
+        };
+        save_projects_to(&file_path, &[project(&a, 10), project(&b, 10)]).unwrap();
+
+        for format in [ExportFormat::Json, ExportFormat::Toml] {
+            let projects = load_projects_from(&file_path).unwrap();
+            let mut exported = Vec::new();
+            write_projects(&mut exported, &projects, format).unwrap();
+            assert_eq!(
+                read_projects(exported.as_slice(), format).unwrap(),
+                projects
+            );
         }
+
+        let missing = dir_path.join("missing");
+        let imported = [
+            project(&a, 20),
+            // Duplicates are compared by canonical path.
+            project(&b.join("../a"), 30),
+            project(&b, 5),
+            project(&missing, 1),
+        ];
+        let mut json = Vec::new();
+        write_projects(&mut json, &imported, ExportFormat::Json).unwrap();
+        let import = |strategy| {
+            save_projects_to(&file_path, &[project(&a, 10), project(&b, 10)]).unwrap();
+            let report =
+                import_projects_file_in(&file_path, json.as_slice(), ExportFormat::Json, strategy)
+                    .unwrap();
+            let projects: Vec<_> = load_projects_from(&file_path)
+                .unwrap()
+                .into_iter()
+                .map(|p| (p.path, p.last_accessed.unwrap()))
+                .collect();
+            (report, projects)
+        };
+
+        let (report, projects) = import(MergeStrategy::AddMissingOnly);
+        assert_eq!(
+            report,
+            ImportReport {
+                added: 1,
+                missing: 1,
+                ..ImportReport::default()
+            }
+        );
+        assert_eq!(
+            projects,
+            [(a.clone(), 10), (b.clone(), 10), (missing.clone(), 1)]
+        );
+
+        let (report, projects) = import(MergeStrategy::KeepNewest);
+        assert_eq!(
+            report,
+            ImportReport {
+                added: 1,
+                updated: 1,
+                missing: 1,
+                ..ImportReport::default()
+            }
+        );
+        assert_eq!(
+            projects,
+            [(a.clone(), 20), (b.clone(), 10), (missing.clone(), 1)]
+        );
+
+        save_projects_to(&file_path, &[project(&b, 10), project(&dir_path, 10)]).unwrap();
+        let report = import_projects_file_in(
+            &file_path,
+            json.as_slice(),
+            ExportFormat::Json,
+            MergeStrategy::Replace,
+        )
+        .unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                added: 2,
+                removed: 1,
+                missing: 1,
+                ..ImportReport::default()
+            }
+        );
+        let paths: Vec<_> = load_projects_from(&file_path)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.path)
+            .collect();
+        assert_eq!(paths, [a, b, missing]);
+
+        // Invalid imports leave the projects file untouched.
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        let result = import_projects_file_in(
+            &file_path,
+            "[{".as_bytes(),
+            ExportFormat::Json,
+            MergeStrategy::Replace,
+        );
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), content);
     }
 }
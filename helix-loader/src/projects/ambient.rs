@@ -0,0 +1,135 @@
+//! Ambient project roots from `$HELIX_PROJECT_PATHS`, searched for unregistered projects by
+//! name the way `cd` searches `$CDPATH`.
+
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+use super::canonicalize;
+
+/// The environment variable listing the ambient project roots.
+pub const PROJECT_PATHS_VAR: &str = "HELIX_PROJECT_PATHS";
+
+/// The ambient project roots listed in `$HELIX_PROJECT_PATHS`, see [parse_project_paths].
+pub fn ambient_roots() -> Vec<PathBuf> {
+    std::env::var_os(PROJECT_PATHS_VAR)
+        .map(|value| parse_project_paths(&value))
+        .unwrap_or_default()
+}
+
+/// Splits a list of roots separated by the platform's path list separator, `:` on Unix and `;`
+/// on Windows, expanding `~` to the home directory.
+///
+/// Empty entries and roots which aren't directories are skipped.
+pub fn parse_project_paths(value: &OsStr) -> Vec<PathBuf> {
+    std::env::split_paths(value)
+        .filter(|root| !root.as_os_str().is_empty())
+        .map(|root| helix_stdx::path::expand_tilde(&root).into_owned())
+        .filter(|root| root.is_dir())
+        .collect()
+}
+
+/// The directory named `name` directly under the first of `roots` which has one.
+///
+/// Only plain names are looked up, so paths such as `./name` or `work/name` never match.
+pub fn find_ambient_project(roots: &[PathBuf], name: &str) -> Option<PathBuf> {
+    let mut components = Path::new(name).components();
+    let (Some(Component::Normal(name)), None) = (components.next(), components.next()) else {
+        return None;
+    };
+    roots
+        .iter()
+        .map(|root| root.join(name))
+        .find(|path| path.is_dir())
+        .map(|path| canonicalize(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::{match_project_in, Project, ProjectMatch};
+
+    #[test]
+    fn find_in_first_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = canonicalize(dir.path());
+        let (src, work) = (root.join("src"), root.join("work"));
+        for path in [src.join("foo"), work.join("foo"), work.join("bar")] {
+            std::fs::create_dir_all(path).unwrap();
+        }
+        std::fs::write(src.join("bar"), "").unwrap();
+
+        let roots = [src.clone(), work.clone()];
+        assert_eq!(find_ambient_project(&roots, "foo"), Some(src.join("foo")));
+        // Files are skipped in favor of directories under later roots.
+        assert_eq!(find_ambient_project(&roots, "bar"), Some(work.join("bar")));
+        assert_eq!(find_ambient_project(&roots, "baz"), None);
+        for query in ["", ".", "..", "./foo", "src/foo"] {
+            assert_eq!(
+                find_ambient_project(&[root.clone()], query),
+                None,
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn registered_projects_take_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = canonicalize(dir.path());
+        let (src, other) = (root.join("src"), root.join("other"));
+        for path in [src.join("foo"), src.join("bar"), other.join("foo")] {
+            std::fs::create_dir_all(path).unwrap();
+        }
+        let mut registered = Project::new(other.join("foo"));
+        registered.name = Some("foo".to_string());
+        let projects = [registered];
+        let roots = [src.clone()];
+
+        assert_eq!(
+            match_project_in(&projects, &roots, "foo"),
+            ProjectMatch::Found(&projects[0])
+        );
+        assert_eq!(
+            match_project_in(&projects, &roots, "bar"),
+            ProjectMatch::Ambient(src.join("bar"))
+        );
+        assert_eq!(
+            match_project_in(&projects, &roots, "baz"),
+            ProjectMatch::NotFound
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_unix_path_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        std::fs::create_dir(&a).unwrap();
+        std::fs::create_dir(&b).unwrap();
+        let missing = dir.path().join("missing");
+
+        let value = format!("{}::{}:{}", a.display(), missing.display(), b.display());
+        assert_eq!(parse_project_paths(OsStr::new(&value)), [a, b]);
+        assert_eq!(parse_project_paths(OsStr::new("")), Vec::<PathBuf>::new());
+
+        let home = helix_stdx::path::expand_tilde(Path::new("~"));
+        if home.is_dir() && home != Path::new("~") {
+            assert_eq!(parse_project_paths(OsStr::new("~")), [home.into_owned()]);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn parse_windows_path_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        std::fs::create_dir(&a).unwrap();
+        std::fs::create_dir(&b).unwrap();
+        let missing = dir.path().join("missing");
+
+        // Drive letters contain colons, so only semicolons separate the roots.
+        let value = format!("{};;{};{}", a.display(), missing.display(), b.display());
+        assert_eq!(parse_project_paths(OsStr::new(&value)), [a, b]);
+        assert_eq!(parse_project_paths(OsStr::new("")), Vec::<PathBuf>::new());
+    }
+}
@@ -0,0 +1,274 @@
+//! Updating a projects file in place, so that comments and formatting added by hand survive.
+
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table};
+
+use super::{Project, ProjectsError, ProjectsFileOrder, Result};
+
+/// Rewrites the `[[projects]]` tables of `content` to hold `projects`.
+///
+/// Tables of unchanged projects are left as they are and only the changed keys of the others
+/// are replaced. Removed projects hand their leading comments on to the next table. New
+/// projects are inserted before the first table that comes after them in `order`.
+///
+/// Returns `None` if `content` isn't a projects file that can be edited.
+pub(super) fn update_projects_file(
+    content: &str,
+    projects: &[Project],
+    order: ProjectsFileOrder,
+) -> Option<Result<String>> {
+    let mut document: DocumentMut = content.parse().ok()?;
+    let tables = match document.get_mut("projects") {
+        Some(item) => item.as_array_of_tables_mut()?,
+        None => {
+            document.insert("projects", Item::ArrayOfTables(ArrayOfTables::new()));
+            document["projects"].as_array_of_tables_mut().unwrap()
+        }
+    };
+    let first_position = tables.iter().find_map(Table::position);
+
+    let mut entries: Vec<(Option<PathBuf>, Table)> = Vec::with_capacity(projects.len());
+    let mut comments = String::new();
+    for mut table in tables.iter().cloned() {
+        take_comments(&mut table, std::mem::take(&mut comments));
+        // Entries that can't be decoded are skipped when loading and kept as they are.
+        let Some(old) = decode(&table) else {
+            entries.push((None, table));
+            continue;
+        };
+        // Tables for a path which is already taken, written differently, were merged on load.
+        let taken = entries
+            .iter()
+            .any(|(path, _)| path.as_ref() == Some(&old.path));
+        let new = projects.iter().find(|project| project.path == old.path);
+        let Some(new) = new.filter(|_| !taken) else {
+            comments = prefix(&table);
+            if !comments
+                .lines()
+                .any(|line| line.trim_start().starts_with('#'))
+            {
+                comments.clear();
+            }
+            continue;
+        };
+        // Paths are cleaned up on load, so a path that was written differently compares equal.
+        let path_written = table.get("path").and_then(Item::as_str);
+        if old != *new || path_written != new.path.to_str() {
+            match encode(new) {
+                Ok(new) => update_table(&mut table, &new),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        entries.push((Some(old.path), table));
+    }
+
+    for project in projects {
+        if entries
+            .iter()
+            .any(|(path, _)| path.as_ref() == Some(&project.path))
+        {
+            continue;
+        }
+        let table = match encode(project) {
+            Ok(table) => table,
+            Err(err) => return Some(Err(err)),
+        };
+        let index = entries
+            .iter()
+            .position(|(path, _)| {
+                let other = path
+                    .as_ref()
+                    .and_then(|path| projects.iter().find(|p| p.path == *path));
+                other.is_some_and(|other| order.cmp(project, other) == Ordering::Less)
+            })
+            .unwrap_or(entries.len());
+        entries.insert(index, (Some(project.path.clone()), table));
+    }
+
+    tables.clear();
+    let mut position = first_position.unwrap_or(1);
+    for (_, mut table) in entries {
+        table.set_position(position);
+        position += 1;
+        tables.push(table);
+    }
+    if !comments.is_empty() {
+        let trailing = document.trailing().as_str().unwrap_or_default();
+        document.set_trailing(format!("{comments}{trailing}"));
+    }
+    Some(Ok(document.to_string()))
+}
+
+fn decode(table: &Table) -> Option<Project> {
+    toml::from_str(&table.to_string()).ok()
+}
+
+fn encode(project: &Project) -> Result<Table> {
+    let content = toml::to_string(project).map_err(|err| ProjectsError::Serialize {
+        format: "TOML",
+        source: err.into(),
+    })?;
+    let document: DocumentMut = content.parse().map_err(|err| ProjectsError::Serialize {
+        format: "TOML",
+        source: Box::new(err),
+    })?;
+    Ok(document.as_table().clone())
+}
+
+/// Replaces the values of `table` which differ from `new`, keeping the order and the
+/// formatting of the keys, and removes the keys `new` doesn't have.
+fn update_table(table: &mut Table, new: &Table) {
+    let old = table.clone();
+    table.retain(|key, _| new.contains_key(key));
+    for (key, item) in new.iter() {
+        let (Some(value), old_value) = (item.as_value(), old.get(key).and_then(Item::as_value))
+        else {
+            continue;
+        };
+        if old_value.is_some_and(|old_value| same_value(old_value, value)) {
+            continue;
+        }
+        let mut value = value.clone();
+        if let Some(old_value) = old_value {
+            *value.decor_mut() = old_value.decor().clone();
+        }
+        table.insert(key, Item::Value(value));
+    }
+}
+
+fn same_value(a: &toml_edit::Value, b: &toml_edit::Value) -> bool {
+    let parse = |value: &toml_edit::Value| {
+        let value = value.clone().decorated("", "");
+        toml::from_str::<toml::Table>(&format!("value = {value}")).ok()
+    };
+    parse(a).is_some_and(|a| Some(a) == parse(b))
+}
+
+/// The whitespace and comments before the header of `table`.
+fn prefix(table: &Table) -> String {
+    table
+        .decor()
+        .prefix()
+        .and_then(|prefix| prefix.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Moves the `comments` of a removed table in front of `table`. They replace the whitespace
+/// before `table`, or are put before its own comments.
+fn take_comments(table: &mut Table, comments: String) {
+    if comments.is_empty() {
+        return;
+    }
+    let prefix = prefix(table);
+    let prefix = if prefix.trim().is_empty() {
+        comments
+    } else {
+        comments + &prefix
+    };
+    table.decor_mut().set_prefix(prefix);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{load_projects_from, save_projects_to, update_projects_in};
+    use super::*;
+    use std::path::Path;
+
+    const FIXTURE: &str = "\
+# Projects, grouped by hand.
+
+# Work
+[[projects]]
+path = '/work/api'  # the backend
+name = 'api'
+last_accessed = 100
+
+[[projects]]
+path = '/work/web'
+last_accessed = 200
+
+# Personal
+[[projects]]
+path = '/home/dotfiles'
+access_count = 3 # rarely
+";
+
+    fn update(f: impl FnOnce(&mut Vec<Project>)) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        std::fs::write(&file_path, FIXTURE).unwrap();
+        update_projects_in(&file_path, |projects| {
+            f(projects);
+            (true, ())
+        })
+        .unwrap();
+        std::fs::read_to_string(&file_path).unwrap()
+    }
+
+    #[test]
+    fn keep_unchanged_file() {
+        assert_eq!(update(|_| ()), FIXTURE);
+    }
+
+    #[test]
+    fn keep_comments_when_adding() {
+        let content = update(|projects| projects.push(Project::new(PathBuf::from("/work/cli"))));
+        assert_eq!(
+            content,
+            FIXTURE.replace(
+                "\n[[projects]]\npath = '/work/web'",
+                "\n[[projects]]\npath = \"/work/cli\"\nname = \"cli\"\n\n[[projects]]\npath = '/work/web'"
+            )
+        );
+    }
+
+    #[test]
+    fn keep_comments_when_touching() {
+        let content = update(|projects| {
+            projects[0].last_accessed = Some(300);
+            projects[0].access_count = 1;
+            projects[2].access_count = 4;
+        });
+        assert_eq!(
+            content,
+            FIXTURE
+                .replace(
+                    "last_accessed = 100\n",
+                    "last_accessed = 300\naccess_count = 1\n"
+                )
+                .replace("access_count = 3 # rarely", "access_count = 4 # rarely")
+        );
+    }
+
+    #[test]
+    fn keep_comments_when_removing() {
+        let content = update(|projects| projects.retain(|p| p.path != Path::new("/work/api")));
+        assert_eq!(
+            content,
+            FIXTURE.replace(
+                "[[projects]]\npath = '/work/api'  # the backend\nname = 'api'\nlast_accessed = 100\n\n",
+                ""
+            )
+        );
+
+        // The comments of the last table are kept too.
+        let content = update(|projects| projects.truncate(2));
+        assert!(content.ends_with("last_accessed = 200\n\n# Personal\n"));
+    }
+
+    #[test]
+    fn create_new_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let projects = [Project::new(PathBuf::from("/src/helix"))];
+        save_projects_to(&file_path, &projects).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "[[projects]]\npath = \"/src/helix\"\nname = \"helix\"\n"
+        );
+        assert_eq!(load_projects_from(&file_path).unwrap(), projects);
+    }
+}
@@ -0,0 +1,99 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub(super) type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The errors of the projects functions.
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectsError {
+    /// A file or directory required to `action` doesn't exist.
+    #[error("Failed to {action}: {} (not found)", path.display())]
+    NotFound { action: &'static str, path: PathBuf },
+    #[error("Failed to {action}: {}", path.display())]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// Reading or writing the stream of an import or export failed.
+    #[error("Failed to {action}")]
+    Stream {
+        action: &'static str,
+        #[source]
+        source: io::Error,
+    },
+    /// The projects file is not valid TOML.
+    #[error("Failed to parse projects file: {}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    /// The projects to import are not in the expected format.
+    #[error("{message}")]
+    InvalidImport {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+    #[error("Failed to serialize projects to {format}")]
+    Serialize {
+        format: &'static str,
+        #[source]
+        source: BoxError,
+    },
+    /// Another instance held the projects lock for too long.
+    #[error("Timed out waiting for projects lock: {}", path.display())]
+    Lock { path: PathBuf },
+    #[error("unknown merge strategy '{0}'")]
+    UnknownMergeStrategy(String),
+    #[error("unknown project import source '{0}'")]
+    UnknownImportSource(String),
+    #[error("unknown project template '{0}'")]
+    UnknownTemplate(String),
+    /// The name of a project created from a template can't be used as a file name.
+    #[error("invalid project name '{0}'")]
+    InvalidProjectName(String),
+    /// The directory of a project to create from a template exists already and isn't empty.
+    #[error("Failed to create project: {} already exists and is not empty", path.display())]
+    DestinationNotEmpty { path: PathBuf },
+    #[error("Failed to initialize git repository: {}: {message}", path.display())]
+    GitInit { path: PathBuf, message: String },
+}
+
+/// An entry of the projects file which was skipped because it's invalid.
+#[derive(Debug, thiserror::Error)]
+#[error("Skipped entry {} of the projects file{}: {source}", index + 1, path_suffix(path))]
+pub struct EntryError {
+    /// The index of the entry among the `[[projects]]` tables.
+    pub index: usize,
+    /// The path of the entry, if it could be read.
+    pub path: Option<String>,
+    #[source]
+    pub source: toml::de::Error,
+}
+
+fn path_suffix(path: &Option<String>) -> String {
+    match path {
+        Some(path) => format!(" ({path})"),
+        None => String::new(),
+    }
+}
+
+impl ProjectsError {
+    /// An I/O error while trying to `action` at `path`, or [ProjectsError::NotFound] if `path`
+    /// doesn't exist.
+    pub(super) fn io(action: &'static str, path: &Path, source: io::Error) -> Self {
+        let path = path.to_path_buf();
+        if source.kind() == io::ErrorKind::NotFound {
+            Self::NotFound { action, path }
+        } else {
+            Self::Io {
+                action,
+                path,
+                source,
+            }
+        }
+    }
+}
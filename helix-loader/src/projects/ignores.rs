@@ -0,0 +1,94 @@
+//! The `ignore` patterns of projects, which the file picker and global search leave out without
+//! the files having to be ignored by git.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use super::Project;
+
+/// A matcher for the `ignore` patterns of `project`, anchored at the project root like the
+/// patterns of a `.gitignore` file in it, and the errors of the invalid patterns, which are
+/// skipped.
+///
+/// Returns no matcher if the project has no valid patterns.
+pub fn project_ignore_matcher(project: &Project) -> (Option<Gitignore>, Vec<ignore::Error>) {
+    let mut builder = GitignoreBuilder::new(&project.path);
+    let mut errors = Vec::new();
+    let mut valid = false;
+    for pattern in &project.ignore {
+        match builder.add_line(None, pattern) {
+            Ok(_) => valid = true,
+            Err(err) => errors.push(err),
+        }
+    }
+    if !valid {
+        return (None, errors);
+    }
+    match builder.build() {
+        Ok(matcher) => (Some(matcher), errors),
+        Err(err) => {
+            errors.push(err);
+            (None, errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    fn project(patterns: &[&str]) -> Project {
+        let mut project = Project::new(PathBuf::from("/src/game"));
+        project.ignore = patterns.iter().map(|pattern| pattern.to_string()).collect();
+        project
+    }
+
+    fn ignored(matcher: &Gitignore, path: &str, is_dir: bool) -> bool {
+        matcher.matched(Path::new(path), is_dir).is_ignore()
+    }
+
+    #[test]
+    fn match_relative_to_project_root() {
+        let (matcher, errors) =
+            project_ignore_matcher(&project(&["/assets/", "*.blend", "!keep.blend"]));
+        let matcher = matcher.unwrap();
+        assert!(errors.is_empty());
+
+        assert!(ignored(&matcher, "/src/game/assets", true));
+        assert!(!ignored(&matcher, "/src/game/assets", false));
+        assert!(!ignored(&matcher, "/src/game/code/assets", true));
+        assert!(ignored(&matcher, "/src/game/code/level.blend", false));
+        assert!(!ignored(&matcher, "/src/game/code/keep.blend", false));
+        assert!(!ignored(&matcher, "/src/game/code/main.rs", false));
+    }
+
+    #[test]
+    fn skip_invalid_patterns() {
+        let (matcher, errors) = project_ignore_matcher(&project(&["target/{", "*.log"]));
+        assert_eq!(errors.len(), 1);
+        let matcher = matcher.unwrap();
+        assert!(ignored(&matcher, "/src/game/debug.log", false));
+        assert!(!ignored(&matcher, "/src/game/target/{", true));
+
+        let (matcher, errors) = project_ignore_matcher(&project(&["target/{"]));
+        assert!(matcher.is_none());
+        assert_eq!(errors.len(), 1);
+        assert!(project_ignore_matcher(&project(&[])).0.is_none());
+    }
+
+    #[test]
+    fn persist_ignore_patterns() {
+        use crate::projects::{load_projects_from, save_projects_to};
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let projects = [project(&["/assets/", "*.blend"]), project(&[])];
+        save_projects_to(&file_path, &projects[..1]).unwrap();
+        assert_eq!(load_projects_from(&file_path).unwrap(), projects[..1]);
+
+        // Projects without patterns leave out the key.
+        save_projects_to(&file_path, &projects[1..]).unwrap();
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert!(!content.contains("ignore"), "{content}");
+    }
+}
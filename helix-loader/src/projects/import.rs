@@ -0,0 +1,385 @@
+//! Importing projects from the directory history of other tools.
+
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use super::{canonicalize, path_key, unix_now, Project, ProjectsError, Result};
+
+/// Imported projects count as last accessed this long ago so that projects opened in Helix
+/// rank above them, see [Project::frecency].
+const IMPORTED_AGE: u64 = 7 * 24 * 60 * 60;
+
+/// A tool to import projects from, see [import_projects].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    /// The output of `zoxide query --list --score`: one directory per line, preceded by its
+    /// score.
+    Zoxide,
+    /// The `storage.json` of VS Code, see [vscode_storage_path].
+    VsCode,
+}
+
+impl ImportSource {
+    pub const ALL: &[Self] = &[Self::Zoxide, Self::VsCode];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Zoxide => "zoxide",
+            Self::VsCode => "vscode",
+        }
+    }
+}
+
+impl std::str::FromStr for ImportSource {
+    type Err = ProjectsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL.iter().find(|source| source.name() == s) {
+            Some(source) => Ok(*source),
+            None => Err(ProjectsError::UnknownImportSource(s.to_string())),
+        }
+    }
+}
+
+/// The result of [import_projects].
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportedProjects {
+    pub projects: Vec<Project>,
+    /// The number of entries which were not imported: remote or missing directories,
+    /// directories without a `.git` and duplicates.
+    pub skipped: usize,
+}
+
+/// Reads the directories exported by `source` as projects.
+///
+/// Directories which no longer exist are skipped, as are directories without a `.git` if
+/// `require_git` is set. The paths of the returned projects are canonical.
+pub fn import_projects(
+    source: ImportSource,
+    reader: impl BufRead,
+    require_git: bool,
+) -> Result<ImportedProjects> {
+    let (entries, skipped) = match source {
+        ImportSource::Zoxide => (parse_zoxide(reader)?, 0),
+        ImportSource::VsCode => parse_vscode(reader)?,
+    };
+    let now = unix_now();
+    let mut seen = HashSet::new();
+    let mut imported = ImportedProjects {
+        projects: Vec::new(),
+        skipped,
+    };
+    for (path, access_count) in entries {
+        if !path.is_dir() || (require_git && !path.join(".git").exists()) {
+            imported.skipped += 1;
+            continue;
+        }
+        let path = canonicalize(&path);
+        if !seen.insert(path.clone()) {
+            imported.skipped += 1;
+            continue;
+        }
+        imported.projects.push(Project {
+            access_count,
+            last_accessed: Some(now.saturating_sub(IMPORTED_AGE)),
+            ..Project::new(path)
+        });
+    }
+    Ok(imported)
+}
+
+/// Parses zoxide entries into paths and access counts. Scores grow by one per access and decay
+/// over time, so the rounded up score is used as the access count.
+fn parse_zoxide(reader: impl BufRead) -> Result<Vec<(PathBuf, u32)>> {
+    let mut entries = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|source| ProjectsError::Stream {
+            action: "read zoxide entries",
+            source,
+        })?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((score, path)) = line
+            .split_once(char::is_whitespace)
+            .and_then(|(score, path)| Some((score.parse::<f64>().ok()?, path.trim_start())))
+        else {
+            return Err(ProjectsError::InvalidImport {
+                message: format!("Invalid zoxide entry on line {}: {line}", i + 1),
+                source: None,
+            });
+        };
+        // `as` saturates, covering scores beyond `u32::MAX`.
+        entries.push((PathBuf::from(path), score.ceil().max(1.0) as u32));
+    }
+    Ok(entries)
+}
+
+/// The `storage.json` of VS Code, which lists the recently opened folders.
+pub fn vscode_storage_path() -> Option<PathBuf> {
+    let config_dir = if cfg!(windows) {
+        PathBuf::from(std::env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        etcetera::home_dir()
+            .ok()?
+            .join("Library/Application Support")
+    } else {
+        match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => etcetera::home_dir().ok()?.join(".config"),
+        }
+    };
+    Some(config_dir.join("Code/User/globalStorage/storage.json"))
+}
+
+/// Parses the folders of a VS Code `storage.json`, most recently opened first. Returns the
+/// local folders and the number of skipped remote and virtual folders.
+fn parse_vscode(reader: impl BufRead) -> Result<(Vec<(PathBuf, u32)>, usize)> {
+    let storage: serde_json::Value =
+        serde_json::from_reader(reader).map_err(|err| ProjectsError::InvalidImport {
+            message: "Invalid VS Code storage".to_string(),
+            source: Some(err.into()),
+        })?;
+    let recent = &storage["openedPathsList"];
+    let windows = &storage["windowsState"];
+    // Folders opened through a remote connection may have a local looking URI.
+    let is_remote = |entry: &&serde_json::Value| entry.get("remoteAuthority").is_some();
+    let mut skipped = value_list(&recent["entries"])
+        .iter()
+        .filter(is_remote)
+        .count();
+    let candidates = value_list(&recent["entries"])
+        .iter()
+        .filter(|entry| !is_remote(entry))
+        .filter_map(|entry| entry.get("folderUri"))
+        // Older versions of VS Code list folders as plain URIs.
+        .chain(
+            value_list(&recent["workspaces3"])
+                .iter()
+                .map(|entry| entry.get("folderUri").unwrap_or(entry)),
+        )
+        .chain(windows["lastActiveWindow"].get("folder"))
+        .chain(
+            value_list(&windows["openedWindows"])
+                .iter()
+                .filter_map(|window| window.get("folder")),
+        )
+        .chain(
+            value_list(&storage["backupWorkspaces"]["folders"])
+                .iter()
+                .filter_map(|folder| folder.get("folderUri")),
+        );
+    let mut uris: Vec<&str> = Vec::new();
+    for uri in candidates.filter_map(serde_json::Value::as_str) {
+        if !uris.contains(&uri) {
+            uris.push(uri);
+        }
+    }
+
+    let folders = uris
+        .into_iter()
+        .filter_map(|uri| {
+            let path = file_uri_to_path(uri);
+            skipped += usize::from(path.is_none());
+            Some((path?, 1))
+        })
+        .collect();
+    Ok((folders, skipped))
+}
+
+fn value_list(value: &serde_json::Value) -> &[serde_json::Value] {
+    value.as_array().map_or(&[], Vec::as_slice)
+}
+
+/// Converts a `file://` URI to a path. URIs with other schemes, like the `vscode-remote` scheme
+/// of remote workspaces, aren't local paths.
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let (host, path) = rest.split_at(rest.find('/')?);
+    let path = percent_decode(path)?;
+    let path = match (host, path.as_bytes()) {
+        ("" | "localhost", [b'/', drive, b':', ..]) if drive.is_ascii_alphabetic() => {
+            path[1..].to_string()
+        }
+        ("" | "localhost", _) => path,
+        // UNC paths on Windows.
+        (host, _) => format!("//{}{path}", percent_decode(host)?),
+    };
+    Some(PathBuf::from(path))
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Adds `imported` to `projects`, skipping projects whose path is already registered.
+/// Returns the number of added projects.
+pub fn merge_projects(projects: &mut Vec<Project>, imported: Vec<Project>) -> usize {
+    let mut paths: HashSet<PathBuf> = projects.iter().map(|p| path_key(&p.path)).collect();
+    let len = projects.len();
+    projects.extend(
+        imported
+            .into_iter()
+            .filter(|project| paths.insert(path_key(&project.path))),
+    );
+    projects.len() - len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn import_from_zoxide() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = canonicalize(dir.path());
+        let repo = dir.join("repo");
+        let plain = dir.join("plain");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir(&plain).unwrap();
+
+        let output = format!(
+            "  12.5 {}\n   0.2 {}\n   3.0 {}\n\n",
+            repo.display(),
+            plain.display(),
+            dir.join("missing").display()
+        );
+        let imported = import_projects(ImportSource::Zoxide, output.as_bytes(), true).unwrap();
+        assert_eq!(imported.skipped, 2);
+        let projects = imported.projects;
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].path, repo);
+        assert_eq!(projects[0].name.as_deref(), Some("repo"));
+        assert_eq!(projects[0].access_count, 13);
+        assert!(projects[0].last_accessed.is_some());
+
+        let imported = import_projects(ImportSource::Zoxide, output.as_bytes(), false).unwrap();
+        assert_eq!(imported.skipped, 1);
+        let paths: Vec<_> = imported
+            .projects
+            .iter()
+            .map(|p| (&p.path, p.access_count))
+            .collect();
+        assert_eq!(paths, [(&repo, 13), (&plain, 1)]);
+
+        let err = import_projects(ImportSource::Zoxide, "not a score\n".as_bytes(), true);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn merge_without_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        std::fs::create_dir(&a).unwrap();
+        std::fs::create_dir(&b).unwrap();
+
+        // Paths are compared canonically.
+        let mut projects = vec![Project::new(b.join("../a"))];
+        let imported = vec![Project::new(canonicalize(&a)), Project::new(b.clone())];
+        assert_eq!(merge_projects(&mut projects, imported), 1);
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[1].path, b);
+        assert_eq!(merge_projects(&mut projects, vec![Project::new(b)]), 0);
+    }
+
+    fn vscode_folders(json: &str) -> (Vec<String>, usize) {
+        let (folders, skipped) = parse_vscode(json.as_bytes()).unwrap();
+        let folders = folders
+            .into_iter()
+            .map(|(path, _)| path.to_str().unwrap().to_string())
+            .collect();
+        (folders, skipped)
+    }
+
+    #[test]
+    fn parse_vscode_storage() {
+        let linux = vscode_folders(include_str!("../../tests/data/vscode/linux.json"));
+        assert_eq!(
+            linux,
+            (
+                vec![
+                    "/home/user/src/helix".to_string(),
+                    "/home/user/src/my project".to_string(),
+                    "/home/user/dotfiles".to_string(),
+                ],
+                2
+            )
+        );
+
+        let macos = vscode_folders(include_str!("../../tests/data/vscode/macos.json"));
+        assert_eq!(
+            macos,
+            (
+                vec![
+                    "/Users/user/Developer/app".to_string(),
+                    "/Users/user/Library/Mobile Documents/site".to_string(),
+                ],
+                0
+            )
+        );
+
+        let windows = vscode_folders(include_str!("../../tests/data/vscode/windows.json"));
+        assert_eq!(
+            windows,
+            (
+                vec![
+                    "c:/Users/user/source/repos/App".to_string(),
+                    "//fileserver/share/project".to_string(),
+                    "d:/work/tools".to_string(),
+                ],
+                1
+            )
+        );
+
+        assert!(parse_vscode("{".as_bytes()).is_err());
+        assert_eq!(vscode_folders("{}"), (Vec::new(), 0));
+    }
+
+    #[test]
+    fn import_from_vscode() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = canonicalize(dir.path());
+        let repo = dir.join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        let uri = |path: &Path| format!("file://{}", path.display());
+        let storage = format!(
+            r#"{{ "openedPathsList": {{ "entries": [
+                {{ "folderUri": "{}" }},
+                {{ "folderUri": "{}" }},
+                {{ "folderUri": "vscode-vfs://github/helix-editor/helix" }}
+            ] }} }}"#,
+            uri(&repo),
+            uri(&dir.join("missing")),
+        );
+        let imported = import_projects(ImportSource::VsCode, storage.as_bytes(), false).unwrap();
+        assert_eq!(imported.skipped, 2);
+        assert_eq!(imported.projects.len(), 1);
+        assert_eq!(imported.projects[0].path, repo);
+        assert_eq!(imported.projects[0].name.as_deref(), Some("repo"));
+    }
+
+    #[test]
+    fn parse_import_source() {
+        assert_eq!(
+            "zoxide".parse::<ImportSource>().unwrap(),
+            ImportSource::Zoxide
+        );
+        assert!("autojump".parse::<ImportSource>().is_err());
+    }
+}
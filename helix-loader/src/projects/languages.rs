@@ -0,0 +1,251 @@
+//! Detecting the main languages of a project, cached in the projects file.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use once_cell::sync::Lazy;
+
+use super::{unix_now, Project};
+
+/// The most files whose types are counted when a project has no known manifest.
+const MAX_SAMPLED_FILES: usize = 2000;
+/// The deepest directory level below the project root that is sampled.
+const MAX_SAMPLE_DEPTH: usize = 4;
+/// At most this many languages are detected per project.
+const MAX_LANGUAGES: usize = 3;
+
+/// Manifest files in the project root and the language they imply, named as in
+/// `languages.toml`.
+const MANIFESTS: &[(&str, &str)] = &[
+    ("Cargo.toml", "rust"),
+    ("go.mod", "go"),
+    ("pyproject.toml", "python"),
+    ("tsconfig.json", "typescript"),
+    ("package.json", "javascript"),
+];
+
+/// Directories which hold dependencies or build output rather than the project's own files.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "build", "dist"];
+
+/// The language of each file type of the user's `languages.toml`, by file extension or name.
+static FILE_TYPES: Lazy<HashMap<String, String>> = Lazy::new(|| {
+    let config = crate::config::user_lang_config().unwrap_or_else(|err| {
+        log::warn!("Failed to load languages.toml for language detection: {err}");
+        crate::config::default_lang_config()
+    });
+    file_types(&config)
+});
+
+fn file_types(config: &toml::Value) -> HashMap<String, String> {
+    let mut file_types = HashMap::new();
+    let languages = config.get("language").and_then(toml::Value::as_array);
+    for language in languages.into_iter().flatten() {
+        let Some(name) = language.get("name").and_then(toml::Value::as_str) else {
+            continue;
+        };
+        let types = language.get("file-types").and_then(toml::Value::as_array);
+        // Glob file types are left out, extensions and file names cover the common cases.
+        for file_type in types.into_iter().flatten().filter_map(toml::Value::as_str) {
+            file_types
+                .entry(file_type.to_string())
+                .or_insert_with(|| name.to_string());
+        }
+    }
+    file_types
+}
+
+/// Detects the main languages of the project at `path`, named as in `languages.toml`.
+///
+/// The manifests in the project root, such as `Cargo.toml` or `package.json`, decide the
+/// languages. Without a known manifest the file types of up to [MAX_SAMPLED_FILES] files near
+/// the root are counted, so that detection stays fast in large repositories.
+pub fn detect_project_languages(path: &Path) -> Vec<String> {
+    let mut languages: Vec<String> = MANIFESTS
+        .iter()
+        .filter(|(manifest, _)| path.join(manifest).is_file())
+        .map(|(_, language)| language.to_string())
+        .collect();
+    // A TypeScript project has a `package.json` as well.
+    if languages.iter().any(|language| language == "typescript") {
+        languages.retain(|language| language != "javascript");
+    }
+    if languages.is_empty() {
+        languages = count_languages(path, &FILE_TYPES);
+    }
+    languages
+}
+
+/// The languages making up at least a tenth of the sampled files below `root` whose type is
+/// known, most common first.
+fn count_languages(root: &Path, file_types: &HashMap<String, String>) -> Vec<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut sampled = 0;
+    let mut dirs = VecDeque::from([(root.to_path_buf(), 0)]);
+    'walk: while let Some((dir, depth)) = dirs.pop_front() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if depth < MAX_SAMPLE_DEPTH
+                    && !name.starts_with('.')
+                    && !SKIPPED_DIRS.contains(&name)
+                {
+                    dirs.push_back((entry.path(), depth + 1));
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let extension = Path::new(name).extension().and_then(|ext| ext.to_str());
+            let language = file_types
+                .get(name)
+                .or_else(|| extension.and_then(|ext| file_types.get(ext)));
+            if let Some(language) = language {
+                *counts.entry(language).or_default() += 1;
+            }
+            sampled += 1;
+            if sampled >= MAX_SAMPLED_FILES {
+                break 'walk;
+            }
+        }
+    }
+
+    let total: usize = counts.values().sum();
+    let mut counts: Vec<_> = counts
+        .into_iter()
+        .filter(|&(_, count)| count * 10 >= total)
+        .collect();
+    counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    counts
+        .into_iter()
+        .take(MAX_LANGUAGES)
+        .map(|(language, _)| language.to_string())
+        .collect()
+}
+
+/// Detects the languages of the projects whose cached languages are missing or older than the
+/// last change to their directory. Projects whose directory can't be read keep their cache.
+///
+/// Returns whether any project was updated.
+pub fn refresh_project_languages(projects: &mut [Project]) -> bool {
+    let now = unix_now();
+    let mut updated = false;
+    for project in projects {
+        let Some(modified) = std::fs::metadata(&project.path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        else {
+            continue;
+        };
+        if project
+            .languages_detected
+            .is_some_and(|detected| detected >= modified.as_secs())
+        {
+            continue;
+        }
+        project.languages = detect_project_languages(&project.path);
+        project.languages_detected = Some(now);
+        updated = true;
+    }
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn detect_languages_from_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        std::fs::write(dir.path().join("package.json"), "").unwrap();
+        assert_eq!(detect_project_languages(dir.path()), ["rust", "javascript"]);
+
+        std::fs::write(dir.path().join("tsconfig.json"), "").unwrap();
+        assert_eq!(detect_project_languages(dir.path()), ["rust", "typescript"]);
+    }
+
+    #[test]
+    fn count_file_types() {
+        let config: toml::Value = toml::from_str(
+            r#"
+            [[language]]
+            name = "c"
+            file-types = ["c", "h"]
+            [[language]]
+            name = "cpp"
+            file-types = ["cpp", "h"]
+            [[language]]
+            name = "make"
+            file-types = ["Makefile", { glob = "*.mk" }]
+            [[language]]
+            name = "markdown"
+            file-types = ["md"]
+            "#,
+        )
+        .unwrap();
+        let file_types = file_types(&config);
+        assert_eq!(file_types["h"], "c");
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for dir in ["src/lib", ".git", "node_modules/dep"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let files = [
+            "Makefile",
+            "README.md",
+            "src/main.c",
+            "src/util.c",
+            "src/util.h",
+            "src/lib/a.cpp",
+            "src/lib/b.cpp",
+            "src/lib/c.cpp",
+            "src/lib/notes.txt",
+            ".git/x.c",
+            "node_modules/dep/y.md",
+            "node_modules/dep/z.md",
+        ];
+        for file in files {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        // Ties are broken by name, and at most three languages are kept.
+        assert_eq!(count_languages(root, &file_types), ["c", "cpp", "make"]);
+    }
+
+    #[test]
+    fn refresh_outdated_languages() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("go.mod"), "").unwrap();
+        let mut projects = [
+            Project::new(dir.path().to_path_buf()),
+            Project {
+                languages: vec!["zig".to_string()],
+                languages_detected: Some(u64::MAX),
+                ..Project::new(dir.path().to_path_buf())
+            },
+            Project::new(PathBuf::from("/nonexistent/project")),
+        ];
+
+        assert!(refresh_project_languages(&mut projects));
+        assert_eq!(projects[0].languages, ["go"]);
+        assert!(projects[0].languages_detected.is_some());
+        // Languages detected after the last change to the directory are kept.
+        assert_eq!(projects[1].languages, ["zig"]);
+        assert!(projects[2].languages.is_empty());
+
+        assert!(!refresh_project_languages(&mut projects));
+    }
+}
@@ -0,0 +1,55 @@
+//! Finding the README of a project, shown when previewing projects.
+
+use std::path::{Path, PathBuf};
+
+/// The lowercased names of README files, in order of preference.
+const README_NAMES: &[&str] = &["readme.md", "readme.rst", "readme.txt", "readme"];
+
+/// The README file in the root of the project at `path`, matched case-insensitively. If there
+/// are several, the first in the order of `README.md`, `README.rst`, `README.txt` and `README`
+/// is returned.
+pub fn find_readme(path: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(path)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_lowercase();
+            let rank = README_NAMES.iter().position(|readme| *readme == name)?;
+            let path = entry.path();
+            path.is_file().then_some((rank, path))
+        })
+        // Comparing the paths as well keeps the choice stable between case variants of a name.
+        .min()
+        .map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_readme_by_preference() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(find_readme(dir.path()), None);
+
+        std::fs::write(dir.path().join("readme.txt"), "").unwrap();
+        assert_eq!(find_readme(dir.path()), Some(dir.path().join("readme.txt")));
+
+        std::fs::write(dir.path().join("Readme.rst"), "").unwrap();
+        assert_eq!(find_readme(dir.path()), Some(dir.path().join("Readme.rst")));
+
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+        assert_eq!(find_readme(dir.path()), Some(dir.path().join("README.md")));
+    }
+
+    #[test]
+    fn find_readme_ignores_other_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("README.md")).unwrap();
+        std::fs::write(dir.path().join("README.html"), "").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "").unwrap();
+        assert_eq!(find_readme(dir.path()), None);
+
+        assert_eq!(find_readme(&dir.path().join("missing")), None);
+    }
+}
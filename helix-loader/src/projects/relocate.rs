@@ -0,0 +1,288 @@
+//! Finding projects again after their repositories were moved, by the URL of their `origin`
+//! remote.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::{check_projects, path_key, scan, Project, ProjectStatus, ScanOptions};
+
+/// The outcome of [relocate_missing_projects] for a project whose directory is missing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Relocation {
+    /// The project was found at `to` and its entry now points there.
+    Moved { from: PathBuf, to: PathBuf },
+    /// More than one repository has the remote of the project, so it was left where it was.
+    Ambiguous {
+        path: PathBuf,
+        candidates: Vec<PathBuf>,
+    },
+    /// The project has no known remote, or no repository with its remote was found.
+    Unresolved { path: PathBuf },
+}
+
+/// The git directory of the repository at `path`, following the `gitdir:` file of worktrees
+/// and submodules.
+fn git_dir(path: &Path) -> Option<PathBuf> {
+    let dot_git = path.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = std::fs::read_to_string(&dot_git).ok()?;
+    let git_dir = content.strip_prefix("gitdir:")?.trim();
+    Some(path.join(git_dir))
+}
+
+/// The URL of the `origin` remote of the git repository at `path`, read from its config file.
+pub fn origin_remote(path: &Path) -> Option<String> {
+    let mut git_dir = git_dir(path)?;
+    // Worktrees share the config of the main repository.
+    if let Ok(common_dir) = std::fs::read_to_string(git_dir.join("commondir")) {
+        git_dir = git_dir.join(common_dir.trim());
+    }
+    let config = std::fs::read_to_string(git_dir.join("config")).ok()?;
+    parse_origin_url(&config)
+}
+
+fn parse_origin_url(config: &str) -> Option<String> {
+    let mut in_origin = false;
+    for line in config.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[') {
+            let section = section.trim_end_matches(']');
+            in_origin =
+                section
+                    .split_once(char::is_whitespace)
+                    .is_some_and(|(name, subsection)| {
+                        name.eq_ignore_ascii_case("remote") && subsection.trim() == "\"origin\""
+                    });
+            continue;
+        }
+        if !in_origin {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim().eq_ignore_ascii_case("url") {
+                return Some(value.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Reduces a remote URL to its host and path, so that the SSH and HTTPS URLs of a repository
+/// and URLs with or without a trailing `.git` compare equal.
+fn normalize_remote(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let (url, scp_like) = match url.split_once("://") {
+        Some((_, rest)) => (rest, false),
+        None => (url, true),
+    };
+    let url = match url.split_once('@') {
+        Some((user, rest)) if !user.contains('/') => rest,
+        _ => url,
+    };
+    // `git@host:owner/repo` is the short form of `ssh://git@host/owner/repo`.
+    if scp_like && !url.starts_with('/') {
+        url.replacen(':', "/", 1)
+    } else {
+        url.to_string()
+    }
+}
+
+/// Finds the projects whose directory is missing in the git repositories within `search_roots`,
+/// by the `origin` remote cached on each project when it was registered.
+///
+/// A project is moved to the repository with its remote if there is exactly one which isn't
+/// registered yet. Its entry keeps all of its metadata. Projects with several candidates, or
+/// sharing their remote with another missing project, are reported as ambiguous and left
+/// unchanged, like the projects no repository was found for.
+pub fn relocate_missing_projects(
+    projects: &mut [Project],
+    search_roots: &[PathBuf],
+) -> Vec<Relocation> {
+    let missing: Vec<usize> = check_projects(projects)
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, status)| status == ProjectStatus::Missing)
+        .map(|(i, _)| i)
+        .collect();
+    if missing.is_empty() {
+        return Vec::new();
+    }
+
+    let registered: HashSet<_> = projects.iter().map(|p| path_key(&p.path)).collect();
+    let mut repositories: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    let options = ScanOptions::default();
+    for root in search_roots {
+        let repos = match scan(root, &options) {
+            Ok(scanned) => scanned.repos,
+            Err(err) => {
+                log::warn!(
+                    "Failed to scan {} for moved projects: {err}",
+                    root.display()
+                );
+                continue;
+            }
+        };
+        for repo in repos {
+            if registered.contains(&path_key(&repo)) {
+                continue;
+            }
+            if let Some(remote) = origin_remote(&repo) {
+                let candidates = repositories.entry(normalize_remote(&remote)).or_default();
+                if !candidates.contains(&repo) {
+                    candidates.push(repo);
+                }
+            }
+        }
+    }
+
+    let mut missing_remotes: HashMap<String, usize> = HashMap::new();
+    for &i in &missing {
+        if let Some(remote) = &projects[i].remote {
+            *missing_remotes.entry(normalize_remote(remote)).or_default() += 1;
+        }
+    }
+
+    let mut relocations = Vec::new();
+    for i in missing {
+        let project = &mut projects[i];
+        let path = project.path.clone();
+        let Some(remote) = project.remote.as_deref().map(normalize_remote) else {
+            relocations.push(Relocation::Unresolved { path });
+            continue;
+        };
+        let candidates = repositories
+            .get(&remote)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        match candidates {
+            [] => relocations.push(Relocation::Unresolved { path }),
+            [to] if missing_remotes[&remote] == 1 => {
+                project.path = to.clone();
+                relocations.push(Relocation::Moved {
+                    from: path,
+                    to: to.clone(),
+                });
+            }
+            _ => relocations.push(Relocation::Ambiguous {
+                path,
+                candidates: candidates.to_vec(),
+            }),
+        }
+    }
+    relocations
+}
+
+/// Points the registered project at `from` to `to`, keeping its metadata. Used to apply a
+/// [Relocation::Moved] to the projects file as it is on disk.
+///
+/// Returns `false` if no project is registered at `from`, or one is already registered at `to`.
+pub fn relocate_project(projects: &mut [Project], from: &Path, to: &Path) -> bool {
+    let to_key = path_key(to);
+    if projects.iter().any(|p| path_key(&p.path) == to_key) {
+        return false;
+    }
+    let from_key = path_key(from);
+    match projects.iter_mut().find(|p| path_key(&p.path) == from_key) {
+        Some(project) => {
+            project.path = to.to_path_buf();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_repo(path: &Path, remote: &str) {
+        std::fs::create_dir_all(path.join(".git")).unwrap();
+        std::fs::write(
+            path.join(".git/config"),
+            format!(
+                "[core]\n\tbare = false\n[remote \"upstream\"]\n\turl = https://example.com/fork.git\n[remote \"origin\"]\n\turl = {remote}\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n"
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn normalize_remote_urls() {
+        let https = normalize_remote("https://github.com/helix-editor/helix.git");
+        assert_eq!(https, "github.com/helix-editor/helix");
+        assert_eq!(normalize_remote("git@github.com:helix-editor/helix"), https);
+        assert_eq!(
+            normalize_remote("ssh://git@github.com/helix-editor/helix/"),
+            https
+        );
+        assert_eq!(normalize_remote("/srv/git/helix.git"), "/srv/git/helix");
+    }
+
+    #[test]
+    fn relocate_moved_repositories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = super::super::canonicalize(dir.path());
+        let src = root.join("src");
+        init_repo(&src.join("helix"), "git@github.com:helix-editor/helix.git");
+        init_repo(&src.join("a/dotfiles"), "https://example.com/dotfiles");
+        init_repo(&src.join("b/dotfiles"), "https://example.com/dotfiles");
+        init_repo(&src.join("registered"), "https://example.com/registered");
+        assert_eq!(
+            origin_remote(&src.join("helix")).as_deref(),
+            Some("git@github.com:helix-editor/helix.git")
+        );
+
+        let code = root.join("code");
+        let with_remote = |name: &str, remote: Option<&str>| Project {
+            remote: remote.map(str::to_string),
+            access_count: 3,
+            ..Project::new(code.join(name))
+        };
+        let mut projects = vec![
+            with_remote("helix", Some("https://github.com/helix-editor/helix")),
+            with_remote("dotfiles", Some("https://example.com/dotfiles.git")),
+            with_remote("notes", None),
+            with_remote("registered", Some("https://example.com/registered")),
+            Project::new(src.join("registered")),
+        ];
+        let relocations = relocate_missing_projects(&mut projects, &[src.clone()]);
+
+        assert_eq!(
+            relocations,
+            [
+                Relocation::Moved {
+                    from: code.join("helix"),
+                    to: src.join("helix"),
+                },
+                Relocation::Ambiguous {
+                    path: code.join("dotfiles"),
+                    candidates: vec![src.join("a/dotfiles"), src.join("b/dotfiles")],
+                },
+                Relocation::Unresolved {
+                    path: code.join("notes"),
+                },
+                // The only repository with the remote is registered already.
+                Relocation::Unresolved {
+                    path: code.join("registered"),
+                },
+            ]
+        );
+        assert_eq!(projects[0].path, src.join("helix"));
+        assert_eq!(projects[0].name.as_deref(), Some("helix"));
+        assert_eq!(projects[0].access_count, 3);
+        assert_eq!(projects[1].path, code.join("dotfiles"));
+
+        assert!(relocate_project(
+            &mut projects,
+            &src.join("helix"),
+            &src.join("other")
+        ));
+        assert!(!relocate_project(
+            &mut projects,
+            &src.join("other"),
+            &src.join("registered")
+        ));
+    }
+}
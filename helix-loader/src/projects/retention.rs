@@ -0,0 +1,81 @@
+//! Archiving projects which weren't accessed for a while, see `editor.project-retention-days`.
+
+use super::Project;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The projects which weren't accessed in the `retention_days` before `now`, in seconds since
+/// the Unix epoch, and should be archived.
+///
+/// Projects which are archived already or were never accessed, see [Project::last_accessed_at],
+/// are kept, and a `retention_days` of 0 keeps every project.
+pub fn stale_projects(projects: &[Project], retention_days: u64, now: u64) -> Vec<&Project> {
+    if retention_days == 0 {
+        return Vec::new();
+    }
+    let cutoff = now.saturating_sub(retention_days.saturating_mul(SECONDS_PER_DAY));
+    projects
+        .iter()
+        .filter(|project| !project.archived)
+        .filter(|project| {
+            project
+                .last_accessed_at(now)
+                .is_some_and(|accessed| accessed < cutoff)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn project(path: &str, days_ago: Option<u64>) -> Project {
+        let mut project = Project::new(PathBuf::from(path));
+        project.last_accessed = days_ago.map(|days| NOW - days * SECONDS_PER_DAY);
+        project
+    }
+
+    fn paths(projects: Vec<&Project>) -> Vec<&str> {
+        projects
+            .iter()
+            .map(|project| project.path.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn stale_projects_by_last_access() {
+        let mut archived = project("/archived", Some(400));
+        archived.archived = true;
+        let projects = [
+            project("/recent", Some(1)),
+            project("/old", Some(200)),
+            project("/at-cutoff", Some(180)),
+            project("/never-accessed", None),
+            archived,
+        ];
+
+        assert_eq!(paths(stale_projects(&projects, 180, NOW)), ["/old"]);
+        assert_eq!(
+            paths(stale_projects(&projects, 1, NOW)),
+            ["/old", "/at-cutoff"]
+        );
+        assert!(stale_projects(&projects, 0, NOW).is_empty());
+        // A clock before the accesses doesn't archive anything.
+        assert!(stale_projects(&projects, 1, 0).is_empty());
+    }
+
+    #[test]
+    fn skip_invalid_access_times() {
+        let mut epoch = project("/epoch", None);
+        epoch.last_accessed = Some(0);
+        let mut future = project("/future", None);
+        future.last_accessed = Some(NOW + 400 * SECONDS_PER_DAY);
+        let projects = [epoch, future, project("/old", Some(200))];
+
+        // Zero counts as never accessed and the future as now.
+        assert_eq!(paths(stale_projects(&projects, 180, NOW)), ["/old"]);
+    }
+}
@@ -0,0 +1,664 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicU64};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{
+    canonicalize, load_projects_with_warnings_from, merge_system_projects, origin_remote, path_key,
+    projects_file_path, set_archived, set_theme, stale_projects, system_projects, unix_now,
+    update_projects_in, without_system_projects, EntryError, Project, Result,
+};
+
+/// How often a [ProjectsStore] writes its changes at most, see [ProjectsStore::flush_if_due].
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How many [ProjectsEvent]s a [ProjectsStore] keeps, see [ProjectsStore::events_since].
+const MAX_EVENTS: usize = 64;
+
+/// The last generation handed out to a [ProjectsStore]. Generations are shared by all stores so
+/// that they keep increasing when a store is replaced, for example when switching profiles.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn next_generation() -> u64 {
+    GENERATION.fetch_add(1, atomic::Ordering::Relaxed) + 1
+}
+
+/// A coarse description of a change to the projects of a [ProjectsStore], see
+/// [ProjectsStore::events_since].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectsEvent {
+    /// The project at the path was registered.
+    Added(PathBuf),
+    /// The project at the path was removed.
+    Removed(PathBuf),
+    /// The project at the path was archived, unarchived or accessed.
+    Updated(PathBuf),
+    /// Any number of projects changed, for example because the projects file was reread.
+    Reloaded,
+}
+
+/// A change made to a [ProjectsStore] which is not written to the projects file yet.
+#[derive(Debug, Clone)]
+enum Change {
+    Add(PathBuf),
+    Remove(PathBuf),
+    Archive(PathBuf, bool),
+    Theme(PathBuf, Option<String>),
+    /// `count` accesses to the project at the path, the last of them at `last_accessed`.
+    Touch {
+        path: PathBuf,
+        count: u32,
+        last_accessed: u64,
+    },
+}
+
+impl Change {
+    fn apply(&self, projects: &mut Vec<Project>) {
+        match self {
+            Self::Add(path) => {
+                let key = path_key(path);
+                if !projects.iter().any(|p| path_key(&p.path) == key) {
+                    projects.push(Project::new(path.clone()));
+                }
+            }
+            Self::Remove(path) => {
+                let key = path_key(path);
+                projects.retain(|p| path_key(&p.path) != key);
+            }
+            Self::Archive(path, archived) => {
+                set_archived(projects, path, *archived);
+            }
+            Self::Theme(path, theme) => {
+                set_theme(projects, path, theme.clone());
+            }
+            Self::Touch {
+                path,
+                count,
+                last_accessed,
+            } => {
+                let key = path_key(path);
+                let project = match projects.iter().position(|p| path_key(&p.path) == key) {
+                    Some(i) => &mut projects[i],
+                    None => {
+                        projects.push(Project::new(path.clone()));
+                        projects.last_mut().unwrap()
+                    }
+                };
+                project.access_count = project.access_count.saturating_add(*count);
+                project.last_accessed = project.last_accessed.max(Some(*last_accessed));
+                if project.remote.is_none() {
+                    project.remote = origin_remote(&project.path);
+                }
+            }
+        }
+    }
+}
+
+/// The modification time and length of a file, to tell whether it changed since it was read.
+type FileStamp = Option<(SystemTime, u64)>;
+
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// The registered projects, kept in memory and written to the projects file in batches.
+///
+/// Changes are applied to the in-memory list right away and recorded until the next flush, which
+/// replays them onto the projects file as it is on disk at that point. This way changes made by
+/// other instances in the meantime are kept.
+#[derive(Debug)]
+pub struct ProjectsStore {
+    file_path: PathBuf,
+    /// The projects of the system projects file, see [super::set_system_file].
+    system: Vec<Project>,
+    projects: Vec<Project>,
+    /// The invalid entries skipped when the projects file was last read.
+    warnings: Vec<EntryError>,
+    /// The stamp of the projects file when it was last read or written.
+    stamp: FileStamp,
+    changes: Vec<Change>,
+    interval: Duration,
+    last_flush: Instant,
+    /// Increased on every change to the projects, see [Self::generation].
+    generation: u64,
+    /// The latest changes along with their generations.
+    events: VecDeque<(u64, ProjectsEvent)>,
+    /// The generation of the last change which is not in `events` anymore.
+    horizon: u64,
+}
+
+impl ProjectsStore {
+    /// Loads the projects file at `file_path`, writing changes at most once per
+    /// [FLUSH_INTERVAL].
+    pub fn load(file_path: PathBuf) -> Result<Self> {
+        Self::with_interval(file_path, FLUSH_INTERVAL)
+    }
+
+    /// Like [Self::load], writing changes at most once per `interval`.
+    pub fn with_interval(file_path: PathBuf, interval: Duration) -> Result<Self> {
+        Self::with_system_projects(file_path, Vec::new(), interval)
+    }
+
+    /// Loads the user's projects file, see [projects_file_path], along with the projects of the
+    /// system projects file like [super::load_projects].
+    pub fn open() -> Result<Self> {
+        Self::with_system_projects(projects_file_path(), system_projects(), FLUSH_INTERVAL)
+    }
+
+    fn with_system_projects(
+        file_path: PathBuf,
+        system: Vec<Project>,
+        interval: Duration,
+    ) -> Result<Self> {
+        let mut store = Self {
+            file_path,
+            system,
+            projects: Vec::new(),
+            warnings: Vec::new(),
+            stamp: None,
+            changes: Vec::new(),
+            interval,
+            last_flush: Instant::now(),
+            generation: 0,
+            events: VecDeque::new(),
+            horizon: 0,
+        };
+        store.reload()?;
+        store.generation = next_generation();
+        store.horizon = store.generation;
+        store.events.clear();
+        Ok(store)
+    }
+
+    /// The registered projects, including the pending changes.
+    pub fn projects(&self) -> &[Project] {
+        &self.projects
+    }
+
+    /// The invalid entries which were skipped when the projects file was last read.
+    pub fn warnings(&self) -> &[EntryError] {
+        &self.warnings
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Whether there are changes which are not written to the projects file yet.
+    pub fn is_dirty(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// A number which increases whenever the projects change, including when changes made by
+    /// other instances are read from the projects file.
+    ///
+    /// Components showing projects can remember it and check [Self::changed_since] when they are
+    /// next rendered.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Whether the projects changed after `generation`, see [Self::generation].
+    pub fn changed_since(&self, generation: u64) -> bool {
+        self.generation > generation
+    }
+
+    /// The changes made after `generation`, oldest first, or `None` if some of them are not
+    /// kept anymore and the projects should be read again as a whole.
+    pub fn events_since(
+        &self,
+        generation: u64,
+    ) -> Option<impl Iterator<Item = &ProjectsEvent> + '_> {
+        if generation < self.horizon {
+            return None;
+        }
+        let events = self.events.iter();
+        Some(
+            events
+                .filter(move |(g, _)| *g > generation)
+                .map(|(_, event)| event),
+        )
+    }
+
+    fn notify(&mut self, event: ProjectsEvent) {
+        self.generation = next_generation();
+        // Accessing the same project repeatedly doesn't need an event per access.
+        if let Some((generation, last)) = self.events.back_mut() {
+            if *last == event && matches!(event, ProjectsEvent::Updated(_)) {
+                *generation = self.generation;
+                return;
+            }
+        }
+        if self.events.len() == MAX_EVENTS {
+            if let Some((generation, _)) = self.events.pop_front() {
+                self.horizon = generation;
+            }
+        }
+        self.events.push_back((self.generation, event));
+    }
+
+    /// Replaces the projects, notifying of a reload if they changed.
+    fn replace(&mut self, projects: Vec<Project>) {
+        if projects != self.projects {
+            self.projects = projects;
+            self.notify(ProjectsEvent::Reloaded);
+        }
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        let key = path_key(path);
+        self.projects.iter().any(|p| path_key(&p.path) == key)
+    }
+
+    fn change(&mut self, change: Change) {
+        change.apply(&mut self.projects);
+        self.changes.push(change);
+    }
+
+    /// Adds `path` unless it is already registered, see [super::add_project].
+    pub fn add(&mut self, path: &Path) -> bool {
+        if self.contains(path) {
+            return false;
+        }
+        let path = canonicalize(path);
+        self.change(Change::Add(path.clone()));
+        self.notify(ProjectsEvent::Added(path));
+        true
+    }
+
+    /// Removes the project at `path`, see [super::remove_project].
+    pub fn remove(&mut self, path: &Path) -> bool {
+        if !self.contains(path) {
+            return false;
+        }
+        self.change(Change::Remove(path.to_path_buf()));
+        self.notify(ProjectsEvent::Removed(path.to_path_buf()));
+        true
+    }
+
+    /// Archives or unarchives the project at `path`, see [super::set_project_archived].
+    pub fn set_archived(&mut self, path: &Path, archived: bool) -> bool {
+        if !self.contains(path) {
+            return false;
+        }
+        self.change(Change::Archive(path.to_path_buf(), archived));
+        self.notify(ProjectsEvent::Updated(path.to_path_buf()));
+        true
+    }
+
+    /// Sets or unsets the theme of the project at `path`, see [super::set_theme].
+    pub fn set_theme(&mut self, path: &Path, theme: Option<String>) -> bool {
+        if !self.contains(path) {
+            return false;
+        }
+        self.change(Change::Theme(path.to_path_buf(), theme));
+        self.notify(ProjectsEvent::Updated(path.to_path_buf()));
+        true
+    }
+
+    /// Archives the projects which weren't accessed in the last `retention_days`, see
+    /// [stale_projects], and returns them.
+    pub fn archive_stale(&mut self, retention_days: u64) -> Vec<Project> {
+        let stale: Vec<Project> = stale_projects(&self.projects, retention_days, unix_now())
+            .into_iter()
+            .cloned()
+            .collect();
+        for project in &stale {
+            self.change(Change::Archive(project.path.clone(), true));
+            self.notify(ProjectsEvent::Updated(project.path.clone()));
+        }
+        stale
+    }
+
+    /// Records an access to the project at `path`, registering it first if needed, see
+    /// [super::touch_project].
+    ///
+    /// Repeated accesses to the same project between flushes are recorded as one change.
+    pub fn touch(&mut self, path: &Path) -> bool {
+        let added = !self.contains(path);
+        let path = canonicalize(path);
+        let last_accessed = unix_now();
+        Change::Touch {
+            path: path.clone(),
+            count: 1,
+            last_accessed,
+        }
+        .apply(&mut self.projects);
+        self.notify(if added {
+            ProjectsEvent::Added(path.clone())
+        } else {
+            ProjectsEvent::Updated(path.clone())
+        });
+        if let Some(Change::Touch {
+            path: last_path,
+            count,
+            last_accessed: last,
+        }) = self.changes.last_mut()
+        {
+            if *last_path == path {
+                *count = count.saturating_add(1);
+                *last = last_accessed;
+                return added;
+            }
+        }
+        self.changes.push(Change::Touch {
+            path,
+            count: 1,
+            last_accessed,
+        });
+        added
+    }
+
+    /// Rereads the projects file if it changed on disk since it was last read or written, for
+    /// example by another instance, keeping the pending changes.
+    ///
+    /// Returns whether the file was reread.
+    pub fn revalidate(&mut self) -> Result<bool> {
+        if file_stamp(&self.file_path) == self.stamp {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        // Stamped before reading so that a write in between is picked up by the next check.
+        let stamp = file_stamp(&self.file_path);
+        let (mut projects, warnings) = load_projects_with_warnings_from(&self.file_path)?;
+        for warning in &warnings {
+            log::warn!("{warning}");
+        }
+        merge_system_projects(&mut projects, self.system.clone());
+        for change in &self.changes {
+            change.apply(&mut projects);
+        }
+        self.replace(projects);
+        self.warnings = warnings;
+        self.stamp = stamp;
+        Ok(())
+    }
+
+    /// Applies `f` to the up to date projects list, like [super::update_projects], and writes the
+    /// result right away if `f` returns `true`. Pending changes are written first.
+    pub fn update<T>(&mut self, f: impl FnOnce(&mut Vec<Project>) -> (bool, T)) -> Result<T> {
+        self.flush()?;
+        let system = &self.system;
+        let (projects, result) = update_projects_in(&self.file_path, |projects| {
+            merge_system_projects(projects, system.clone());
+            let (modified, result) = f(projects);
+            let merged = projects.clone();
+            if modified {
+                *projects = without_system_projects(projects, system);
+            }
+            (modified, (merged, result))
+        })?;
+        self.replace(projects);
+        self.stamp = file_stamp(&self.file_path);
+        Ok(result)
+    }
+
+    /// Writes the pending changes if there are any and the last flush is at least the flush
+    /// interval ago.
+    ///
+    /// Returns whether the projects file was written.
+    pub fn flush_if_due(&mut self) -> Result<bool> {
+        if !self.is_dirty() || self.last_flush.elapsed() < self.interval {
+            return Ok(false);
+        }
+        self.flush()?;
+        Ok(true)
+    }
+
+    /// Writes the pending changes to the projects file right away.
+    ///
+    /// The changes are applied to the current contents of the file while holding the projects
+    /// lock, after which the in-memory list is replaced by the result so that it also reflects
+    /// the changes made by other instances. Without pending changes the file is only reread.
+    pub fn flush(&mut self) -> Result<()> {
+        if !self.is_dirty() {
+            return self.reload();
+        }
+        let (changes, system) = (&self.changes, &self.system);
+        let projects = update_projects_in(&self.file_path, |projects| {
+            merge_system_projects(projects, system.clone());
+            for change in changes {
+                change.apply(projects);
+            }
+            let merged = projects.clone();
+            *projects = without_system_projects(projects, system);
+            (true, merged)
+        })?;
+        self.replace(projects);
+        self.stamp = file_stamp(&self.file_path);
+        self.changes.clear();
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::{add_project_in, load_projects_from, save_projects_to};
+
+    #[test]
+    fn batch_touches_into_one_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let project = canonicalize(dir.path());
+
+        let mut store = ProjectsStore::with_interval(file_path.clone(), FLUSH_INTERVAL).unwrap();
+        assert!(store.touch(&project));
+        for _ in 0..99 {
+            assert!(!store.touch(&project));
+            assert!(!store.flush_if_due().unwrap());
+        }
+        assert!(!file_path.exists());
+        assert_eq!(store.projects()[0].access_count, 100);
+
+        store.flush().unwrap();
+        assert!(!store.is_dirty());
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].access_count, 100);
+
+        // Without changes the file is only reread.
+        std::fs::remove_file(&file_path).unwrap();
+        store.flush().unwrap();
+        assert!(!file_path.exists());
+        assert!(store.projects().is_empty());
+    }
+
+    #[test]
+    fn flush_when_due() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+
+        let mut store = ProjectsStore::with_interval(file_path.clone(), Duration::ZERO).unwrap();
+        assert!(!store.flush_if_due().unwrap());
+        store.touch(dir.path());
+        assert!(store.flush_if_due().unwrap());
+        assert_eq!(load_projects_from(&file_path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn archive_stale_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let mut old = Project::new(canonicalize(dir.path()).join("old"));
+        old.last_accessed = Some(unix_now() - 100 * 24 * 60 * 60);
+        let mut recent = Project::new(canonicalize(dir.path()).join("recent"));
+        recent.last_accessed = Some(unix_now());
+        save_projects_to(&file_path, &[old.clone(), recent]).unwrap();
+
+        let mut store = ProjectsStore::load(file_path.clone()).unwrap();
+        assert!(store.archive_stale(0).is_empty());
+        assert_eq!(store.archive_stale(30), [old]);
+        assert!(store.archive_stale(30).is_empty());
+
+        store.flush().unwrap();
+        let projects = load_projects_from(&file_path).unwrap();
+        assert!(projects[0].archived);
+        assert!(!projects[1].archived);
+    }
+
+    #[test]
+    fn merge_with_changes_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let a = canonicalize(dir.path()).join("a");
+        let b = canonicalize(dir.path()).join("b");
+        let c = canonicalize(dir.path()).join("c");
+        let mut existing = Project::new(a.clone());
+        existing.access_count = 3;
+        save_projects_to(&file_path, &[existing, Project::new(b.clone())]).unwrap();
+
+        let mut store = ProjectsStore::load(file_path.clone()).unwrap();
+        store.touch(&a);
+        store.touch(&a);
+        assert!(store.set_archived(&b, true));
+        // Another instance registers a project in the meantime.
+        add_project_in(&file_path, &c).unwrap();
+        assert!(!store.remove(&c));
+
+        store.flush().unwrap();
+        let projects = load_projects_from(&file_path).unwrap();
+        let paths: Vec<_> = projects.iter().map(|p| p.path.clone()).collect();
+        assert_eq!(paths, [a, b, c]);
+        assert_eq!(projects[0].access_count, 5);
+        assert!(projects[1].archived);
+        assert_eq!(store.projects(), projects);
+    }
+
+    #[test]
+    fn set_project_theme() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let a = canonicalize(dir.path()).join("a");
+        save_projects_to(&file_path, &[Project::new(a.clone())]).unwrap();
+
+        let mut store = ProjectsStore::load(file_path.clone()).unwrap();
+        assert!(store.set_theme(&a, Some("onelight".to_string())));
+        assert!(!store.set_theme(&dir.path().join("b"), None));
+        store.flush().unwrap();
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert!(content.contains("theme = \"onelight\""), "{content}");
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects[0].theme.as_deref(), Some("onelight"));
+
+        assert!(store.set_theme(&a, None));
+        store.flush().unwrap();
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert!(!content.contains("theme"), "{content}");
+    }
+
+    #[test]
+    fn revalidate_against_changes_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let a = canonicalize(dir.path()).join("a");
+        let b = canonicalize(dir.path()).join("b");
+
+        let mut store = ProjectsStore::load(file_path.clone()).unwrap();
+        assert!(!store.revalidate().unwrap());
+        store.touch(&a);
+
+        // Another instance registers a project. The pending access is kept.
+        add_project_in(&file_path, &b).unwrap();
+        assert!(store.revalidate().unwrap());
+        let paths: Vec<_> = store.projects().iter().map(|p| p.path.clone()).collect();
+        assert_eq!(paths, [b.clone(), a.clone()]);
+        assert!(store.is_dirty());
+        assert!(!store.revalidate().unwrap());
+
+        // Writes of the store itself don't need a reread.
+        store.flush().unwrap();
+        assert!(!store.revalidate().unwrap());
+        assert_eq!(load_projects_from(&file_path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn update_with_system_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let system = vec![Project::new(PathBuf::from("/opt/src/shared"))];
+        let user = Project::new(canonicalize(dir.path()).join("user"));
+        save_projects_to(&file_path, std::slice::from_ref(&user)).unwrap();
+
+        let mut store =
+            ProjectsStore::with_system_projects(file_path.clone(), system.clone(), FLUSH_INTERVAL)
+                .unwrap();
+        assert_eq!(store.projects(), [user.clone(), system[0].clone()]);
+
+        let count = store
+            .update(|projects| {
+                projects[0].access_count = 2;
+                (true, projects.len())
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(store.projects().len(), 2);
+        // Unchanged system projects aren't written to the user's file.
+        let projects = load_projects_from(&file_path).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].access_count, 2);
+
+        store.touch(&system[0].path);
+        store.flush().unwrap();
+        assert_eq!(load_projects_from(&file_path).unwrap().len(), 2);
+        assert_eq!(store.projects().len(), 2);
+    }
+
+    #[test]
+    fn notify_of_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("projects.toml");
+        let a = canonicalize(dir.path()).join("a");
+        let b = canonicalize(dir.path()).join("b");
+
+        let mut store = ProjectsStore::load(file_path.clone()).unwrap();
+        let start = store.generation();
+        assert!(!store.changed_since(start));
+        assert_eq!(store.events_since(start).unwrap().count(), 0);
+
+        assert!(store.add(&a));
+        assert!(!store.add(&a));
+        store.touch(&a);
+        store.touch(&a);
+        store.touch(&b);
+        assert!(store.changed_since(start));
+        let events: Vec<_> = store.events_since(start).unwrap().cloned().collect();
+        assert_eq!(
+            events,
+            [
+                ProjectsEvent::Added(a.clone()),
+                ProjectsEvent::Updated(a.clone()),
+                ProjectsEvent::Added(b.clone()),
+            ]
+        );
+
+        // Writing the changes doesn't change the projects.
+        let written = store.generation();
+        store.flush().unwrap();
+        assert!(!store.changed_since(written));
+
+        // Neither does rereading the file, until another instance changes it.
+        store.revalidate().unwrap();
+        assert!(!store.changed_since(written));
+        std::fs::remove_file(&file_path).unwrap();
+        assert!(store.revalidate().unwrap());
+        let events: Vec<_> = store.events_since(written).unwrap().cloned().collect();
+        assert_eq!(events, [ProjectsEvent::Reloaded]);
+
+        assert!(!store.remove(&b));
+        for _ in 0..MAX_EVENTS {
+            store.add(&a);
+            store.remove(&a);
+        }
+        assert!(store.events_since(written).is_none());
+        let last = store.generation() - 1;
+        let events: Vec<_> = store.events_since(last).unwrap().cloned().collect();
+        assert_eq!(events, [ProjectsEvent::Removed(a)]);
+    }
+}
@@ -0,0 +1,328 @@
+//! Creating projects from templates, the directories in `templates` of the config directory.
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use super::{
+    add_project_in, canonicalize, load_projects_from, path_key, projects_file_path, Project,
+    ProjectsError, Result,
+};
+
+/// The placeholder replaced by the project name in the paths and contents of template files.
+const NAME_PLACEHOLDER: &str = "{{name}}";
+
+/// The directory holding the project templates, one subdirectory per template.
+pub fn templates_dir() -> PathBuf {
+    crate::config_dir().join("templates")
+}
+
+/// The names of the project templates, sorted.
+pub fn list_templates() -> Vec<String> {
+    list_templates_in(&templates_dir())
+}
+
+fn list_templates_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    templates.sort();
+    templates
+}
+
+/// Creates a project at `dest` by copying the template named `template`, replacing `{{name}}`
+/// with `name` in the file names and the contents of the text files, and registers it with
+/// [add_project](super::add_project). A `git init` is run in the project first if `git_init` is
+/// set. The `.git` directory of the template is not copied.
+///
+/// Nothing is copied if `dest` exists and isn't an empty directory. If creating the project
+/// fails halfway, the files copied so far are removed again.
+pub fn create_project_from_template(
+    template: &str,
+    dest: &Path,
+    name: &str,
+    git_init: bool,
+) -> Result<Project> {
+    create_project_from_template_in(
+        &templates_dir(),
+        &projects_file_path(),
+        template,
+        dest,
+        name,
+        git_init,
+    )
+}
+
+fn create_project_from_template_in(
+    templates_dir: &Path,
+    file_path: &Path,
+    template: &str,
+    dest: &Path,
+    name: &str,
+    git_init: bool,
+) -> Result<Project> {
+    let mut components = Path::new(template).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return Err(ProjectsError::UnknownTemplate(template.to_string()));
+    }
+    let template_dir = templates_dir.join(template);
+    if !template_dir.is_dir() {
+        return Err(ProjectsError::UnknownTemplate(template.to_string()));
+    }
+    let mut components = Path::new(name).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(normal)), None) if normal == name
+    ) {
+        return Err(ProjectsError::InvalidProjectName(name.to_string()));
+    }
+
+    let existed = match std::fs::symlink_metadata(dest) {
+        Ok(_) => {
+            let is_empty = std::fs::read_dir(dest)
+                .map(|mut entries| entries.next().is_none())
+                .unwrap_or(false);
+            if !is_empty {
+                return Err(ProjectsError::DestinationNotEmpty {
+                    path: dest.to_path_buf(),
+                });
+            }
+            true
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => false,
+        Err(err) => return Err(ProjectsError::io("create project", dest, err)),
+    };
+
+    // The files are listed before anything is created, so that a destination within the
+    // template isn't copied into itself.
+    let mut files = Vec::new();
+    list_files(&template_dir, PathBuf::new(), &mut files)?;
+    let result = copy_files(&template_dir, dest, name, &files).and_then(|()| {
+        if git_init {
+            run_git_init(dest)
+        } else {
+            Ok(())
+        }
+    });
+    if let Err(err) = result {
+        if let Err(err) = std::fs::remove_dir_all(dest) {
+            log::warn!("Failed to clean up {}: {err}", dest.display());
+        }
+        if existed {
+            let _ = std::fs::create_dir(dest);
+        }
+        return Err(err);
+    }
+
+    add_project_in(file_path, dest)?;
+    let key = path_key(dest);
+    let project = load_projects_from(file_path)?
+        .into_iter()
+        .find(|project| path_key(&project.path) == key)
+        .unwrap_or_else(|| Project::new(canonicalize(dest)));
+    Ok(project)
+}
+
+/// A file or directory of a template, relative to the template directory.
+enum TemplateEntry {
+    Dir(PathBuf),
+    File(PathBuf),
+}
+
+fn list_files(root: &Path, relative: PathBuf, files: &mut Vec<TemplateEntry>) -> Result<()> {
+    let dir = root.join(&relative);
+    let entries =
+        std::fs::read_dir(&dir).map_err(|err| ProjectsError::io("read template", &dir, err))?;
+    let mut entries: Vec<_> = entries
+        .collect::<std::io::Result<_>>()
+        .map_err(|err| ProjectsError::io("read template", &dir, err))?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let file_name = entry.file_name();
+        if relative.as_os_str().is_empty() && file_name == ".git" {
+            continue;
+        }
+        let relative = relative.join(file_name);
+        if entry.path().is_dir() {
+            files.push(TemplateEntry::Dir(relative.clone()));
+            list_files(root, relative, files)?;
+        } else {
+            files.push(TemplateEntry::File(relative));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the name placeholder in the components of `path`. Components which aren't valid
+/// UTF-8 are kept as they are.
+fn substitute_path(path: &Path, name: &str) -> PathBuf {
+    path.components()
+        .map(|component| match component.as_os_str().to_str() {
+            Some(component) => OsString::from(component.replace(NAME_PLACEHOLDER, name)),
+            None => component.as_os_str().to_os_string(),
+        })
+        .collect()
+}
+
+fn copy_files(template_dir: &Path, dest: &Path, name: &str, files: &[TemplateEntry]) -> Result<()> {
+    std::fs::create_dir_all(dest).map_err(|err| ProjectsError::io("create project", dest, err))?;
+    for entry in files {
+        match entry {
+            TemplateEntry::Dir(relative) => {
+                let target = dest.join(substitute_path(relative, name));
+                std::fs::create_dir_all(&target)
+                    .map_err(|err| ProjectsError::io("create directory", &target, err))?;
+            }
+            TemplateEntry::File(relative) => {
+                let source = template_dir.join(relative);
+                let target = dest.join(substitute_path(relative, name));
+                let content = std::fs::read(&source)
+                    .map_err(|err| ProjectsError::io("read template file", &source, err))?;
+                // Binary files are copied as they are.
+                let content = match String::from_utf8(content) {
+                    Ok(text) => text.replace(NAME_PLACEHOLDER, name).into_bytes(),
+                    Err(err) => err.into_bytes(),
+                };
+                std::fs::write(&target, content)
+                    .map_err(|err| ProjectsError::io("write file", &target, err))?;
+                // Keeps scripts of the template executable.
+                if let Ok(metadata) = std::fs::metadata(&source) {
+                    let _ = std::fs::set_permissions(&target, metadata.permissions());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_git_init(path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("init")
+        .current_dir(path)
+        .output()
+        .map_err(|err| ProjectsError::io("run git init", path, err))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(ProjectsError::GitInit {
+        path: path.to_path_buf(),
+        message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn create_project_from_template_substitutes_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        let file_path = dir.path().join("projects.toml");
+        write(
+            &templates.join("rust/LICENSE"),
+            "Copyright {{name}} authors\n",
+        );
+        write(
+            &templates.join("rust/src/{{name}}.rs"),
+            "//! The {{name}} crate.\n",
+        );
+        write(&templates.join("rust/.helix/config.toml"), "");
+        write(&templates.join("rust/.git/HEAD"), "ref: refs/heads/main\n");
+        std::fs::write(templates.join("rust/logo.bin"), [0xff, 0xfe, b'{']).unwrap();
+        write(&templates.join("c/Makefile"), "");
+        assert_eq!(list_templates_in(&templates), ["c", "rust"]);
+
+        let dest = dir.path().join("src/game");
+        let project =
+            create_project_from_template_in(&templates, &file_path, "rust", &dest, "game", false)
+                .unwrap();
+        assert_eq!(project.path, canonicalize(&dest));
+        assert_eq!(project.name.as_deref(), Some("game"));
+        assert_eq!(
+            std::fs::read_to_string(dest.join("LICENSE")).unwrap(),
+            "Copyright game authors\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dest.join("src/game.rs")).unwrap(),
+            "//! The game crate.\n"
+        );
+        assert!(dest.join(".helix/config.toml").is_file());
+        assert_eq!(
+            std::fs::read(dest.join("logo.bin")).unwrap(),
+            [0xff, 0xfe, b'{']
+        );
+        assert!(!dest.join(".git").exists());
+        assert_eq!(load_projects_from(&file_path).unwrap(), vec![project]);
+    }
+
+    #[test]
+    fn create_project_from_template_refuses_collisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        let file_path = dir.path().join("projects.toml");
+        write(&templates.join("rust/LICENSE"), "");
+
+        let dest = dir.path().join("game");
+        write(&dest.join("notes.md"), "");
+        assert!(matches!(
+            create_project_from_template_in(&templates, &file_path, "rust", &dest, "game", false),
+            Err(ProjectsError::DestinationNotEmpty { .. })
+        ));
+        assert!(!dest.join("LICENSE").exists());
+        assert!(!file_path.exists());
+
+        // An empty directory is fine.
+        let empty = dir.path().join("empty");
+        std::fs::create_dir(&empty).unwrap();
+        create_project_from_template_in(&templates, &file_path, "rust", &empty, "empty", false)
+            .unwrap();
+        assert!(empty.join("LICENSE").is_file());
+
+        let other = dir.path().join("other");
+        for (template, name) in [("missing", "other"), ("../rust", "other"), ("rust", "a/b")] {
+            assert!(
+                create_project_from_template_in(
+                    &templates, &file_path, template, &other, name, false
+                )
+                .is_err(),
+                "{template} {name}"
+            );
+        }
+        assert!(!other.exists());
+    }
+
+    #[test]
+    fn create_project_from_template_with_git() {
+        if helix_stdx::env::which("git").is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        write(&templates.join("rust/LICENSE"), "");
+        let dest = dir.path().join("game");
+        create_project_from_template_in(
+            &templates,
+            &dir.path().join("projects.toml"),
+            "rust",
+            &dest,
+            "game",
+            true,
+        )
+        .unwrap();
+        assert!(dest.join(".git").is_dir());
+    }
+}
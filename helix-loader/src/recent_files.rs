@@ -0,0 +1,331 @@
+//! The files recently opened or written in each project, kept in the state directory.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The maximum number of files remembered per project.
+pub const MAX_RECENT_FILES: usize = 100;
+/// How often a [RecentFilesStore] writes its changes at most.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RecentFile {
+    /// The path relative to the project root, so that the list survives moving the project.
+    path: PathBuf,
+    last_accessed: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RecentFiles {
+    /// The project the list belongs to, used to detect file name collisions.
+    project: PathBuf,
+    #[serde(default)]
+    files: Vec<RecentFile>,
+}
+
+impl RecentFiles {
+    /// Records an access to `path` at `last_accessed`, keeping the files ordered from the most
+    /// recently accessed and dropping the oldest beyond [MAX_RECENT_FILES].
+    fn record(&mut self, path: PathBuf, last_accessed: u64) {
+        let last_accessed = match self.files.iter().position(|file| file.path == path) {
+            Some(i) => self.files.remove(i).last_accessed.max(last_accessed),
+            None => last_accessed,
+        };
+        let index = self
+            .files
+            .partition_point(|file| file.last_accessed > last_accessed);
+        self.files.insert(
+            index,
+            RecentFile {
+                path,
+                last_accessed,
+            },
+        );
+        self.files.truncate(MAX_RECENT_FILES);
+    }
+}
+
+fn recent_files_dir() -> PathBuf {
+    crate::state_dir().join("recent-files")
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn load(dir: &Path, project: &Path) -> RecentFiles {
+    let file_path = dir.join(crate::session::session_file_name(project));
+    let empty = || RecentFiles {
+        project: project.to_path_buf(),
+        files: Vec::new(),
+    };
+    let Ok(content) = std::fs::read_to_string(&file_path) else {
+        return empty();
+    };
+    match toml::from_str::<RecentFiles>(&content) {
+        Ok(recent) if recent.project == project => recent,
+        Ok(_) => empty(),
+        Err(err) => {
+            log::warn!(
+                "Failed to parse recent files {}: {err}",
+                file_path.display()
+            );
+            empty()
+        }
+    }
+}
+
+fn save(dir: &Path, recent: &RecentFiles) -> Result<()> {
+    let file_path = dir.join(crate::session::session_file_name(&recent.project));
+    crate::ensure_parent_dir(&file_path);
+    let content = toml::to_string_pretty(recent).context("Failed to serialize recent files")?;
+
+    let mut file = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temporary file in: {}", dir.display()))?;
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write recent files: {}", file_path.display()))?;
+    file.persist(&file_path)
+        .with_context(|| format!("Failed to write recent files: {}", file_path.display()))?;
+    Ok(())
+}
+
+/// The files of `recent` which still exist, with their absolute paths.
+fn existing_files(recent: RecentFiles) -> Vec<(PathBuf, u64)> {
+    recent
+        .files
+        .into_iter()
+        .map(|file| (recent.project.join(file.path), file.last_accessed))
+        .filter(|(path, _)| path.is_file())
+        .collect()
+}
+
+/// Records an access to the file at `path` in the recent files of the project at `project`.
+/// Files outside of the project are ignored.
+pub fn record_file_access(project: &Path, path: &Path) -> Result<()> {
+    let mut store = RecentFilesStore::with_dir(recent_files_dir(), Duration::ZERO);
+    store.record(project, path);
+    store.flush()
+}
+
+/// The files recently accessed in the project at `project` which still exist, with the time of
+/// their last access in seconds since the Unix epoch, most recently accessed first.
+pub fn recent_files(project: &Path) -> Vec<(PathBuf, u64)> {
+    let project = canonicalize(project);
+    existing_files(load(&recent_files_dir(), &project))
+}
+
+/// Moves the recent files of the project at `from` to the project at `to`, after the project was
+/// moved. Files recorded for `to` already are kept.
+pub fn move_recent_files(from: &Path, to: &Path) -> Result<()> {
+    move_recent_files_in(&recent_files_dir(), from, &canonicalize(to))
+}
+
+fn move_recent_files_in(dir: &Path, from: &Path, to: &Path) -> Result<()> {
+    let moved = load(dir, from);
+    if moved.files.is_empty() {
+        return Ok(());
+    }
+    let mut recent = load(dir, to);
+    for file in moved.files.into_iter().rev() {
+        recent.record(file.path, file.last_accessed);
+    }
+    save(dir, &recent)?;
+    let file_path = dir.join(crate::session::session_file_name(from));
+    std::fs::remove_file(&file_path)
+        .with_context(|| format!("Failed to remove recent files: {}", file_path.display()))
+}
+
+/// Accesses to files, kept in memory and written to the recent files of their projects in
+/// batches.
+///
+/// Each flush merges the recorded accesses into the lists as they are on disk at that point, so
+/// that accesses recorded by other instances in the meantime are kept.
+#[derive(Debug)]
+pub struct RecentFilesStore {
+    dir: PathBuf,
+    /// Accesses recorded since the last flush, by project.
+    pending: HashMap<PathBuf, RecentFiles>,
+    interval: Duration,
+    last_flush: Instant,
+}
+
+impl Default for RecentFilesStore {
+    /// A store for the recent files in the state directory, writing changes at most once per
+    /// [FLUSH_INTERVAL].
+    fn default() -> Self {
+        Self::with_dir(recent_files_dir(), FLUSH_INTERVAL)
+    }
+}
+
+impl RecentFilesStore {
+    /// A store for the recent files in `dir`, writing changes at most once per `interval`.
+    pub fn with_dir(dir: PathBuf, interval: Duration) -> Self {
+        Self {
+            dir,
+            pending: HashMap::new(),
+            interval,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Whether there are accesses which are not written yet.
+    pub fn is_dirty(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Records an access to the file at `path` in the project at `project`. Returns `false` if
+    /// the file is outside of the project.
+    pub fn record(&mut self, project: &Path, path: &Path) -> bool {
+        let project = canonicalize(project);
+        let path = canonicalize(path);
+        let Ok(relative) = path.strip_prefix(&project) else {
+            return false;
+        };
+        if relative.as_os_str().is_empty() {
+            return false;
+        }
+        let relative = relative.to_path_buf();
+        self.pending
+            .entry(project.clone())
+            .or_insert_with(|| RecentFiles {
+                project,
+                files: Vec::new(),
+            })
+            .record(relative, unix_now());
+        true
+    }
+
+    /// The recent files of the project at `project`, including the accesses which are not
+    /// written yet, see [recent_files].
+    pub fn recent_files(&self, project: &Path) -> Vec<(PathBuf, u64)> {
+        let project = canonicalize(project);
+        let mut recent = load(&self.dir, &project);
+        if let Some(pending) = self.pending.get(&project) {
+            // Oldest first, so that accesses within the same second keep their order.
+            for file in pending.files.iter().rev() {
+                recent.record(file.path.clone(), file.last_accessed);
+            }
+        }
+        existing_files(recent)
+    }
+
+    /// Writes the pending accesses if there are any and the last flush is at least the flush
+    /// interval ago. Returns whether anything was written.
+    pub fn flush_if_due(&mut self) -> Result<bool> {
+        if !self.is_dirty() || self.last_flush.elapsed() < self.interval {
+            return Ok(false);
+        }
+        self.flush()?;
+        Ok(true)
+    }
+
+    /// Writes the pending accesses right away.
+    pub fn flush(&mut self) -> Result<()> {
+        for (project, pending) in std::mem::take(&mut self.pending) {
+            let mut recent = load(&self.dir, &project);
+            for file in pending.files.into_iter().rev() {
+                recent.record(file.path, file.last_accessed);
+            }
+            save(&self.dir, &recent)?;
+        }
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_list_recent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = canonicalize(dir.path()).join("project");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        for name in ["src/main.rs", "src/lib.rs", "README.md"] {
+            std::fs::write(project.join(name), "").unwrap();
+        }
+        let state = dir.path().join("state");
+
+        let mut store = RecentFilesStore::with_dir(state.clone(), FLUSH_INTERVAL);
+        assert!(store.record(&project, &project.join("src/main.rs")));
+        assert!(store.record(&project, &project.join("README.md")));
+        assert!(store.record(&project, &project.join("src/lib.rs")));
+        assert!(store.record(&project, &project.join("src/main.rs")));
+        // Files outside of the project aren't recorded.
+        assert!(!store.record(&project, &dir.path().join("other.rs")));
+        assert!(!store.flush_if_due().unwrap());
+
+        let paths = |files: Vec<(PathBuf, u64)>| -> Vec<PathBuf> {
+            files.into_iter().map(|(path, _)| path).collect()
+        };
+        // Accesses within the same second keep their order.
+        let expected = ["src/main.rs", "src/lib.rs", "README.md"].map(|name| project.join(name));
+        let files = store.recent_files(&project);
+        assert_eq!(paths(files.clone()), expected);
+        assert!(files.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        store.flush().unwrap();
+        assert!(!store.is_dirty());
+        let other = RecentFilesStore::with_dir(state.clone(), FLUSH_INTERVAL);
+        assert_eq!(paths(other.recent_files(&project)), expected);
+
+        // Paths are stored relative to the project.
+        let content =
+            std::fs::read_to_string(state.join(crate::session::session_file_name(&project)))
+                .unwrap();
+        assert!(content.contains("path = \"README.md\""));
+
+        // Files which no longer exist are left out.
+        std::fs::remove_file(project.join("src/lib.rs")).unwrap();
+        assert_eq!(
+            paths(other.recent_files(&project)),
+            [project.join("src/main.rs"), project.join("README.md")]
+        );
+    }
+
+    #[test]
+    fn move_recent_files_with_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = PathBuf::from("/code/project");
+        let to = PathBuf::from("/src/project");
+        let mut recent = RecentFiles {
+            project: from.clone(),
+            files: Vec::new(),
+        };
+        recent.record(PathBuf::from("README.md"), 10);
+        save(dir.path(), &recent).unwrap();
+
+        move_recent_files_in(dir.path(), &from, &to).unwrap();
+        assert!(load(dir.path(), &from).files.is_empty());
+        let moved = load(dir.path(), &to);
+        assert_eq!(moved.project, to);
+        assert_eq!(moved.files[0].path, Path::new("README.md"));
+    }
+
+    #[test]
+    fn keep_most_recent_files() {
+        let mut recent = RecentFiles::default();
+        for i in 0..MAX_RECENT_FILES as u64 + 10 {
+            recent.record(PathBuf::from(format!("{i}.rs")), i);
+        }
+        recent.record(PathBuf::from("50.rs"), 5);
+        assert_eq!(recent.files.len(), MAX_RECENT_FILES);
+        assert_eq!(recent.files[0].path, Path::new("109.rs"));
+        assert_eq!(recent.files.last().unwrap().path, Path::new("10.rs"));
+        // An older access doesn't move a file back.
+        assert_eq!(recent.files[59].path, Path::new("50.rs"));
+        assert_eq!(recent.files[59].last_accessed, 50);
+    }
+}
@@ -0,0 +1,449 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The version of the session file format. Session files with another version are ignored.
+pub const SESSION_VERSION: u32 = 2;
+/// The maximum number of project sessions kept in the session file. The least recently saved
+/// sessions are dropped first.
+pub const MAX_SESSIONS: usize = 50;
+/// The maximum number of documents persisted in a session.
+pub const MAX_SESSION_DOCUMENTS: usize = 100;
+/// The maximum number of selection ranges persisted per document.
+pub const MAX_SESSION_RANGES: usize = 64;
+
+/// The sessions of all projects as stored in the session file, most recently saved first.
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionFile {
+    version: u32,
+    #[serde(default, rename = "session")]
+    sessions: Vec<Session>,
+}
+
+/// The open documents and window layout of a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// The project the session belongs to.
+    pub project: PathBuf,
+    /// The working directory at the time the session was saved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<PathBuf>,
+    /// Index of the focused view, counting the views of `layout` in order.
+    #[serde(default)]
+    pub focus: usize,
+    #[serde(default)]
+    pub documents: Vec<SessionDocument>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<SessionLayout>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionDocument {
+    pub path: PathBuf,
+    /// Selection ranges as character offsets.
+    #[serde(default)]
+    pub selection: Vec<SessionRange>,
+    #[serde(default)]
+    pub primary: usize,
+    /// The language ID reported to language servers if it was overridden.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRange {
+    pub anchor: usize,
+    pub head: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// The window layout, referring to documents by their index in [Session::documents].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SessionLayout {
+    View {
+        document: usize,
+    },
+    Split {
+        direction: SplitDirection,
+        children: Vec<SessionLayout>,
+    },
+}
+
+impl SessionLayout {
+    /// Removes the views for which `keep` returns `false`, collapsing splits that are
+    /// left with a single child. Returns `None` if no views are left.
+    pub fn retain(self, keep: &impl Fn(usize) -> bool) -> Option<Self> {
+        match self {
+            Self::View { document } => keep(document).then_some(self),
+            Self::Split {
+                direction,
+                children,
+            } => {
+                let mut children: Vec<_> = children
+                    .into_iter()
+                    .filter_map(|child| child.retain(keep))
+                    .collect();
+                match children.len() {
+                    0 => None,
+                    1 => children.pop(),
+                    _ => Some(Self::Split {
+                        direction,
+                        children,
+                    }),
+                }
+            }
+        }
+    }
+
+    /// The documents of all views in layout order.
+    pub fn views(&self) -> Vec<usize> {
+        match self {
+            Self::View { document } => vec![*document],
+            Self::Split { children, .. } => children.iter().flat_map(Self::views).collect(),
+        }
+    }
+}
+
+impl Session {
+    pub fn new(project: PathBuf) -> Self {
+        Self {
+            project,
+            working_directory: None,
+            focus: 0,
+            documents: Vec::new(),
+            layout: None,
+        }
+    }
+
+    /// Drops documents and selection ranges beyond the session limits.
+    fn truncate(&mut self) {
+        let len = self.documents.len().min(MAX_SESSION_DOCUMENTS);
+        self.documents.truncate(len);
+        for document in &mut self.documents {
+            document.selection.truncate(MAX_SESSION_RANGES);
+            if document.primary >= document.selection.len() {
+                document.primary = 0;
+            }
+        }
+        self.layout = self
+            .layout
+            .take()
+            .and_then(|layout| layout.retain(&|i| i < len));
+    }
+}
+
+/// The path of the session file, `session.toml` next to the projects file of the default
+/// profile.
+pub fn session_file_path() -> PathBuf {
+    crate::projects::profile_file_path(None).with_file_name("session.toml")
+}
+
+/// A file name that is stable across versions for the given project path.
+pub(crate) fn session_file_name(project: &Path) -> String {
+    // 64-bit FNV-1a
+    let hash = project
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+        });
+    let name = project
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("root");
+    format!("{name}-{hash:016x}.toml")
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Saves the session of the project at `project`, replacing any previous session. A session
+/// without documents removes the previous one instead.
+pub fn save_session(project: &Path, session: &Session) -> Result<()> {
+    let project = canonicalize(project);
+    save_session_in(&session_file_path(), &project, session.clone())
+}
+
+fn save_session_in(file_path: &Path, project: &Path, mut session: Session) -> Result<()> {
+    let mut sessions = read_sessions(file_path);
+    sessions.retain(|session| session.project != project);
+    if !session.documents.is_empty() {
+        session.project = project.to_path_buf();
+        session.truncate();
+        sessions.insert(0, session);
+    }
+    sessions.truncate(MAX_SESSIONS);
+    write_sessions(file_path, sessions)
+}
+
+/// Loads the session of the project at `project`.
+///
+/// Returns `None` if there is no session or the session file can't be read.
+pub fn load_session(project: &Path) -> Option<Session> {
+    let project = canonicalize(project);
+    load_session_in(&session_file_path(), &project)
+}
+
+fn load_session_in(file_path: &Path, project: &Path) -> Option<Session> {
+    let mut session = read_sessions(file_path)
+        .into_iter()
+        .find(|session| session.project == project)?;
+    session.truncate();
+    Some(session)
+}
+
+/// Moves the session of the project at `from` to the project at `to`, after the project was
+/// moved. Documents and the working directory within the project move along with it.
+///
+/// Returns `false` if `from` has no session or `to` already has one, which is kept.
+pub fn move_session(from: &Path, to: &Path) -> Result<bool> {
+    let to = canonicalize(to);
+    move_session_in(&session_file_path(), from, &to)
+}
+
+fn move_session_in(file_path: &Path, from: &Path, to: &Path) -> Result<bool> {
+    let mut sessions = read_sessions(file_path);
+    if sessions.iter().any(|session| session.project == to) {
+        return Ok(false);
+    }
+    let Some(session) = sessions.iter_mut().find(|session| session.project == from) else {
+        return Ok(false);
+    };
+    let rebase = |path: &mut PathBuf| {
+        if let Ok(relative) = path.strip_prefix(from) {
+            *path = to.join(relative);
+        }
+    };
+    session.project = to.to_path_buf();
+    if let Some(working_directory) = &mut session.working_directory {
+        rebase(working_directory);
+    }
+    for document in &mut session.documents {
+        rebase(&mut document.path);
+    }
+    write_sessions(file_path, sessions)?;
+    Ok(true)
+}
+
+/// Reads the sessions of the session file at `file_path`. A missing file, or one which can't
+/// be read or has another version, has no sessions.
+fn read_sessions(file_path: &Path) -> Vec<Session> {
+    let Ok(content) = std::fs::read_to_string(file_path) else {
+        return Vec::new();
+    };
+    let file = toml::from_str::<toml::Value>(&content).and_then(|value| {
+        let version = value
+            .get("version")
+            .and_then(|version| version.as_integer());
+        if version != Some(SESSION_VERSION.into()) {
+            return Ok(None);
+        }
+        value.try_into::<SessionFile>().map(Some)
+    });
+    match file {
+        Ok(Some(file)) => file.sessions,
+        Ok(None) => {
+            log::info!(
+                "Ignoring session file {} with unsupported version",
+                file_path.display()
+            );
+            Vec::new()
+        }
+        Err(err) => {
+            log::warn!(
+                "Failed to parse session file {}: {err}",
+                file_path.display()
+            );
+            Vec::new()
+        }
+    }
+}
+
+fn write_sessions(file_path: &Path, sessions: Vec<Session>) -> Result<()> {
+    crate::ensure_parent_dir(file_path);
+    let file = SessionFile {
+        version: SESSION_VERSION,
+        sessions,
+    };
+    let content = toml::to_string_pretty(&file).context("Failed to serialize sessions")?;
+
+    let dir = file_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create temporary file in: {}", dir.display()))?;
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write session file: {}", file_path.display()))?;
+    file.persist(file_path)
+        .with_context(|| format!("Failed to write session file: {}", file_path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(path: &str) -> SessionDocument {
+        SessionDocument {
+            path: PathBuf::from(path),
+            selection: vec![SessionRange { anchor: 0, head: 3 }],
+            primary: 0,
+            language_id: None,
+        }
+    }
+
+    #[test]
+    fn roundtrip_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("session.toml");
+        let project = dir.path().join("project");
+        let session = Session {
+            working_directory: Some(project.join("src")),
+            focus: 1,
+            documents: vec![
+                document("/a.rs"),
+                SessionDocument {
+                    language_id: Some("jinja2".into()),
+                    ..document("/b.html")
+                },
+            ],
+            layout: Some(SessionLayout::Split {
+                direction: SplitDirection::Vertical,
+                children: vec![
+                    SessionLayout::View { document: 0 },
+                    SessionLayout::Split {
+                        direction: SplitDirection::Horizontal,
+                        children: vec![
+                            SessionLayout::View { document: 1 },
+                            SessionLayout::View { document: 0 },
+                        ],
+                    },
+                ],
+            }),
+            ..Session::new(project.clone())
+        };
+
+        save_session_in(&file_path, &project, session.clone()).unwrap();
+        assert_eq!(load_session_in(&file_path, &project), Some(session.clone()));
+        // Sessions are only loaded for the project they were saved for.
+        assert_eq!(load_session_in(&file_path, dir.path()), None);
+
+        // The sessions of other projects are kept, and a session without documents removes
+        // the previous one.
+        let other = dir.path().join("other");
+        save_session_in(&file_path, &other, session.clone()).unwrap();
+        save_session_in(&file_path, &project, Session::new(project.clone())).unwrap();
+        assert_eq!(load_session_in(&file_path, &project), None);
+        assert_eq!(
+            load_session_in(&file_path, &other),
+            Some(Session {
+                project: other,
+                ..session
+            })
+        );
+    }
+
+    #[test]
+    fn missing_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("session.toml");
+        assert_eq!(load_session_in(&file_path, dir.path()), None);
+        // A corrupt session file is ignored rather than failing the startup.
+        std::fs::write(&file_path, "version = 2\nsession = 1\n").unwrap();
+        assert_eq!(load_session_in(&file_path, dir.path()), None);
+        std::fs::write(&file_path, "session = [\n").unwrap();
+        assert_eq!(load_session_in(&file_path, dir.path()), None);
+    }
+
+    #[test]
+    fn ignore_other_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("session.toml");
+        let project = dir.path().join("project");
+        let content = format!(
+            "version = {}\n[[session]]\nproject = {:?}\n",
+            SESSION_VERSION + 1,
+            project.to_str().unwrap()
+        );
+        std::fs::write(&file_path, content).unwrap();
+        assert_eq!(load_session_in(&file_path, &project), None);
+    }
+
+    #[test]
+    fn limit_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("session.toml");
+        let project = |i: usize| PathBuf::from(format!("/project{i}"));
+        for i in 0..=MAX_SESSIONS {
+            let session = Session {
+                documents: vec![document("/a.rs")],
+                ..Session::new(project(i))
+            };
+            save_session_in(&file_path, &project(i), session).unwrap();
+        }
+        // The least recently saved session is dropped.
+        assert_eq!(load_session_in(&file_path, &project(0)), None);
+        assert!(load_session_in(&file_path, &project(1)).is_some());
+        assert!(load_session_in(&file_path, &project(MAX_SESSIONS)).is_some());
+    }
+
+    #[test]
+    fn move_session_with_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("session.toml");
+        let from = PathBuf::from("/code/project");
+        let to = PathBuf::from("/src/project");
+        let session = Session {
+            working_directory: Some(from.join("src")),
+            documents: vec![
+                document("/code/project/src/main.rs"),
+                document("/etc/hosts"),
+            ],
+            ..Session::new(from.clone())
+        };
+        save_session_in(&file_path, &from, session).unwrap();
+
+        assert!(move_session_in(&file_path, &from, &to).unwrap());
+        assert!(!move_session_in(&file_path, &from, &to).unwrap());
+        assert_eq!(load_session_in(&file_path, &from), None);
+        let moved = load_session_in(&file_path, &to).unwrap();
+        assert_eq!(moved.working_directory, Some(to.join("src")));
+        assert_eq!(moved.documents[0].path, to.join("src/main.rs"));
+        // Documents outside of the project keep their path.
+        assert_eq!(moved.documents[1].path, Path::new("/etc/hosts"));
+    }
+
+    #[test]
+    fn truncate_large_sessions() {
+        let mut session = Session::new(PathBuf::from("/project"));
+        session.documents = (0..MAX_SESSION_DOCUMENTS + 10)
+            .map(|i| document(&format!("/{i}.rs")))
+            .collect();
+        session.documents[0].selection = vec![SessionRange { anchor: 0, head: 0 }; 1000];
+        session.documents[0].primary = 999;
+        session.layout = Some(SessionLayout::Split {
+            direction: SplitDirection::Vertical,
+            children: vec![
+                SessionLayout::View { document: 1 },
+                SessionLayout::View {
+                    document: MAX_SESSION_DOCUMENTS + 5,
+                },
+            ],
+        });
+
+        session.truncate();
+        assert_eq!(session.documents.len(), MAX_SESSION_DOCUMENTS);
+        assert_eq!(session.documents[0].selection.len(), MAX_SESSION_RANGES);
+        assert_eq!(session.documents[0].primary, 0);
+        assert_eq!(session.layout, Some(SessionLayout::View { document: 1 }));
+    }
+}
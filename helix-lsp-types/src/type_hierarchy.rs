@@ -8,6 +8,13 @@
 
 pub type TypeHierarchyClientCapabilities = DynamicRegistrationClientCapabilities;
 
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum TypeHierarchyServerCapabilities {
+    Options(TypeHierarchyOptions),
+    RegistrationOptions(TypeHierarchyRegistrationOptions),
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
 pub struct TypeHierarchyOptions {
     #[serde(flatten)]
@@ -63,7 +70,7 @@ pub struct TypeHierarchyItem {
 
     /// Tags for this item.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tags: Option<SymbolTag>,
+    pub tags: Option<Vec<SymbolTag>>,
 
     /// More detail for this item, e.g. the signature of a function.
     #[serde(skip_serializing_if = "Option::is_none")]
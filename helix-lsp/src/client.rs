@@ -58,6 +58,8 @@ pub struct Client {
     config: Option<Value>,
     root_path: std::path::PathBuf,
     root_uri: Option<lsp::Url>,
+    /// The editor workspace the server was started in.
+    workspace: PathBuf,
     workspace_folders: Mutex<Vec<lsp::WorkspaceFolder>>,
     initialize_notify: Arc<Notify>,
     /// workspace folders added while the server is still initializing
@@ -74,6 +76,11 @@ pub fn try_add_doc(
     ) -> bool {
         let (workspace, workspace_is_cwd) = find_workspace();
         let workspace = path::normalize(workspace);
+        // Servers started in another workspace may have been configured by that
+        // workspace's languages.toml, so they are never shared with this one.
+        if self.workspace != workspace {
+            return false;
+        }
         let root = find_lsp_workspace(
             doc_path
                 .and_then(|x| x.parent().and_then(|x| x.to_str()))
@@ -207,6 +214,7 @@ pub fn start(
         server_environment: impl IntoIterator<Item = (impl AsRef<OsStr>, impl AsRef<OsStr>)>,
         root_path: PathBuf,
         root_uri: Option<lsp::Url>,
+        workspace: PathBuf,
         id: LanguageServerId,
         name: String,
         req_timeout: u64,
@@ -256,6 +264,7 @@ pub fn start(
             req_timeout,
             root_path,
             root_uri,
+            workspace,
             workspace_folders: Mutex::new(workspace_folders),
             initialize_notify: initialize_notify.clone(),
         };
@@ -313,6 +322,9 @@ pub fn supports_feature(&self, feature: LanguageServerFeature) -> bool {
                 capabilities.document_formatting_provider,
                 Some(OneOf::Left(true) | OneOf::Right(_))
             ),
+            LanguageServerFeature::OnTypeFormatting => {
+                capabilities.document_on_type_formatting_provider.is_some()
+            }
             LanguageServerFeature::GotoDeclaration => matches!(
                 capabilities.declaration_provider,
                 Some(
@@ -389,6 +401,26 @@ pub fn supports_feature(&self, feature: LanguageServerFeature) -> bool {
                         | ColorProviderCapability::Options(_)
                 )
             ),
+            LanguageServerFeature::CallHierarchy => matches!(
+                capabilities.call_hierarchy_provider,
+                Some(
+                    CallHierarchyServerCapability::Simple(true)
+                        | CallHierarchyServerCapability::Options(_)
+                )
+            ),
+            LanguageServerFeature::TypeHierarchy => matches!(
+                capabilities.type_hierarchy_provider,
+                Some(OneOf::Left(true) | OneOf::Right(_))
+            ),
+            LanguageServerFeature::LinkedEditingRange => matches!(
+                capabilities.linked_editing_range_provider,
+                Some(
+                    LinkedEditingRangeServerCapabilities::Simple(true)
+                        | LinkedEditingRangeServerCapabilities::Options(_)
+                        | LinkedEditingRangeServerCapabilities::RegistrationOptions(_)
+                )
+            ),
+            LanguageServerFeature::CodeLens => capabilities.code_lens_provider.is_some(),
         }
     }
 
@@ -657,6 +689,9 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                     formatting: Some(lsp::DocumentFormattingClientCapabilities {
                         dynamic_registration: Some(false),
                     }),
+                    on_type_formatting: Some(lsp::DocumentOnTypeFormattingClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
                     code_action: Some(lsp::CodeActionClientCapabilities {
                         code_action_literal_support: Some(lsp::CodeActionLiteralSupport {
                             code_action_kind: lsp::CodeActionKindLiteralSupport {
@@ -702,6 +737,18 @@ pub(crate) async fn initialize(&self, enable_snippets: bool) -> Result<lsp::Init
                         dynamic_registration: Some(false),
                         resolve_support: None,
                     }),
+                    call_hierarchy: Some(lsp::CallHierarchyClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    type_hierarchy: Some(lsp::TypeHierarchyClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    linked_editing_range: Some(lsp::LinkedEditingRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    code_lens: Some(lsp::CodeLensClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
                     ..Default::default()
                 }),
                 window: Some(lsp::WindowClientCapabilities {
@@ -1156,6 +1203,46 @@ pub fn text_document_document_color(
         Some(self.call::<lsp::request::DocumentColor>(params))
     }
 
+    pub fn text_document_code_lens(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        work_done_token: Option<lsp::ProgressToken>,
+    ) -> Option<impl Future<Output = Result<Option<Vec<lsp::CodeLens>>>>> {
+        self.capabilities
+            .get()
+            .unwrap()
+            .code_lens_provider
+            .as_ref()?;
+        let params = lsp::CodeLensParams {
+            text_document,
+            work_done_progress_params: lsp::WorkDoneProgressParams {
+                work_done_token: work_done_token.clone(),
+            },
+            partial_result_params: helix_lsp_types::PartialResultParams {
+                partial_result_token: work_done_token,
+            },
+        };
+
+        Some(self.call::<lsp::request::CodeLensRequest>(params))
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        code_lens: &lsp::CodeLens,
+    ) -> Option<impl Future<Output = Result<lsp::CodeLens>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support resolving code lenses.
+        match capabilities.code_lens_provider {
+            Some(lsp::CodeLensOptions {
+                resolve_provider: Some(true),
+            }) => (),
+            _ => return None,
+        }
+
+        Some(self.call_with_ref::<lsp::request::CodeLensResolve>(code_lens))
+    }
+
     pub fn text_document_hover(
         &self,
         text_document: lsp::TextDocumentIdentifier,
@@ -1239,6 +1326,44 @@ pub fn text_document_range_formatting(
         Some(self.call::<lsp::request::RangeFormatting>(params))
     }
 
+    /// Requests the edits formatting the document after `ch` was typed before `position`.
+    ///
+    /// Returns `None` if the server doesn't format on `ch`.
+    pub fn text_document_on_type_formatting(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+        ch: char,
+        options: lsp::FormattingOptions,
+    ) -> Option<impl Future<Output = Result<Option<Vec<lsp::TextEdit>>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+        let provider = capabilities.document_on_type_formatting_provider.as_ref()?;
+
+        // Return early if `ch` isn't one of the trigger characters.
+        let ch = ch.to_string();
+        if provider.first_trigger_character != ch
+            && !provider
+                .more_trigger_character
+                .as_ref()
+                .is_some_and(|triggers| triggers.contains(&ch))
+        {
+            return None;
+        }
+
+        let options = self.get_merged_formatting_options(options);
+
+        let params = lsp::DocumentOnTypeFormattingParams {
+            text_document_position: lsp::TextDocumentPositionParams {
+                text_document,
+                position,
+            },
+            ch,
+            options,
+        };
+
+        Some(self.call::<lsp::request::OnTypeFormatting>(params))
+    }
+
     pub fn text_document_diagnostic(
         &self,
         text_document: lsp::TextDocumentIdentifier,
@@ -1293,6 +1418,140 @@ pub fn text_document_document_highlight(
         Some(self.call::<lsp::request::DocumentHighlightRequest>(params))
     }
 
+    pub fn prepare_call_hierarchy(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+        work_done_token: Option<lsp::ProgressToken>,
+    ) -> Option<impl Future<Output = Result<Option<Vec<lsp::CallHierarchyItem>>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support call hierarchy.
+        match capabilities.call_hierarchy_provider {
+            Some(
+                lsp::CallHierarchyServerCapability::Simple(true)
+                | lsp::CallHierarchyServerCapability::Options(_),
+            ) => (),
+            _ => return None,
+        }
+
+        let params = lsp::CallHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document,
+                position,
+            },
+            work_done_progress_params: lsp::WorkDoneProgressParams { work_done_token },
+        };
+
+        Some(self.call::<lsp::request::CallHierarchyPrepare>(params))
+    }
+
+    pub fn call_hierarchy_incoming_calls(
+        &self,
+        item: lsp::CallHierarchyItem,
+    ) -> impl Future<Output = Result<Option<Vec<lsp::CallHierarchyIncomingCall>>>> {
+        let params = lsp::CallHierarchyIncomingCallsParams {
+            item,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        self.call::<lsp::request::CallHierarchyIncomingCalls>(params)
+    }
+
+    pub fn call_hierarchy_outgoing_calls(
+        &self,
+        item: lsp::CallHierarchyItem,
+    ) -> impl Future<Output = Result<Option<Vec<lsp::CallHierarchyOutgoingCall>>>> {
+        let params = lsp::CallHierarchyOutgoingCallsParams {
+            item,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        self.call::<lsp::request::CallHierarchyOutgoingCalls>(params)
+    }
+
+    pub fn text_document_linked_editing_range(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+        work_done_token: Option<lsp::ProgressToken>,
+    ) -> Option<impl Future<Output = Result<Option<lsp::LinkedEditingRanges>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support linked editing ranges.
+        match capabilities.linked_editing_range_provider {
+            Some(
+                lsp::LinkedEditingRangeServerCapabilities::Simple(true)
+                | lsp::LinkedEditingRangeServerCapabilities::Options(_)
+                | lsp::LinkedEditingRangeServerCapabilities::RegistrationOptions(_),
+            ) => (),
+            _ => return None,
+        }
+
+        let params = lsp::LinkedEditingRangeParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document,
+                position,
+            },
+            work_done_progress_params: lsp::WorkDoneProgressParams { work_done_token },
+        };
+
+        Some(self.call::<lsp::request::LinkedEditingRange>(params))
+    }
+
+    pub fn prepare_type_hierarchy(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        position: lsp::Position,
+        work_done_token: Option<lsp::ProgressToken>,
+    ) -> Option<impl Future<Output = Result<Option<Vec<lsp::TypeHierarchyItem>>>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support type hierarchy.
+        match capabilities.type_hierarchy_provider {
+            Some(OneOf::Left(true) | OneOf::Right(_)) => (),
+            _ => return None,
+        }
+
+        let params = lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document,
+                position,
+            },
+            work_done_progress_params: lsp::WorkDoneProgressParams { work_done_token },
+        };
+
+        Some(self.call::<lsp::request::TypeHierarchyPrepare>(params))
+    }
+
+    pub fn type_hierarchy_supertypes(
+        &self,
+        item: lsp::TypeHierarchyItem,
+    ) -> impl Future<Output = Result<Option<Vec<lsp::TypeHierarchyItem>>>> {
+        let params = lsp::TypeHierarchySupertypesParams {
+            item,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        self.call::<lsp::request::TypeHierarchySupertypes>(params)
+    }
+
+    pub fn type_hierarchy_subtypes(
+        &self,
+        item: lsp::TypeHierarchyItem,
+    ) -> impl Future<Output = Result<Option<Vec<lsp::TypeHierarchyItem>>>> {
+        let params = lsp::TypeHierarchySubtypesParams {
+            item,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        self.call::<lsp::request::TypeHierarchySubtypes>(params)
+    }
+
     fn goto_request<
         T: lsp::request::Request<
             Params = lsp::GotoDefinitionParams,
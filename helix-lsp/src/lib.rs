@@ -254,17 +254,43 @@ pub fn range_to_lsp_range(
         range: Range,
         offset_encoding: OffsetEncoding,
     ) -> lsp::Range {
-        let start = pos_to_lsp_pos(doc, range.from(), offset_encoding);
-        let end = pos_to_lsp_pos(doc, range.to(), offset_encoding);
+        char_range_to_lsp_range(range.from()..range.to(), doc, offset_encoding)
+    }
+
+    /// Converts a range of char indices in the document to [`lsp::Range`], the inverse of
+    /// [`lsp_range_to_char_range`].
+    ///
+    /// Panics when [`pos_to_lsp_pos`] would for the start or end of `range`.
+    pub fn char_range_to_lsp_range(
+        range: std::ops::Range<usize>,
+        doc: &Rope,
+        offset_encoding: OffsetEncoding,
+    ) -> lsp::Range {
+        let start = pos_to_lsp_pos(doc, range.start, offset_encoding);
+        let end = pos_to_lsp_pos(doc, range.end, offset_encoding);
 
         lsp::Range::new(start, end)
     }
 
+    /// Converts [`lsp::Range`] to a range in the document, see [`lsp_range_to_char_range`].
     pub fn lsp_range_to_range(
         doc: &Rope,
-        mut range: lsp::Range,
+        range: lsp::Range,
         offset_encoding: OffsetEncoding,
     ) -> Option<Range> {
+        let range = lsp_range_to_char_range(range, doc, offset_encoding)?;
+        Some(Range::new(range.start, range.end))
+    }
+
+    /// Converts [`lsp::Range`] to a range of char indices in the document.
+    ///
+    /// Returns `None` if [`lsp_pos_to_pos`] does for the start or end of `range`. A range whose
+    /// end is before its start is treated as an empty range at its end.
+    pub fn lsp_range_to_char_range(
+        mut range: lsp::Range,
+        doc: &Rope,
+        offset_encoding: OffsetEncoding,
+    ) -> Option<std::ops::Range<usize>> {
         // This is sort of an edgecase. It's not clear from the spec how to deal with
         // ranges where end < start. They don't make much sense but vscode simply caps start to end
         // and because it's not specified quite a few LS rely on this as a result (for example the TS server)
@@ -279,7 +305,7 @@ pub fn lsp_range_to_range(
         let start = lsp_pos_to_pos(doc, range.start, offset_encoding)?;
         let end = lsp_pos_to_pos(doc, range.end, offset_encoding)?;
 
-        Some(Range::new(start, end))
+        Some(start..end)
     }
 
     /// If the LS did not provide a range for the completion or the range of the
@@ -406,32 +432,54 @@ pub fn generate_transaction_from_snippet(
         (transaction, snippet)
     }
 
+    /// An error converting [lsp::TextEdit]s into a [Transaction], see
+    /// [generate_transaction_from_edits].
+    #[derive(Error, Debug, Clone, PartialEq, Eq)]
+    pub enum TextEditError {
+        #[error("text edits overlap at line {}, character {}", .0.line + 1, .0.character + 1)]
+        Overlapping(lsp::Position),
+    }
+
+    /// Creates a [Transaction] applying the [lsp::TextEdit]s of a formatting response or workspace
+    /// edit to `doc`. This is the single conversion of text edits for every request returning
+    /// them.
+    ///
+    /// The edits may be given in any order. Insertions at the same position are applied in the
+    /// order they are given, ahead of an edit replacing text from there. Edits with positions
+    /// outside of the document are discarded, and overlapping edits are an error.
     pub fn generate_transaction_from_edits(
         doc: &Rope,
         mut edits: Vec<lsp::TextEdit>,
         offset_encoding: OffsetEncoding,
-    ) -> Transaction {
+    ) -> Result<Transaction, TextEditError> {
         // Sort edits by start range, since some LSPs (Omnisharp) send them
-        // in reverse order.
-        edits.sort_by_key(|edit| edit.range.start);
+        // in reverse order. The sort is stable, keeping insertions at the same
+        // position in order.
+        edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+        if let Some(edits) = edits
+            .windows(2)
+            .find(|edits| edits[1].range.start < edits[0].range.end)
+        {
+            return Err(TextEditError::Overlapping(edits[1].range.start));
+        }
 
         // Generate a diff if the edit is a full document replacement.
         #[allow(clippy::collapsible_if)]
         if edits.len() == 1 {
-            let is_document_replacement = edits.first().and_then(|edit| {
-                let start = lsp_pos_to_pos(doc, edit.range.start, offset_encoding)?;
-                let end = lsp_pos_to_pos(doc, edit.range.end, offset_encoding)?;
-                Some(start..end)
-            }) == Some(0..doc.len_chars());
+            let is_document_replacement = edits
+                .first()
+                .and_then(|edit| lsp_range_to_char_range(edit.range, doc, offset_encoding))
+                == Some(0..doc.len_chars());
             if is_document_replacement {
                 let new_text = Rope::from(edits.pop().unwrap().new_text);
-                return helix_core::diff::compare_ropes(doc, &new_text);
+                return Ok(helix_core::diff::compare_ropes(doc, &new_text));
             }
         }
 
-        Transaction::change(
-            doc,
-            edits.into_iter().map(|edit| {
+        let mut last_end = 0;
+        let changes: Vec<_> = edits
+            .into_iter()
+            .filter_map(|edit| {
                 // simplify "" into None for cleaner changesets
                 let replacement = if !edit.new_text.is_empty() {
                     Some(edit.new_text.into())
@@ -439,30 +487,31 @@ pub fn generate_transaction_from_edits(
                     None
                 };
 
-                let start =
-                    if let Some(start) = lsp_pos_to_pos(doc, edit.range.start, offset_encoding) {
-                        start
-                    } else {
-                        return (0, 0, None);
-                    };
-                let end = if let Some(end) = lsp_pos_to_pos(doc, edit.range.end, offset_encoding) {
-                    end
-                } else {
-                    return (0, 0, None);
-                };
-
+                let start = lsp_pos_to_pos(doc, edit.range.start, offset_encoding)?;
+                let end = lsp_pos_to_pos(doc, edit.range.end, offset_encoding)?;
                 if start > end {
                     log::error!(
                         "Invalid LSP text edit start {:?} > end {:?}, discarding",
                         start,
                         end
                     );
-                    return (0, 0, None);
+                    return None;
+                }
+                // Positions past the end of a line are clamped to it, which can make edits
+                // overlap that didn't in the server's positions.
+                if start < last_end {
+                    log::error!(
+                        "LSP text edit {:?} overlaps the previous edit once clamped, discarding",
+                        edit.range
+                    );
+                    return None;
                 }
+                last_end = end;
 
-                (start, end, replacement)
-            }),
-        )
+                Some((start, end, replacement))
+            })
+            .collect();
+        Ok(Transaction::change(doc, changes.into_iter()))
     }
 }
 
@@ -920,6 +969,7 @@ fn start_client(
         &ls_config.environment,
         root_path,
         root_uri,
+        workspace,
         id,
         name,
         ls_config.timeout,
@@ -1043,6 +1093,76 @@ macro_rules! test_case {
         test_case!("", (u32::MAX, u32::MAX) => Some(0));
     }
 
+    #[test]
+    fn converts_lsp_range_to_char_range() {
+        use lsp::{Position, Range};
+
+        // Emoji take 4 bytes in UTF-8, 2 code units in UTF-16 and a single char.
+        let doc = Rope::from("a😀b\n😀😀c");
+        let range = |start: (u32, u32), end: (u32, u32)| {
+            Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+        };
+        for (char_range, utf8, utf16, utf32) in [
+            (
+                1..2,
+                range((0, 1), (0, 5)),
+                range((0, 1), (0, 3)),
+                range((0, 1), (0, 2)),
+            ),
+            (
+                2..5,
+                range((0, 5), (1, 4)),
+                range((0, 3), (1, 2)),
+                range((0, 2), (1, 1)),
+            ),
+            (
+                4..6,
+                range((1, 0), (1, 8)),
+                range((1, 0), (1, 4)),
+                range((1, 0), (1, 2)),
+            ),
+            (
+                0..7,
+                range((0, 0), (1, 9)),
+                range((0, 0), (1, 5)),
+                range((0, 0), (1, 3)),
+            ),
+        ] {
+            for (encoding, lsp_range) in [
+                (OffsetEncoding::Utf8, utf8),
+                (OffsetEncoding::Utf16, utf16),
+                (OffsetEncoding::Utf32, utf32),
+            ] {
+                assert_eq!(
+                    char_range_to_lsp_range(char_range.clone(), &doc, encoding),
+                    lsp_range,
+                    "{encoding:?}"
+                );
+                assert_eq!(
+                    lsp_range_to_char_range(lsp_range, &doc, encoding),
+                    Some(char_range.clone()),
+                    "{encoding:?}"
+                );
+            }
+        }
+
+        // Offsets within an emoji resolve to the emoji.
+        assert_eq!(
+            lsp_range_to_char_range(range((0, 2), (0, 3)), &doc, OffsetEncoding::Utf16),
+            Some(1..2)
+        );
+        // Offsets past the end of a line are capped to the line end.
+        assert_eq!(
+            lsp_range_to_char_range(range((0, 1), (0, 99)), &doc, OffsetEncoding::Utf16),
+            Some(1..3)
+        );
+        // Inverted ranges become empty ranges at their end.
+        assert_eq!(
+            lsp_range_to_char_range(range((1, 4), (0, 3)), &doc, OffsetEncoding::Utf16),
+            Some(2..2)
+        );
+    }
+
     #[test]
     fn emoji_format_gh_4791() {
         use lsp::{Position, Range, TextEdit};
@@ -1078,8 +1198,98 @@ fn emoji_format_gh_4791() {
 
         let mut source = Rope::from_str("[\n\"🇺🇸\",\n\"🎄\",\n]");
 
-        let transaction = generate_transaction_from_edits(&source, edits, OffsetEncoding::Utf16);
+        let transaction =
+            generate_transaction_from_edits(&source, edits, OffsetEncoding::Utf16).unwrap();
         assert!(transaction.apply(&mut source));
         assert_eq!(source, "[\n  \"🇺🇸\",\n  \"🎄\",\n]");
     }
+
+    fn text_edit(start: (u32, u32), end: (u32, u32), new_text: &str) -> lsp::TextEdit {
+        lsp::TextEdit::new(
+            lsp::Range::new(
+                lsp::Position::new(start.0, start.1),
+                lsp::Position::new(end.0, end.1),
+            ),
+            new_text.to_string(),
+        )
+    }
+
+    fn apply_edits(text: &str, edits: Vec<lsp::TextEdit>) -> Result<String, TextEditError> {
+        let mut doc = Rope::from_str(text);
+        let transaction = generate_transaction_from_edits(&doc, edits, OffsetEncoding::Utf16)?;
+        assert!(transaction.apply(&mut doc));
+        Ok(doc.to_string())
+    }
+
+    #[test]
+    fn apply_adjacent_edits() {
+        // Given in reverse order, as some servers do.
+        let edits = vec![
+            text_edit((0, 3), (0, 6), "BAR"),
+            text_edit((0, 0), (0, 3), "FOO"),
+        ];
+        assert_eq!(apply_edits("foobar\n", edits).unwrap(), "FOOBAR\n");
+
+        // Edits meeting at a line break.
+        let edits = vec![
+            text_edit((0, 1), (1, 0), " "),
+            text_edit((1, 0), (1, 1), "B"),
+        ];
+        assert_eq!(apply_edits("a\nb\n", edits).unwrap(), "a B\n");
+    }
+
+    #[test]
+    fn apply_zero_length_insertions() {
+        let edits = vec![
+            text_edit((0, 1), (0, 1), "1"),
+            text_edit((0, 1), (0, 3), "X"),
+            text_edit((0, 1), (0, 1), "2"),
+            text_edit((0, 0), (0, 0), "<"),
+            text_edit((0, 4), (0, 4), ">"),
+        ];
+        // Insertions at the same position keep their order, ahead of the replacement.
+        assert_eq!(apply_edits("abcd\n", edits).unwrap(), "<a12Xd>\n");
+        assert_eq!(
+            apply_edits("", vec![text_edit((0, 0), (0, 0), "new\n")]).unwrap(),
+            "new\n"
+        );
+    }
+
+    #[test]
+    fn apply_edits_deleting_lines() {
+        let text = "one\ntwo\nthree\nfour\n";
+        let edits = vec![text_edit((1, 0), (2, 0), ""), text_edit((3, 0), (4, 0), "")];
+        assert_eq!(apply_edits(text, edits).unwrap(), "one\nthree\n");
+        // Deleting every line replaces the whole document.
+        assert_eq!(
+            apply_edits(text, vec![text_edit((0, 0), (4, 0), "")]).unwrap(),
+            ""
+        );
+        // An end past the last line is clamped to the end of the document.
+        assert_eq!(
+            apply_edits(text, vec![text_edit((3, 0), (10, 0), "")]).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+    }
+
+    #[test]
+    fn reject_overlapping_edits() {
+        let edits = vec![
+            text_edit((0, 0), (0, 4), "a"),
+            text_edit((0, 2), (0, 6), "b"),
+        ];
+        assert_eq!(
+            apply_edits("foobar\n", edits),
+            Err(TextEditError::Overlapping(lsp::Position::new(0, 2)))
+        );
+
+        let edits = vec![
+            text_edit((2, 0), (2, 1), "c"),
+            text_edit((0, 0), (3, 0), ""),
+        ];
+        assert_eq!(
+            apply_edits("a\nb\nc\n", edits),
+            Err(TextEditError::Overlapping(lsp::Position::new(2, 0)))
+        );
+    }
 }
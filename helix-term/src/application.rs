@@ -1,6 +1,7 @@
 use arc_swap::{access::Map, ArcSwap};
 use futures_util::Stream;
 use helix_core::{diagnostic::Severity, pos_at_coords, syntax, Range, Selection};
+use helix_loader::session::Session;
 use helix_lsp::{
     lsp::{self, notification::Notification},
     util::lsp_range_to_range,
@@ -21,10 +22,11 @@
 
 use crate::{
     args::Args,
+    commands,
     compositor::{Compositor, Event},
     config::Config,
     handlers,
-    job::Jobs,
+    job::{self, Jobs},
     keymap::Keymaps,
     ui::{self, overlay::overlaid},
 };
@@ -123,6 +125,13 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
         #[cfg(feature = "integration")]
         let backend = TestBackend::new(120, 150);
 
+        helix_loader::projects::set_file_order(config.editor.projects_file_order);
+        helix_loader::projects::set_profile(config.editor.project_profile.clone());
+        helix_loader::projects::set_system_file(config.editor.system_projects_file.clone());
+        helix_loader::projects::set_system_hooks_trusted(
+            config.editor.project_hooks.trust_system_projects,
+        );
+
         let theme_mode = backend.get_theme_mode();
         let terminal = Terminal::new(backend)?;
         let area = terminal.size();
@@ -163,7 +172,7 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
 
             // If the first file is a directory, skip it and open a picker
             if let Some((first, _)) = files_it.next_if(|(p, _)| p.is_dir()) {
-                let picker = ui::file_picker(&editor, first);
+                let picker = ui::file_picker(&mut editor, first);
                 compositor.push(Box::new(overlaid(picker)));
             }
 
@@ -238,11 +247,25 @@ pub fn new(args: Args, config: Config, lang_loader: syntax::Loader) -> Result<Se
             }
         } else if stdin().is_terminal() || cfg!(feature = "integration") {
             editor.new_file(Action::VerticalSplit);
+            if let Some(session) = editor.project_session() {
+                if editor.config().restore_session {
+                    editor.restore_project_session(session);
+                } else {
+                    compositor.push(Box::new(restore_session_prompt(session)));
+                }
+            } else if editor.config().project_startup_picker {
+                // Closing the picker leaves the scratch buffer.
+                if let Some(picker) = commands::startup_project_picker(&editor) {
+                    compositor.push(Box::new(overlaid(picker)));
+                }
+            }
         } else {
             editor
                 .new_file_from_stdin(Action::VerticalSplit)
                 .unwrap_or_else(|_| editor.new_file(Action::VerticalSplit));
         }
+        crate::handlers::projects::archive_stale_projects(&editor);
+        Self::validate_language_config(&editor);
 
         #[cfg(windows)]
         let signals = futures_util::stream::empty();
@@ -284,6 +307,10 @@ async fn render(&mut self) {
 
         helix_event::start_frame();
         cx.editor.needs_redraw = false;
+        cx.editor.sync_projects();
+        // Zen mode shows the UI while a picker is open.
+        cx.editor
+            .update_zen_mode(self.compositor.has_id(ui::picker::ID));
 
         let area = self
             .terminal
@@ -382,7 +409,20 @@ pub fn handle_config_events(&mut self, config_event: ConfigEvent) {
         let old_editor_config = self.editor.config();
 
         match config_event {
-            ConfigEvent::Refresh => self.refresh_config(),
+            ConfigEvent::Refresh => match self.refresh_config() {
+                Ok(_) => {
+                    self.editor.set_status("Config refreshed");
+                    Self::validate_language_config(&self.editor);
+                }
+                Err(err) => self.editor.set_error(err.to_string()),
+            },
+            ConfigEvent::WorkspaceChanged => {
+                // The last picker may be rooted in the previous workspace.
+                self.compositor.last_picker = None;
+                if let Err(err) = self.refresh_config() {
+                    self.editor.set_error(err.to_string());
+                }
+            }
 
             // Since only the Application can make changes to Editor's config,
             // the Editor must send up a new copy of a modified config so that
@@ -409,54 +449,77 @@ pub fn handle_config_events(&mut self, config_event: ConfigEvent) {
         }
     }
 
-    fn refresh_config(&mut self) {
-        let mut refresh_config = || -> Result<(), Error> {
-            let default_config = Config::load_default()
-                .map_err(|err| anyhow::anyhow!("Failed to load config: {}", err))?;
-
-            // Update the syntax language loader before setting the theme. Setting the theme will
-            // call `Loader::set_scopes` which must be done before the documents are re-parsed for
-            // the sake of locals highlighting.
-            let lang_loader = helix_core::config::user_lang_loader()?;
-            self.editor.syn_loader.store(Arc::new(lang_loader));
-            Self::load_configured_theme(
-                &mut self.editor,
-                &default_config,
-                self.terminal.backend().supports_true_color(),
-                self.theme_mode,
-            );
+    fn refresh_config(&mut self) -> Result<(), Error> {
+        let default_config = Config::load_default()
+            .map_err(|err| anyhow::anyhow!("Failed to load config: {}", err))?;
 
-            // Re-parse any open documents with the new language config.
-            let lang_loader = self.editor.syn_loader.load();
-            for document in self.editor.documents.values_mut() {
-                // Re-detect .editorconfig
-                document.detect_editor_config();
-                document.detect_language(&lang_loader);
-                let diagnostics = Editor::doc_diagnostics(
-                    &self.editor.language_servers,
-                    &self.editor.diagnostics,
-                    document,
-                );
-                document.replace_diagnostics(diagnostics, &[], None);
-            }
+        // Update the syntax language loader before setting the theme. Setting the theme will
+        // call `Loader::set_scopes` which must be done before the documents are re-parsed for
+        // the sake of locals highlighting.
+        let lang_loader = helix_core::config::user_lang_loader()?;
+        self.editor.syn_loader.store(Arc::new(lang_loader));
+        Self::load_configured_theme(
+            &mut self.editor,
+            &default_config,
+            self.terminal.backend().supports_true_color(),
+            self.theme_mode,
+        );
 
-            self.terminal.reconfigure((&default_config.editor).into())?;
-            // Store new config
-            self.config.store(Arc::new(default_config));
-            Ok(())
-        };
+        // Re-parse any open documents with the new language config.
+        let lang_loader = self.editor.syn_loader.load();
+        for document in self.editor.documents.values_mut() {
+            // Re-detect .editorconfig
+            document.detect_editor_config();
+            document.detect_language(&lang_loader);
+            let diagnostics = Editor::doc_diagnostics(
+                &self.editor.language_servers,
+                &self.editor.diagnostics,
+                document,
+            );
+            document.replace_diagnostics(diagnostics, &[], None);
+        }
 
-        match refresh_config() {
-            Ok(_) => {
-                self.editor.set_status("Config refreshed");
+        self.terminal.reconfigure((&default_config.editor).into())?;
+        helix_loader::projects::set_file_order(default_config.editor.projects_file_order);
+        helix_loader::projects::set_profile(default_config.editor.project_profile.clone());
+        helix_loader::projects::set_system_file(default_config.editor.system_projects_file.clone());
+        helix_loader::projects::set_system_hooks_trusted(
+            default_config.editor.project_hooks.trust_system_projects,
+        );
+        self.editor.refresh_projects();
+        // Store new config
+        self.config.store(Arc::new(default_config));
+        Ok(())
+    }
+
+    /// Checks the language configuration for mistakes in the background, see
+    /// [syntax::config::LanguageConfiguration::validate]. The mistakes are logged and summarized
+    /// in a warning.
+    fn validate_language_config(editor: &Editor) {
+        let loader = editor.syn_loader.load_full();
+        tokio::task::spawn_blocking(move || {
+            let mut count = 0;
+            for config in loader.language_configs() {
+                for err in config.validate(&loader) {
+                    warn!(
+                        "Invalid configuration for language '{}': {err}",
+                        config.language_id
+                    );
+                    count += 1;
+                }
             }
-            Err(err) => {
-                self.editor.set_error(err.to_string());
+            if count == 0 {
+                return;
             }
-        }
+            let message = format!(
+                "Found {count} mistake{} in the language configuration, see the log (:log-open)",
+                if count == 1 { "" } else { "s" }
+            );
+            job::dispatch_blocking(move |editor, _| editor.set_warning(message));
+        });
     }
 
-    /// Load the theme set in configuration
+    /// Load the theme set in configuration, or the theme of the current project if it has one
     fn load_configured_theme(
         editor: &mut Editor,
         config: &Config,
@@ -464,10 +527,9 @@ fn load_configured_theme(
         mode: Option<theme::Mode>,
     ) {
         let true_color = terminal_true_color || config.editor.true_color || crate::true_color();
-        let theme = config
-            .theme
-            .as_ref()
-            .and_then(|theme_config| {
+        let theme = Self::load_project_theme(editor, true_color)
+            .or_else(|| {
+                let theme_config = config.theme.as_ref()?;
                 let theme = theme_config.choose(mode);
                 editor
                     .theme_loader
@@ -493,6 +555,38 @@ fn load_configured_theme(
         editor.set_theme(theme);
     }
 
+    /// The `theme` of the registered project containing the working directory. A theme that
+    /// can't be used is reported and left out in favor of the configured one.
+    fn load_project_theme(editor: &mut Editor, true_color: bool) -> Option<theme::Theme> {
+        let cwd = helix_stdx::env::current_working_dir();
+        let name = editor
+            .projects
+            .read(|store| {
+                helix_loader::projects::find_project_for_path(store.projects(), &cwd)?
+                    .theme
+                    .clone()
+            })
+            .unwrap_or_else(|err| {
+                log::warn!("Failed to load projects: {err}");
+                None
+            })?;
+        match editor.theme_loader.load(&name) {
+            Ok(theme) if true_color || theme.is_16_color() => Some(theme),
+            Ok(_) => {
+                editor.set_warning(format!(
+                    "Project theme `{name}` requires true color support, using the configured theme"
+                ));
+                None
+            }
+            Err(err) => {
+                editor.set_warning(format!(
+                    "Failed to load project theme `{name}`, using the configured theme: {err}"
+                ));
+                None
+            }
+        }
+    }
+
     #[cfg(windows)]
     // no signal handling available on windows
     pub async fn handle_signals(&mut self, _signal: ()) -> bool {
@@ -551,7 +645,10 @@ pub async fn handle_signals(&mut self, signal: i32) -> bool {
                 self.render().await;
             }
             signal::SIGUSR1 => {
-                self.refresh_config();
+                match self.refresh_config() {
+                    Ok(_) => self.editor.set_status("Config refreshed"),
+                    Err(err) => self.editor.set_error(err.to_string()),
+                }
                 self.render().await;
             }
             signal::SIGTERM | signal::SIGINT => {
@@ -637,6 +734,8 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 
         self.editor
             .set_doc_path(doc_save_event.doc_id, &doc_save_event.path);
+        crate::handlers::recent_files::record(&self.editor, &doc_save_event.path);
+        crate::handlers::code_lens::request_code_lenses(&mut self.editor, doc_save_event.doc_id);
         // TODO: fix being overwritten by lsp
         self.editor.set_status(format!(
             "'{}' written, {lines}L {size}",
@@ -1252,6 +1351,17 @@ fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::I
         DummyEventStream
     }
 
+    /// The text on the screen as of the last render, one line per row.
+    #[cfg(feature = "integration")]
+    pub fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        let rows = buffer.content.chunks(buffer.area.width as usize);
+        let lines: Vec<String> = rows
+            .map(|cells| cells.iter().map(|cell| cell.symbol.as_str()).collect())
+            .collect();
+        lines.join("\n")
+    }
+
     pub async fn run<S>(&mut self, input_stream: &mut S) -> Result<i32, Error>
     where
         S: Stream<Item = std::io::Result<TerminalEvent>> + Unpin,
@@ -1292,6 +1402,9 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
             errs.push(err);
         }
 
+        self.editor.projects.flush();
+        crate::handlers::recent_files::flush();
+
         if self.editor.close_language_servers(None).await.is_err() {
             log::error!("Timed out waiting for language servers to shutdown");
             errs.push(anyhow::format_err!(
@@ -1302,3 +1415,22 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
         errs
     }
 }
+
+/// Asks whether to restore the session of the current project, which replaces the scratch
+/// buffer opened on startup.
+fn restore_session_prompt(session: Session) -> ui::Prompt {
+    let mut session = Some(session);
+    ui::Prompt::new(
+        "Restore the previous session? (y/n):".into(),
+        None,
+        ui::completers::none,
+        move |cx: &mut crate::compositor::Context, input: &str, event: ui::PromptEvent| {
+            if event != ui::PromptEvent::Validate || !input.eq_ignore_ascii_case("y") {
+                return;
+            }
+            if let Some(session) = session.take() {
+                cx.editor.restore_project_session(session);
+            }
+        },
+    )
+}
@@ -4,6 +4,16 @@
 use indexmap::IndexMap;
 use std::path::{Path, PathBuf};
 
+/// The output format of `--list-projects`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectListFormat {
+    /// One project per line as `name\tpath\tlast_accessed`.
+    #[default]
+    Tsv,
+    /// A JSON array of the projects.
+    Json,
+}
+
 #[derive(Default)]
 pub struct Args {
     pub display_help: bool,
@@ -19,6 +29,18 @@ pub struct Args {
     pub config_file: Option<PathBuf>,
     pub files: IndexMap<PathBuf, Vec<Position>>,
     pub working_directory: Option<PathBuf>,
+    /// The root of the project given with `--project`. Also set as the working directory.
+    pub project: Option<PathBuf>,
+    /// Whether the project given with `--project` is an unregistered directory under one of the
+    /// roots of `$HELIX_PROJECT_PATHS`.
+    pub ambient_project: bool,
+    pub list_projects: bool,
+    pub project_list_format: ProjectListFormat,
+    /// The root given with `--scan-projects`.
+    pub scan_projects: Option<PathBuf>,
+    pub scan_options: helix_loader::projects::ScanOptions,
+    /// Only report what `--scan-projects` would register.
+    pub scan_dry_run: bool,
 }
 
 impl Args {
@@ -26,17 +48,14 @@ pub fn parse_args() -> Result<Args> {
         let mut args = Args::default();
         let mut argv = std::env::args().peekable();
         let mut line_number = 0;
-
+        let mut project = None;
+        let mut project_list_format = None;
+        let mut scan_options = None;
+        // Files are resolved once all flags are known since they are relative to the project
+        // root when `--project` is given.
+        let mut files = Vec::new();
         let mut insert_file_with_position = |file_with_position: &str| {
-            let (filename, position) = parse_file(file_with_position);
-
-            // Before setting the working directory, resolve all the paths in args.files
-            let filename = helix_stdx::path::canonicalize(filename);
-
-            args.files
-                .entry(filename)
-                .and_modify(|positions| positions.push(position))
-                .or_insert_with(|| vec![position]);
+            files.push(file_with_position.to_string());
         };
 
         argv.next(); // skip the program, we don't care about that
@@ -88,6 +107,46 @@ pub fn parse_args() -> Result<Args> {
                         anyhow::bail!("--working-dir must specify an initial working directory")
                     }
                 },
+                "--project" => match argv.next() {
+                    Some(query) => project = Some(query),
+                    None => anyhow::bail!("--project must specify a project name or path"),
+                },
+                "--list-projects" => args.list_projects = true,
+                "--format" => match argv.next().as_deref() {
+                    Some("tsv") => project_list_format = Some(ProjectListFormat::Tsv),
+                    Some("json") => project_list_format = Some(ProjectListFormat::Json),
+                    _ => anyhow::bail!("--format must be followed by either 'tsv' or 'json'"),
+                },
+                "--scan-projects" => match argv.next() {
+                    Some(root) => args.scan_projects = Some(root.into()),
+                    None => anyhow::bail!("--scan-projects must specify a directory to scan"),
+                },
+                "--max-depth" => {
+                    let depth = argv.next().and_then(|depth| depth.parse().ok());
+                    let Some(depth) = depth else {
+                        anyhow::bail!("--max-depth must be followed by a number");
+                    };
+                    scan_options
+                        .get_or_insert_with(helix_loader::projects::ScanOptions::default)
+                        .max_depth = Some(depth);
+                }
+                "--max-repos" => {
+                    let max = argv.next().and_then(|max| max.parse().ok());
+                    let Some(max) = max else {
+                        anyhow::bail!("--max-repos must be followed by a number");
+                    };
+                    scan_options
+                        .get_or_insert_with(helix_loader::projects::ScanOptions::default)
+                        .max_repos = Some(max);
+                }
+                "--exclude" => match argv.next() {
+                    Some(name) => scan_options
+                        .get_or_insert_with(helix_loader::projects::ScanOptions::default)
+                        .exclude
+                        .push(name),
+                    None => anyhow::bail!("--exclude must specify a directory name"),
+                },
+                "--dry-run" => args.scan_dry_run = true,
                 arg if arg.starts_with("--") => {
                     anyhow::bail!("unexpected double dash argument: {}", arg)
                 }
@@ -118,6 +177,53 @@ pub fn parse_args() -> Result<Args> {
             insert_file_with_position(&arg);
         }
 
+        if let Some(format) = project_list_format {
+            if !args.list_projects {
+                anyhow::bail!("--format can only be used with --list-projects");
+            }
+            args.project_list_format = format;
+        }
+
+        if let Some(options) = scan_options {
+            if args.scan_projects.is_none() {
+                anyhow::bail!(
+                    "--max-depth, --exclude and --max-repos can only be used with --scan-projects"
+                );
+            }
+            args.scan_options = options;
+        }
+
+        if args.scan_dry_run && args.scan_projects.is_none() {
+            anyhow::bail!("--dry-run can only be used with --scan-projects");
+        }
+
+        if let Some(query) = project {
+            if args.working_directory.is_some() {
+                anyhow::bail!("--project and --working-dir can't be used together");
+            }
+            let (root, ambient) = resolve_project(&query)?;
+            args.ambient_project = ambient;
+            args.working_directory = Some(root.clone());
+            args.project = Some(root);
+        }
+
+        for file_with_position in files {
+            let (filename, position) = parse_file(&file_with_position);
+
+            // Before setting the working directory, resolve all the paths in args.files
+            let filename = match &args.project {
+                Some(root) => {
+                    helix_stdx::path::normalize(root.join(helix_stdx::path::expand_tilde(filename)))
+                }
+                None => helix_stdx::path::canonicalize(filename),
+            };
+
+            args.files
+                .entry(filename)
+                .and_modify(|positions| positions.push(position))
+                .or_insert_with(|| vec![position]);
+        }
+
         if line_number != 0 {
             if let Some(first_position) = args
                 .files
@@ -132,6 +238,31 @@ pub fn parse_args() -> Result<Args> {
     }
 }
 
+/// Resolves the argument of `--project` to the root of a registered project, falling back to a
+/// directory under one of the roots of `$HELIX_PROJECT_PATHS` and then to an existing directory.
+///
+/// Also returns whether the project was found under `$HELIX_PROJECT_PATHS`.
+fn resolve_project(query: &str) -> Result<(PathBuf, bool)> {
+    use helix_loader::projects::{self, ProjectMatch};
+
+    let projects = projects::load_projects()?;
+    match projects::match_project_in(&projects, &projects::ambient_roots(), query) {
+        ProjectMatch::Found(project) => Ok((project.path.clone(), false)),
+        ProjectMatch::Ambient(path) => Ok((path, true)),
+        ProjectMatch::Ambiguous(candidates) => {
+            anyhow::bail!(projects::ambiguous_project_message(query, &candidates))
+        }
+        ProjectMatch::NotFound => {
+            let path = helix_stdx::path::expand_tilde(Path::new(query));
+            if path.is_dir() {
+                Ok((helix_stdx::path::canonicalize(path), false))
+            } else {
+                anyhow::bail!("no project matches '{query}'")
+            }
+        }
+    }
+}
+
 /// Parse arg into [`PathBuf`] and position.
 pub(crate) fn parse_file(s: &str) -> (PathBuf, Position) {
     let def = || (PathBuf::from(s), Position::default());
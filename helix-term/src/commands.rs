@@ -64,8 +64,9 @@
 
 use crate::{
     compositor::{self, Component, Compositor},
-    filter_picker_entry,
+    filter_picker_entry, is_project_ignored,
     job::Callback,
+    project_ignore_matcher,
     ui::{self, overlay::overlaid, Picker, PickerColumn, Popup, Prompt, PromptEvent},
 };
 
@@ -383,6 +384,7 @@ pub fn doc(&self) -> &str {
         search_selection_detect_word_boundaries, "Use current selection as the search pattern, automatically wrapping with `\\b` on word boundaries",
         make_search_word_bounded, "Modify current search to make it word bounded",
         global_search, "Global search in workspace folder",
+        project_global_search, "Global search in the project of the current file",
         extend_line, "Select current line, if already selected, extend to another line based on the anchor",
         extend_line_below, "Select current line, if already selected, extend to next line",
         extend_line_above, "Select current line, if already selected, extend to previous line",
@@ -407,7 +409,9 @@ pub fn doc(&self) -> &str {
         file_explorer_in_current_buffer_directory, "Open file explorer at current buffer's directory",
         file_explorer_in_current_directory, "Open file explorer at current working directory",
         code_action, "Perform code action",
+        code_lens, "Run a code lens of the current line (LSP)",
         buffer_picker, "Open buffer picker",
+        project_buffer_picker, "Open buffer picker for the project of the current buffer",
         jumplist_picker, "Open jumplist picker",
         symbol_picker, "Open symbol picker",
         syntax_symbol_picker, "Open symbol picker from syntax information",
@@ -433,6 +437,10 @@ pub fn doc(&self) -> &str {
         add_newline_below, "Add newline below",
         goto_type_definition, "Goto type definition",
         goto_implementation, "Goto implementation",
+        incoming_calls, "Open incoming calls picker (LSP)",
+        outgoing_calls, "Open outgoing calls picker (LSP)",
+        supertypes, "Open supertypes picker (LSP)",
+        subtypes, "Open subtypes picker (LSP)",
         goto_file_start, "Goto line number <n> else file start",
         goto_file_end, "Goto file end",
         extend_to_file_start, "Extend to line number<n> else file start",
@@ -2191,13 +2199,20 @@ fn search_impl(
         }
         if show_warnings {
             if wrap_around && mat.is_some() {
-                editor.set_status("Wrapped around document");
+                editor.set_warning(match direction {
+                    Direction::Forward => "Search hit bottom, continuing at top",
+                    Direction::Backward => "Search hit top, continuing at bottom",
+                });
             } else {
                 editor.set_error("No more matches");
             }
         }
     }
 
+    if editor.config().search.highlight_all_matches {
+        editor.search_highlight = Some(regex.clone());
+    }
+
     let (view, doc) = current!(editor);
     let text = doc.text().slice(..);
     let selection = doc.selection(view.id);
@@ -2452,37 +2467,113 @@ fn make_search_word_bounded(cx: &mut Context) {
 }
 
 fn global_search(cx: &mut Context) {
+    let reg = cx.register.unwrap_or('/');
+    let picker = global_search_picker(cx.editor, reg, "");
+    cx.push_layer(picker);
+}
+
+fn project_global_search(cx: &mut Context) {
+    let reg = cx.register.unwrap_or('/');
+    let root = focused_project_root(cx.editor);
+    let picker = global_search_picker_in(cx.editor, reg, "", Some(root));
+    cx.push_layer(picker);
+}
+
+/// The root of the registered project containing the focused document, falling back to the
+/// working directory.
+pub(crate) fn focused_project_root(editor: &Editor) -> PathBuf {
+    doc!(editor)
+        .path()
+        .and_then(|path| {
+            editor
+                .projects
+                .read(|store| {
+                    helix_loader::projects::find_project_for_path(store.projects(), path)
+                        .map(|project| project.path.clone())
+                })
+                .unwrap_or_else(|err| {
+                    log::warn!("Failed to load projects: {err}");
+                    None
+                })
+        })
+        .unwrap_or_else(helix_stdx::env::current_working_dir)
+}
+
+/// The picker of [global_search], searching for `query` right away if it isn't empty.
+pub(crate) fn global_search_picker(
+    editor: &mut Editor,
+    reg: char,
+    query: &str,
+) -> Box<dyn Component> {
+    global_search_picker_in(editor, reg, query, None)
+}
+
+/// Like [global_search_picker], searching within `root`, which is shown in the prompt, rather
+/// than the working directory.
+fn global_search_picker_in(
+    editor: &mut Editor,
+    reg: char,
+    query: &str,
+    root: Option<PathBuf>,
+) -> Box<dyn Component> {
     #[derive(Debug)]
     struct FileResult {
         path: PathBuf,
         /// 0 indexed lines
         line_num: usize,
+        /// The matched line without its line ending.
+        line: Box<str>,
+        /// The byte range of the first match within `line`.
+        matched: std::ops::Range<usize>,
     }
 
     impl FileResult {
-        fn new(path: &Path, line_num: usize) -> Self {
+        fn new(
+            path: &Path,
+            line_num: usize,
+            line: &str,
+            matcher: &grep_regex::RegexMatcher,
+        ) -> Self {
+            use grep_matcher::Matcher;
+
+            let line = line.trim_end_matches(['\r', '\n']);
+            let matched = matcher
+                .find(line.as_bytes())
+                .ok()
+                .flatten()
+                .map_or(0..0, |m| m.start()..m.end());
             Self {
                 path: path.to_path_buf(),
                 line_num,
+                line: line.into(),
+                matched,
             }
         }
     }
 
     struct GlobalSearchConfig {
+        /// The directory to search, the working directory if unset.
+        root: Option<PathBuf>,
         smart_case: bool,
         file_picker_config: helix_view::editor::FilePickerConfig,
         directory_style: Style,
         number_style: Style,
         colon_style: Style,
+        match_style: Style,
     }
 
-    let config = cx.editor.config();
+    let config = editor.config();
+    let prompt = root
+        .as_deref()
+        .map(|root| format!("{}: ", helix_stdx::path::fold_home_dir(root).display()));
     let config = GlobalSearchConfig {
+        root,
         smart_case: config.search.smart_case,
         file_picker_config: config.file_picker.clone(),
-        directory_style: cx.editor.theme.get("ui.text.directory"),
-        number_style: cx.editor.theme.get("constant.numeric.integer"),
-        colon_style: cx.editor.theme.get("punctuation"),
+        directory_style: editor.theme.get("ui.text.directory"),
+        number_style: editor.theme.get("constant.numeric.integer"),
+        colon_style: editor.theme.get("punctuation"),
+        match_style: editor.theme.get("special"),
     };
 
     let columns = [
@@ -2508,7 +2599,25 @@ struct GlobalSearchConfig {
                 Span::styled((item.line_num + 1).to_string(), config.number_style),
             ]))
         }),
-        PickerColumn::hidden("contents"),
+        // The regex typed into this column is searched for by `get_files`, so it isn't used to
+        // filter the results again.
+        PickerColumn::new(
+            "contents",
+            |item: &FileResult, config: &GlobalSearchConfig| {
+                let line = &item.line;
+                let (start, end) = (item.matched.start, item.matched.end);
+                let start_trimmed = line.len() - line.trim_start().len();
+                let before = line
+                    .get(start_trimmed.min(start)..start)
+                    .unwrap_or_default();
+                Cell::from(Spans::from(vec![
+                    Span::raw(before),
+                    Span::styled(line.get(start..end).unwrap_or_default(), config.match_style),
+                    Span::raw(line.get(end..).unwrap_or_default()),
+                ]))
+            },
+        )
+        .without_filtering(),
     ];
 
     let get_files = |query: &str,
@@ -2519,10 +2628,17 @@ struct GlobalSearchConfig {
             return async { Ok(()) }.boxed();
         }
 
-        let search_root = helix_stdx::env::current_working_dir();
+        let search_root = config
+            .root
+            .clone()
+            .unwrap_or_else(helix_stdx::env::current_working_dir);
         if !search_root.exists() {
-            return async { Err(anyhow::anyhow!("Current working directory does not exist")) }
-                .boxed();
+            let err = if config.root.is_some() {
+                anyhow::anyhow!("Project directory does not exist")
+            } else {
+                anyhow::anyhow!("Current working directory does not exist")
+            };
+            return async { Err(err) }.boxed();
         }
 
         let documents: Vec<_> = editor
@@ -2549,6 +2665,7 @@ struct GlobalSearchConfig {
         let absolute_root = search_root
             .canonicalize()
             .unwrap_or_else(|_| search_root.clone());
+        let project_ignore = project_ignore_matcher(editor, &absolute_root);
 
         let injector = injector.clone();
         async move {
@@ -2566,6 +2683,9 @@ struct GlobalSearchConfig {
                 .max_depth(config.file_picker_config.max_depth)
                 .filter_entry(move |entry| {
                     filter_picker_entry(entry, &absolute_root, dedup_symlinks)
+                        && !project_ignore
+                            .as_ref()
+                            .is_some_and(|matcher| is_project_ignored(matcher, entry))
                 })
                 .add_custom_ignore_filename(helix_loader::config_dir().join("ignore"))
                 .add_custom_ignore_filename(".helix/ignore")
@@ -2588,9 +2708,14 @@ struct GlobalSearchConfig {
                         };
 
                         let mut stop = false;
-                        let sink = sinks::UTF8(|line_num, _line_content| {
+                        let sink = sinks::UTF8(|line_num, line_content| {
                             stop = injector
-                                .push(FileResult::new(entry.path(), line_num as usize - 1))
+                                .push(FileResult::new(
+                                    entry.path(),
+                                    line_num as usize - 1,
+                                    line_content,
+                                    &matcher,
+                                ))
                                 .is_err();
 
                             Ok(!stop)
@@ -2636,8 +2761,7 @@ struct GlobalSearchConfig {
         .boxed()
     };
 
-    let reg = cx.register.unwrap_or('/');
-    cx.editor.registers.last_search_register = reg;
+    editor.registers.last_search_register = reg;
 
     let picker = Picker::new(
         columns,
@@ -2676,9 +2800,11 @@ struct GlobalSearchConfig {
         Some((path.as_path().into(), Some((*line_num, *line_num))))
     })
     .with_history_register(Some(reg))
-    .with_dynamic_query(get_files, Some(275));
+    .with_query(query, editor)
+    .with_dynamic_query(get_files, Some(275))
+    .with_prompt(prompt.unwrap_or_default());
 
-    cx.push_layer(Box::new(overlaid(picker)));
+    Box::new(overlaid(picker))
 }
 
 enum Extend {
@@ -3006,6 +3132,7 @@ fn ensure_selections_forward(cx: &mut Context) {
 
 fn enter_insert_mode(cx: &mut Context) {
     cx.editor.mode = Mode::Insert;
+    cx.editor.search_highlight = None;
 }
 
 // inserts at the start of each selection
@@ -3159,6 +3286,26 @@ fn file_explorer_in_current_directory(cx: &mut Context) {
 }
 
 fn buffer_picker(cx: &mut Context) {
+    buffer_picker_impl(cx, None);
+}
+
+/// Opens the buffer picker with the buffers in the innermost project of the current buffer.
+fn project_buffer_picker(cx: &mut Context) {
+    let Some(root) = cx
+        .editor
+        .document_project_root(doc!(cx.editor))
+        .map(Path::to_path_buf)
+    else {
+        cx.editor
+            .set_error("The current buffer is not in a registered project");
+        return;
+    };
+    buffer_picker_impl(cx, Some(&root));
+}
+
+/// Opens the buffer picker, listing only the buffers whose innermost project is at
+/// `project_root` if given.
+fn buffer_picker_impl(cx: &mut Context, project_root: Option<&Path>) {
     let current = view!(cx.editor).doc;
 
     struct BufferMeta {
@@ -3181,6 +3328,9 @@ struct BufferMeta {
         .editor
         .documents
         .values()
+        .filter(|doc| {
+            project_root.is_none_or(|root| cx.editor.document_project_root(doc) == Some(root))
+        })
         .map(new_meta)
         .collect::<Vec<BufferMeta>>();
 
@@ -3199,15 +3349,11 @@ struct BufferMeta {
             }
             flags.into()
         }),
-        PickerColumn::new("path", |meta: &BufferMeta, _| {
-            let path = meta
-                .path
+        PickerColumn::new("path", |meta: &BufferMeta, aliases: &ui::PathAliases| {
+            meta.path
                 .as_deref()
-                .map(helix_stdx::path::get_relative_path);
-            path.as_deref()
-                .and_then(Path::to_str)
-                .unwrap_or(SCRATCH_BUFFER_NAME)
-                .to_string()
+                .map(|path| helix_core::path::display_path(path, aliases))
+                .unwrap_or_else(|| SCRATCH_BUFFER_NAME.to_string())
                 .into()
         }),
     ];
@@ -3225,7 +3371,8 @@ struct BufferMeta {
         0
     };
 
-    let picker = Picker::new(columns, 2, items, (), |cx, meta, action| {
+    let aliases = ui::path_aliases(&cx.editor.config());
+    let picker = Picker::new(columns, 2, items, aliases, |cx, meta, action| {
         cx.editor.switch(meta.id, action);
     })
     .with_initial_cursor(initial_cursor)
@@ -3431,52 +3578,12 @@ pub fn command_palette(cx: &mut Context) {
 
     cx.callback.push(Box::new(
         move |compositor: &mut Compositor, cx: &mut compositor::Context| {
-            let keymap = compositor.find::<ui::EditorView>().unwrap().keymaps.map()
-                [&cx.editor.mode]
-                .reverse_map();
-
-            let commands = MappableCommand::STATIC_COMMAND_LIST.iter().cloned().chain(
-                typed::TYPABLE_COMMAND_LIST
-                    .iter()
-                    .map(|cmd| MappableCommand::Typable {
-                        name: cmd.name.to_owned(),
-                        args: String::new(),
-                        doc: cmd.doc.to_owned(),
-                    }),
+            let palette = ui::CommandPalette::new(
+                &compositor.find::<ui::EditorView>().unwrap().keymaps.map(),
+                cx.editor.mode,
             );
 
-            let columns = [
-                ui::PickerColumn::new("name", |item, _| match item {
-                    MappableCommand::Typable { name, .. } => format!(":{name}").into(),
-                    MappableCommand::Static { name, .. } => (*name).into(),
-                    MappableCommand::Macro { .. } => {
-                        unreachable!("macros aren't included in the command palette")
-                    }
-                }),
-                ui::PickerColumn::new(
-                    "bindings",
-                    |item: &MappableCommand, keymap: &crate::keymap::ReverseKeymap| {
-                        keymap
-                            .get(item.name())
-                            .map(|bindings| {
-                                bindings.iter().fold(String::new(), |mut acc, bind| {
-                                    if !acc.is_empty() {
-                                        acc.push(' ');
-                                    }
-                                    for key in bind {
-                                        acc.push_str(&key.key_sequence_format());
-                                    }
-                                    acc
-                                })
-                            })
-                            .unwrap_or_default()
-                            .into()
-                    },
-                ),
-                ui::PickerColumn::new("doc", |item: &MappableCommand, _| item.doc().into()),
-            ];
-
-            let picker = Picker::new(columns, 0, commands, keymap, move |cx, command, _action| {
+            let picker = palette.into_picker(move |cx, command, _action| {
                 let mut ctx = Context {
                     register,
                     count,
@@ -3546,7 +3653,8 @@ fn insert_with_indent(cx: &mut Context, cursor_fallback: IndentFallbackPos) {
 
     let text = doc.text().slice(..);
     let contents = doc.text();
-    let selection = doc.selection(view.id);
+    // Insert on every line spanned by the selection.
+    let selection = doc.selection(view.id).split_at_newlines(text);
 
     let syntax = doc.syntax();
     let tab_width = doc.tab_width();
@@ -3554,7 +3662,7 @@ fn insert_with_indent(cx: &mut Context, cursor_fallback: IndentFallbackPos) {
     let mut ranges = SmallVec::with_capacity(selection.len());
     let mut offs = 0;
 
-    let mut transaction = Transaction::change_by_selection(contents, selection, |range| {
+    let mut transaction = Transaction::change_by_selection(contents, &selection, |range| {
         let cursor_line = range.cursor_line(text);
         let cursor_line_start = text.line_to_char(cursor_line);
 
@@ -4079,8 +4187,9 @@ fn goto_prev_change(cx: &mut Context) {
 }
 
 fn goto_next_change_impl(cx: &mut Context, direction: Direction) {
-    let count = cx.count() as u32 - 1;
+    let count = cx.count();
     let motion = move |editor: &mut Editor| {
+        let wrap_around = editor.config().search.wrap_around;
         let (view, doc) = current!(editor);
         let doc_text = doc.text().slice(..);
         let diff_handle = if let Some(diff_handle) = doc.diff_handle() {
@@ -4089,24 +4198,30 @@ fn goto_next_change_impl(cx: &mut Context, direction: Direction) {
             editor.set_status("Diff is not available in current buffer");
             return;
         };
+        let hunks: Vec<_> = {
+            let diff = diff_handle.load();
+            (0..diff.len()).map(|idx| diff.nth_hunk(idx)).collect()
+        };
 
         let selection = doc.selection(view.id).clone().transform(|range| {
-            let cursor_line = range.cursor_line(doc_text) as u32;
-
-            let diff = diff_handle.load();
-            let hunk_idx = match direction {
-                Direction::Forward => diff
-                    .next_hunk(cursor_line)
-                    .map(|idx| (idx + count).min(diff.len() - 1)),
-                Direction::Backward => diff
-                    .prev_hunk(cursor_line)
-                    .map(|idx| idx.saturating_sub(count)),
-            };
-            let Some(hunk_idx) = hunk_idx else {
+            let cursor = range.cursor(doc_text);
+            let mut pos = cursor;
+            for _ in 0..count {
+                pos = match direction {
+                    Direction::Forward => {
+                        movement::goto_next_change(doc_text, &hunks, pos, wrap_around)
+                    }
+                    Direction::Backward => {
+                        movement::goto_prev_change(doc_text, &hunks, pos, wrap_around)
+                    }
+                };
+            }
+            if pos == cursor {
                 return range;
-            };
-            let hunk = diff.nth_hunk(hunk_idx);
-            let new_range = hunk_range(hunk, doc_text);
+            }
+            let line = doc_text.char_to_line(pos) as u32;
+            let hunk_idx = hunks.partition_point(|hunk| hunk.after.start < line);
+            let new_range = hunk_range(hunks[hunk_idx].clone(), doc_text);
             if editor.mode == Mode::Select {
                 let head = if new_range.head < range.anchor {
                     new_range.anchor
@@ -5076,8 +5191,14 @@ fn format_selections(cx: &mut Context) {
     tokio::spawn(async move {
         match future.await {
             Ok(Some(res)) => {
-                let transaction =
-                    helix_lsp::util::generate_transaction_from_edits(&text, res, offset_encoding);
+                let transaction = match helix_lsp::util::generate_transaction_from_edits(
+                    &text,
+                    res,
+                    offset_encoding,
+                ) {
+                    Ok(transaction) => transaction,
+                    Err(err) => return log::error!("format sections failed: {err}"),
+                };
                 job::dispatch(move |editor, _compositor| {
                     let Some(doc) = editor.document_mut(doc_id) else {
                         return;
@@ -6083,13 +6204,30 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
                         'w' => textobject::textobject_word(text, range, objtype, count, false),
                         'W' => textobject::textobject_word(text, range, objtype, count, true),
                         't' => textobject_treesitter("class", range),
-                        'f' => textobject_treesitter("function", range),
+                        'f' => doc
+                            .syntax()
+                            .and_then(|syntax| {
+                                textobject::textobject_function(
+                                    text, range, syntax, &loader, objtype,
+                                )
+                            })
+                            .unwrap_or(range),
                         'a' => textobject_treesitter("parameter", range),
-                        'c' => textobject_treesitter("comment", range),
+                        'c' => {
+                            // The comment textobject queries group consecutive line comments,
+                            // other comments are selected by their syntax node or prefix.
+                            let comment = textobject_treesitter("comment", range);
+                            if comment != range {
+                                comment
+                            } else {
+                                textobject::textobject_comment(text, range, doc.syntax(), objtype)
+                            }
+                        }
                         'T' => textobject_treesitter("test", range),
                         'e' => textobject_treesitter("entry", range),
                         'x' => textobject_treesitter("xml-element", range),
                         'p' => textobject::textobject_paragraph(text, range, objtype, count),
+                        's' => textobject::textobject_sentence(text, range, objtype),
                         'm' => textobject::textobject_pair_surround_closest(
                             doc.syntax(),
                             text,
@@ -6125,10 +6263,11 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
         ("w", "Word"),
         ("W", "WORD"),
         ("p", "Paragraph"),
+        ("s", "Sentence"),
         ("t", "Type definition (tree-sitter)"),
         ("f", "Function (tree-sitter)"),
         ("a", "Argument/parameter (tree-sitter)"),
-        ("c", "Comment (tree-sitter)"),
+        ("c", "Comment"),
         ("T", "Test (tree-sitter)"),
         ("e", "Data structure entry (tree-sitter)"),
         ("m", "Closest surrounding pair (tree-sitter)"),
@@ -6306,6 +6445,8 @@ fn surround_delete(cx: &mut Context) {
 enum ShellBehavior {
     Replace,
     Ignore,
+    /// Like [ShellBehavior::Ignore], deleting the selections afterwards.
+    Delete,
     Insert,
     Append,
 }
@@ -6411,15 +6552,15 @@ async fn shell_impl_async(
     };
 
     let output = if !output.status.success() {
-        if output.stderr.is_empty() {
-            match output.status.code() {
-                Some(exit_code) => bail!("Shell command failed: status {}", exit_code),
-                None => bail!("Shell command failed"),
-            }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        match output.status.code() {
+            _ if !stderr.is_empty() => bail!("Shell command failed: {stderr}"),
+            Some(exit_code) => bail!("Shell command failed: status {}", exit_code),
+            None => bail!("Shell command failed"),
         }
-        String::from_utf8_lossy(&output.stderr)
-        // Prioritize `stderr` output over `stdout`
     } else if !output.stderr.is_empty() {
+        // Prioritize `stderr` output over `stdout`
         let stderr = String::from_utf8_lossy(&output.stderr);
         log::debug!("Command printed to stderr: {stderr}");
         stderr
@@ -6432,7 +6573,7 @@ async fn shell_impl_async(
 
 fn shell(cx: &mut compositor::Context, cmd: &str, behavior: &ShellBehavior) {
     let pipe = match behavior {
-        ShellBehavior::Replace | ShellBehavior::Ignore => true,
+        ShellBehavior::Replace | ShellBehavior::Ignore | ShellBehavior::Delete => true,
         ShellBehavior::Insert | ShellBehavior::Append => false,
     };
 
@@ -6473,10 +6614,16 @@ fn shell(cx: &mut compositor::Context, cmd: &str, behavior: &ShellBehavior) {
             }
         };
 
+        let output = match behavior {
+            ShellBehavior::Delete => Tendril::new(),
+            _ => output,
+        };
         let output_len = output.chars().count();
 
         let (from, to, deleted_len) = match behavior {
-            ShellBehavior::Replace => (range.from(), range.to(), range.len()),
+            ShellBehavior::Replace | ShellBehavior::Delete => {
+                (range.from(), range.to(), range.len())
+            }
             ShellBehavior::Insert => (range.from(), range.from(), 0),
             ShellBehavior::Append => (range.to(), range.to(), 0),
             _ => (range.from(), range.from(), 0),
@@ -5,7 +5,10 @@
         self, CodeAction, CodeActionOrCommand, CodeActionTriggerKind, DiagnosticSeverity,
         NumberOrString,
     },
-    util::{diagnostic_to_lsp_diagnostic, lsp_range_to_range, range_to_lsp_range},
+    util::{
+        diagnostic_to_lsp_diagnostic, lsp_range_to_char_range, lsp_range_to_range,
+        range_to_lsp_range,
+    },
     Client, LanguageServerId, OffsetEncoding,
 };
 use tokio_stream::StreamExt;
@@ -56,6 +59,10 @@ macro_rules! language_server_with_feature {
     }};
 }
 
+mod hierarchy;
+
+pub use hierarchy::{incoming_calls, outgoing_calls, subtypes, supertypes};
+
 /// A wrapper around `lsp::Location` that swaps out the LSP URI for `helix_core::Uri` and adds
 /// the server's  offset encoding.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -654,6 +661,39 @@ fn action_fixes_diagnostics(action: &CodeActionOrCommand) -> bool {
     )
 }
 
+/// Opens a picker over the code lenses of the cursor line and runs the chosen one.
+pub fn code_lens(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text().slice(..);
+    let line = text.char_to_line(doc.selection(view.id).primary().cursor(text));
+    let lenses: Vec<_> = doc
+        .code_lenses
+        .iter()
+        .filter(|lens| text.char_to_line(lens.char_idx) == line)
+        .cloned()
+        .collect();
+    if lenses.is_empty() {
+        cx.editor.set_error("No code lenses on the current line");
+        return;
+    }
+
+    let columns = [ui::PickerColumn::new(
+        "title",
+        |lens: &helix_view::document::CodeLens, _| match &lens.lens.command {
+            Some(command) => command.title.as_str().into(),
+            None => "".into(),
+        },
+    )];
+    let picker = Picker::new(columns, 0, lenses, (), |cx, lens, _action| {
+        crate::handlers::code_lens::execute_code_lens(
+            cx.editor,
+            lens.lens.clone(),
+            lens.language_server_id,
+        );
+    });
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
 pub fn code_action(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
 
@@ -1113,10 +1153,9 @@ fn get_prefill_from_lsp_response(
             Some(lsp::PrepareRenameResponse::Range(range)) => {
                 let text = doc!(editor).text();
 
-                Ok(lsp_range_to_range(text, range, offset_encoding)
-                    .ok_or("lsp sent invalid selection range for rename")?
-                    .fragment(text.slice(..))
-                    .into())
+                let range = lsp_range_to_char_range(range, text, offset_encoding)
+                    .ok_or("lsp sent invalid selection range for rename")?;
+                Ok(text.slice(range).into())
             }
             Some(lsp::PrepareRenameResponse::RangeWithPlaceholder { placeholder, .. }) => {
                 Ok(placeholder)
@@ -1329,9 +1368,9 @@ fn compute_inlay_hints_for_view(
     let first_char_in_range = doc_slice.line_to_char(first_line);
     let last_char_in_range = doc_slice.line_to_char(last_line);
 
-    let range = helix_lsp::util::range_to_lsp_range(
+    let range = helix_lsp::util::char_range_to_lsp_range(
+        first_char_in_range..last_char_in_range,
         doc_text,
-        helix_core::Range::new(first_char_in_range, last_char_in_range),
         language_server.offset_encoding(),
     );
 
@@ -0,0 +1,507 @@
+//! Pickers for LSP hierarchies, such as the callers or callees of a function or the
+//! supertypes or subtypes of a type.
+//!
+//! The hierarchy is shown as a tree where `Tab` expands or collapses the selected
+//! row. Children are requested from the language server the first time a row is
+//! expanded.
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use futures_util::future::BoxFuture;
+use helix_core::syntax::config::LanguageServerFeature;
+use helix_lsp::{lsp, Client, LanguageServerId, OffsetEncoding};
+use helix_view::Editor;
+
+use super::{jump_to_location, location_to_file_location, Location};
+use crate::{
+    commands::Context,
+    compositor::Compositor,
+    job::{self, Callback},
+    ui::{
+        self,
+        overlay::{overlaid, Overlay},
+        Picker,
+    },
+};
+
+/// An item of the hierarchy as returned by the language server.
+struct HierarchyItem<T> {
+    item: T,
+    name: String,
+    kind: lsp::SymbolKind,
+    /// Where selecting the item jumps to.
+    location: Location,
+}
+
+type FetchChildren<T> =
+    fn(&Client, T, OffsetEncoding) -> BoxFuture<'static, anyhow::Result<Vec<HierarchyItem<T>>>>;
+
+struct Node<T> {
+    item: HierarchyItem<T>,
+    depth: usize,
+    /// `None` until the children have been requested from the language server.
+    children: Option<Vec<usize>>,
+    expanded: bool,
+}
+
+struct Hierarchy<T> {
+    language_server_id: LanguageServerId,
+    offset_encoding: OffsetEncoding,
+    fetch_children: FetchChildren<T>,
+    nodes: Vec<Node<T>>,
+    roots: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowState {
+    Collapsed,
+    Expanded,
+    Leaf,
+}
+
+/// A visible row of the hierarchy picker.
+struct HierarchyRow {
+    node: usize,
+    depth: usize,
+    name: String,
+    kind: lsp::SymbolKind,
+    location: Location,
+    state: RowState,
+}
+
+impl<T> Hierarchy<T> {
+    fn push(&mut self, item: HierarchyItem<T>, depth: usize) -> usize {
+        self.nodes.push(Node {
+            item,
+            depth,
+            children: None,
+            expanded: false,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn set_children(&mut self, parent: usize, children: Vec<HierarchyItem<T>>) {
+        let depth = self.nodes[parent].depth + 1;
+        let children = children
+            .into_iter()
+            .map(|child| self.push(child, depth))
+            .collect();
+        let parent = &mut self.nodes[parent];
+        parent.children = Some(children);
+        parent.expanded = true;
+    }
+
+    /// The rows of all roots and the descendants of expanded nodes, in tree order.
+    fn rows(&self) -> Vec<HierarchyRow> {
+        let mut rows = Vec::new();
+        let mut stack: Vec<usize> = self.roots.iter().rev().copied().collect();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let state = match &node.children {
+                Some(children) if children.is_empty() => RowState::Leaf,
+                Some(children) if node.expanded => {
+                    stack.extend(children.iter().rev());
+                    RowState::Expanded
+                }
+                _ => RowState::Collapsed,
+            };
+            rows.push(HierarchyRow {
+                node: index,
+                depth: node.depth,
+                name: node.item.name.clone(),
+                kind: node.item.kind,
+                location: node.item.location.clone(),
+                state,
+            });
+        }
+        rows
+    }
+}
+
+struct HierarchyData<T> {
+    cwd: PathBuf,
+    hierarchy: Arc<Mutex<Hierarchy<T>>>,
+}
+
+type HierarchyPicker<T> = Picker<HierarchyRow, HierarchyData<T>>;
+
+/// Updates the rows of the picker showing `hierarchy`, if it is still open.
+fn refresh_picker<T: Send + 'static>(
+    compositor: &mut Compositor,
+    hierarchy: &Arc<Mutex<Hierarchy<T>>>,
+) {
+    let Some(Overlay {
+        content: picker, ..
+    }) = compositor.find::<Overlay<HierarchyPicker<T>>>()
+    else {
+        return;
+    };
+    if !Arc::ptr_eq(&picker.editor_data().hierarchy, hierarchy) {
+        return;
+    }
+    let rows = hierarchy.lock().unwrap().rows();
+    picker.set_options(rows);
+}
+
+/// Expands or collapses `node`, requesting its children if they are not known yet.
+fn toggle_node<T: Clone + Send + 'static>(
+    editor: &mut Editor,
+    hierarchy: Arc<Mutex<Hierarchy<T>>>,
+    node: usize,
+) {
+    let mut tree = hierarchy.lock().unwrap();
+    if tree.nodes[node].children.is_some() {
+        tree.nodes[node].expanded = !tree.nodes[node].expanded;
+        drop(tree);
+        job::dispatch_blocking(move |_editor, compositor| refresh_picker(compositor, &hierarchy));
+        return;
+    }
+
+    let Some(language_server) = editor.language_server_by_id(tree.language_server_id) else {
+        editor.set_error("Language server is no longer running");
+        return;
+    };
+    let future = (tree.fetch_children)(
+        language_server,
+        tree.nodes[node].item.item.clone(),
+        tree.offset_encoding,
+    );
+    drop(tree);
+
+    tokio::spawn(async move {
+        let response = future.await;
+        job::dispatch(move |editor, compositor| match response {
+            Ok(children) => {
+                hierarchy.lock().unwrap().set_children(node, children);
+                refresh_picker(compositor, &hierarchy);
+            }
+            Err(err) => editor.set_error(format!("Failed to expand hierarchy: {err}")),
+        })
+        .await
+    });
+}
+
+fn hierarchy_picker<T: Clone + Send + Sync + 'static>(
+    hierarchy: Arc<Mutex<Hierarchy<T>>>,
+) -> HierarchyPicker<T> {
+    let columns = [
+        ui::PickerColumn::new("name", |row: &HierarchyRow, _: &HierarchyData<T>| {
+            let marker = match row.state {
+                RowState::Collapsed => "▸ ",
+                RowState::Expanded => "▾ ",
+                RowState::Leaf => "  ",
+            };
+            format!("{}{marker}{}", "  ".repeat(row.depth), row.name).into()
+        }),
+        ui::PickerColumn::new("kind", |row: &HierarchyRow, _: &HierarchyData<T>| {
+            super::display_symbol_kind(row.kind).into()
+        }),
+        ui::PickerColumn::new("location", |row: &HierarchyRow, data: &HierarchyData<T>| {
+            let path = match row.location.uri.as_path() {
+                Some(path) => path
+                    .strip_prefix(&data.cwd)
+                    .unwrap_or(path)
+                    .to_string_lossy(),
+                None => row.location.uri.to_string().into(),
+            };
+            format!("{path}:{}", row.location.range.start.line + 1).into()
+        }),
+    ];
+
+    let rows = hierarchy.lock().unwrap().rows();
+    let data = HierarchyData {
+        cwd: helix_stdx::env::current_working_dir(),
+        hierarchy: hierarchy.clone(),
+    };
+    Picker::new(columns, 0, rows, data, |cx, row, action| {
+        jump_to_location(cx.editor, &row.location, action)
+    })
+    .with_preview(|_editor, row| location_to_file_location(&row.location))
+    .with_expand(move |cx, row| toggle_node(cx.editor, hierarchy.clone(), row.node))
+}
+
+fn location(uri: lsp::Url, range: lsp::Range, offset_encoding: OffsetEncoding) -> Option<Location> {
+    super::lsp_location_to_location(lsp::Location::new(uri, range), offset_encoding)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallDirection {
+    Incoming,
+    Outgoing,
+}
+
+fn call_hierarchy_item(
+    item: lsp::CallHierarchyItem,
+    offset_encoding: OffsetEncoding,
+) -> Option<HierarchyItem<lsp::CallHierarchyItem>> {
+    Some(HierarchyItem {
+        name: item.name.clone(),
+        kind: item.kind,
+        location: location(item.uri.clone(), item.selection_range, offset_encoding)?,
+        item,
+    })
+}
+
+fn incoming_call_children(
+    language_server: &Client,
+    item: lsp::CallHierarchyItem,
+    offset_encoding: OffsetEncoding,
+) -> BoxFuture<'static, anyhow::Result<Vec<HierarchyItem<lsp::CallHierarchyItem>>>> {
+    let future = language_server.call_hierarchy_incoming_calls(item);
+    Box::pin(async move {
+        let calls = future.await?.unwrap_or_default();
+        Ok(calls
+            .into_iter()
+            .filter_map(|call| {
+                // Jump to the call site within the caller rather than to its name.
+                let range = call
+                    .from_ranges
+                    .first()
+                    .copied()
+                    .unwrap_or(call.from.selection_range);
+                Some(HierarchyItem {
+                    name: call.from.name.clone(),
+                    kind: call.from.kind,
+                    location: location(call.from.uri.clone(), range, offset_encoding)?,
+                    item: call.from,
+                })
+            })
+            .collect())
+    })
+}
+
+fn outgoing_call_children(
+    language_server: &Client,
+    item: lsp::CallHierarchyItem,
+    offset_encoding: OffsetEncoding,
+) -> BoxFuture<'static, anyhow::Result<Vec<HierarchyItem<lsp::CallHierarchyItem>>>> {
+    let future = language_server.call_hierarchy_outgoing_calls(item);
+    Box::pin(async move {
+        let calls = future.await?.unwrap_or_default();
+        Ok(calls
+            .into_iter()
+            .filter_map(|call| call_hierarchy_item(call.to, offset_encoding))
+            .collect())
+    })
+}
+
+fn call_hierarchy(cx: &mut Context, direction: CallDirection) {
+    let (view, doc) = current_ref!(cx.editor);
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::CallHierarchy);
+    let language_server_id = language_server.id();
+    let offset_encoding = language_server.offset_encoding();
+    let pos = doc.position(view.id, offset_encoding);
+    let future = language_server
+        .prepare_call_hierarchy(doc.identifier(), pos, None)
+        .unwrap();
+    let fetch_children: FetchChildren<lsp::CallHierarchyItem> = match direction {
+        CallDirection::Incoming => incoming_call_children,
+        CallDirection::Outgoing => outgoing_call_children,
+    };
+
+    cx.jobs.callback(async move {
+        let items = future.await?.unwrap_or_default();
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            let mut hierarchy = Hierarchy {
+                language_server_id,
+                offset_encoding,
+                fetch_children,
+                nodes: Vec::new(),
+                roots: Vec::new(),
+            };
+            for item in items {
+                if let Some(item) = call_hierarchy_item(item, offset_encoding) {
+                    let root = hierarchy.push(item, 0);
+                    hierarchy.roots.push(root);
+                }
+            }
+            let Some(&root) = hierarchy.roots.first() else {
+                editor.set_error("No call hierarchy found for the symbol under the cursor");
+                return;
+            };
+
+            let hierarchy = Arc::new(Mutex::new(hierarchy));
+            compositor.push(Box::new(overlaid(hierarchy_picker(hierarchy.clone()))));
+            toggle_node(editor, hierarchy, root);
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+pub fn incoming_calls(cx: &mut Context) {
+    call_hierarchy(cx, CallDirection::Incoming);
+}
+
+pub fn outgoing_calls(cx: &mut Context) {
+    call_hierarchy(cx, CallDirection::Outgoing);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TypeDirection {
+    Supertypes,
+    Subtypes,
+}
+
+fn type_hierarchy_item(
+    item: lsp::TypeHierarchyItem,
+    offset_encoding: OffsetEncoding,
+) -> Option<HierarchyItem<lsp::TypeHierarchyItem>> {
+    Some(HierarchyItem {
+        name: item.name.clone(),
+        kind: item.kind,
+        location: location(item.uri.clone(), item.selection_range, offset_encoding)?,
+        item,
+    })
+}
+
+fn type_hierarchy_items(
+    items: Option<Vec<lsp::TypeHierarchyItem>>,
+    offset_encoding: OffsetEncoding,
+) -> Vec<HierarchyItem<lsp::TypeHierarchyItem>> {
+    items
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| type_hierarchy_item(item, offset_encoding))
+        .collect()
+}
+
+fn supertype_children(
+    language_server: &Client,
+    item: lsp::TypeHierarchyItem,
+    offset_encoding: OffsetEncoding,
+) -> BoxFuture<'static, anyhow::Result<Vec<HierarchyItem<lsp::TypeHierarchyItem>>>> {
+    let future = language_server.type_hierarchy_supertypes(item);
+    Box::pin(async move { Ok(type_hierarchy_items(future.await?, offset_encoding)) })
+}
+
+fn subtype_children(
+    language_server: &Client,
+    item: lsp::TypeHierarchyItem,
+    offset_encoding: OffsetEncoding,
+) -> BoxFuture<'static, anyhow::Result<Vec<HierarchyItem<lsp::TypeHierarchyItem>>>> {
+    let future = language_server.type_hierarchy_subtypes(item);
+    Box::pin(async move { Ok(type_hierarchy_items(future.await?, offset_encoding)) })
+}
+
+fn type_hierarchy(cx: &mut Context, direction: TypeDirection) {
+    let (view, doc) = current_ref!(cx.editor);
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::TypeHierarchy);
+    let language_server_id = language_server.id();
+    let offset_encoding = language_server.offset_encoding();
+    let pos = doc.position(view.id, offset_encoding);
+    let future = language_server
+        .prepare_type_hierarchy(doc.identifier(), pos, None)
+        .unwrap();
+    let fetch_children: FetchChildren<lsp::TypeHierarchyItem> = match direction {
+        TypeDirection::Supertypes => supertype_children,
+        TypeDirection::Subtypes => subtype_children,
+    };
+
+    cx.jobs.callback(async move {
+        let items = type_hierarchy_items(future.await?, offset_encoding);
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            let mut hierarchy = Hierarchy {
+                language_server_id,
+                offset_encoding,
+                fetch_children,
+                nodes: Vec::new(),
+                roots: Vec::new(),
+            };
+            for item in items {
+                let root = hierarchy.push(item, 0);
+                hierarchy.roots.push(root);
+            }
+            let Some(&root) = hierarchy.roots.first() else {
+                editor.set_error("No type hierarchy found for the symbol under the cursor");
+                return;
+            };
+
+            let hierarchy = Arc::new(Mutex::new(hierarchy));
+            compositor.push(Box::new(overlaid(hierarchy_picker(hierarchy.clone()))));
+            toggle_node(editor, hierarchy, root);
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+pub fn supertypes(cx: &mut Context) {
+    type_hierarchy(cx, TypeDirection::Supertypes);
+}
+
+pub fn subtypes(cx: &mut Context) {
+    type_hierarchy(cx, TypeDirection::Subtypes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str) -> HierarchyItem<()> {
+        HierarchyItem {
+            item: (),
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            location: Location {
+                uri: helix_core::Uri::from(PathBuf::from("/file.rs")),
+                range: lsp::Range::default(),
+                offset_encoding: OffsetEncoding::default(),
+            },
+        }
+    }
+
+    fn rendered_rows(hierarchy: &Hierarchy<()>) -> Vec<(usize, &str, RowState)> {
+        hierarchy
+            .rows()
+            .into_iter()
+            .map(|row| (row.depth, &*hierarchy.nodes[row.node].item.name, row.state))
+            .collect()
+    }
+
+    #[test]
+    fn rows_follow_expanded_nodes() {
+        let mut hierarchy = Hierarchy {
+            language_server_id: LanguageServerId::default(),
+            offset_encoding: OffsetEncoding::default(),
+            fetch_children: |_, _, _| unreachable!(),
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+        let root = hierarchy.push(item("main"), 0);
+        hierarchy.roots.push(root);
+        assert_eq!(
+            rendered_rows(&hierarchy),
+            [(0, "main", RowState::Collapsed)]
+        );
+
+        hierarchy.set_children(root, vec![item("foo"), item("bar")]);
+        let foo = hierarchy.nodes[root].children.as_ref().unwrap()[0];
+        hierarchy.set_children(foo, vec![item("baz")]);
+        let bar = hierarchy.nodes[root].children.as_ref().unwrap()[1];
+        hierarchy.set_children(bar, Vec::new());
+        assert_eq!(
+            rendered_rows(&hierarchy),
+            [
+                (0, "main", RowState::Expanded),
+                (1, "foo", RowState::Expanded),
+                (2, "baz", RowState::Collapsed),
+                (1, "bar", RowState::Leaf),
+            ]
+        );
+
+        // Collapsing hides all descendants but keeps them for expanding again.
+        hierarchy.nodes[foo].expanded = false;
+        assert_eq!(
+            rendered_rows(&hierarchy),
+            [
+                (0, "main", RowState::Expanded),
+                (1, "foo", RowState::Collapsed),
+                (1, "bar", RowState::Leaf),
+            ]
+        );
+    }
+}
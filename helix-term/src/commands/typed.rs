@@ -1,6 +1,7 @@
 use std::fmt::Write;
 use std::io::BufReader;
 use std::ops::{self, Deref};
+use std::sync::{Arc, Mutex};
 
 use crate::job::Job;
 
@@ -10,6 +11,7 @@
 use helix_core::fuzzy::fuzzy_match;
 use helix_core::indent::MAX_INDENT;
 use helix_core::line_ending;
+use helix_core::Change;
 use helix_stdx::path::home_dir;
 use helix_view::document::{read_to_string, DEFAULT_LANGUAGE_NAME};
 use helix_view::editor::{CloseError, ConfigEvent};
@@ -119,6 +121,10 @@ fn quit(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow
     }
 
     cx.block_try_flush_writes()?;
+    if cx.editor.tree.views().count() == 1 {
+        cx.editor.save_project_session();
+        cx.editor.save_project_last_file();
+    }
     cx.editor.close(view!(cx.editor).id);
 
     Ok(())
@@ -130,6 +136,10 @@ fn force_quit(cx: &mut compositor::Context, _args: Args, event: PromptEvent) ->
     }
 
     cx.block_try_flush_writes()?;
+    if cx.editor.tree.views().count() == 1 {
+        cx.editor.save_project_session();
+        cx.editor.save_project_last_file();
+    }
     cx.editor.close(view!(cx.editor).id);
 
     Ok(())
@@ -964,6 +974,9 @@ fn quit_all_impl(cx: &mut compositor::Context, force: bool) -> anyhow::Result<()
         buffers_remaining_impl(cx.editor)?;
     }
 
+    cx.editor.save_project_session();
+    cx.editor.save_project_last_file();
+
     // close all views
     let views: Vec<_> = cx.editor.tree.views().map(|(view, _)| view.id).collect();
     for view_id in views {
@@ -1946,6 +1959,63 @@ fn hsplit_new(cx: &mut compositor::Context, _args: Args, event: PromptEvent) ->
     Ok(())
 }
 
+fn tab_open(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    match args.first() {
+        Some(arg) => {
+            let (path, pos) = crate::args::parse_file(arg);
+            let path = helix_stdx::path::expand_tilde(path);
+            let doc_id = cx.editor.open(&path, Action::Load)?;
+            cx.editor.new_tab(doc_id, None);
+            let (view, doc) = current!(cx.editor);
+            let pos = Selection::point(pos_at_coords(doc.text().slice(..), pos, true));
+            doc.set_selection(view.id, pos);
+            align_view(doc, view, Align::Center);
+        }
+        None => {
+            let doc_id = cx.editor.new_file(Action::Load);
+            cx.editor.new_tab(doc_id, None);
+        }
+    }
+
+    Ok(())
+}
+
+fn tab_next(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    cx.editor.focus_next_tab();
+
+    Ok(())
+}
+
+fn tab_prev(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    cx.editor.focus_prev_tab();
+
+    Ok(())
+}
+
+fn tab_close(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    if !cx.editor.close_tab() {
+        bail!("Cannot close the last tab");
+    }
+
+    Ok(())
+}
+
 fn debug_eval(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
@@ -2257,6 +2327,61 @@ fn language(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> any
     Ok(())
 }
 
+fn set_language_id(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc!(cx.editor);
+    let doc_id = doc.id();
+    if args.has_flag("reset") {
+        cx.editor.set_language_id(doc_id, None);
+    } else if let Some(language_id) = args.first() {
+        cx.editor
+            .set_language_id(doc_id, Some(language_id.to_string()));
+    } else {
+        let language_id = doc.language_id().unwrap_or(DEFAULT_LANGUAGE_NAME);
+        cx.editor.set_status(language_id.to_string());
+    }
+    Ok(())
+}
+
+fn align_cursors(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    use helix_core::selection::AlignTo;
+
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let align_to = match args.first() {
+        None | Some("max") => AlignTo::Max,
+        Some("min") => AlignTo::Min,
+        Some(column) => match column.parse::<usize>() {
+            Ok(column) if column > 0 => AlignTo::Column(column - 1),
+            _ => bail!("Expected 'min', 'max' or a column number, got '{column}'"),
+        },
+    };
+
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+    let selection = helix_core::selection::align_cursors(
+        text,
+        doc.selection(view.id).clone(),
+        align_to,
+        doc.tab_width(),
+    );
+    doc.set_selection(view.id, selection);
+    Ok(())
+}
+
 fn sort(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
@@ -2336,6 +2461,331 @@ fn reflow(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyho
     Ok(())
 }
 
+/// A part of the replacement of `:substitute`.
+enum ReplacementPart {
+    Text(String),
+    /// The text of a capture group of the match, where 0 is the whole match.
+    Group(usize),
+}
+
+/// The parsed argument of `:substitute`: `/pattern/replacement/flags`.
+struct Substitution {
+    regex: Regex,
+    replacement: Vec<ReplacementPart>,
+    /// Replace all matches on each line rather than only the first one.
+    global: bool,
+    /// Ask for confirmation before replacing each match.
+    confirm: bool,
+}
+
+impl Substitution {
+    /// Parses `/pattern/replacement/flags`. Any punctuation other than `\` may be used instead of
+    /// `/` and needs to be escaped with `\` within the pattern or replacement. An empty pattern
+    /// reuses `last_search`.
+    fn parse(arg: &str, last_search: Option<&str>) -> anyhow::Result<Self> {
+        let mut chars = arg.chars();
+        let delimiter = chars
+            .next()
+            .ok_or_else(|| anyhow!("expected /pattern/replacement/flags"))?;
+        ensure!(
+            delimiter.is_ascii_punctuation() && delimiter != '\\',
+            "invalid delimiter '{delimiter}'"
+        );
+
+        // Splits the argument at unescaped delimiters, removing the escape of escaped ones.
+        let mut parts = Vec::new();
+        let mut part = String::new();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => match chars.next() {
+                    Some(ch) if ch == delimiter => part.push(ch),
+                    Some(ch) => {
+                        part.push('\\');
+                        part.push(ch);
+                    }
+                    None => part.push('\\'),
+                },
+                ch if ch == delimiter && parts.len() < 2 => parts.push(std::mem::take(&mut part)),
+                ch => part.push(ch),
+            }
+        }
+        parts.push(part);
+        let mut parts = parts.into_iter();
+        let pattern = parts.next().unwrap();
+        let replacement = parts.next().unwrap_or_default();
+        let flags = parts.next().unwrap_or_default();
+
+        let (mut global, mut confirm, mut case_insensitive) = (false, false, false);
+        for flag in flags.chars() {
+            match flag {
+                'g' => global = true,
+                'c' => confirm = true,
+                'i' => case_insensitive = true,
+                _ => bail!("unknown flag '{flag}'"),
+            }
+        }
+
+        let pattern = match pattern.as_str() {
+            "" => last_search.ok_or_else(|| anyhow!("no previous search pattern"))?,
+            pattern => pattern,
+        };
+        let regex = regex::RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()?;
+        let replacement = Self::parse_replacement(&replacement, regex.captures_len())?;
+
+        Ok(Self {
+            regex,
+            replacement,
+            global,
+            confirm,
+        })
+    }
+
+    /// Parses the `\0` to `\9` backreferences of the replacement, with `&` as an alias for `\0`.
+    /// `\n` and `\t` insert a line feed and a tab respectively, other characters are escaped
+    /// with `\`.
+    fn parse_replacement(
+        replacement: &str,
+        captures_len: usize,
+    ) -> anyhow::Result<Vec<ReplacementPart>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut push_group = |text: &mut String, group| {
+            if !text.is_empty() {
+                parts.push(ReplacementPart::Text(std::mem::take(text)));
+            }
+            parts.push(ReplacementPart::Group(group));
+        };
+
+        let mut chars = replacement.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '&' => push_group(&mut text, 0),
+                '\\' => match chars.next() {
+                    Some(digit @ '0'..='9') => {
+                        let group = digit as usize - '0' as usize;
+                        ensure!(group < captures_len, "invalid backreference \\{group}");
+                        push_group(&mut text, group);
+                    }
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(ch) => text.push(ch),
+                    None => text.push('\\'),
+                },
+                ch => text.push(ch),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(ReplacementPart::Text(text));
+        }
+        Ok(parts)
+    }
+
+    fn expand(&self, captures: &regex::Captures) -> Tendril {
+        let mut replacement = Tendril::new();
+        for part in &self.replacement {
+            match part {
+                ReplacementPart::Text(text) => replacement.push_str(text),
+                ReplacementPart::Group(group) => {
+                    if let Some(group) = captures.get(*group) {
+                        replacement.push_str(group.as_str());
+                    }
+                }
+            }
+        }
+        replacement
+    }
+
+    /// Returns the replacements of the matches on the lines covered by `selection`.
+    fn changes(&self, text: RopeSlice, selection: &Selection) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let mut next_line = 0;
+        for range in selection {
+            let (start_line, end_line) = range.line_range(text);
+            for line in start_line.max(next_line)..=end_line {
+                let line_start = text.line_to_char(line);
+                let line_end = line_end_char_index(&text, line);
+                let line_text: Cow<str> = text.slice(line_start..line_end).into();
+                let line_start_byte = text.char_to_byte(line_start);
+
+                let limit = if self.global { usize::MAX } else { 1 };
+                for captures in self.regex.captures_iter(&line_text).take(limit) {
+                    let whole = captures.get(0).unwrap();
+                    let from = text.byte_to_char(line_start_byte + whole.start());
+                    let to = text.byte_to_char(line_start_byte + whole.end());
+                    changes.push((from, to, Some(self.expand(&captures))));
+                }
+            }
+            next_line = end_line + 1;
+        }
+        changes
+    }
+}
+
+fn substitute(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let last_search = cx
+        .editor
+        .registers
+        .first(cx.editor.registers.last_search_register, cx.editor)
+        .map(Cow::into_owned);
+    let substitution = Substitution::parse(&args[0], last_search.as_deref())?;
+
+    let scrolloff = cx.editor.config().scrolloff;
+    let (view, doc) = current!(cx.editor);
+    let mut changes = substitution.changes(doc.text().slice(..), doc.selection(view.id));
+    ensure!(!changes.is_empty(), "pattern not found");
+
+    if !substitution.confirm {
+        let transaction = Transaction::change(doc.text(), changes.into_iter());
+        doc.apply(&transaction, view.id);
+        doc.append_changes_to_history(view);
+        view.ensure_cursor_in_view(doc, scrolloff);
+        return Ok(());
+    }
+
+    changes.reverse();
+    let pending = PendingSubstitution {
+        doc_id: doc.id(),
+        view_id: view.id,
+        version: doc.version(),
+        selection: doc.selection(view.id).clone(),
+        changes,
+        accepted: Vec::new(),
+    };
+    pending.confirm_next(cx.jobs);
+    Ok(())
+}
+
+/// The state of a `:substitute` with the `c` flag while the matches are confirmed one by one.
+/// The accepted replacements are applied at once when all matches have been confirmed or when
+/// the confirmation is cancelled.
+struct PendingSubstitution {
+    doc_id: DocumentId,
+    view_id: ViewId,
+    /// The version of the document the changes were computed for.
+    version: i32,
+    /// The selection before the substitution, restored at the end.
+    selection: Selection,
+    /// The replacements left to confirm, in reverse order.
+    changes: Vec<Change>,
+    accepted: Vec<Change>,
+}
+
+impl PendingSubstitution {
+    fn confirm_next(self, jobs: &mut Jobs) {
+        let callback = async move {
+            let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+                move |editor: &mut Editor, compositor: &mut Compositor| {
+                    let Some((from, to, replacement)) = self.changes.last() else {
+                        return;
+                    };
+                    if !editor.tree.contains(self.view_id) {
+                        return;
+                    }
+                    let scrolloff = editor.config().scrolloff;
+                    let Some(doc) = editor.documents.get_mut(&self.doc_id) else {
+                        return;
+                    };
+                    if doc.version() != self.version {
+                        editor.set_error("The document changed, cancelled the substitution");
+                        return;
+                    }
+                    doc.set_selection(self.view_id, Selection::single(*from, *to));
+                    let view = editor.tree.get_mut(self.view_id);
+                    view.ensure_cursor_in_view(doc, scrolloff);
+
+                    let prompt = format!(
+                        "Replace with '{}'? (y/n/a/q):",
+                        replacement.as_deref().unwrap_or_default()
+                    );
+                    let mut pending = Some(self);
+                    let prompt = Prompt::new(
+                        prompt.into(),
+                        None,
+                        ui::completers::none,
+                        move |cx: &mut compositor::Context, input: &str, event: PromptEvent| {
+                            if event == PromptEvent::Update {
+                                return;
+                            }
+                            let Some(mut pending) = pending.take() else {
+                                return;
+                            };
+                            if event == PromptEvent::Abort {
+                                pending.finish(cx.editor);
+                                return;
+                            }
+                            let change = pending.changes.pop().unwrap();
+                            match input {
+                                "y" | "Y" => pending.accepted.push(change),
+                                "a" | "A" => {
+                                    pending.accepted.push(change);
+                                    pending.accepted.extend(pending.changes.drain(..).rev());
+                                }
+                                "q" | "Q" => pending.changes.clear(),
+                                _ => (),
+                            }
+                            if pending.changes.is_empty() {
+                                pending.finish(cx.editor);
+                            } else {
+                                pending.confirm_next(cx.jobs);
+                            }
+                        },
+                    );
+                    compositor.push(Box::new(prompt));
+                },
+            ));
+            Ok(call)
+        };
+        // Jobs that are waited for are polled by the event loop itself, which shows the next
+        // prompt before any further keys are handled.
+        jobs.add(Job::with_callback(callback).wait_before_exiting());
+    }
+
+    /// Applies the accepted replacements in a single transaction.
+    fn finish(self, editor: &mut Editor) {
+        let scrolloff = editor.config().scrolloff;
+        let Some(doc) = editor.documents.get_mut(&self.doc_id) else {
+            return;
+        };
+        if !editor.tree.contains(self.view_id) {
+            return;
+        }
+        doc.set_selection(self.view_id, self.selection);
+        if self.accepted.is_empty() {
+            return;
+        }
+        let view = editor.tree.get_mut(self.view_id);
+        let transaction = Transaction::change(doc.text(), self.accepted.into_iter());
+        doc.apply(&transaction, view.id);
+        doc.append_changes_to_history(view);
+        view.ensure_cursor_in_view(doc, scrolloff);
+    }
+}
+
+fn grep(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let query = args.first().unwrap_or_default().to_string();
+    let callback = async move {
+        let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                compositor.push(crate::commands::global_search_picker(editor, '/', &query));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
 fn tree_sitter_subtree(
     cx: &mut compositor::Context,
     _args: Args,
@@ -2452,7 +2902,7 @@ fn insert_output(
 }
 
 fn pipe_to(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
-    pipe_impl(cx, args, event, &ShellBehavior::Ignore)
+    pipe_impl(cx, args, event, &ShellBehavior::Delete)
 }
 
 fn pipe(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
@@ -2610,6 +3060,21 @@ fn redraw(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyh
     Ok(())
 }
 
+fn zen_mode(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    cx.editor.zen_mode = !cx.editor.zen_mode;
+    // The views are laid out for zen mode before the next render.
+    cx.editor.set_status(if cx.editor.zen_mode {
+        "Zen mode enabled"
+    } else {
+        "Zen mode disabled"
+    });
+    Ok(())
+}
+
 fn move_buffer(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
@@ -2636,6 +3101,70 @@ fn move_buffer(cx: &mut compositor::Context, args: Args, event: PromptEvent) ->
     Ok(())
 }
 
+fn rename_file(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc!(cx.editor);
+    let old_path = doc
+        .path()
+        .context("Scratch buffer cannot be renamed. Use :write instead")?
+        .clone();
+    ensure!(
+        !doc.is_modified(),
+        "Buffer has unsaved changes. Use :write before renaming it"
+    );
+
+    // Relative paths are relative to the directory of the file rather than the working directory.
+    let new_path = helix_stdx::path::expand_tilde(Path::new(args.first().unwrap()));
+    let new_path = match old_path.parent() {
+        Some(dir) if new_path.is_relative() => dir.join(new_path),
+        _ => new_path.into_owned(),
+    };
+    let new_path = helix_stdx::path::normalize(new_path);
+    ensure!(
+        !new_path.is_dir(),
+        "Cannot rename to '{}': it is a directory",
+        new_path.display()
+    );
+
+    if !new_path.exists() {
+        if let Err(err) = cx.editor.move_path(&old_path, &new_path) {
+            bail!("Could not rename file: {err}");
+        }
+        return Ok(());
+    }
+
+    let prompt = format!(
+        "'{}' already exists, overwrite it? (y/n):",
+        new_path.display()
+    );
+    let callback = async move {
+        let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+            move |_editor: &mut Editor, compositor: &mut Compositor| {
+                let prompt = Prompt::new(
+                    prompt.into(),
+                    None,
+                    ui::completers::none,
+                    move |cx: &mut compositor::Context, input: &str, event: PromptEvent| {
+                        if event != PromptEvent::Validate || !input.eq_ignore_ascii_case("y") {
+                            return;
+                        }
+                        if let Err(err) = cx.editor.move_path(&old_path, &new_path) {
+                            cx.editor.set_error(format!("Could not rename file: {err}"));
+                        }
+                    },
+                );
+                compositor.push(Box::new(prompt));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+    Ok(())
+}
+
 fn yank_diagnostic(
     cx: &mut compositor::Context,
     args: Args,
@@ -2728,20 +3257,12 @@ fn noop(_cx: &mut compositor::Context, _args: Args, _event: PromptEvent) -> anyh
     Ok(())
 }
 
-fn project_scan(
-    cx: &mut compositor::Context,
-    args: Args,
-    event: PromptEvent,
-) -> anyhow::Result<()> {
-    if event != PromptEvent::Validate {
-        return Ok(());
-    }
-
-    use helix_loader::projects;
+/// The directories to scan for repositories: the command arguments, or the configured
+/// `project-scan-roots`, or the home directory.
+fn scan_roots(editor: &Editor, args: &Args) -> anyhow::Result<Vec<PathBuf>> {
     use helix_stdx::path::expand_tilde;
     use std::path::Path;
 
-    // Determine root directories to scan
     // Priority: command args > config project_scan_roots > home directory
     let roots: Vec<std::path::PathBuf> = if !args.is_empty() {
         // Use command line arguments
@@ -2750,7 +3271,7 @@ fn project_scan(
             .collect()
     } else {
         // Use configured roots, or fall back to home directory
-        let config_roots = &cx.editor.config().project_scan_roots;
+        let config_roots = &editor.config().project_scan_roots;
         if config_roots.is_empty() {
             vec![home_dir()?]
         } else {
@@ -2769,6 +3290,21 @@ fn project_scan(
             return Err(anyhow!("Path is not a directory: {}", root.display()));
         }
     }
+    Ok(roots)
+}
+
+fn project_scan(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    use helix_loader::projects;
+
+    let roots = scan_roots(cx.editor, &args)?;
 
     // Show status that scanning has started
     let roots_display: Vec<_> = roots.iter().map(|r| r.display().to_string()).collect();
@@ -2776,186 +3312,1054 @@ fn project_scan(
 
     // Perform scanning in background to avoid blocking UI
     // Use spawn_blocking to avoid blocking the tokio runtime during file system operations
+    let options = projects::ScanOptions {
+        max_repos: Some(cx.editor.config().project_scan_max_repos).filter(|&max| max != 0),
+        ..projects::ScanOptions::default()
+    };
     let callback = async move {
         let result = tokio::task::spawn_blocking(move || {
-            let mut all_repos = Vec::new();
+            let mut found = 0;
+            let mut added = 0;
+            let mut errors = 0;
+            let mut truncated = Vec::new();
             for root_path in &roots {
-                match projects::scan_git_repositories(root_path) {
-                    Ok(repos) => all_repos.extend(repos),
-                    Err(e) => {
-                        return Err(format!("Failed to scan {}: {}", root_path.display(), e));
-                    }
+                let report = projects::scan_and_register(root_path, &options)
+                    .map_err(|e| format!("Failed to scan {}: {}", root_path.display(), e))?;
+                for err in &report.errors {
+                    log::warn!("Failed to scan {}: {err}", root_path.display());
                 }
-            }
-
-            // Load existing projects
-            let mut existing_projects = projects::load_projects().unwrap_or_default();
-            let mut existing_paths: std::collections::HashSet<_> = existing_projects
-                .iter()
-                .map(|p| p.path.canonicalize().unwrap_or_else(|_| p.path.clone()))
-                .collect();
-
-            // Add new repositories
-            let mut new_count = 0;
-            for repo_path in &all_repos {
-                let canonical = repo_path.canonicalize().unwrap_or_else(|_| repo_path.clone());
-                if !existing_paths.contains(&canonical) {
-                    let name = repo_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .map(|s| s.to_string());
-                    existing_paths.insert(canonical.clone());
-                    existing_projects.push(projects::Project {
-                        path: canonical,
-                        name,
-                        last_accessed: None,
-                    });
-                    new_count += 1;
+                found += report.added.len() + report.known.len();
+                added += report.added.len();
+                errors += report.errors.len();
+                if report.truncated {
+                    truncated.push(root_path.display().to_string());
                 }
             }
-
-            // Save updated projects
-            let total_count = existing_projects.len();
-            let save_result = projects::save_projects(&existing_projects);
-
-            Ok((all_repos, new_count, total_count, save_result))
-        }).await;
+            Ok::<_, String>((found, added, errors, truncated))
+        })
+        .await;
 
         let call: job::Callback = match result {
-            Ok(Ok((all_repos, new_count, total_count, save_result))) => {
+            Ok(Ok((found, added, errors, truncated))) => {
                 job::Callback::Editor(Box::new(move |editor| {
-                    match save_result {
-                        Ok(_) => {
-                            editor.set_status(format!(
-                                "Found {} git repositories ({} new). Total projects: {}",
-                                all_repos.len(),
-                                new_count,
-                                total_count
-                            ));
-                        }
-                        Err(e) => {
-                            editor.set_error(format!("Failed to save projects: {}", e));
-                        }
+                    editor.refresh_projects();
+                    let status = format!("Found {found} git repositories ({added} new)");
+                    if !truncated.is_empty() {
+                        editor.set_warning(format!(
+                            "{status}. Stopped at `editor.project-scan-max-repos` in {}",
+                            truncated.join(", ")
+                        ));
+                    } else if errors > 0 {
+                        editor.set_warning(format!(
+                            "{status}. {errors} directories could not be read, see the log"
+                        ));
+                    } else {
+                        editor.set_status(status);
                     }
                 }))
             }
-            Ok(Err(err_msg)) => {
-                job::Callback::Editor(Box::new(move |editor| {
-                    editor.set_error(err_msg);
-                }))
+            Ok(Err(err_msg)) => job::Callback::Editor(Box::new(move |editor| {
+                editor.set_error(err_msg);
+            })),
+            Err(e) => job::Callback::Editor(Box::new(move |editor| {
+                editor.set_error(format!("Scanning task failed: {}", e));
+            })),
+        };
+        Ok(call)
+    };
+
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+fn projects_relocate(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    use helix_loader::projects::{self, Relocation};
+
+    let roots = scan_roots(cx.editor, &args)?;
+    cx.editor.set_status("Looking for moved projects...");
+
+    let shared = cx.editor.projects.clone();
+    let callback = async move {
+        let result = tokio::task::spawn_blocking(move || {
+            // Scanning can take a while, so the projects file is only locked to apply the moves.
+            let mut snapshot = shared.list(true)?;
+            let relocations = projects::relocate_missing_projects(&mut snapshot, &roots);
+            let moves: Vec<_> = relocations
+                .iter()
+                .filter_map(|relocation| match relocation {
+                    Relocation::Moved { from, to } => Some((from.clone(), to.clone())),
+                    _ => None,
+                })
+                .collect();
+            let moved = shared.update(|projects| {
+                let moved: Vec<_> = moves
+                    .into_iter()
+                    .filter(|(from, to)| projects::relocate_project(projects, from, to))
+                    .collect();
+                (!moved.is_empty(), moved)
+            })?;
+            for (from, to) in &moved {
+                log::info!("Relocated project {} to {}", from.display(), to.display());
+                if let Err(err) = helix_loader::session::move_session(from, to) {
+                    log::warn!("Failed to move the session of {}: {err}", from.display());
+                }
+                if let Err(err) = helix_loader::bookmarks::move_bookmarks(from, to) {
+                    log::warn!("Failed to move the bookmarks of {}: {err}", from.display());
+                }
+                if let Err(err) = helix_loader::project_state::move_project_state(from, to) {
+                    log::warn!(
+                        "Failed to move the project state of {}: {err}",
+                        from.display()
+                    );
+                }
+                if let Err(err) = helix_loader::recent_files::move_recent_files(from, to) {
+                    log::warn!(
+                        "Failed to move the recent files of {}: {err}",
+                        from.display()
+                    );
+                }
             }
-            Err(e) => {
-                job::Callback::Editor(Box::new(move |editor| {
-                    editor.set_error(format!("Scanning task failed: {}", e));
-                }))
+            let mut ambiguous = 0;
+            let mut unresolved = 0;
+            for relocation in &relocations {
+                match relocation {
+                    Relocation::Moved { .. } => (),
+                    Relocation::Ambiguous { path, candidates } => {
+                        ambiguous += 1;
+                        let candidates: Vec<_> =
+                            candidates.iter().map(|c| c.display().to_string()).collect();
+                        log::info!(
+                            "Not relocating project {}, it could be any of {}",
+                            path.display(),
+                            candidates.join(", ")
+                        );
+                    }
+                    Relocation::Unresolved { path } => {
+                        unresolved += 1;
+                        log::info!("No repository found for project {}", path.display());
+                    }
+                }
+            }
+            anyhow::Ok((moved.len(), ambiguous, unresolved))
+        })
+        .await;
+
+        let call: job::Callback = job::Callback::Editor(Box::new(move |editor| match result {
+            Ok(Ok((0, 0, 0))) => editor.set_status("No projects are missing"),
+            Ok(Ok((moved, 0, 0))) => {
+                editor.refresh_projects();
+                editor.set_status(format!("Relocated {moved} projects"));
+            }
+            Ok(Ok((moved, ambiguous, unresolved))) => {
+                editor.refresh_projects();
+                editor.set_warning(format!(
+                    "Relocated {moved} projects, {ambiguous} ambiguous, {unresolved} not found, see the log"
+                ));
+            }
+            Ok(Err(err)) => editor.set_error(format!("Failed to relocate projects: {err}")),
+            Err(err) => editor.set_error(format!("Relocation task failed: {err}")),
+        }));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+/// Reads the directories exported by `source`: the output of its command line tool or its
+/// storage file.
+fn read_import_source(source: helix_loader::projects::ImportSource) -> anyhow::Result<Vec<u8>> {
+    use helix_loader::projects::{self, ImportSource};
+
+    let (program, args) = match source {
+        ImportSource::Zoxide => ("zoxide", ["query", "--list", "--score"]),
+        ImportSource::VsCode => {
+            let path = projects::vscode_storage_path()
+                .context("Unable to find the VS Code configuration directory")?;
+            return std::fs::read(&path)
+                .map_err(|err| anyhow!("Failed to read {}: {err}", path.display()));
+        }
+    };
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|err| anyhow!("Failed to run {program}: {err}"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+fn project_import(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    use helix_loader::projects::{self, ImportSource};
+
+    let source: ImportSource = args.first().unwrap().parse()?;
+    let require_git = !args.has_flag("all");
+    cx.editor
+        .set_status(format!("Importing projects from {}...", source.name()));
+
+    let shared = cx.editor.projects.clone();
+    let callback = async move {
+        let result = tokio::task::spawn_blocking(move || {
+            let output = read_import_source(source)?;
+            let imported = projects::import_projects(source, output.as_slice(), require_git)?;
+            let candidates = imported.projects.len();
+            let added = shared
+                .update(|projects| {
+                    let added = projects::merge_projects(projects, imported.projects);
+                    (added > 0, added)
+                })
+                .map_err(|err| anyhow!("Failed to save projects: {err}"))?;
+            // Projects which are already registered count as skipped.
+            anyhow::Ok((added, imported.skipped + candidates - added))
+        })
+        .await;
+
+        let call: job::Callback = job::Callback::Editor(Box::new(move |editor| match result {
+            Ok(Ok((added, skipped))) => {
+                editor.refresh_projects();
+                editor.set_status(format!(
+                    "Imported {added} projects from {}, skipped {skipped}",
+                    source.name()
+                ));
+            }
+            Ok(Err(err)) => editor.set_error(err.to_string()),
+            Err(err) => editor.set_error(format!("Import task failed: {err}")),
+        }));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+fn projects_export(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    use helix_loader::projects::{self, ExportFormat};
+
+    let path = helix_stdx::path::expand_tilde(Path::new(args.first().unwrap()));
+    projects::export_projects_file(&path, ExportFormat::for_path(&path))
+        .map_err(|err| anyhow!("Failed to export projects: {err:#}"))?;
+    cx.editor
+        .set_status(format!("Exported projects to {}", path.display()));
+
+    Ok(())
+}
+
+fn projects_import(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    use helix_loader::projects::{self, ExportFormat, MergeStrategy};
+
+    let path = helix_stdx::path::expand_tilde(Path::new(args.first().unwrap()));
+    let strategy: MergeStrategy = args.get_flag("merge").unwrap_or("keep-newest").parse()?;
+    let file = std::fs::File::open(&path)
+        .map_err(|err| anyhow!("Failed to open {}: {err}", path.display()))?;
+    let report = projects::import_projects_file(
+        BufReader::new(file),
+        ExportFormat::for_path(&path),
+        strategy,
+    )
+    .map_err(|err| anyhow!("Failed to import projects: {err:#}"))?;
+    cx.editor.refresh_projects();
+
+    let mut status = format!(
+        "Imported projects: {} added, {} updated",
+        report.added, report.updated
+    );
+    if report.removed > 0 {
+        write!(status, ", {} removed", report.removed)?;
+    }
+    if report.missing > 0 {
+        write!(status, ", {} not found on this machine", report.missing)?;
+    }
+    cx.editor.set_status(status);
+
+    Ok(())
+}
+
+/// Makes `path` the current project: changes the working directory and reloads
+/// the workspace configuration. Registered projects also get their access time
+/// recorded in the projects list.
+///
+/// Buffers from the previous project stay open. Language servers started for the
+/// previous project are not reused for documents opened afterwards. With project
+/// sessions enabled the session of the previous project is saved and the session
+/// of the new project replaces the layout of the current tab. Otherwise the file focused when
+/// last leaving the new project is opened, see `editor.project-restore-last-file`.
+///
+/// When the switch leaves or enters a registered project, its `on_close` or `on_open` hook runs
+/// in the background.
+fn switch_project(editor: &mut Editor, path: &Path, registered: bool) -> anyhow::Result<()> {
+    use crate::handlers::projects::{run_project_hooks, ProjectHook};
+    use helix_loader::projects;
+
+    if projects::project_status(path) == projects::ProjectStatus::Missing {
+        bail!(
+            "Project directory is unavailable: {}. Is its drive mounted?",
+            path.display()
+        );
+    }
+
+    let projects_list = editor.projects.list(true).unwrap_or_else(|err| {
+        log::warn!("Failed to load projects: {err}");
+        Vec::new()
+    });
+    let cwd = helix_stdx::env::current_working_dir();
+    let old = projects::find_project_for_path(&projects_list, &cwd);
+    let new = projects::find_project_for_path(&projects_list, path);
+    if old.map(|p| &p.path) != new.map(|p| &p.path) {
+        let hooks: Vec<_> = old
+            .map(|old| (old, ProjectHook::Close))
+            .into_iter()
+            .chain(new.map(|new| (new, ProjectHook::Open)))
+            .collect();
+        run_project_hooks(editor, &hooks);
+    }
+
+    editor.save_project_session();
+    editor.save_project_last_file();
+    editor
+        .set_cwd(path)
+        .map_err(|err| anyhow!("Failed to change directory to {}: {err}", path.display()))?;
+    editor.config_events.0.send(ConfigEvent::WorkspaceChanged)?;
+
+    if registered {
+        if let Err(err) = editor.projects.update(|projects| {
+            let updated = projects::update_project_last_accessed(projects, path);
+            (updated, ())
+        }) {
+            log::warn!("Failed to update project last accessed time: {}", err);
+        }
+    }
+
+    editor.set_status(format!("Switched to project: {}", path.display()));
+    // A warning about missing files replaces the status message.
+    match editor.project_session() {
+        Some(session) => editor.restore_project_session(session),
+        None => {
+            editor.restore_project_last_file();
+        }
+    }
+    Ok(())
+}
+
+fn project_switch(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    use helix_loader::projects;
+
+    let include_archived = args.has_flag("archived");
+    let (projects_list, warnings) = cx
+        .editor
+        .projects
+        .read(|store| {
+            let projects: Vec<_> = store
+                .projects()
+                .iter()
+                .filter(|project| include_archived || !project.archived)
+                .cloned()
+                .collect();
+            let warnings: Vec<_> = store.warnings().iter().map(ToString::to_string).collect();
+            (projects, warnings)
+        })
+        .map_err(|err| anyhow!("Failed to load projects: {err}"))?;
+    if let Some(warning) = warnings.first() {
+        match warnings.len() {
+            1 => cx.editor.set_warning(warning.to_string()),
+            n => cx
+                .editor
+                .set_warning(format!("{warning} (and {} more)", n - 1)),
+        }
+    }
+
+    if let Some(query) = args.first() {
+        let roots = projects::ambient_roots();
+        return match projects::match_project_in(&projects_list, &roots, query) {
+            projects::ProjectMatch::Found(project) => {
+                switch_project(cx.editor, &project.path, true)
+            }
+            projects::ProjectMatch::Ambient(path) => {
+                let registered = cx.editor.config().project_register_ambient
+                    && cx
+                        .editor
+                        .projects
+                        .update_now(|store| store.add(&path))
+                        .map_err(|err| log::warn!("Failed to register project: {err}"))
+                        .is_ok();
+                switch_project(cx.editor, &path, registered)
+            }
+            projects::ProjectMatch::Ambiguous(candidates) => {
+                let paths: Vec<_> = candidates
+                    .iter()
+                    .map(|project| project.path.display().to_string())
+                    .collect();
+                bail!("Project '{query}' is ambiguous: {}", paths.join(", "))
+            }
+            projects::ProjectMatch::NotFound => {
+                let path = helix_stdx::path::expand_tilde(Path::new(query));
+                switch_project(cx.editor, &helix_stdx::path::canonicalize(path), false)
             }
         };
+    }
+
+    if projects_list.is_empty() {
+        cx.editor.set_error("No projects found. Use :project-scan to scan for git repositories.");
+        return Ok(());
+    }
+
+    let shared = cx.editor.projects.clone();
+    let callback = async move {
+        use ui::overlay::overlaid;
+
+        // Detecting languages reads the project directories, so it's kept off the UI thread.
+        let refreshed = tokio::task::spawn_blocking(move || {
+            shared.update(|projects| {
+                let updated = projects::refresh_project_languages(projects);
+                (updated, projects.clone())
+            })
+        })
+        .await?;
+        let projects_list = match refreshed {
+            Ok(mut refreshed) => {
+                if !include_archived {
+                    refreshed.retain(|project| !project.archived);
+                }
+                refreshed
+            }
+            Err(err) => {
+                log::warn!("Failed to detect the languages of projects: {err}");
+                projects_list
+            }
+        };
+
+        let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+            move |editor: &mut helix_view::Editor,
+                  compositor: &mut crate::compositor::Compositor| {
+                let picker = project_picker(projects_list, include_archived, editor);
+                compositor.push(Box::new(overlaid(picker)));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+/// A project shown in a project picker with its [status](helix_loader::projects::ProjectStatus).
+/// Missing projects stay listed but are dimmed.
+pub struct PickerProject {
+    project: helix_loader::projects::Project,
+    status: helix_loader::projects::ProjectStatus,
+    /// The README of the project, looked up once the project is previewed.
+    readme: once_cell::sync::OnceCell<Option<PathBuf>>,
+}
+
+/// READMEs are previewed up to this many bytes.
+const README_PREVIEW_MAX_LEN: u64 = 64 * 1024;
+
+/// Previews the README of a project, or its directory if it has none.
+fn project_preview<'a>(
+    _editor: &'a Editor,
+    item: &'a PickerProject,
+) -> Option<ui::FileLocation<'a>> {
+    let path = item
+        .readme
+        .get_or_init(|| helix_loader::projects::find_readme(&item.project.path))
+        .as_deref()
+        .unwrap_or(&item.project.path);
+    Some((path.into(), None))
+}
+
+type ProjectPicker = ui::Picker<PickerProject, ProjectPickerData>;
+
+/// The data shared by the columns of a project picker.
+pub struct ProjectPickerData {
+    missing_style: Style,
+    diff_providers: helix_vcs::DiffProviderRegistry,
+    /// The VCS summaries of the projects which were shown, for the lifetime of the picker.
+    /// `None` while a summary is being read, or if the project isn't a readable repository.
+    vcs_summaries: Arc<Mutex<HashMap<PathBuf, Option<helix_vcs::VcsSummary>>>>,
+}
+
+impl ProjectPickerData {
+    fn new(editor: &Editor) -> Self {
+        Self {
+            missing_style: editor.theme.get("ui.text.inactive"),
+            diff_providers: editor.diff_providers.clone(),
+            vcs_summaries: Arc::default(),
+        }
+    }
+
+    /// The cached VCS summary of the project at `path`. The first call for a project reads it in
+    /// the background and redraws once it's available, so that rendering never waits for git.
+    fn vcs_summary(&self, path: &Path) -> Option<helix_vcs::VcsSummary> {
+        let mut summaries = self.vcs_summaries.lock().unwrap();
+        if let Some(summary) = summaries.get(path) {
+            return summary.clone();
+        }
+        summaries.insert(path.to_path_buf(), None);
+
+        let diff_providers = self.diff_providers.clone();
+        let vcs_summaries = self.vcs_summaries.clone();
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            if let Some(summary) = diff_providers.project_vcs_summary(&path) {
+                vcs_summaries.lock().unwrap().insert(path, Some(summary));
+                helix_event::request_redraw();
+            }
+        });
+        None
+    }
+}
+
+fn picker_projects(projects: Vec<helix_loader::projects::Project>) -> Vec<PickerProject> {
+    let statuses = helix_loader::projects::check_projects(&projects);
+    projects
+        .into_iter()
+        .zip(statuses)
+        .map(|(project, status)| PickerProject {
+            project,
+            status,
+            readme: Default::default(),
+        })
+        .collect()
+}
+
+/// Returns a refresh callback for [ui::Picker::with_refresh] which lists the projects again
+/// whenever they change, for example when a project is added while the picker is open.
+fn refresh_picker_projects(
+    include_archived: bool,
+    sort: bool,
+    editor: &Editor,
+) -> impl FnMut(&Editor) -> Option<Vec<PickerProject>> {
+    let mut generation = editor.projects.generation();
+    move |editor| {
+        if !editor.projects.changed_since(generation) {
+            return None;
+        }
+        let projects = editor.projects.list(include_archived);
+        // Listing may reread the projects file, which counts as a change of its own.
+        generation = editor.projects.generation();
+        let mut projects = projects
+            .map_err(|err| log::warn!("Failed to load projects: {err}"))
+            .ok()?;
+        if sort {
+            helix_loader::projects::sort_by_frecency(&mut projects);
+        }
+        Some(picker_projects(projects))
+    }
+}
+
+/// A picker over `projects` which switches to the selected project, most
+/// frequently and recently used projects first. The picker follows changes to the projects,
+/// listing archived projects too if `include_archived` is set.
+pub fn project_picker(
+    mut projects: Vec<helix_loader::projects::Project>,
+    include_archived: bool,
+    editor: &Editor,
+) -> ProjectPicker {
+    helix_loader::projects::sort_by_frecency(&mut projects);
+    let refresh = refresh_picker_projects(include_archived, true, editor);
+    ui::Picker::new(
+        project_picker_columns(),
+        0,
+        picker_projects(projects),
+        ProjectPickerData::new(editor),
+        move |cx, item: &PickerProject, _action| {
+            if let Err(err) = switch_project(cx.editor, &item.project.path, true) {
+                cx.editor.set_error(err.to_string());
+            }
+        },
+    )
+    .with_preview(project_preview)
+    .with_truncated_previews(README_PREVIEW_MAX_LEN)
+    .with_refresh(refresh)
+}
+
+/// The picker shown on startup by `editor.project-startup-picker`: the
+/// registered projects, unless the current working directory is already
+/// inside one of them.
+pub fn startup_project_picker(editor: &Editor) -> Option<ProjectPicker> {
+    use helix_loader::projects;
+
+    let projects_list = editor
+        .projects
+        .list(false)
+        .map_err(|err| log::warn!("Failed to load projects: {err}"))
+        .ok()?;
+    let cwd = helix_stdx::env::current_working_dir();
+    if projects_list.is_empty() || projects::find_project_for_path(&projects_list, &cwd).is_some() {
+        return None;
+    }
+    Some(project_picker(projects_list, false, editor))
+}
+
+fn project_picker_columns() -> [ui::PickerColumn<PickerProject, ProjectPickerData>; 3] {
+    [
+        ui::PickerColumn::new("path", |item: &PickerProject, data: &ProjectPickerData| {
+            let path_str = item.project.path.to_string_lossy();
+            let mut text = if let Some(name) = &item.project.name {
+                format!("{} ({})", name, path_str)
+            } else {
+                path_str.into_owned()
+            };
+            if item.project.archived {
+                text.push_str(" [archived]");
+            }
+            match item.status {
+                helix_loader::projects::ProjectStatus::Available => text.into(),
+                helix_loader::projects::ProjectStatus::Missing => {
+                    Span::styled(format!("{text} [missing]"), data.missing_style).into()
+                }
+            }
+        }),
+        // Not filtered, so that summaries are only read for the projects which are shown.
+        ui::PickerColumn::new(
+            "branch",
+            |item: &PickerProject, data: &ProjectPickerData| {
+                if item.status == helix_loader::projects::ProjectStatus::Missing {
+                    return "".into();
+                }
+                match data.vcs_summary(&item.project.path) {
+                    Some(summary) if summary.dirty => format!("{}*", summary.head).into(),
+                    Some(summary) => summary.head.into(),
+                    None => "".into(),
+                }
+            },
+        )
+        .without_filtering(),
+        ui::PickerColumn::new("languages", |item: &PickerProject, _| {
+            item.project.languages.join(", ").into()
+        }),
+    ]
+}
+
+/// The project that the current buffer belongs to: the root of the repository
+/// containing the focused document, or the current working directory.
+fn current_project_root(editor: &Editor) -> PathBuf {
+    doc!(editor)
+        .path()
+        .and_then(|path| path.parent())
+        .map(helix_loader::find_workspace_in)
+        .and_then(|(root, is_cwd)| (!is_cwd).then_some(root))
+        .unwrap_or_else(helix_stdx::env::current_working_dir)
+}
+
+fn project_add(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let path = match args.first() {
+        Some(path) => {
+            helix_stdx::path::canonicalize(helix_stdx::path::expand_tilde(Path::new(path)))
+        }
+        None => current_project_root(cx.editor),
+    };
+    if !path.is_dir() {
+        bail!("Project path is not a directory: {}", path.display());
+    }
+
+    let added = cx
+        .editor
+        .projects
+        .update_now(|store| store.add(&path))
+        .map_err(|err| anyhow!("Failed to add project: {err}"))?;
+    if added {
+        cx.editor.refresh_projects();
+        cx.editor
+            .set_status(format!("Added project: {}", path.display()));
+    } else {
+        cx.editor
+            .set_status(format!("Project already registered: {}", path.display()));
+    }
+
+    Ok(())
+}
+
+fn project_new(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let template = &args[0];
+    let path = helix_stdx::path::canonicalize(helix_stdx::path::expand_tilde(Path::new(&args[1])));
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        bail!("Invalid project path: {}", path.display());
+    };
+    let project = helix_loader::projects::create_project_from_template(
+        template,
+        &path,
+        name,
+        args.has_flag("git"),
+    )?;
+
+    // The project was registered in the projects file, behind the back of the editor's store.
+    cx.editor
+        .projects
+        .read(|_| ())
+        .map_err(|err| anyhow!("Failed to load projects: {err}"))?;
+    cx.editor.refresh_projects();
+    cx.editor.set_status(format!(
+        "Created project from template '{template}': {}",
+        project.path.display()
+    ));
+    Ok(())
+}
+
+fn project_remove(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    fn remove(editor: &mut Editor, path: &Path) {
+        match editor.projects.update_now(|store| store.remove(path)) {
+            Ok(true) => {
+                editor.refresh_projects();
+                editor.set_status(format!("Removed project: {}", path.display()))
+            }
+            Ok(false) => editor.set_error(format!("Not a registered project: {}", path.display())),
+            Err(err) => editor.set_error(format!("Failed to remove project: {err}")),
+        }
+    }
+
+    if let Some(path) = args.first() {
+        let path = helix_stdx::path::expand_tilde(Path::new(path));
+        remove(cx.editor, &helix_stdx::path::normalize(path));
+        return Ok(());
+    }
+
+    let projects_list = cx
+        .editor
+        .projects
+        .list(true)
+        .map_err(|err| anyhow!("Failed to load projects: {err}"))?;
+    if projects_list.is_empty() {
+        bail!("No projects found");
+    }
+
+    let callback = async move {
+        let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let refresh = refresh_picker_projects(true, false, editor);
+                let picker = ui::Picker::new(
+                    project_picker_columns(),
+                    0,
+                    picker_projects(projects_list),
+                    ProjectPickerData::new(editor),
+                    |cx, item: &PickerProject, _action| {
+                        remove(cx.editor, &item.project.path);
+                    },
+                )
+                .with_preview(project_preview)
+                .with_truncated_previews(README_PREVIEW_MAX_LEN)
+                .with_refresh(refresh);
+                compositor.push(Box::new(ui::overlay::overlaid(picker)));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+fn project_archive_impl(editor: &mut Editor, args: Args, archived: bool) -> anyhow::Result<()> {
+    let path = match args.first() {
+        Some(path) => {
+            helix_stdx::path::canonicalize(helix_stdx::path::expand_tilde(Path::new(path)))
+        }
+        None => current_project_root(editor),
+    };
+    let found = editor
+        .projects
+        .update_now(|store| store.set_archived(&path, archived))
+        .map_err(|err| anyhow!("Failed to update project: {err}"))?;
+    if !found {
+        bail!("Not a registered project: {}", path.display());
+    }
+    editor.refresh_projects();
+    let action = if archived { "Archived" } else { "Unarchived" };
+    editor.set_status(format!("{action} project: {}", path.display()));
+
+    Ok(())
+}
+
+fn project_archive(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    project_archive_impl(cx.editor, args, true)
+}
+
+fn project_unarchive(
+    cx: &mut compositor::Context,
+    args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    project_archive_impl(cx.editor, args, false)
+}
+
+fn project_set(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let path = current_project_root(cx.editor);
+    let value = args.get(1).map(|value| value.to_string());
+    let found = match &args[0] {
+        "theme" => {
+            if let Some(name) = &value {
+                cx.editor
+                    .theme_loader
+                    .load(name)
+                    .map_err(|err| anyhow!("Could not load theme: {err}"))?;
+            }
+            cx.editor
+                .projects
+                .update_now(|store| store.set_theme(&path, value.clone()))
+                .map_err(|err| anyhow!("Failed to update project: {err}"))?
+        }
+        key => bail!("Unknown project setting '{key}', expected 'theme'"),
+    };
+    if !found {
+        bail!("Not a registered project: {}", path.display());
+    }
+    // Apply the theme if the project is the current one.
+    if helix_stdx::env::current_working_dir().starts_with(&path) {
+        cx.editor
+            .config_events
+            .0
+            .send(ConfigEvent::WorkspaceChanged)?;
+    }
+    match value {
+        Some(value) => cx.editor.set_status(format!(
+            "Set {} of project {}: {value}",
+            &args[0],
+            path.display()
+        )),
+        None => cx
+            .editor
+            .set_status(format!("Unset {} of project {}", &args[0], path.display())),
+    }
+
+    Ok(())
+}
+
+fn recent_files(
+    cx: &mut compositor::Context,
+    _args: Args,
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (project, _) = helix_loader::find_workspace();
+    let files: Vec<_> = crate::handlers::recent_files::recent_files(&project)
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    if files.is_empty() {
+        if !cx.editor.config().project_recent_files {
+            bail!("Recent files are disabled, see editor.project-recent-files");
+        }
+        bail!("No recent files in {}", project.display());
+    }
+
+    let callback = async move {
+        let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut crate::compositor::Compositor| {
+                let picker = ui::recent_files_picker(editor, project, files);
+                compositor.push(Box::new(ui::overlay::overlaid(picker)));
+            },
+        ));
         Ok(call)
     };
-
     cx.jobs.callback(callback);
 
     Ok(())
 }
 
-fn project_switch(
+fn bookmark(cx: &mut compositor::Context, args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let project = focused_project_root(cx.editor);
+    let (view, doc) = current_ref!(cx.editor);
+    let path = doc.path().context("Scratch buffers can't be bookmarked")?;
+    let text = doc.text().slice(..);
+    let position = helix_core::coords_at_pos(text, doc.selection(view.id).primary().cursor(text));
+    helix_loader::bookmarks::add_bookmark(
+        &project,
+        &args[0],
+        path,
+        position.row + 1,
+        position.col + 1,
+    )?;
+    cx.editor.set_status(format!(
+        "Bookmarked line {} as {}",
+        position.row + 1,
+        args[0].trim()
+    ));
+    Ok(())
+}
+
+fn bookmark_remove(
     cx: &mut compositor::Context,
-    _args: Args,
+    args: Args,
     event: PromptEvent,
 ) -> anyhow::Result<()> {
     if event != PromptEvent::Validate {
         return Ok(());
     }
 
-    use helix_loader::projects;
-
-    // Load projects
-    let projects_list = match projects::load_projects() {
-        Ok(projs) => projs,
-        Err(e) => {
-            cx.editor.set_error(format!("Failed to load projects: {}", e));
-            return Ok(());
-        }
-    };
+    let project = focused_project_root(cx.editor);
+    if !helix_loader::bookmarks::remove_bookmark(&project, &args[0])? {
+        bail!("No bookmark {} in {}", &args[0], project.display());
+    }
+    cx.editor
+        .set_status(format!("Removed bookmark {}", &args[0]));
+    Ok(())
+}
 
-    if projects_list.is_empty() {
-        cx.editor.set_error("No projects found. Use :project-scan to scan for git repositories.");
+fn bookmarks(cx: &mut compositor::Context, _args: Args, event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
         return Ok(());
     }
 
-    // Use callback to push layer
-    let callback = async move {
-        use ui::overlay::overlaid;
-        use ui::PickerColumn;
-
-        // Reload projects in case they were updated
-        let projects_list_for_picker = match projects::load_projects() {
-            Ok(projs) => projs,
-            Err(e) => {
-                let call: job::Callback = job::Callback::Editor(Box::new(move |editor| {
-                    editor.set_error(format!("Failed to load projects: {}", e));
-                }));
-                return Ok(call);
-            }
-        };
-
-        let call: job::Callback = job::Callback::EditorCompositor(Box::new(
-            move |_editor: &mut helix_view::Editor, compositor: &mut crate::compositor::Compositor| {
-                // Create picker columns
-                let columns = [PickerColumn::new(
-                    "path",
-                    |project: &projects::Project, _: &()| {
-                        let path_str = project.path.to_string_lossy();
-                        if let Some(name) = &project.name {
-                            format!("{} ({})", name, path_str).into()
-                        } else {
-                            path_str.into()
-                        }
-                    },
-                )];
-
-                // Create picker
-                let picker = ui::Picker::new(
-                    columns,
-                    0,
-                    projects_list_for_picker.clone(),
-                    (),
-                    move |cx, project: &projects::Project, _action| {
-                        let project_path = &project.path;
-                        if !project_path.exists() {
-                            cx.editor.set_error(format!(
-                                "Project path does not exist: {}",
-                                project_path.display()
-                            ));
-                            return;
-                        }
+    struct BookmarkItem {
+        bookmark: helix_loader::bookmarks::Bookmark,
+        path: PathBuf,
+        /// Whether the file still exists.
+        available: bool,
+    }
 
-                        // Change working directory
-                        if let Err(e) = cx.editor.set_cwd(project_path) {
-                            cx.editor.set_error(format!(
-                                "Failed to change directory to {}: {}",
-                                project_path.display(),
-                                e
-                            ));
-                            return;
-                        }
+    let project = focused_project_root(cx.editor);
+    let items: Vec<_> = helix_loader::bookmarks::list_bookmarks(&project)
+        .into_iter()
+        .map(|bookmark| {
+            let path = project.join(&bookmark.path);
+            BookmarkItem {
+                available: path.is_file(),
+                path,
+                bookmark,
+            }
+        })
+        .collect();
+    if items.is_empty() {
+        bail!("No bookmarks in {}", project.display());
+    }
 
-                        // Update last accessed time - reload projects to get latest state
-                        if let Ok(mut updated_projects) = projects::load_projects() {
-                            projects::update_project_last_accessed(&mut updated_projects, project_path);
-                            if let Err(e) = projects::save_projects(&updated_projects) {
-                                log::warn!("Failed to update project last accessed time: {}", e);
-                            }
-                        }
+    let columns = [
+        ui::PickerColumn::new("name", |item: &BookmarkItem, _| {
+            item.bookmark.name.as_str().into()
+        }),
+        ui::PickerColumn::new("location", |item: &BookmarkItem, _| {
+            let location = format!(
+                "{}:{}:{}",
+                item.bookmark.path.display(),
+                item.bookmark.line,
+                item.bookmark.column
+            );
+            if item.available {
+                location.into()
+            } else {
+                format!("{location} (unavailable)").into()
+            }
+        }),
+    ];
 
-                        cx.editor.set_status(format!(
-                            "Switched to project: {}",
-                            project_path.display()
+    let callback = async move {
+        let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+            move |_editor: &mut Editor, compositor: &mut crate::compositor::Compositor| {
+                let picker = ui::Picker::new(columns, 0, items, (), |cx, item, action| {
+                    if !item.available {
+                        cx.editor.set_error(format!(
+                            "Bookmark {} is unavailable: {} no longer exists",
+                            item.bookmark.name,
+                            item.bookmark.path.display()
                         ));
-                    },
-                );
-
-                compositor.push(Box::new(overlaid(picker)));
+                        return;
+                    }
+                    if let Err(err) = cx.editor.open(&item.path, action) {
+                        cx.editor
+                            .set_error(format!("Failed to open {}: {err}", item.path.display()));
+                        return;
+                    }
+                    // The file may have become shorter since the bookmark was set.
+                    let config = cx.editor.config();
+                    let (view, doc) = current!(cx.editor);
+                    let text = doc.text().slice(..);
+                    let position = Position::new(item.bookmark.line - 1, item.bookmark.column - 1);
+                    let pos = pos_at_coords(text, position, true);
+                    doc.set_selection(view.id, Selection::point(pos));
+                    if action.align_view(view, doc.id()) {
+                        view.ensure_cursor_in_view_center(doc, config.scrolloff);
+                    }
+                })
+                .with_preview(|_editor, item| {
+                    let line = item.bookmark.line - 1;
+                    item.available
+                        .then(|| (item.path.as_path().into(), Some((line, line))))
+                });
+                compositor.push(Box::new(ui::overlay::overlaid(picker)));
             },
         ));
         Ok(call)
@@ -3069,11 +4473,189 @@ fn project_switch(
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "projects-relocate",
+        aliases: &[],
+        doc: "Find projects whose directory is missing among the git repositories in the given directories, by the URL of their origin remote, and update their paths. Uses configured project-scan-roots, or home directory if not configured.",
+        fun: projects_relocate,
+        completer: CommandCompleter::all(completers::filename),
+        signature: Signature {
+            positionals: (0, None),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "project-import",
+        aliases: &[],
+        doc: "Import the directories known to another tool as projects, skipping directories which aren't git repositories. Supported sources: zoxide, vscode.",
+        fun: project_import,
+        completer: CommandCompleter::positional(&[completers::project_import_source]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            flags: &[Flag {
+                name: "all",
+                alias: Some('a'),
+                doc: "also import directories which aren't git repositories",
+                ..Flag::DEFAULT
+            }],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "projects-export",
+        aliases: &[],
+        doc: "Export the projects list to a file, as TOML if the file has a .toml extension and as JSON otherwise.",
+        fun: projects_export,
+        completer: CommandCompleter::positional(&[completers::filename]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "projects-import",
+        aliases: &[],
+        doc: "Import a projects list exported with :projects-export. Registered projects are kept unless the import was accessed more recently.",
+        fun: projects_import,
+        completer: CommandCompleter::positional(&[completers::filename]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            flags: &[Flag {
+                name: "merge",
+                alias: Some('m'),
+                doc: "how to merge with the registered projects: keep-newest (the default), add-missing or replace",
+                completions: Some(&["keep-newest", "add-missing", "replace"]),
+            }],
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "project-switch",
         aliases: &["ps"],
-        doc: "Switch to a project from the persisted list using a picker.",
+        doc: "Switch to a project by name, path suffix like `client/api`, path or directory under the roots of `HELIX_PROJECT_PATHS`, changing the working directory and reloading the workspace configuration. Opens a picker if no project is given.",
         fun: project_switch,
+        completer: CommandCompleter::positional(&[completers::project]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            flags: &[Flag {
+                name: "archived",
+                alias: Some('a'),
+                doc: "include archived projects",
+                ..Flag::DEFAULT
+            }],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "project-add",
+        aliases: &[],
+        doc: "Register a project. Defaults to the repository of the current buffer, or the current working directory.",
+        fun: project_add,
+        completer: CommandCompleter::positional(&[completers::directory]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "project-new",
+        aliases: &[],
+        doc: "Create and register a project at the given path from a template in the `templates` directory of the config directory.",
+        fun: project_new,
+        completer: CommandCompleter::positional(&[completers::project_template, completers::directory]),
+        signature: Signature {
+            positionals: (2, Some(2)),
+            flags: &[Flag {
+                name: "git",
+                alias: Some('g'),
+                doc: "initialize a git repository in the project",
+                ..Flag::DEFAULT
+            }],
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "project-remove",
+        aliases: &[],
+        doc: "Remove a project from the persisted list. Opens a picker if no path is given.",
+        fun: project_remove,
+        completer: CommandCompleter::positional(&[completers::directory]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "project-archive",
+        aliases: &[],
+        doc: "Hide a project from the project picker and completion while keeping its metadata. Defaults to the repository of the current buffer, or the current working directory.",
+        fun: project_archive,
+        completer: CommandCompleter::positional(&[completers::directory]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "project-unarchive",
+        aliases: &[],
+        doc: "Show an archived project in the project picker and completion again. Defaults to the repository of the current buffer, or the current working directory.",
+        fun: project_unarchive,
+        completer: CommandCompleter::positional(&[completers::directory]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "project-set",
+        aliases: &[],
+        doc: "Set a setting of the project of the current buffer, or of the current working directory, or unset it if no value is given. The only setting is 'theme', the theme used while the project is the current one.",
+        fun: project_set,
+        completer: CommandCompleter::positional(&[completers::project_setting, completers::theme]),
+        signature: Signature {
+            positionals: (1, Some(2)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "recent-files",
+        aliases: &[],
+        doc: "Open a picker of the files recently opened or written in the current project. Requires editor.project-recent-files.",
+        fun: recent_files,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "bookmark",
+        aliases: &[],
+        doc: "Bookmark the cursor position under the given name in the current project, replacing the bookmark with that name.",
+        fun: bookmark,
+        completer: CommandCompleter::positional(&[completers::bookmark]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "bookmark-remove",
+        aliases: &[],
+        doc: "Remove the bookmark with the given name from the current project.",
+        fun: bookmark_remove,
+        completer: CommandCompleter::positional(&[completers::bookmark]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "bookmarks",
+        aliases: &[],
+        doc: "Open a picker of the bookmarks of the current project.",
+        fun: bookmarks,
         completer: CommandCompleter::none(),
         signature: Signature {
             positionals: (0, Some(0)),
@@ -3760,6 +5342,50 @@ fn project_switch(
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "tabopen",
+        aliases: &["tabnew"],
+        doc: "Open the file in a new tab, or a new scratch buffer if no file is given.",
+        fun: tab_open,
+        completer: CommandCompleter::positional(&[completers::filename]),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "tabnext",
+        aliases: &["tabn"],
+        doc: "Switch to the next tab.",
+        fun: tab_next,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "tabprev",
+        aliases: &["tabp"],
+        doc: "Switch to the previous tab.",
+        fun: tab_prev,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "tabclose",
+        aliases: &["tabc"],
+        doc: "Close the current tab and all of its splits. Buffers are kept open.",
+        fun: tab_close,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "tutor",
         aliases: &[],
@@ -3793,6 +5419,23 @@ fn project_switch(
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "set-language-id",
+        aliases: &[],
+        doc: "Set the language ID reported to the language servers of the current buffer, keeping its language for highlighting (show current ID if no value specified).",
+        fun: set_language_id,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            flags: &[Flag {
+                name: "reset",
+                alias: Some('r'),
+                doc: "use the language ID of the buffer's language again",
+                ..Flag::DEFAULT
+            }],
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "set-option",
         aliases: &["set"],
@@ -3829,6 +5472,17 @@ fn project_switch(
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "align",
+        aliases: &[],
+        doc: "Move all cursors to the same column of their lines: 'max' (the default) for the rightmost cursor, 'min' for the leftmost cursor or a column number.",
+        fun: align_cursors,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "sort",
         aliases: &[],
@@ -3865,6 +5519,30 @@ fn project_switch(
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "substitute",
+        aliases: &["s"],
+        doc: "Replace matches of a regex on the selected lines: `/pattern/replacement/flags`. The flags are `g` to replace all matches on each line, `c` to confirm each replacement and `i` to ignore case.",
+        fun: substitute,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            raw_after: Some(0),
+            ..Signature::DEFAULT
+        },
+    },
+    TypableCommand {
+        name: "grep",
+        aliases: &[],
+        doc: "Search for a regex in the files of the working directory, like `<space>/`.",
+        fun: grep,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(1)),
+            raw_after: Some(0),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "tree-sitter-subtree",
         aliases: &["ts-subtree"],
@@ -3922,7 +5600,7 @@ fn project_switch(
     },
     TypableCommand {
         name: "insert-output",
-        aliases: &[],
+        aliases: &["pipe-from"],
         doc: "Run shell command, inserting output before each selection.",
         fun: insert_output,
         completer: SHELL_COMPLETER,
@@ -3947,7 +5625,7 @@ fn project_switch(
     TypableCommand {
         name: "pipe-to",
         aliases: &[],
-        doc: "Pipe each selection to the shell command, ignoring output.",
+        doc: "Pipe each selection to the shell command and delete it, ignoring output.",
         fun: pipe_to,
         completer: SHELL_COMPLETER,
         signature: SHELL_SIGNATURE,
@@ -3993,6 +5671,17 @@ fn project_switch(
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "zen-mode",
+        aliases: &[],
+        doc: "Toggle zen mode, which hides the UI around the text as configured by `editor.zen-mode`.",
+        fun: zen_mode,
+        completer: CommandCompleter::none(),
+        signature: Signature {
+            positionals: (0, Some(0)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "move",
         aliases: &["mv"],
@@ -4004,6 +5693,17 @@ fn project_switch(
             ..Signature::DEFAULT
         },
     },
+    TypableCommand {
+        name: "rename-file",
+        aliases: &[],
+        doc: "Rename the file of the current buffer. Relative paths are resolved against the directory of the file.",
+        fun: rename_file,
+        completer: CommandCompleter::positional(&[completers::document_relative_filename]),
+        signature: Signature {
+            positionals: (1, Some(1)),
+            ..Signature::DEFAULT
+        },
+    },
     TypableCommand {
         name: "yank-diagnostic",
         aliases: &[],
@@ -4071,6 +5771,16 @@ fn execute_command_line(
         return Ok(());
     }
 
+    // `:s/pattern/replacement/flags` doesn't separate the command from its argument.
+    if let Some(arg) = input
+        .trim_start()
+        .strip_prefix('s')
+        .filter(|arg| arg.starts_with('/'))
+    {
+        let cmd = TYPABLE_COMMAND_MAP.get("substitute").unwrap();
+        return execute_command(cx, cmd, arg, event);
+    }
+
     // If command is numeric, interpret as line number and go there.
     if command.parse::<usize>().is_ok() && rest.trim().is_empty() {
         let cmd = TYPABLE_COMMAND_MAP.get("goto").unwrap();
@@ -22,6 +22,8 @@ pub struct ConfigRaw {
     pub theme: Option<theme::Config>,
     pub keys: Option<HashMap<Mode, KeyTrie>>,
     pub editor: Option<toml::Value>,
+    /// Settings read by `helix-loader` itself, such as `workspace-markers`.
+    pub helix: Option<toml::Value>,
 }
 
 impl Default for Config {
@@ -15,17 +15,22 @@
 use self::document_colors::DocumentColorsHandler;
 
 mod auto_save;
+pub mod code_lens;
 pub mod completion;
 pub mod diagnostics;
 mod document_colors;
+mod linked_editing;
+mod on_type_formatting;
+pub mod projects;
 mod prompt;
+pub mod recent_files;
 mod signature_help;
 mod snippet;
 
 pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
     events::register();
 
-    let event_tx = completion::CompletionHandler::new(config).spawn();
+    let event_tx = completion::CompletionHandler::new(config.clone()).spawn();
     let signature_hints = SignatureHelpHandler::new().spawn();
     let auto_save = AutoSaveHandler::new().spawn();
     let document_colors = DocumentColorsHandler::default().spawn();
@@ -50,6 +55,11 @@ pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
     diagnostics::register_hooks(&handlers);
     snippet::register_hooks(&handlers);
     document_colors::register_hooks(&handlers);
+    code_lens::register_hooks(&handlers);
+    linked_editing::register_hooks(&handlers);
+    on_type_formatting::register_hooks(&handlers);
     prompt::register_hooks(&handlers);
+    projects::register_hooks(&handlers, &config.load().editor);
+    recent_files::register_hooks(&handlers);
     handlers
 }
@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+
+use futures_util::{future::join_all, stream::FuturesOrdered, StreamExt};
+use helix_core::syntax::config::LanguageServerFeature;
+use helix_event::{cancelable_future, register_hook, TaskHandle};
+use helix_lsp::{lsp, LanguageServerId};
+use helix_view::{
+    document::CodeLens,
+    events::{
+        ConfigDidChange, DocumentDidChange, DocumentDidOpen, LanguageServerExited,
+        LanguageServerInitialized,
+    },
+    handlers::Handlers,
+    DocumentId, Editor,
+};
+
+use crate::job;
+
+/// Requests the code lenses of a document from its language servers. They replace the current
+/// lenses of the document once they are resolved.
+///
+/// Lenses are requested when a document is opened and after it is saved. In between, the
+/// positions of the current lenses follow the edits to the document.
+pub fn request_code_lenses(editor: &mut Editor, doc_id: DocumentId) {
+    if !editor.config().lsp.code_lens {
+        return;
+    }
+
+    let Some(doc) = editor.document_mut(doc_id) else {
+        return;
+    };
+
+    let cancel = doc.code_lens_controller.restart();
+
+    let mut seen_language_servers = HashSet::new();
+    let mut futures: FuturesOrdered<_> = doc
+        .language_servers_with_feature(LanguageServerFeature::CodeLens)
+        .filter(|ls| seen_language_servers.insert(ls.id()))
+        .map(|language_server| {
+            let text = doc.text().clone();
+            let offset_encoding = language_server.offset_encoding();
+            let language_server_id = language_server.id();
+            let future = language_server
+                .text_document_code_lens(doc.identifier(), None)
+                .unwrap();
+
+            async move {
+                let lenses: Vec<_> = future
+                    .await?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|lens| {
+                        let char_idx = helix_lsp::util::lsp_pos_to_pos(
+                            &text,
+                            lens.range.start,
+                            offset_encoding,
+                        )?;
+                        Some(CodeLens {
+                            char_idx,
+                            lens,
+                            language_server_id,
+                        })
+                    })
+                    .collect();
+                anyhow::Ok(lenses)
+            }
+        })
+        .collect();
+
+    if futures.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut all_lenses = Vec::new();
+        loop {
+            match cancelable_future(futures.next(), &cancel).await {
+                Some(Some(Ok(items))) => all_lenses.extend(items),
+                Some(Some(Err(err))) => log::error!("code lens request failed: {err}"),
+                Some(None) => break,
+                // The request was cancelled.
+                None => return,
+            }
+        }
+        job::dispatch(move |editor, _| resolve_code_lenses(editor, doc_id, all_lenses, cancel))
+            .await;
+    });
+}
+
+/// Resolves the lenses which don't have a command yet, since their title is only known then,
+/// and attaches all of them to the document.
+fn resolve_code_lenses(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    lenses: Vec<CodeLens>,
+    cancel: TaskHandle,
+) {
+    let futures: Vec<_> = lenses
+        .into_iter()
+        .map(|mut lens| {
+            let future = if lens.lens.command.is_none() {
+                editor
+                    .language_server_by_id(lens.language_server_id)
+                    .and_then(|ls| ls.resolve_code_lens(&lens.lens))
+            } else {
+                None
+            };
+            async move {
+                if let Some(future) = future {
+                    match future.await {
+                        Ok(resolved) => lens.lens = resolved,
+                        Err(err) => log::error!("code lens resolve failed: {err}"),
+                    }
+                }
+                lens
+            }
+        })
+        .collect();
+
+    tokio::spawn(async move {
+        let Some(lenses) = cancelable_future(join_all(futures), &cancel).await else {
+            return;
+        };
+        job::dispatch(move |editor, _| attach_code_lenses(editor, doc_id, lenses)).await;
+    });
+}
+
+fn attach_code_lenses(editor: &mut Editor, doc_id: DocumentId, mut lenses: Vec<CodeLens>) {
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+
+    // Lenses without a command have nothing to show or run.
+    lenses.retain(|lens| lens.lens.command.is_some());
+    lenses.sort_by_key(|lens| lens.char_idx);
+    doc.code_lenses = lenses;
+}
+
+/// Resolves `lens` if it doesn't have a command yet and runs its command.
+pub fn execute_code_lens(editor: &mut Editor, lens: lsp::CodeLens, server_id: LanguageServerId) {
+    if let Some(command) = lens.command {
+        editor.execute_lsp_command(command, server_id);
+        return;
+    }
+
+    let Some(future) = editor
+        .language_server_by_id(server_id)
+        .and_then(|ls| ls.resolve_code_lens(&lens))
+    else {
+        editor.set_error("Code lens has no command");
+        return;
+    };
+    tokio::spawn(async move {
+        match future.await {
+            Ok(lsp::CodeLens {
+                command: Some(command),
+                ..
+            }) => {
+                job::dispatch(move |editor, _| editor.execute_lsp_command(command, server_id)).await
+            }
+            Ok(_) => job::dispatch(|editor, _| editor.set_error("Code lens has no command")).await,
+            Err(err) => log::error!("code lens resolve failed: {err}"),
+        }
+    });
+}
+
+pub(super) fn register_hooks(_handlers: &Handlers) {
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        request_code_lenses(event.editor, event.doc);
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        // Keep the lenses above the lines they were reported for.
+        event.changes.update_positions(
+            event
+                .doc
+                .code_lenses
+                .iter_mut()
+                .map(|lens| (&mut lens.char_idx, helix_core::Assoc::Before)),
+        );
+
+        // The positions of lenses in flight refer to the text before the change.
+        event.doc.code_lens_controller.cancel();
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut LanguageServerInitialized<'_>| {
+        let doc_ids: Vec<_> = event.editor.documents().map(|doc| doc.id()).collect();
+
+        for doc_id in doc_ids {
+            request_code_lenses(event.editor, doc_id);
+        }
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut LanguageServerExited<'_>| {
+        for doc in event.editor.documents_mut() {
+            doc.code_lenses
+                .retain(|lens| lens.language_server_id != event.server_id);
+        }
+
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut ConfigDidChange<'_>| {
+        if event.new.lsp.code_lens && !event.old.lsp.code_lens {
+            let doc_ids: Vec<_> = event.editor.documents().map(|doc| doc.id()).collect();
+
+            for doc_id in doc_ids {
+                request_code_lenses(event.editor, doc_id);
+            }
+        }
+
+        Ok(())
+    });
+}
@@ -181,6 +181,8 @@ fn update_completion_filter(cx: &mut commands::Context, c: Option<char>) {
                 // this occurs if typing a trigger char)
                 if c.is_some() {
                     trigger_auto_completion(cx.editor, false);
+                } else {
+                    trigger_completion_on_delete(cx.editor);
                 }
             } else {
                 let handle = cx.editor.handlers.completions.request_controller.restart();
@@ -190,6 +192,21 @@ fn update_completion_filter(cx: &mut commands::Context, c: Option<char>) {
     }))
 }
 
+/// Requests completions after deleting backwards into a word if
+/// `editor.completion-trigger-on-delete` is enabled.
+fn trigger_completion_on_delete(editor: &Editor) {
+    if !editor.config().completion_trigger_on_delete {
+        return;
+    }
+    let (view, doc) = current_ref!(editor);
+    let text = doc.text().slice(..);
+    let cursor = doc.selection(view.id).primary().cursor(text);
+    // Only within words, not after deleting up to a word boundary.
+    if cursor > 0 && char_is_word(text.char(cursor - 1)) {
+        trigger_auto_completion(editor, false);
+    }
+}
+
 fn clear_completions(cx: &mut commands::Context) {
     cx.callback.push(Box::new(|compositor, cx| {
         let editor_view = compositor.find::<ui::EditorView>().unwrap();
@@ -237,6 +254,15 @@ fn completion_post_command_hook(
                 _ => CompletionEvent::Cancel,
             };
             cx.editor.handlers.completions.event(event);
+            if matches!(
+                command,
+                MappableCommand::Static {
+                    name: "delete_char_backward",
+                    ..
+                }
+            ) {
+                trigger_completion_on_delete(cx.editor);
+            }
         }
     }
     Ok(())
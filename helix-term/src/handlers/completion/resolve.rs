@@ -123,6 +123,9 @@ fn handle_event(
             self.next_request = None;
             None
         } else {
+            // The selection moved on, so the previous item doesn't need to be resolved anymore.
+            self.task_controller.cancel();
+            self.in_flight = None;
             self.next_request = Some(request);
             Some(Instant::now() + Duration::from_millis(150))
         }
@@ -0,0 +1,182 @@
+use std::borrow::Cow;
+
+use helix_core::syntax::config::LanguageServerFeature;
+use helix_core::{Assoc, Range, Transaction};
+use helix_event::register_hook;
+use helix_lsp::{lsp, OffsetEncoding};
+use helix_view::document::Mode;
+use helix_view::events::DocumentDidChange;
+use helix_view::handlers::Handlers;
+use helix_view::{Document, DocumentId, Editor};
+
+use crate::events::{OnModeSwitch, PostCommand, PostInsertChar};
+use crate::job;
+
+/// Requests the ranges linked to the tag name under the cursor when entering insert mode and
+/// keeps them in sync while typing.
+pub(super) fn register_hooks(_handlers: &Handlers) {
+    register_hook!(move |event: &mut OnModeSwitch<'_, '_>| {
+        match (event.old_mode, event.new_mode) {
+            (Mode::Insert, _) => doc_mut!(event.cx.editor).linked_ranges.clear(),
+            (_, Mode::Insert) => request_linked_ranges(event.cx.editor),
+            _ => (),
+        }
+        Ok(())
+    });
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        for range in &mut event.doc.linked_ranges {
+            // Insertions at either end of a range extend it.
+            let mut positions = [(range.anchor, Assoc::Before), (range.head, Assoc::After)];
+            event
+                .changes
+                .update_positions(positions.iter_mut().map(|(pos, assoc)| (pos, *assoc)));
+            *range = Range::new(positions[0].0, positions[1].0);
+        }
+        Ok(())
+    });
+    register_hook!(move |event: &mut PostInsertChar<'_, '_>| {
+        sync_linked_ranges(event.cx.editor);
+        Ok(())
+    });
+    register_hook!(move |event: &mut PostCommand<'_, '_>| {
+        if event.cx.editor.mode == Mode::Insert {
+            sync_linked_ranges(event.cx.editor);
+        }
+        Ok(())
+    });
+}
+
+fn request_linked_ranges(editor: &mut Editor) {
+    let (view, doc) = current!(editor);
+    let selection = doc.selection(view.id);
+    if selection.len() > 1 {
+        return;
+    }
+    let cursor = selection.primary().cursor(doc.text().slice(..));
+    if !is_on_tag_name(doc, cursor) {
+        return;
+    }
+    let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::LinkedEditingRange)
+        .next()
+    else {
+        return;
+    };
+
+    let offset_encoding = language_server.offset_encoding();
+    let position = doc.position(view.id, offset_encoding);
+    let future = language_server
+        .text_document_linked_editing_range(doc.identifier(), position, None)
+        .unwrap();
+    let doc_id = doc.id();
+    let version = doc.version();
+
+    tokio::spawn(async move {
+        match future.await {
+            Ok(Some(ranges)) => {
+                job::dispatch(move |editor, _| {
+                    attach_linked_ranges(editor, doc_id, version, ranges, offset_encoding)
+                })
+                .await
+            }
+            Ok(None) => (),
+            Err(err) => log::error!("linked editing range request failed: {err}"),
+        }
+    });
+}
+
+fn attach_linked_ranges(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    version: i32,
+    ranges: lsp::LinkedEditingRanges,
+    offset_encoding: OffsetEncoding,
+) {
+    if editor.mode != Mode::Insert {
+        return;
+    }
+    // The ranges are outdated if the document changed in the meantime.
+    let Some(doc) = editor.documents.get_mut(&doc_id) else {
+        return;
+    };
+    if doc.version() != version {
+        return;
+    }
+
+    let mut linked_ranges: Vec<_> = ranges
+        .ranges
+        .into_iter()
+        .filter_map(|range| {
+            let range =
+                helix_lsp::util::lsp_range_to_char_range(range, doc.text(), offset_encoding)?;
+            Some(Range::new(range.start, range.end))
+        })
+        .collect();
+    if linked_ranges.len() < 2 {
+        return;
+    }
+    linked_ranges.sort_by_key(Range::from);
+    doc.linked_ranges = linked_ranges;
+}
+
+/// Whether `cursor` is on or right after the name of a tag. Without a syntax tree the language
+/// server decides whether there are linked ranges at the cursor.
+fn is_on_tag_name(doc: &Document, cursor: usize) -> bool {
+    let Some(syntax) = doc.syntax() else {
+        return true;
+    };
+    let byte = doc.text().char_to_byte(cursor) as u32;
+    [byte, byte.saturating_sub(1)].into_iter().any(|byte| {
+        syntax
+            .descendant_for_byte_range(byte, byte + 1)
+            .is_some_and(|node| {
+                // `tag_name` is used by HTML and similar grammars, `Name` by XML.
+                node.kind() == "tag_name"
+                    || node.kind() == "Name"
+                        && node
+                            .parent()
+                            .is_some_and(|parent| parent.kind().ends_with("Tag"))
+            })
+    })
+}
+
+/// Characters that end linked editing when typed, as they can't be part of a tag name.
+fn is_tag_name_char(ch: char) -> bool {
+    !ch.is_whitespace() && !matches!(ch, '<' | '>' | '/' | '=' | '"' | '\'')
+}
+
+/// Replaces the other linked ranges with the text of the linked range containing the cursor.
+///
+/// Linked editing ends once the text can't be a tag name anymore.
+fn sync_linked_ranges(editor: &mut Editor) {
+    let (view, doc) = current!(editor);
+    if doc.linked_ranges.is_empty() {
+        return;
+    }
+    let text = doc.text().slice(..);
+    let cursor = doc.selection(view.id).primary().cursor(text);
+    let Some(source) = doc
+        .linked_ranges
+        .iter()
+        .find(|range| range.from() <= cursor && cursor <= range.to())
+    else {
+        return;
+    };
+    let name: Cow<str> = text.slice(source.from()..source.to()).into();
+    if !name.chars().all(is_tag_name_char) {
+        doc.linked_ranges.clear();
+        return;
+    }
+
+    let changes: Vec<_> = doc
+        .linked_ranges
+        .iter()
+        .filter(|range| text.slice(range.from()..range.to()) != name.as_ref())
+        .map(|range| (range.from(), range.to(), Some(name.as_ref().into())))
+        .collect();
+    if changes.is_empty() {
+        return;
+    }
+    let transaction = Transaction::change(doc.text(), changes.into_iter());
+    doc.apply(&transaction, view.id);
+}
@@ -0,0 +1,102 @@
+use helix_core::indent::IndentStyle;
+use helix_core::syntax::config::LanguageServerFeature;
+use helix_event::register_hook;
+use helix_lsp::{lsp, OffsetEncoding};
+use helix_view::document::Mode;
+use helix_view::handlers::Handlers;
+use helix_view::{DocumentId, Editor};
+
+use crate::events::{PostCommand, PostInsertChar};
+use crate::job;
+
+/// Asks the language server to format the document after typing one of its trigger characters,
+/// see `editor.lsp.on-type-formatting`.
+pub(super) fn register_hooks(_handlers: &Handlers) {
+    register_hook!(move |event: &mut PostInsertChar<'_, '_>| {
+        request_on_type_formatting(event.cx.editor, event.c);
+        Ok(())
+    });
+    register_hook!(move |event: &mut PostCommand<'_, '_>| {
+        // Line breaks are inserted by a command rather than typed as a character.
+        if event.cx.editor.mode == Mode::Insert && event.command.name() == "insert_newline" {
+            request_on_type_formatting(event.cx.editor, '\n');
+        }
+        Ok(())
+    });
+}
+
+fn request_on_type_formatting(editor: &mut Editor, ch: char) {
+    if !editor.config().lsp.on_type_formatting {
+        return;
+    }
+    let (view, doc) = current!(editor);
+    let options = lsp::FormattingOptions {
+        tab_size: doc.tab_width() as u32,
+        insert_spaces: matches!(doc.indent_style, IndentStyle::Spaces(_)),
+        ..Default::default()
+    };
+    // The first language server which formats on `ch`.
+    let Some((future, offset_encoding)) = doc
+        .language_servers_with_feature(LanguageServerFeature::OnTypeFormatting)
+        .find_map(|language_server| {
+            let offset_encoding = language_server.offset_encoding();
+            let position = doc.position(view.id, offset_encoding);
+            let future = language_server.text_document_on_type_formatting(
+                doc.identifier(),
+                position,
+                ch,
+                options.clone(),
+            )?;
+            Some((future, offset_encoding))
+        })
+    else {
+        return;
+    };
+    let doc_id = doc.id();
+    let version = doc.version();
+
+    tokio::spawn(async move {
+        match future.await {
+            Ok(Some(edits)) if !edits.is_empty() => {
+                job::dispatch(move |editor, _| {
+                    apply_on_type_formatting(editor, doc_id, version, edits, offset_encoding)
+                })
+                .await
+            }
+            Ok(_) => (),
+            Err(err) => log::error!("on type formatting request failed: {err}"),
+        }
+    });
+}
+
+/// Applies the formatting `edits` as part of the insertion that triggered them, so that they are
+/// undone together.
+fn apply_on_type_formatting(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    version: i32,
+    edits: Vec<lsp::TextEdit>,
+    offset_encoding: OffsetEncoding,
+) {
+    // The edits are outdated if the document changed or lost focus in the meantime.
+    let (view, doc) = current!(editor);
+    if doc.id() != doc_id || doc.version() != version {
+        return;
+    }
+    let transaction = match helix_lsp::util::generate_transaction_from_edits(
+        doc.text(),
+        edits,
+        offset_encoding,
+    ) {
+        Ok(transaction) => transaction,
+        Err(err) => {
+            log::error!("Failed to apply on type formatting: {err}");
+            return;
+        }
+    };
+    doc.apply(&transaction, view.id);
+    // The insertion was committed to the history already if insert mode was left.
+    if editor.mode != Mode::Insert {
+        doc.append_changes_to_history(view);
+    }
+}
@@ -0,0 +1,233 @@
+use std::{fmt, path::Path, process::Stdio, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, bail, ensure};
+use arc_swap::ArcSwap;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use helix_event::register_hook;
+use helix_loader::projects::Project;
+use helix_view::{
+    editor::Config,
+    events::{ConfigDidChange, DocumentDidOpen},
+    handlers::Handlers,
+    Editor,
+};
+
+use crate::job;
+
+/// Archives the projects which weren't accessed within `editor.project-retention-days` in the
+/// background and reports them in a warning, so that none disappear from the pickers unnoticed.
+pub fn archive_stale_projects(editor: &Editor) {
+    let retention_days = editor.config().project_retention_days;
+    if retention_days == 0 {
+        return;
+    }
+
+    let projects = editor.projects.clone();
+    tokio::task::spawn_blocking(move || {
+        let archived = match projects.update_now(|store| store.archive_stale(retention_days)) {
+            Ok(archived) => archived,
+            Err(err) => {
+                log::warn!("Failed to archive stale projects: {err}");
+                return;
+            }
+        };
+        if archived.is_empty() {
+            return;
+        }
+        for project in &archived {
+            log::info!("Archived stale project: {}", project.path.display());
+        }
+        let names: Vec<_> = archived
+            .iter()
+            .map(|project| match project.display_name() {
+                Some(name) => name.to_string(),
+                None => project.path.display().to_string(),
+            })
+            .collect();
+        let message = format!(
+            "Archived {} project{} not accessed in {retention_days} days: {}",
+            archived.len(),
+            if archived.len() == 1 { "" } else { "s" },
+            names.join(", ")
+        );
+        job::dispatch_blocking(move |editor, _| {
+            editor.refresh_projects();
+            editor.set_warning(message);
+        });
+    });
+}
+
+/// Records an access to the repository of every opened document in the projects list,
+/// registering it first, when `editor.project-auto-register` is enabled.
+///
+/// The `editor.project-auto-register-exclude` patterns are compiled here and whenever the config
+/// changes rather than for every opened document.
+pub(super) fn register_hooks(_handlers: &Handlers, config: &Config) {
+    let excludes = Arc::new(ArcSwap::from_pointee(exclude_globs(
+        &config.project_auto_register_exclude,
+    )));
+
+    let open_excludes = excludes.clone();
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        let config = event.editor.config();
+        if !config.project_auto_register {
+            return Ok(());
+        }
+        let Some(dir) = event
+            .editor
+            .document(event.doc)
+            .and_then(|doc| doc.path())
+            .and_then(|path| path.parent())
+        else {
+            return Ok(());
+        };
+        let (root, is_cwd) = helix_loader::find_workspace_in(dir);
+        if is_cwd {
+            return Ok(());
+        }
+        if open_excludes.load().is_match(&root) {
+            return Ok(());
+        }
+
+        let projects = event.editor.projects.clone();
+        tokio::task::spawn_blocking(move || {
+            let added = projects.with_store(|store| {
+                // New projects are written right away for the other instances' pickers.
+                let added = store.touch(&root);
+                if added {
+                    store.flush()?;
+                } else {
+                    store.flush_if_due()?;
+                }
+                Ok(added)
+            });
+            match added {
+                Ok(true) => job::dispatch_blocking(|editor, _| editor.refresh_projects()),
+                Ok(false) => (),
+                Err(err) => log::warn!("Failed to register project {}: {err}", root.display()),
+            }
+        });
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut ConfigDidChange<'_>| {
+        if event.new.project_auto_register_exclude != event.old.project_auto_register_exclude {
+            excludes.store(Arc::new(exclude_globs(
+                &event.new.project_auto_register_exclude,
+            )));
+        }
+        Ok(())
+    });
+}
+
+fn exclude_globs(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = helix_stdx::path::expand_tilde(Path::new(pattern));
+        match Glob::new(&pattern.to_string_lossy()) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(err) => log::warn!("Invalid project exclude pattern {pattern:?}: {err}"),
+        }
+    }
+    builder.build().unwrap_or_else(|err| {
+        log::warn!("Failed to build project exclude patterns: {err}");
+        GlobSet::empty()
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectHook {
+    Open,
+    Close,
+}
+
+impl fmt::Display for ProjectHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Open => "on-open",
+            Self::Close => "on-close",
+        })
+    }
+}
+
+/// Runs the given hooks of the projects one after another in the background, each with its
+/// project root as the working directory. A project without its own hook runs the default of
+/// `editor.project-hooks`, if any.
+///
+/// The output of the commands is logged. A failure or timeout is shown as a warning but doesn't
+/// affect the switch to or from the project, nor the hooks after it.
+pub fn run_project_hooks(editor: &Editor, hooks: &[(&Project, ProjectHook)]) {
+    let config = editor.config();
+    let commands: Vec<_> = hooks
+        .iter()
+        .filter_map(|&(project, hook)| {
+            let (own, default) = match hook {
+                ProjectHook::Open => (&project.on_open, &config.project_hooks.on_open),
+                ProjectHook::Close => (&project.on_close, &config.project_hooks.on_close),
+            };
+            let command = own.as_ref().or(default.as_ref())?.clone();
+            let name = project.display_name().unwrap_or_default().to_string();
+            Some((hook, command, project.path.clone(), name))
+        })
+        .collect();
+    if commands.is_empty() {
+        return;
+    }
+    let shell = config.shell.clone();
+    let timeout = config.project_hooks.timeout;
+
+    tokio::spawn(async move {
+        for (hook, command, path, name) in commands {
+            if let Err(err) = run_hook_command(&shell, &command, &path, &name, timeout).await {
+                log::warn!("Project {hook} hook of {} failed: {err}", path.display());
+                job::dispatch(move |editor, _| {
+                    editor.set_warning(format!("Project {hook} hook failed: {err}"));
+                })
+                .await;
+            }
+        }
+    });
+}
+
+async fn run_hook_command(
+    shell: &[String],
+    command: &str,
+    path: &Path,
+    name: &str,
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    ensure!(!shell.is_empty(), "No shell set");
+    let mut process = tokio::process::Command::new(&shell[0]);
+    process
+        .args(&shell[1..])
+        .arg(command)
+        .current_dir(path)
+        .env("HELIX_PROJECT_PATH", path)
+        .env("HELIX_PROJECT_NAME", name)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let output = tokio::time::timeout(timeout, process.output())
+        .await
+        .map_err(|_| anyhow!("`{command}` timed out after {}ms", timeout.as_millis()))?
+        .map_err(|err| anyhow!("Failed to run `{command}`: {err}"))?;
+    for (stream, content) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+        if !content.is_empty() {
+            log::info!(
+                "Project hook `{command}` {stream}: {}",
+                String::from_utf8_lossy(content).trim_end()
+            );
+        }
+    }
+    if !output.status.success() {
+        match output.status.code() {
+            Some(code) => bail!("`{command}` exited with status {code}"),
+            None => bail!("`{command}` was terminated"),
+        }
+    }
+    Ok(())
+}
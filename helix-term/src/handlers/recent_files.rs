@@ -0,0 +1,62 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use helix_event::register_hook;
+use helix_loader::recent_files::RecentFilesStore;
+use helix_view::{events::DocumentDidOpen, handlers::Handlers, Editor};
+
+/// The file accesses recorded by the editor, written in batches so that opening a buffer doesn't
+/// rewrite the recent files of the project.
+static STORE: Mutex<Option<RecentFilesStore>> = Mutex::new(None);
+
+fn with_store<T>(f: impl FnOnce(&mut RecentFilesStore) -> T) -> T {
+    let mut store = STORE.lock().unwrap();
+    f(store.get_or_insert_with(RecentFilesStore::default))
+}
+
+/// Records an access to the file at `path` in the recent files of the current project when
+/// `editor.project-recent-files` is enabled.
+pub fn record(editor: &Editor, path: &Path) {
+    if !editor.config().project_recent_files {
+        return;
+    }
+    let (project, _) = helix_loader::find_workspace();
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        with_store(|store| {
+            if store.record(&project, &path) {
+                if let Err(err) = store.flush_if_due() {
+                    log::warn!("Failed to save recent files: {err}");
+                }
+            }
+        })
+    });
+}
+
+/// The recent files of the project at `project`, including the accesses which are not written
+/// yet, see [helix_loader::recent_files::recent_files].
+pub fn recent_files(project: &Path) -> Vec<(PathBuf, u64)> {
+    with_store(|store| store.recent_files(project))
+}
+
+/// Writes the pending file accesses. Called when the editor exits.
+pub fn flush() {
+    if let Some(store) = STORE.lock().unwrap().as_mut() {
+        if let Err(err) = store.flush() {
+            log::warn!("Failed to save recent files: {err}");
+        }
+    }
+}
+
+/// Records the files of opened documents. Written documents are recorded by
+/// `Application::handle_document_write`.
+pub(super) fn register_hooks(_handlers: &Handlers) {
+    register_hook!(move |event: &mut DocumentDidOpen<'_>| {
+        if let Some(path) = event.editor.document(event.doc).and_then(|doc| doc.path()) {
+            record(event.editor, path);
+        }
+        Ok(())
+    });
+}
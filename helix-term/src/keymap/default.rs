@@ -235,6 +235,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "D" => workspace_diagnostics_picker,
             "g" => changed_file_picker,
             "a" => code_action,
+            "l" => code_lens,
             "'" => last_picker,
             "G" => { "Debug (experimental)" sticky=true
                 "l" => dap_launch,
@@ -288,10 +289,16 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "k" => hover,
             "r" => rename_symbol,
             "h" => select_references_to_symbol_under_cursor,
+            "i" => incoming_calls,
+            "o" => outgoing_calls,
+            "t" => { "Type hierarchy"
+                "h" => supertypes,
+                "s" => subtypes,
+            },
             "c" => toggle_comments,
             "C" => toggle_block_comments,
             "A-c" => toggle_line_comments,
-            "?" => command_palette,
+            "space" | "?" => command_palette,
         },
         "z" => { "View"
             "z" | "c" => align_view_center,
@@ -12,38 +12,18 @@
 pub mod keymap;
 pub mod ui;
 
-#[cfg(not(windows))]
-use std::env::var_os;
-
 use std::path::Path;
 
 use futures_util::Future;
 mod handlers;
 
+use helix_view::Editor;
+use ignore::gitignore::Gitignore;
 use ignore::DirEntry;
 use url::Url;
 
-#[cfg(windows)]
 fn true_color() -> bool {
-    true
-}
-
-#[cfg(not(windows))]
-fn true_color() -> bool {
-    if var_os("COLORTERM").is_some_and(|v| v == "truecolor" || v == "24bit")
-        || var_os("WSL_DISTRO_NAME").is_some()
-    {
-        return true;
-    }
-
-    match termini::TermInfo::from_env() {
-        Ok(t) => {
-            t.extended_cap("RGB").is_some()
-                || t.extended_cap("Tc").is_some()
-                || (t.extended_cap("setrgbf").is_some() && t.extended_cap("setrgbb").is_some())
-        }
-        Err(_) => false,
-    }
+    tui::backend::ColorDepth::from_env() == tui::backend::ColorDepth::TrueColor
 }
 
 /// Function used for filtering dir entries in the various file pickers.
@@ -71,6 +51,39 @@ fn filter_picker_entry(entry: &DirEntry, root: &Path, dedup_symlinks: bool) -> b
     true
 }
 
+/// The matcher for the `ignore` patterns of the registered project containing `root`, which the
+/// file picker and global search leave out. Invalid patterns are skipped with a warning.
+fn project_ignore_matcher(editor: &mut Editor, root: &Path) -> Option<Gitignore> {
+    let project = editor
+        .projects
+        .read(|store| {
+            helix_loader::projects::find_project_for_path(store.projects(), root).cloned()
+        })
+        .unwrap_or_else(|err| {
+            log::warn!("Failed to load projects: {err}");
+            None
+        })?;
+    let (matcher, errors) = helix_loader::projects::project_ignore_matcher(&project);
+    for err in &errors {
+        log::warn!(
+            "Invalid ignore pattern of {}: {err}",
+            project.path.display()
+        );
+    }
+    if let Some(err) = errors.first() {
+        editor.set_warning(format!("Skipped invalid project ignore pattern: {err}"));
+    }
+    matcher
+}
+
+/// Whether `entry` matches the `ignore` patterns of its project, see [project_ignore_matcher].
+fn is_project_ignored(matcher: &Gitignore, entry: &DirEntry) -> bool {
+    let is_dir = entry
+        .file_type()
+        .is_some_and(|file_type| file_type.is_dir());
+    entry.path().starts_with(matcher.path()) && matcher.matched(entry.path(), is_dir).is_ignore()
+}
+
 /// Opens URL in external program.
 fn open_external_url_callback(
     url: Url,
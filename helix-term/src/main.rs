@@ -1,8 +1,9 @@
 use anyhow::{Context, Error, Result};
 use helix_loader::VERSION_AND_GIT_HASH;
 use helix_term::application::Application;
-use helix_term::args::Args;
+use helix_term::args::{Args, ProjectListFormat};
 use helix_term::config::{Config, ConfigLoadError};
+use std::io::Write;
 
 fn setup_logging(verbosity: u64) -> Result<()> {
     let mut base_config = fern::Dispatch::new();
@@ -75,6 +76,19 @@ async fn main_impl() -> Result<i32> {
     --vsplit                       Split all given files vertically into different windows
     --hsplit                       Split all given files horizontally into different windows
     -w, --working-dir <path>       Specify an initial working directory
+    --project <name-or-path>       Open a registered project, matched by name, name prefix, fuzzy
+                                   name or path suffix, or a directory under the roots of
+                                   $HELIX_PROJECT_PATHS. Files are relative to the project root
+    --list-projects                Print the registered projects which aren't archived, one per
+                                   line as name<TAB>path<TAB>last-accessed
+    --format {{tsv|json}}            Set the output format of --list-projects
+    --scan-projects <path>         Register the git repositories within a directory which aren't
+                                   registered yet and print their paths
+    --max-depth <n>                Only look <n> directories deep for --scan-projects
+    --exclude <name>               Don't look into directories named <name> for --scan-projects.
+                                   Can be given several times
+    --max-repos <n>                Stop --scan-projects after finding <n> repositories
+    --dry-run                      Print what --scan-projects would register without registering
     +[N]                           Open the first given file at line number N, or the last line, if
                                    N is not specified.
 ",
@@ -104,6 +118,61 @@ async fn main_impl() -> Result<i32> {
         std::process::exit(0);
     }
 
+    if args.list_projects {
+        let projects = helix_loader::projects::list_projects(false)?;
+        let output = match args.project_list_format {
+            ProjectListFormat::Tsv => helix_loader::projects::format_project_lines(&projects),
+            ProjectListFormat::Json => serde_json::to_string_pretty(&projects)? + "\n",
+        };
+        let mut stdout = std::io::stdout().lock();
+        if let Err(err) = stdout.write_all(output.as_bytes()) {
+            // Ignore closed pipes like `--health`, for example when piping to `head`.
+            if err.kind() != std::io::ErrorKind::BrokenPipe {
+                return Err(err.into());
+            }
+        }
+        return Ok(0);
+    }
+
+    if let Some(root) = &args.scan_projects {
+        // Only a root which can't be scanned at all fails, unreadable directories below it are
+        // reported along with the repositories which were found.
+        let report = if args.scan_dry_run {
+            helix_loader::projects::scan_for_registration(root, &args.scan_options)?
+        } else {
+            helix_loader::projects::scan_and_register(root, &args.scan_options)?
+        };
+        for err in &report.errors {
+            eprintln!("{err}");
+        }
+        let mut output = String::new();
+        for path in &report.added {
+            output.push_str(&path.to_string_lossy());
+            output.push('\n');
+        }
+        output.push_str(&format!(
+            "{} {}, {} already present, {} errors\n",
+            report.added.len(),
+            if args.scan_dry_run { "to add" } else { "added" },
+            report.known.len(),
+            report.errors.len()
+        ));
+        if report.truncated {
+            eprintln!(
+                "Stopped after {} repositories, there may be more. Use --max-repos to raise \
+                 the limit or --scan-projects on a subdirectory",
+                report.added.len() + report.known.len()
+            );
+        }
+        let mut stdout = std::io::stdout().lock();
+        if let Err(err) = stdout.write_all(output.as_bytes()) {
+            if err.kind() != std::io::ErrorKind::BrokenPipe {
+                return Err(err.into());
+            }
+        }
+        return Ok(0);
+    }
+
     if args.fetch_grammars {
         helix_loader::grammar::fetch_grammars()?;
         return Ok(0);
@@ -120,6 +189,15 @@ async fn main_impl() -> Result<i32> {
     // Application::new() depends on this logic so it must be updated if this changes.
     if let Some(path) = &args.working_directory {
         helix_stdx::env::set_current_working_dir(path)?;
+        if let Some(project) = &args.project {
+            if let Err(err) = helix_loader::projects::update_projects(|projects| {
+                let updated =
+                    helix_loader::projects::update_project_last_accessed(projects, project);
+                (updated, ())
+            }) {
+                log::warn!("Failed to update project last accessed time: {}", err);
+            }
+        }
     } else if let Some((path, _)) = args.files.first().filter(|p| p.0.is_dir()) {
         // If the first file is a directory, it will be the working directory unless -w was specified
         helix_stdx::env::set_current_working_dir(path)?;
@@ -140,6 +218,14 @@ async fn main_impl() -> Result<i32> {
         }
     };
 
+    if let Some(project) = args.project.as_ref().filter(|_| args.ambient_project) {
+        if config.editor.project_register_ambient {
+            if let Err(err) = helix_loader::projects::touch_project(project) {
+                log::warn!("Failed to register project {}: {err}", project.display());
+            }
+        }
+    }
+
     let lang_loader = helix_core::config::user_lang_loader().unwrap_or_else(|err| {
         eprintln!("{}", err);
         eprintln!("Press <ENTER> to continue with default language config");
@@ -0,0 +1,156 @@
+//! The command palette, a picker of the commands along with their key bindings.
+
+use std::collections::{HashMap, HashSet};
+
+use helix_view::document::Mode;
+use helix_view::editor::Action;
+
+use crate::commands::{typed::TYPABLE_COMMAND_LIST, MappableCommand};
+use crate::compositor::Context;
+use crate::keymap::{KeyTrie, ReverseKeymap};
+use crate::ui::{Picker, PickerColumn};
+
+/// The commands listed by the command palette in a mode, with their key bindings in that mode.
+pub struct CommandPalette {
+    commands: Vec<MappableCommand>,
+    bindings: ReverseKeymap,
+}
+
+impl CommandPalette {
+    /// Lists the static commands from [MappableCommand::STATIC_COMMAND_LIST] followed by the
+    /// typable commands. Static commands which are only bound in the keymaps of other modes
+    /// than `mode`, such as `insert_tab` in normal mode, are left out.
+    pub fn new(keymaps: &HashMap<Mode, KeyTrie>, mode: Mode) -> Self {
+        let bindings = keymaps
+            .get(&mode)
+            .map(KeyTrie::reverse_map)
+            .unwrap_or_default();
+        let other_modes: HashSet<String> = keymaps
+            .iter()
+            .filter(|(other, _)| **other != mode)
+            .flat_map(|(_, keymap)| keymap.reverse_map().into_keys())
+            .collect();
+
+        let commands = MappableCommand::STATIC_COMMAND_LIST
+            .iter()
+            .filter(|command| {
+                bindings.contains_key(command.name()) || !other_modes.contains(command.name())
+            })
+            .cloned()
+            .chain(
+                TYPABLE_COMMAND_LIST
+                    .iter()
+                    .map(|cmd| MappableCommand::Typable {
+                        name: cmd.name.to_owned(),
+                        args: String::new(),
+                        doc: cmd.doc.to_owned(),
+                    }),
+            )
+            .collect();
+
+        Self { commands, bindings }
+    }
+
+    pub fn commands(&self) -> &[MappableCommand] {
+        &self.commands
+    }
+
+    /// A picker of the commands, showing their name, key bindings and documentation.
+    /// `callback_fn` is called with the chosen command.
+    pub fn into_picker<F>(self, callback_fn: F) -> Picker<MappableCommand, ReverseKeymap>
+    where
+        F: Fn(&mut Context, &MappableCommand, Action) + 'static,
+    {
+        let columns = [
+            PickerColumn::new("name", |item, _| match item {
+                MappableCommand::Typable { name, .. } => format!(":{name}").into(),
+                MappableCommand::Static { name, .. } => (*name).into(),
+                MappableCommand::Macro { .. } => {
+                    unreachable!("macros aren't included in the command palette")
+                }
+            }),
+            PickerColumn::new(
+                "bindings",
+                |item: &MappableCommand, keymap: &ReverseKeymap| {
+                    keymap
+                        .get(item.name())
+                        .map(|bindings| {
+                            bindings.iter().fold(String::new(), |mut acc, bind| {
+                                if !acc.is_empty() {
+                                    acc.push(' ');
+                                }
+                                for key in bind {
+                                    acc.push_str(&key.key_sequence_format());
+                                }
+                                acc
+                            })
+                        })
+                        .unwrap_or_default()
+                        .into()
+                },
+            ),
+            PickerColumn::new("doc", |item: &MappableCommand, _| item.doc().into()),
+        ];
+
+        Picker::new(columns, 0, self.commands, self.bindings, callback_fn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(palette: &CommandPalette) -> HashSet<String> {
+        palette
+            .commands()
+            .iter()
+            .map(|command| command.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn command_palette_lists_all_commands() {
+        let keymaps = crate::keymap::default::default();
+        let modes = [Mode::Normal, Mode::Select, Mode::Insert];
+        let palettes: Vec<_> = modes
+            .iter()
+            .map(|&mode| names(&CommandPalette::new(&keymaps, mode)))
+            .collect();
+
+        // Every static command is listed in at least one mode.
+        for command in MappableCommand::STATIC_COMMAND_LIST {
+            assert!(
+                palettes.iter().any(|names| names.contains(command.name())),
+                "{} is missing from the command palette",
+                command.name()
+            );
+        }
+        // Typable commands are listed in every mode.
+        for (mode, names) in modes.iter().zip(&palettes) {
+            for command in TYPABLE_COMMAND_LIST {
+                assert!(
+                    names.contains(command.name),
+                    ":{} is missing from the command palette in {mode} mode",
+                    command.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn command_palette_filters_by_mode() {
+        let keymaps = crate::keymap::default::default();
+        let normal = names(&CommandPalette::new(&keymaps, Mode::Normal));
+        let insert = names(&CommandPalette::new(&keymaps, Mode::Insert));
+
+        // Bound in normal mode only.
+        assert!(normal.contains("split_selection"));
+        assert!(!insert.contains("split_selection"));
+        // Bound in insert mode only.
+        assert!(insert.contains("insert_tab"));
+        assert!(!normal.contains("insert_tab"));
+        // Unbound commands are listed in every mode.
+        assert!(normal.contains("project_buffer_picker"));
+        assert!(insert.contains("project_buffer_picker"));
+    }
+}
@@ -268,12 +268,17 @@ macro_rules! language_server {
                     // TODO: add additional _edits to completion_changes?
                     if let Some((additional_edits, offset_encoding)) = additional_edits {
                         if !additional_edits.is_empty() {
-                            let transaction = util::generate_transaction_from_edits(
+                            match util::generate_transaction_from_edits(
                                 doc.text(),
                                 additional_edits,
                                 offset_encoding, // TODO: should probably transcode in Client
-                            );
-                            doc.apply(&transaction, view.id);
+                            ) {
+                                Ok(transaction) => doc.apply(&transaction, view.id),
+                                Err(err) => {
+                                    log::error!("invalid additional completion edits: {err}");
+                                    false
+                                }
+                            };
                         }
                     }
                     // we could have just inserted a trigger char (like a `crate::` completion for rust
@@ -604,12 +609,13 @@ fn lsp_item_to_transaction(
             }
         };
 
-        let Some(range) = util::lsp_range_to_range(doc.text(), edit.range, offset_encoding) else {
+        let Some(range) = util::lsp_range_to_char_range(edit.range, doc.text(), offset_encoding)
+        else {
             return (Transaction::new(doc.text()), None);
         };
 
-        let start_offset = range.anchor as i128 - primary_cursor as i128;
-        let end_offset = range.head as i128 - primary_cursor as i128;
+        let start_offset = range.start as i128 - primary_cursor as i128;
+        let end_offset = range.end as i128 - primary_cursor as i128;
 
         (Some((start_offset, end_offset)), edit.new_text)
     } else {
@@ -3,7 +3,7 @@
 use helix_core::doc_formatter::{DocumentFormatter, GraphemeSource, TextFormat};
 use helix_core::graphemes::Grapheme;
 use helix_core::str_utils::char_to_byte_idx;
-use helix_core::syntax::{self, HighlightEvent, Highlighter, OverlayHighlights};
+use helix_core::syntax::{self, HighlightEvent, Highlighter, OverlayHighlights, QueryDeadline};
 use helix_core::text_annotations::TextAnnotations;
 use helix_core::{visual_offset_from_block, Position, RopeSlice};
 use helix_stdx::rope::RopeSliceExt;
@@ -177,6 +177,8 @@ pub struct TextRenderer<'a> {
     surface: &'a mut Surface,
     pub text_style: Style,
     pub whitespace_style: Style,
+    /// The style of non-breaking spaces, which are easily mistaken for regular spaces.
+    pub nbsp_style: Style,
     pub indent_guide_char: String,
     pub indent_guide_style: Style,
     pub newline: String,
@@ -256,6 +258,7 @@ pub fn new(
             tab,
             virtual_tab,
             whitespace_style: theme.get("ui.virtual.whitespace"),
+            nbsp_style: theme.get("ui.virtual.whitespace.nbsp"),
             indent_width,
             starting_indent: offset.col / indent_width as usize
                 + (offset.col % indent_width as usize != 0) as usize
@@ -288,7 +291,7 @@ pub fn draw_decoration_grapheme(
         row -= self.offset.row as u16;
         // TODO is it correct to apply the whitspace style to all unicode white spaces?
         if grapheme.is_whitespace() {
-            style = style.patch(self.whitespace_style);
+            style = style.patch(self.whitespace_style_of(&grapheme));
         }
 
         let grapheme = match grapheme {
@@ -330,7 +333,7 @@ pub fn draw_grapheme(
         // TODO is it correct to apply the whitespace style to all unicode white spaces?
         let mut style = grapheme_style.syntax_style;
         if is_whitespace {
-            style = style.patch(self.whitespace_style);
+            style = style.patch(self.whitespace_style_of(&grapheme));
         }
         style = style.patch(grapheme_style.overlay_style);
 
@@ -383,6 +386,13 @@ pub fn draw_grapheme(
         width
     }
 
+    fn whitespace_style_of(&self, grapheme: &Grapheme) -> Style {
+        match grapheme {
+            Grapheme::Other { g } if g == "\u{00A0}" => self.nbsp_style,
+            _ => self.whitespace_style,
+        }
+    }
+
     pub fn column_in_bounds(&self, colum: usize, width: usize) -> bool {
         self.offset.col <= colum && colum + width <= self.offset.col + self.viewport.width as usize
     }
@@ -480,6 +490,7 @@ struct SyntaxHighlighter<'h, 'r, 't> {
     theme: &'t Theme,
     text_style: Style,
     style: Style,
+    deadline: QueryDeadline,
 }
 
 impl<'h, 'r, 't> SyntaxHighlighter<'h, 'r, 't> {
@@ -496,6 +507,7 @@ fn new(
             theme,
             style: text_style,
             text_style,
+            deadline: QueryDeadline::new("highlights"),
         };
         highlighter.update_pos();
         highlighter
@@ -521,8 +533,15 @@ fn advance(&mut self) {
         let Some(highlighter) = self.inner.as_mut() else {
             return;
         };
+        // The rest of the text is rendered without highlights if the query takes too long.
+        if self.deadline.is_exceeded() {
+            self.inner = None;
+            self.style = self.text_style;
+            self.update_pos();
+            return;
+        }
 
-        let (event, highlights) = highlighter.advance();
+        let (event, highlights) = self.deadline.time(|| highlighter.advance());
         let base = match event {
             HighlightEvent::Refresh => self.text_style,
             HighlightEvent::Push => self.style,
@@ -572,3 +591,62 @@ fn advance(&mut self) {
         self.update_pos();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arc_swap::ArcSwap;
+    use helix_core::Rope;
+    use helix_view::editor::{Config, WhitespaceRender};
+    use helix_view::graphics::Color;
+
+    use super::*;
+
+    #[test]
+    fn render_nbsp() {
+        let config = Config {
+            whitespace: WhitespaceConfig {
+                render: WhitespaceRender::Basic(WhitespaceRenderValue::All),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let doc = Document::from(
+            Rope::from("a b\u{00A0}c\n"),
+            None,
+            Arc::new(ArcSwap::from_pointee(config)),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let theme: Theme = toml::from_str(
+            r#"
+            "ui.virtual.whitespace" = "gray"
+            "ui.virtual.whitespace.nbsp" = "red"
+            "#,
+        )
+        .unwrap();
+        let area = Rect::new(0, 0, 10, 1);
+        let mut surface = Surface::empty(area);
+        let mut renderer = TextRenderer::new(&mut surface, &doc, &theme, Position::new(0, 0), area);
+
+        for (col, g) in ["a", " ", "b", "\u{00A0}", "c"].into_iter().enumerate() {
+            let grapheme = Grapheme::new(g.into(), col, 4);
+            renderer.draw_grapheme(
+                grapheme,
+                GraphemeStyle {
+                    syntax_style: Style::default(),
+                    overlay_style: Style::default(),
+                },
+                false,
+                &mut 0,
+                &mut false,
+                Position::new(0, col),
+            );
+        }
+
+        assert_eq!(surface[(1, 0)].symbol, "·");
+        assert_eq!(surface[(1, 0)].fg, Color::Gray);
+        assert_eq!(surface[(3, 0)].symbol, "⍽");
+        assert_eq!(surface[(3, 0)].fg, Color::Red);
+    }
+}
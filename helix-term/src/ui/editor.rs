@@ -8,7 +8,7 @@
     ui::{
         document::{render_document, LinePos, TextRenderer},
         statusline,
-        text_decorations::{self, Decoration, DecorationManager, InlineDiagnostics},
+        text_decorations::{self, CodeLenses, Decoration, DecorationManager, InlineDiagnostics},
         Completion, ProgressSpinners,
     },
 };
@@ -22,6 +22,7 @@
     unicode::width::UnicodeWidthStr,
     visual_offset_from_block, Change, Position, Range, Selection, Transaction,
 };
+use helix_stdx::rope::{self, RopeSliceExt};
 use helix_view::{
     annotations::diagnostics::DiagnosticFilter,
     document::{Mode, SCRATCH_BUFFER_NAME},
@@ -31,7 +32,7 @@
     keyboard::{KeyCode, KeyModifiers},
     Document, Editor, Theme, View,
 };
-use std::{mem::take, num::NonZeroUsize, ops, path::PathBuf, rc::Rc};
+use std::{borrow::Cow, mem::take, num::NonZeroUsize, ops, path::PathBuf, rc::Rc};
 
 use tui::{buffer::Buffer as Surface, text::Span};
 
@@ -141,6 +142,14 @@ pub fn render_view(
 
         Self::doc_diagnostics_highlights_into(doc, theme, &mut overlays);
 
+        if let Some(regex) = &editor.search_highlight {
+            if let Some(overlay) =
+                Self::search_match_highlights(doc, regex, view_offset.anchor, inner.height, theme)
+            {
+                overlays.push(overlay);
+            }
+        }
+
         if is_focused {
             if let Some(tabstops) = Self::tabstop_highlights(doc, theme) {
                 overlays.push(tabstops);
@@ -188,14 +197,21 @@ pub fn render_view(
         let enable_cursor_line = view
             .diagnostics_handler
             .show_cursorline_diagnostics(doc, view.id);
-        let inline_diagnostic_config = config.inline_diagnostics.prepare(width, enable_cursor_line);
-        decorations.add_decoration(InlineDiagnostics::new(
-            doc,
-            theme,
-            primary_cursor,
-            inline_diagnostic_config,
-            config.end_of_line_diagnostics,
-        ));
+        // Zen mode hides the diagnostics which aren't part of the text.
+        if view.zen.is_none() {
+            let inline_diagnostic_config =
+                config.inline_diagnostics.prepare(width, enable_cursor_line);
+            decorations.add_decoration(InlineDiagnostics::new(
+                doc,
+                theme,
+                primary_cursor,
+                inline_diagnostic_config,
+                config.end_of_line_diagnostics,
+            ));
+        }
+        if config.lsp.code_lens && !doc.code_lenses.is_empty() {
+            decorations.add_decoration(CodeLenses::new(doc, theme));
+        }
         render_document(
             surface,
             inner,
@@ -207,6 +223,9 @@ pub fn render_view(
             theme,
             decorations,
         );
+        if config.bidi {
+            Self::reorder_bidi(editor, doc, view, inner, surface, is_focused);
+        }
 
         // if we're not at the edge of the screen, draw a right border
         if viewport.right() != view.area.right() {
@@ -220,12 +239,17 @@ pub fn render_view(
             }
         }
 
-        if config.inline_diagnostics.disabled()
+        if view.zen.is_none()
+            && config.inline_diagnostics.disabled()
             && config.end_of_line_diagnostics == DiagnosticFilter::Disable
         {
             Self::render_diagnostics(doc, view, inner, surface, theme);
         }
 
+        if view.statusline_height() == 0 {
+            return;
+        }
+
         let statusline_area = view
             .area
             .clip_top(view.area.height.saturating_sub(1))
@@ -237,6 +261,33 @@ pub fn render_view(
         statusline::render(&mut context, statusline_area, surface);
     }
 
+    /// Reorders the rendered lines of the view into visual order so that right-to-left text
+    /// reads correctly, moving the cached cursor position along.
+    fn reorder_bidi(
+        editor: &Editor,
+        doc: &Document,
+        view: &View,
+        inner: Rect,
+        surface: &mut Surface,
+        is_focused: bool,
+    ) {
+        let cursor = is_focused
+            .then(|| editor.cursor_cache.get(view, doc))
+            .flatten();
+        for row in 0..inner.height {
+            let line = Rect::new(inner.x, inner.y + row, inner.width, 1);
+            let Some(columns) = surface.reorder_bidi_line(line) else {
+                continue;
+            };
+            if let Some(mut pos) = cursor.filter(|pos| pos.row == row as usize) {
+                if let Some(&col) = columns.get(pos.col) {
+                    pos.col = col as usize;
+                    editor.cursor_cache.set(Some(pos));
+                }
+            }
+        }
+    }
+
     pub fn render_rulers(
         editor: &Editor,
         doc: &Document,
@@ -334,7 +385,33 @@ pub fn doc_rainbow_highlights(
         .map_or(visible_range.start as u32, |node| node.start_byte());
         let range = start..visible_range.end as u32;
 
-        Some(syntax.rainbow_highlights(text, theme.rainbow_length(), loader, range))
+        Some(syntax.rainbow_highlights(
+            text,
+            theme.rainbow_length(),
+            theme.find_highlight_exact("error"),
+            loader,
+            range,
+        ))
+    }
+
+    /// Get highlight spans for the matches of the active search within the viewport.
+    pub fn search_match_highlights(
+        doc: &Document,
+        regex: &rope::Regex,
+        anchor: usize,
+        height: u16,
+        theme: &Theme,
+    ) -> Option<OverlayHighlights> {
+        let highlight = theme.find_highlight_exact("ui.search.match")?;
+        let text = doc.text().slice(..);
+        let row = text.char_to_line(anchor.min(text.len_chars()));
+        let range = Self::viewport_byte_range(text, row, height);
+        let ranges = regex
+            .find_iter(text.regex_input_at_bytes(range))
+            .filter(|mat| !mat.is_empty())
+            .map(|mat| text.byte_to_char(mat.start())..text.byte_to_char(mat.end()))
+            .collect();
+        Some(OverlayHighlights::Homogeneous { highlight, ranges })
     }
 
     /// Get highlight spans for document diagnostics
@@ -644,6 +721,65 @@ pub fn render_bufferline(editor: &Editor, viewport: Rect, surface: &mut Surface)
         }
     }
 
+    /// Render tabline at the top
+    pub fn render_tabline(editor: &Editor, viewport: Rect, surface: &mut Surface) {
+        surface.clear_with(
+            viewport,
+            editor
+                .theme
+                .try_get("ui.tabline.background")
+                .or_else(|| editor.theme.try_get("ui.bufferline.background"))
+                .unwrap_or_else(|| editor.theme.get("ui.statusline")),
+        );
+
+        let tabline_active = editor
+            .theme
+            .try_get("ui.tabline.active")
+            .or_else(|| editor.theme.try_get("ui.bufferline.active"))
+            .unwrap_or_else(|| editor.theme.get("ui.statusline.active"));
+
+        let tabline_inactive = editor
+            .theme
+            .try_get("ui.tabline")
+            .or_else(|| editor.theme.try_get("ui.bufferline"))
+            .unwrap_or_else(|| editor.theme.get("ui.statusline.inactive"));
+
+        let mut x = viewport.x;
+
+        for (index, tab) in editor.tabs.iter().enumerate() {
+            let name = match &tab.name {
+                Some(name) => Cow::Borrowed(name.as_str()),
+                None => editor
+                    .tree
+                    .try_get(editor.tab_focus(index))
+                    .and_then(|view| editor.document(view.doc))
+                    .map(|doc| {
+                        doc.path()
+                            .and_then(|path| path.file_name())
+                            .map_or(SCRATCH_BUFFER_NAME.into(), |name| name.to_string_lossy())
+                    })
+                    .unwrap_or(SCRATCH_BUFFER_NAME.into()),
+            };
+
+            let style = if index == editor.active_tab {
+                tabline_active
+            } else {
+                tabline_inactive
+            };
+
+            let text = format!(" {}: {} ", index + 1, name);
+            let rem_width = viewport.right().saturating_sub(x);
+
+            x = surface
+                .set_stringn(x, viewport.y, text, rem_width as usize, style)
+                .0;
+
+            if x >= viewport.right() {
+                break;
+            }
+        }
+    }
+
     pub fn render_gutter<'d>(
         editor: &'d Editor,
         doc: &'d Document,
@@ -728,7 +864,7 @@ pub fn render_diagnostics(
             .primary()
             .cursor(doc.text().slice(..));
 
-        let diagnostics = doc.diagnostics().iter().filter(|diagnostic| {
+        let diagnostics = doc.unique_diagnostics().filter(|diagnostic| {
             diagnostic.range.start <= cursor && diagnostic.range.end >= cursor
         });
 
@@ -1136,7 +1272,7 @@ fn handle_mouse_event(
         } = *event;
 
         let pos_and_view = |editor: &Editor, row, column, ignore_virtual_text| {
-            editor.tree.views().find_map(|(view, _focus)| {
+            editor.tree.traverse().find_map(|(_, view)| {
                 view.pos_at_screen_coords(
                     &editor.documents[&view.doc],
                     row,
@@ -1148,7 +1284,7 @@ fn handle_mouse_event(
         };
 
         let gutter_coords_and_view = |editor: &Editor, row, column| {
-            editor.tree.views().find_map(|(view, _focus)| {
+            editor.tree.traverse().find_map(|(_, view)| {
                 view.gutter_coords_at_screen_coords(row, column)
                     .map(|coords| (coords, view.id))
             })
@@ -1527,16 +1663,22 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         surface.set_style(area, cx.editor.theme.get("ui.background"));
         let config = cx.editor.config();
 
-        // check if bufferline should be rendered
+        // check if tabline and bufferline should be rendered, zen mode hides both
         use helix_view::editor::BufferLine;
-        let use_bufferline = match config.bufferline {
-            BufferLine::Always => true,
-            BufferLine::Multiple if cx.editor.documents.len() > 1 => true,
-            _ => false,
-        };
+        let zen_mode = cx.editor.zen_mode_active();
+        let use_tabline = config.tabs && !zen_mode;
+        let use_bufferline = !zen_mode
+            && match config.bufferline {
+                BufferLine::Always => true,
+                BufferLine::Multiple if cx.editor.documents.len() > 1 => true,
+                _ => false,
+            };
 
-        // -1 for commandline and -1 for bufferline
+        // -1 for commandline, -1 for tabline and -1 for bufferline
         let mut editor_area = area.clip_bottom(1);
+        if use_tabline {
+            editor_area = editor_area.clip_top(1);
+        }
         if use_bufferline {
             editor_area = editor_area.clip_top(1);
         }
@@ -1544,13 +1686,19 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         // if the terminal size suddenly changed, we need to trigger a resize
         cx.editor.resize(editor_area);
 
+        let mut top_area = area.with_height(1);
+        if use_tabline {
+            Self::render_tabline(cx.editor, top_area, surface);
+            top_area = top_area.clip_top(1).with_height(1);
+        }
         if use_bufferline {
-            Self::render_bufferline(cx.editor, area.with_height(1), surface);
+            Self::render_bufferline(cx.editor, top_area, surface);
         }
 
-        for (view, is_focused) in cx.editor.tree.views() {
+        let focus = cx.editor.tree.focus;
+        for (view_id, view) in cx.editor.tree.traverse() {
             let doc = cx.editor.document(view.doc).unwrap();
-            self.render_view(cx.editor, doc, view, area, surface, is_focused);
+            self.render_view(cx.editor, doc, view, area, surface, view_id == focus);
         }
 
         if config.auto_info {
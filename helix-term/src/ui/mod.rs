@@ -1,3 +1,4 @@
+mod command_palette;
 mod completion;
 mod document;
 pub(crate) mod editor;
@@ -15,8 +16,9 @@
 mod text_decorations;
 
 use crate::compositor::Compositor;
-use crate::filter_picker_entry;
 use crate::job::{self, Callback};
+use crate::{filter_picker_entry, is_project_ignored, project_ignore_matcher};
+pub use command_palette::CommandPalette;
 pub use completion::Completion;
 pub use editor::EditorView;
 use helix_stdx::rope;
@@ -110,6 +112,7 @@ pub fn raw_regex_prompt(
                     let (view, doc) = current!(cx.editor);
                     doc.set_selection(view.id, snapshot.clone());
                     doc.set_view_offset(view.id, offset_snapshot);
+                    cx.editor.search_highlight = None;
                 }
                 PromptEvent::Update | PromptEvent::Validate => {
                     // skip empty input
@@ -201,14 +204,28 @@ fn get_excluded_types() -> ignore::types::Types {
         .expect("failed to build excluded_types")
 }
 
+/// The directories shortened in displayed paths with their aliases, see
+/// [helix_view::editor::Config::path_aliases]. Pickers keep their own copy since their data
+/// outlives the config.
+pub type PathAliases = Vec<(PathBuf, String)>;
+
+pub fn path_aliases(config: &helix_view::editor::Config) -> PathAliases {
+    config
+        .path_aliases()
+        .into_iter()
+        .map(|(dir, alias)| (dir, alias.to_string()))
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct FilePickerData {
     root: PathBuf,
     directory_style: Style,
+    path_aliases: PathAliases,
 }
 type FilePicker = Picker<PathBuf, FilePickerData>;
 
-pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
+pub fn file_picker(editor: &mut Editor, root: PathBuf) -> FilePicker {
     use ignore::WalkBuilder;
     use std::time::Instant;
 
@@ -216,12 +233,14 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
     let data = FilePickerData {
         root: root.clone(),
         directory_style: editor.theme.get("ui.text.directory"),
+        path_aliases: path_aliases(&config),
     };
 
     let now = Instant::now();
 
     let dedup_symlinks = config.file_picker.deduplicate_links;
     let absolute_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+    let project_ignore = project_ignore_matcher(editor, &absolute_root);
 
     let mut walk_builder = WalkBuilder::new(&root);
 
@@ -235,7 +254,12 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
         .git_exclude(config.file_picker.git_exclude)
         .sort_by_file_name(|name1, name2| name1.cmp(name2))
         .max_depth(config.file_picker.max_depth)
-        .filter_entry(move |entry| filter_picker_entry(entry, &absolute_root, dedup_symlinks))
+        .filter_entry(move |entry| {
+            filter_picker_entry(entry, &absolute_root, dedup_symlinks)
+                && !project_ignore
+                    .as_ref()
+                    .is_some_and(|matcher| is_project_ignored(matcher, entry))
+        })
         .add_custom_ignore_filename(helix_loader::config_dir().join("ignore"))
         .add_custom_ignore_filename(".helix/ignore")
         .types(get_excluded_types())
@@ -249,35 +273,13 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
         });
     log::debug!("file_picker init {:?}", Instant::now().duration_since(now));
 
-    let columns = [PickerColumn::new(
-        "path",
-        |item: &PathBuf, data: &FilePickerData| {
-            let path = item.strip_prefix(&data.root).unwrap_or(item);
-            let mut spans = Vec::with_capacity(3);
-            if let Some(dirs) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
-                spans.extend([
-                    Span::styled(dirs.to_string_lossy(), data.directory_style),
-                    Span::styled(std::path::MAIN_SEPARATOR_STR, data.directory_style),
-                ]);
-            }
-            let filename = path
-                .file_name()
-                .expect("normalized paths can't end in `..`")
-                .to_string_lossy();
-            spans.push(Span::raw(filename));
-            Spans::from(spans).into()
-        },
-    )];
-    let picker = Picker::new(columns, 0, [], data, move |cx, path: &PathBuf, action| {
-        if let Err(e) = cx.editor.open(path, action) {
-            let err = if let Some(err) = e.source() {
-                format!("{}", err)
-            } else {
-                format!("unable to open \"{}\"", path.display())
-            };
-            cx.editor.set_error(err);
-        }
-    })
+    let picker = Picker::new(
+        file_picker_columns(),
+        0,
+        [],
+        data,
+        |cx, path: &PathBuf, action| open_picked_file(cx, path, action),
+    )
     .with_preview(|_editor, path| Some((path.as_path().into(), None)));
     let injector = picker.injector();
     let timeout = std::time::Instant::now() + std::time::Duration::from_millis(30);
@@ -304,6 +306,62 @@ pub fn file_picker(editor: &Editor, root: PathBuf) -> FilePicker {
     picker
 }
 
+fn file_picker_columns() -> [PickerColumn<PathBuf, FilePickerData>; 1] {
+    [PickerColumn::new(
+        "path",
+        |item: &PathBuf, data: &FilePickerData| {
+            let Ok(path) = item.strip_prefix(&data.root) else {
+                return helix_core::path::shorten_path(item, &data.path_aliases).into();
+            };
+            let mut spans = Vec::with_capacity(3);
+            if let Some(dirs) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                spans.extend([
+                    Span::styled(dirs.to_string_lossy(), data.directory_style),
+                    Span::styled(std::path::MAIN_SEPARATOR_STR, data.directory_style),
+                ]);
+            }
+            let filename = path
+                .file_name()
+                .expect("normalized paths can't end in `..`")
+                .to_string_lossy();
+            spans.push(Span::raw(filename));
+            Spans::from(spans).into()
+        },
+    )]
+}
+
+fn open_picked_file(
+    cx: &mut crate::compositor::Context,
+    path: &Path,
+    action: helix_view::editor::Action,
+) {
+    if let Err(e) = cx.editor.open(path, action) {
+        let err = if let Some(err) = e.source() {
+            format!("{}", err)
+        } else {
+            format!("unable to open \"{}\"", path.display())
+        };
+        cx.editor.set_error(err);
+    }
+}
+
+/// A picker over `files`, which are shown in the given order and relative to `root`.
+pub fn recent_files_picker(editor: &Editor, root: PathBuf, files: Vec<PathBuf>) -> FilePicker {
+    let data = FilePickerData {
+        root,
+        directory_style: editor.theme.get("ui.text.directory"),
+        path_aliases: path_aliases(&editor.config()),
+    };
+    Picker::new(
+        file_picker_columns(),
+        0,
+        files,
+        data,
+        |cx, path: &PathBuf, action| open_picked_file(cx, path, action),
+    )
+    .with_preview(|_editor, path| Some((path.as_path().into(), None)))
+}
+
 type FileExplorer = Picker<(PathBuf, bool), (PathBuf, Style)>;
 
 pub fn file_explorer(root: PathBuf, editor: &Editor) -> Result<FileExplorer, std::io::Error> {
@@ -424,6 +482,7 @@ pub mod completers {
     use once_cell::sync::Lazy;
     use std::borrow::Cow;
     use std::collections::BTreeSet;
+    use std::path::Path;
     use tui::text::Span;
 
     pub type Completer = fn(&Editor, &str) -> Vec<Completion>;
@@ -461,6 +520,66 @@ pub fn theme(_editor: &Editor, input: &str) -> Vec<Completion> {
             .collect()
     }
 
+    /// Completes names of the bookmarks of the current project.
+    pub fn bookmark(editor: &Editor, input: &str) -> Vec<Completion> {
+        let project = crate::commands::focused_project_root(editor);
+        let names = helix_loader::bookmarks::list_bookmarks(&project)
+            .into_iter()
+            .map(|bookmark| bookmark.name);
+
+        fuzzy_match(input, names, false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
+    /// Completes names of the projects in the persisted projects list. Input with a path
+    /// separator is completed to the full paths of the projects it resolves to, see
+    /// [helix_loader::projects::match_project].
+    pub fn project(editor: &Editor, input: &str) -> Vec<Completion> {
+        let projects = editor.projects.list(true).unwrap_or_default();
+
+        if input.contains(std::path::is_separator) {
+            return helix_loader::projects::match_project(&projects, input)
+                .into_candidates()
+                .into_iter()
+                .map(|project| ((0..), project.path.to_string_lossy().into_owned().into()))
+                .collect();
+        }
+
+        helix_loader::projects::match_projects(&projects, input)
+            .into_iter()
+            .filter_map(|(project, _)| Some(((0..), project.name.clone()?.into())))
+            .collect()
+    }
+
+    pub fn project_setting(_editor: &Editor, input: &str) -> Vec<Completion> {
+        fuzzy_match(input, ["theme"], false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
+    pub fn project_template(_editor: &Editor, input: &str) -> Vec<Completion> {
+        let templates = helix_loader::projects::list_templates();
+
+        fuzzy_match(input, templates, false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
+    pub fn project_import_source(_editor: &Editor, input: &str) -> Vec<Completion> {
+        let sources = helix_loader::projects::ImportSource::ALL
+            .iter()
+            .map(|source| source.name());
+
+        fuzzy_match(input, sources, false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
     /// Recursive function to get all keys from this value and add them to vec
     fn get_keys(value: &serde_json::Value, vec: &mut Vec<String>, scope: Option<&str>) {
         if let Some(map) = value.as_object() {
@@ -525,7 +644,27 @@ pub fn filename_with_git_ignore(
         input: &str,
         git_ignore: bool,
     ) -> Vec<Completion> {
-        filename_impl(editor, input, git_ignore, |entry| {
+        let cwd = helix_stdx::env::current_working_dir();
+        filename_impl(editor, &cwd, input, git_ignore, |entry| {
+            let is_dir = entry.file_type().is_some_and(|entry| entry.is_dir());
+
+            if is_dir {
+                FileMatch::AcceptIncomplete
+            } else {
+                FileMatch::Accept
+            }
+        })
+    }
+
+    /// Completes file names relative to the directory of the current document rather than the
+    /// working directory, the way `:rename-file` resolves its argument.
+    pub fn document_relative_filename(editor: &Editor, input: &str) -> Vec<Completion> {
+        let dir = doc!(editor)
+            .path()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(helix_stdx::env::current_working_dir);
+        filename_impl(editor, &dir, input, true, |entry| {
             let is_dir = entry.file_type().is_some_and(|entry| entry.is_dir());
 
             if is_dir {
@@ -576,7 +715,8 @@ pub fn directory_with_git_ignore(
         input: &str,
         git_ignore: bool,
     ) -> Vec<Completion> {
-        filename_impl(editor, input, git_ignore, |entry| {
+        let cwd = helix_stdx::env::current_working_dir();
+        filename_impl(editor, &cwd, input, git_ignore, |entry| {
             let is_dir = entry.file_type().is_some_and(|entry| entry.is_dir());
 
             if is_dir {
@@ -601,6 +741,7 @@ enum FileMatch {
     // TODO: we could return an iter/lazy thing so it can fetch as many as it needs.
     fn filename_impl<F>(
         editor: &Editor,
+        base_dir: &Path,
         input: &str,
         git_ignore: bool,
         filter_fn: F,
@@ -611,7 +752,6 @@ fn filename_impl<F>(
         // Rust's filename handling is really annoying.
 
         use ignore::WalkBuilder;
-        use std::path::Path;
 
         let is_tilde = input == "~";
         let path = helix_stdx::path::expand_tilde(Path::new(input));
@@ -635,12 +775,17 @@ fn filename_impl<F>(
                 match path.parent() {
                     Some(path) if !path.as_os_str().is_empty() => Cow::Borrowed(path),
                     // Path::new("h")'s parent is Some("")...
-                    _ => Cow::Owned(helix_stdx::env::current_working_dir()),
+                    _ => Cow::Borrowed(base_dir),
                 }
             };
 
             (path, file_name)
         };
+        let dir = if dir.is_relative() {
+            Cow::Owned(base_dir.join(dir))
+        } else {
+            dir
+        };
 
         let end = input.len()..;
 
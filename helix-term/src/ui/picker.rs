@@ -129,6 +129,25 @@ fn placeholder(&self) -> &str {
     }
 }
 
+/// A document of the first `max_len` bytes of the file at `path`, for previewing large files.
+fn truncated_document(path: &Path, max_len: u64, editor: &Editor) -> std::io::Result<Document> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)?
+        .take(max_len)
+        .read_to_end(&mut bytes)?;
+    // Cut a character which was split at the end.
+    if let Err(err) = std::str::from_utf8(&bytes) {
+        if err.error_len().is_none() {
+            bytes.truncate(err.valid_up_to());
+        }
+    }
+    let text = helix_core::Rope::from(String::from_utf8_lossy(&bytes).as_ref());
+    let mut doc = Document::from(text, None, editor.config.clone(), editor.syn_loader.clone());
+    // The path is used to detect the language.
+    doc.set_path(Some(path));
+    Ok(doc)
+}
+
 fn inject_nucleo_item<T, D>(
     injector: &nucleo::Injector<T>,
     columns: &[Column<T, D>],
@@ -259,16 +278,23 @@ pub struct Picker<T: 'static + Send + Sync, D: 'static> {
 
     callback_fn: PickerCallback<T>,
     default_action: Action,
+    /// Called with the selected item to expand or collapse it in tree-like pickers.
+    expand_fn: Option<ExpandCallback<T>>,
 
     pub truncate_start: bool,
     /// Caches paths to documents
     preview_cache: HashMap<Arc<Path>, CachedPreview>,
     read_buffer: Vec<u8>,
+    /// Files larger than this many bytes are previewed by their beginning instead of a
+    /// placeholder.
+    preview_truncate_len: Option<u64>,
     /// Given an item in the picker, return the file path and line number to display.
     file_fn: Option<FileCallback<T>>,
     /// An event handler for syntax highlighting the currently previewed file.
     preview_highlight_handler: Sender<Arc<Path>>,
     dynamic_query_handler: Option<Sender<DynamicQueryChange>>,
+    /// Called before rendering, returning new options when the current ones are stale.
+    refresh_fn: Option<RefreshCallback<T>>,
 }
 
 impl<T: 'static + Send + Sync, D: 'static + Send + Sync> Picker<T, D> {
@@ -387,13 +413,16 @@ fn with(
             show_preview: true,
             callback_fn: Box::new(callback_fn),
             default_action: Action::Replace,
+            expand_fn: None,
             completion_height: 0,
             widths,
             preview_cache: HashMap::new(),
             read_buffer: Vec::with_capacity(1024),
+            preview_truncate_len: None,
             file_fn: None,
             preview_highlight_handler: PreviewHighlightHandler::<T, D>::default().spawn(),
             dynamic_query_handler: None,
+            refresh_fn: None,
         }
     }
 
@@ -424,11 +453,31 @@ pub fn with_preview(
         self
     }
 
+    /// Previews only the first `max_len` bytes of larger files, so that previewing them stays
+    /// cheap, rather than showing a placeholder for files too large to preview.
+    pub fn with_truncated_previews(mut self, max_len: u64) -> Self {
+        self.preview_truncate_len = Some(max_len);
+        self
+    }
+
     pub fn with_history_register(mut self, history_register: Option<char>) -> Self {
         self.prompt.with_history_register(history_register);
         self
     }
 
+    /// Shows `prompt` in front of the query, for example to tell what the picker searches.
+    pub fn with_prompt(mut self, prompt: impl Into<Cow<'static, str>>) -> Self {
+        self.prompt.set_prompt(prompt.into());
+        self
+    }
+
+    /// Starts the picker with `query` typed into the prompt.
+    pub fn with_query(mut self, query: &str, editor: &Editor) -> Self {
+        self.prompt.set_line(query.to_string(), editor);
+        self.handle_prompt_change(true);
+        self
+    }
+
     pub fn with_initial_cursor(mut self, cursor: u32) -> Self {
         self.cursor = cursor;
         self
@@ -455,6 +504,37 @@ pub fn with_default_action(mut self, action: Action) -> Self {
         self
     }
 
+    /// Makes `Tab` expand or collapse the selected item instead of moving to the next
+    /// one. The callback is expected to update the options with [Picker::set_options].
+    pub fn with_expand(mut self, expand_fn: impl Fn(&mut Context, &T) + 'static) -> Self {
+        self.expand_fn = Some(Box::new(expand_fn));
+        self
+    }
+
+    /// Calls `refresh_fn` before each render and replaces the options with the ones it returns,
+    /// for pickers over lists which may change while the picker is open.
+    pub fn with_refresh(
+        mut self,
+        refresh_fn: impl FnMut(&Editor) -> Option<Vec<T>> + 'static,
+    ) -> Self {
+        self.refresh_fn = Some(Box::new(refresh_fn));
+        self
+    }
+
+    pub fn editor_data(&self) -> &D {
+        &self.editor_data
+    }
+
+    /// Replaces all options of the picker, cancelling any options still being streamed in.
+    pub fn set_options(&mut self, options: impl IntoIterator<Item = T>) {
+        self.version.fetch_add(1, atomic::Ordering::Relaxed);
+        self.matcher.restart(false);
+        let injector = self.matcher.injector();
+        for item in options {
+            inject_nucleo_item(&injector, &self.columns, item, &self.editor_data);
+        }
+    }
+
     /// Move the cursor by a number of lines, either down (`Forward`) or up (`Backward`)
     pub fn move_by(&mut self, amount: u32, direction: Direction) {
         let len = self.matcher.snapshot().matched_item_count();
@@ -600,7 +680,13 @@ fn get_preview<'picker, 'editor>(
                     // retrieve the `Arc<Path>` key. The `path` in scope here is a `&Path` and
                     // we can cheaply clone the key for the preview highlight handler.
                     let (path, preview) = self.preview_cache.get_key_value(path).unwrap();
-                    if matches!(preview, CachedPreview::Document(doc) if doc.syntax().is_none()) {
+                    // Documents without a language can't be highlighted, don't keep requesting it.
+                    let highlight = matches!(
+                        preview,
+                        CachedPreview::Document(doc)
+                            if doc.syntax().is_none() && doc.language_config().is_some()
+                    );
+                    if highlight {
                         helix_event::send_blocking(&self.preview_highlight_handler, path.clone());
                     }
                     return Some((Preview::Cached(preview), range));
@@ -628,7 +714,11 @@ fn get_preview<'picker, 'editor>(
                                 .collect();
                             Ok(CachedPreview::Directory(file_names))
                         } else if metadata.is_file() {
-                            if metadata.len() > MAX_FILE_SIZE_FOR_PREVIEW {
+                            let truncate_len = self
+                                .preview_truncate_len
+                                .filter(|&max_len| metadata.len() > max_len);
+                            if truncate_len.is_none() && metadata.len() > MAX_FILE_SIZE_FOR_PREVIEW
+                            {
                                 return Ok(CachedPreview::LargeFile);
                             }
                             let content_type = std::fs::File::open(&path).and_then(|file| {
@@ -642,17 +732,20 @@ fn get_preview<'picker, 'editor>(
                             if content_type.is_binary() {
                                 return Ok(CachedPreview::Binary);
                             }
-                            let mut doc = Document::open(
-                                &path,
-                                None,
-                                false,
-                                editor.config.clone(),
-                                editor.syn_loader.clone(),
-                            )
-                            .or(Err(std::io::Error::new(
-                                std::io::ErrorKind::NotFound,
-                                "Cannot open document",
-                            )))?;
+                            let mut doc = match truncate_len {
+                                Some(max_len) => truncated_document(&path, max_len, editor)?,
+                                None => Document::open(
+                                    &path,
+                                    None,
+                                    false,
+                                    editor.config.clone(),
+                                    editor.syn_loader.clone(),
+                                )
+                                .or(Err(std::io::Error::new(
+                                    std::io::ErrorKind::NotFound,
+                                    "Cannot open document",
+                                )))?,
+                            };
                             let loader = editor.syn_loader.load();
                             if let Some(language_config) = doc.detect_language_config(&loader) {
                                 doc.language = Some(language_config);
@@ -692,7 +785,11 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
 
         let text_style = cx.editor.theme.get("ui.text");
         let selected = cx.editor.theme.get("ui.text.focus");
-        let highlight_style = cx.editor.theme.get("special").add_modifier(Modifier::BOLD);
+        let highlight_style = cx
+            .editor
+            .theme
+            .try_get("ui.picker.match")
+            .unwrap_or_else(|| cx.editor.theme.get("special").add_modifier(Modifier::BOLD));
 
         // -- Render the frame:
         // clear area
@@ -777,46 +874,10 @@ fn render_picker(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
                         &mut matcher,
                         &mut indices,
                     );
-                    indices.sort_unstable();
-                    indices.dedup();
-                    let mut indices = indices.drain(..);
-                    let mut next_highlight_idx = indices.next().unwrap_or(u32::MAX);
-                    let mut span_list = Vec::new();
-                    let mut current_span = String::new();
-                    let mut current_style = Style::default();
-                    let mut grapheme_idx = 0u32;
-                    let mut width = 0;
-
                     let spans: &[Span] =
                         cell.content.lines.first().map_or(&[], |it| it.0.as_slice());
-                    for span in spans {
-                        // this looks like a bug on first glance, we are iterating
-                        // graphemes but treating them as char indices. The reason that
-                        // this is correct is that nucleo will only ever consider the first char
-                        // of a grapheme (and discard the rest of the grapheme) so the indices
-                        // returned by nucleo are essentially grapheme indecies
-                        for grapheme in span.content.graphemes(true) {
-                            let style = if grapheme_idx == next_highlight_idx {
-                                next_highlight_idx = indices.next().unwrap_or(u32::MAX);
-                                span.style.patch(highlight_style)
-                            } else {
-                                span.style
-                            };
-                            if style != current_style {
-                                if !current_span.is_empty() {
-                                    span_list.push(Span::styled(current_span, current_style))
-                                }
-                                current_span = String::new();
-                                current_style = style;
-                            }
-                            current_span.push_str(grapheme);
-                            grapheme_idx += 1;
-                        }
-                        width += span.width();
-                    }
-
-                    span_list.push(Span::styled(current_span, current_style));
-                    cell = Cell::from(Spans::from(span_list));
+                    let (spans, width) = highlight_matches(spans, &mut indices, highlight_style);
+                    cell = Cell::from(spans);
                     matcher_index += 1;
                     width
                 } else {
@@ -1008,6 +1069,53 @@ fn render_preview(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context
     }
 }
 
+/// Styles the graphemes of `spans` at the matched `indices` with `highlight_style`. Returns the
+/// styled spans and their width. `indices` is left empty.
+fn highlight_matches(
+    spans: &[Span],
+    indices: &mut Vec<u32>,
+    highlight_style: Style,
+) -> (Spans<'static>, usize) {
+    indices.sort_unstable();
+    indices.dedup();
+    let mut indices = indices.drain(..);
+    let mut next_highlight_idx = indices.next().unwrap_or(u32::MAX);
+    let mut span_list = Vec::new();
+    let mut current_span = String::new();
+    let mut current_style = Style::default();
+    let mut grapheme_idx = 0u32;
+    let mut width = 0;
+
+    for span in spans {
+        // this looks like a bug on first glance, we are iterating
+        // graphemes but treating them as char indices. The reason that
+        // this is correct is that nucleo will only ever consider the first char
+        // of a grapheme (and discard the rest of the grapheme) so the indices
+        // returned by nucleo are essentially grapheme indecies
+        for grapheme in span.content.graphemes(true) {
+            let style = if grapheme_idx == next_highlight_idx {
+                next_highlight_idx = indices.next().unwrap_or(u32::MAX);
+                span.style.patch(highlight_style)
+            } else {
+                span.style
+            };
+            if style != current_style {
+                if !current_span.is_empty() {
+                    span_list.push(Span::styled(current_span, current_style))
+                }
+                current_span = String::new();
+                current_style = style;
+            }
+            current_span.push_str(grapheme);
+            grapheme_idx += 1;
+        }
+        width += span.width();
+    }
+
+    span_list.push(Span::styled(current_span, current_style));
+    (Spans::from(span_list), width)
+}
+
 impl<I: 'static + Send + Sync, D: 'static + Send + Sync> Component for Picker<I, D> {
     fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         // +---------+ +---------+
@@ -1017,6 +1125,14 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         // |         | |         |
         // +---------+ +---------+
 
+        if let Some(options) = self
+            .refresh_fn
+            .as_mut()
+            .and_then(|refresh| refresh(cx.editor))
+        {
+            self.set_options(options);
+        }
+
         let render_preview =
             self.show_preview && self.file_fn.is_some() && area.width > MIN_AREA_WIDTH_FOR_PREVIEW;
 
@@ -1069,6 +1185,11 @@ fn handle_event(&mut self, event: &Event, ctx: &mut Context) -> EventResult {
         };
 
         match key_event {
+            key!(Tab) if self.expand_fn.is_some() => {
+                if let (Some(option), Some(expand_fn)) = (self.selection(), &self.expand_fn) {
+                    expand_fn(ctx, option);
+                }
+            }
             shift!(Tab) | key!(Up) | ctrl!('p') => {
                 self.move_by(1, Direction::Backward);
             }
@@ -1187,3 +1308,40 @@ fn drop(&mut self) {
 }
 
 type PickerCallback<T> = Box<dyn Fn(&mut Context, &T, Action)>;
+type ExpandCallback<T> = Box<dyn Fn(&mut Context, &T)>;
+type RefreshCallback<T> = Box<dyn FnMut(&Editor) -> Option<Vec<T>>>;
+
+#[cfg(test)]
+mod test {
+    use helix_view::graphics::{Color, Rect};
+
+    use super::*;
+
+    #[test]
+    fn highlight_matched_graphemes() {
+        let match_style = Style::default().fg(Color::Red);
+        let dir_style = Style::default().fg(Color::Blue);
+        let spans = [
+            Span::styled("src/", dir_style),
+            Span::raw("ui/"),
+            Span::raw("pick\u{e9}r.rs"),
+        ];
+        // `s`, `u`, `p` and the accented `e`, unsorted and with a duplicate.
+        let mut indices = vec![7, 0, 4, 11, 0];
+        let (spans, width) = highlight_matches(&spans, &mut indices, match_style);
+        assert!(indices.is_empty());
+        assert_eq!(width, 16);
+
+        let area = Rect::new(0, 0, 20, 1);
+        let mut surface = Surface::empty(area);
+        surface.set_spans(0, 0, &spans, area.width);
+        for x in 0..width as u16 {
+            let fg = match x {
+                0 | 4 | 7 | 11 => Color::Red,
+                1..=3 => Color::Blue,
+                _ => Color::Reset,
+            };
+            assert_eq!(surface[(x, 0)].fg, fg, "cell {x}");
+        }
+    }
+}
@@ -133,6 +133,10 @@ pub fn with_language(
         self
     }
 
+    pub fn set_prompt(&mut self, prompt: Cow<'static, str>) {
+        self.prompt = prompt;
+    }
+
     pub fn line(&self) -> &String {
         &self.line
     }
@@ -157,6 +157,8 @@ fn get_render_function<'a, F>(element_id: StatusLineElementID) -> impl Fn(&mut R
         helix_view::editor::StatusLineElement::VersionControl => render_version_control,
         helix_view::editor::StatusLineElement::Register => render_register,
         helix_view::editor::StatusLineElement::CurrentWorkingDirectory => render_cwd,
+        helix_view::editor::StatusLineElement::ProjectName => render_project_name,
+        helix_view::editor::StatusLineElement::SymbolsPath => render_symbols_path,
     }
 }
 
@@ -219,8 +221,7 @@ fn render_diagnostics<'a, F>(context: &mut RenderContext<'a>, write: F)
     let (hints, info, warnings, errors) =
         context
             .doc
-            .diagnostics()
-            .iter()
+            .unique_diagnostics()
             .fold((0, 0, 0, 0), |mut counts, diag| {
                 match diag.severity {
                     Some(Severity::Hint) | None => counts.0 += 1,
@@ -447,10 +448,11 @@ fn render_file_name<'a, F>(context: &mut RenderContext<'a>, write: F)
     F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
 {
     let title = {
-        let rel_path = context.doc.relative_path();
-        let path = rel_path
-            .as_ref()
-            .map(|p| p.to_string_lossy())
+        let config = context.editor.config();
+        let path = context
+            .doc
+            .path()
+            .map(|path| helix_core::path::display_path(path, &config.path_aliases()))
             .unwrap_or_else(|| SCRATCH_BUFFER_NAME.into());
         format!(" {} ", path)
     };
@@ -583,3 +585,46 @@ fn render_cwd<'a, F>(context: &mut RenderContext<'a>, write: F)
         .to_string();
     write(context, cwd.into())
 }
+
+fn render_project_name<'a, F>(context: &mut RenderContext<'a>, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    if let Some(name) = context.editor.document_project_name(context.doc) {
+        write(context, name.into())
+    }
+}
+
+fn render_symbols_path<'a, F>(context: &mut RenderContext<'a>, write: F)
+where
+    F: Fn(&mut RenderContext<'a>, Span<'a>) + Copy,
+{
+    const SEPARATOR: &str = " > ";
+
+    let Some(syntax) = context.doc.syntax() else {
+        return;
+    };
+    let text = context.doc.text().slice(..);
+    let cursor = context
+        .doc
+        .selection(context.view.id)
+        .primary()
+        .cursor(text);
+    let loader = context.editor.syn_loader.load();
+    let path = syntax.symbol_path(text, &loader, text.char_to_byte(cursor) as u32);
+    if path.is_empty() {
+        return;
+    }
+
+    // Drop the outermost scopes if the path takes up more than half of the statusline.
+    let max_width = (context.view.area.width / 2) as usize;
+    let mut start = 0;
+    let mut breadcrumb = path.join(SEPARATOR);
+    while breadcrumb.width() + 2 > max_width && start + 1 < path.len() {
+        start += 1;
+        breadcrumb = format!("…{SEPARATOR}{}", path[start..].join(SEPARATOR));
+    }
+
+    let style = context.editor.theme.get("ui.statusline.breadcrumb");
+    write(context, Span::styled(format!(" {breadcrumb} "), style));
+}
@@ -6,8 +6,10 @@
 
 use crate::ui::document::{LinePos, TextRenderer};
 
+pub use code_lens::CodeLenses;
 pub use diagnostics::InlineDiagnostics;
 
+mod code_lens;
 mod diagnostics;
 
 /// Decorations are the primary mechanism for extending the text rendering.
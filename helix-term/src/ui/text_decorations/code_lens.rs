@@ -0,0 +1,123 @@
+use helix_core::doc_formatter::FormattedGrapheme;
+use helix_core::line_ending::line_end_char_index;
+use helix_core::{Position, RopeSlice};
+use helix_view::annotations::code_lens::code_lenses_after_line;
+use helix_view::document::CodeLens;
+use helix_view::theme::Style;
+use helix_view::{Document, Theme};
+
+use crate::ui::document::{LinePos, TextRenderer};
+use crate::ui::text_decorations::Decoration;
+
+const SEPARATOR: &str = " | ";
+
+/// Renders the titles of code lenses in the virtual lines reserved by
+/// [CodeLensLines](helix_view::annotations::code_lens::CodeLensLines), aligned with the
+/// indentation of the line they apply to.
+pub struct CodeLenses<'a> {
+    text: RopeSlice<'a>,
+    code_lenses: &'a [CodeLens],
+    tab_width: usize,
+    style: Style,
+    /// The document line whose line ending was rendered last, if lenses are shown after it.
+    line_end: Option<usize>,
+}
+
+impl<'a> CodeLenses<'a> {
+    pub fn new(doc: &'a Document, theme: &Theme) -> Self {
+        CodeLenses {
+            text: doc.text().slice(..),
+            code_lenses: &doc.code_lenses,
+            tab_width: doc.tab_width(),
+            style: theme.get("ui.virtual.code-lens"),
+            line_end: None,
+        }
+    }
+
+    /// The line ending of the first line at or after `doc_line` which has lenses after it.
+    fn next_anchor(&self, doc_line: usize) -> usize {
+        let start = if doc_line == 0 {
+            0
+        } else {
+            self.text.line_to_char(doc_line + 1)
+        };
+        let next = self
+            .code_lenses
+            .partition_point(|lens| lens.char_idx < start);
+        let Some(lens) = self.code_lenses.get(next) else {
+            return usize::MAX;
+        };
+        let lens_line = self.text.char_to_line(lens.char_idx);
+        let line = lens_line.saturating_sub(1).max(doc_line);
+        if line + 1 >= self.text.len_lines() {
+            return usize::MAX;
+        }
+        line_end_char_index(&self.text, line)
+    }
+
+    /// The visual width of the indentation of `doc_line`.
+    fn indent_width(&self, doc_line: usize) -> usize {
+        let mut width = 0;
+        for ch in self.text.line(doc_line).chars() {
+            match ch {
+                '\t' => width += self.tab_width - width % self.tab_width,
+                ' ' => width += 1,
+                _ => break,
+            }
+        }
+        width
+    }
+}
+
+impl Decoration for CodeLenses<'_> {
+    fn reset_pos(&mut self, pos: usize) -> usize {
+        self.line_end = None;
+        self.next_anchor(self.text.char_to_line(pos))
+    }
+
+    fn decorate_grapheme(
+        &mut self,
+        _renderer: &mut TextRenderer,
+        grapheme: &FormattedGrapheme,
+    ) -> usize {
+        let doc_line = self.text.char_to_line(grapheme.char_idx);
+        self.line_end = Some(doc_line);
+        self.next_anchor(doc_line + 1)
+    }
+
+    fn render_virt_lines(
+        &mut self,
+        renderer: &mut TextRenderer,
+        pos: LinePos,
+        virt_off: Position,
+    ) -> Position {
+        if self.line_end != Some(pos.doc_line) {
+            return Position::new(0, 0);
+        }
+        self.line_end = None;
+        let lenses = code_lenses_after_line(self.text, self.code_lenses, pos.doc_line);
+        let Some(first) = lenses.first() else {
+            return Position::new(0, 0);
+        };
+
+        let col = self.indent_width(self.text.char_to_line(first.char_idx));
+        let titles: Vec<_> = lenses
+            .iter()
+            .filter_map(|lens| Some(lens.lens.command.as_ref()?.title.as_str()))
+            .collect();
+        let row = pos.visual_line + virt_off.row as u16;
+        if col >= renderer.offset.col {
+            let draw_col = (col - renderer.offset.col) as u16;
+            renderer.set_string_truncated(
+                renderer.viewport.x + draw_col,
+                row,
+                &titles.join(SEPARATOR),
+                renderer.viewport.width.saturating_sub(draw_col) as usize,
+                |_| self.style,
+                true,
+                false,
+            );
+        }
+        Position::new(1, 0)
+    }
+}
@@ -17,9 +17,20 @@ async fn hello_world() -> anyhow::Result<()> {
 
     mod auto_indent;
     mod auto_pairs;
+    mod code_lens;
     mod command_line;
     mod commands;
+    mod completion_on_delete;
+    mod completion_resolve;
     mod languages;
+    mod linked_editing;
+    mod lsp_broadcast;
+    mod lsp_routing;
     mod movement;
+    mod on_type_formatting;
+    mod rename_file;
+    mod sessions;
     mod splits;
+    mod tabs;
+    mod type_hierarchy;
 }
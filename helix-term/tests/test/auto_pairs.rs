@@ -196,6 +196,37 @@ async fn insert_auto_pairs_disabled() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn insert_language_configured_pairs() -> anyhow::Result<()> {
+    let file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    let lang_conf = indoc! {r#"
+            [[language]]
+            name = "rust"
+            auto-pairs = { "<" = ">" }
+        "#};
+
+    // Language pairs replace the editor's pairs instead of extending them.
+    for (keys, output) in [
+        ("i<lt>", format!("<#[|>]#{}", LINE_END)),
+        ("i(", format!("(#[|{}]#", LINE_END)),
+    ] {
+        test_with_config(
+            AppBuilder::new()
+                .with_file(file.path(), None)
+                .with_lang_loader(helpers::test_syntax_loader(Some(lang_conf.into()))),
+            (
+                format!("#[{}|]#", LINE_END),
+                keys,
+                output,
+                LineFeedHandling::AsIs,
+            ),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn insert_multi_range() -> anyhow::Result<()> {
     for pair in DEFAULT_PAIRS {
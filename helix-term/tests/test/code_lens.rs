@@ -0,0 +1,123 @@
+use helix_core::visual_offset_from_block;
+use helix_term::application::Application;
+use helix_view::{current_ref, doc, editor::LspConfig};
+use serde_json::json;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::*;
+
+const REQUEST_LOG: &str = "requests.log";
+
+/// Not a test: runs as the language server of [mock_language_server_loader].
+#[test]
+fn mock_language_server() {
+    let range = |line| {
+        json!({
+            "start": { "line": line, "character": 0 },
+            "end": { "line": line, "character": 1 },
+        })
+    };
+    mock_server(
+        |_| {
+            json!({
+                "codeLensProvider": { "resolveProvider": true },
+                "executeCommandProvider": { "commands": ["run"] },
+            })
+        },
+        |dir, method, message| match method {
+            "textDocument/codeLens" => Some(json!([
+                { "range": range(0), "command": { "title": "1 reference", "command": "references" } },
+                // Resolved to get its title.
+                { "range": range(2), "data": 2 },
+                { "range": range(3), "command": { "title": "Run", "command": "run" } },
+                { "range": range(3), "command": { "title": "Debug", "command": "debug" } },
+            ])),
+            "codeLens/resolve" => {
+                let mut lens = message["params"].clone();
+                lens["command"] = json!({ "title": "2 references", "command": "references" });
+                Some(lens)
+            }
+            "workspace/executeCommand" => {
+                let command = message["params"]["command"].as_str().unwrap();
+                write_log(dir, REQUEST_LOG, command);
+                None
+            }
+            _ => None,
+        },
+    );
+}
+
+/// The rows at which the lines of the document are rendered.
+fn line_rows(app: &Application) -> Vec<usize> {
+    let (view, doc) = current_ref!(app.editor);
+    let text = doc.text().slice(..);
+    let annotations = view.text_annotations(doc, None);
+    let text_fmt = doc.text_format(view.inner_width(doc), None);
+    (0..text.len_lines() - 1)
+        .map(|line| {
+            let pos = text.line_to_char(line);
+            visual_offset_from_block(text, 0, pos, &text_fmt, &annotations)
+                .0
+                .row
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_code_lenses() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = helix_stdx::path::canonicalize(tmp.path());
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "fn a() {}\n\n    fn b() {}\nfn c() {}\n")?;
+
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        code_lens: true,
+        ..Default::default()
+    };
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(&file, None)
+        .with_lang_loader(mock_language_server_loader(
+            "rust",
+            "test::code_lens::mock_language_server",
+            &dir,
+        ))
+        .build()?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| {
+        doc!(app.editor).code_lenses.len() == 4
+    })
+    .await?;
+
+    let titles: Vec<_> = doc!(app.editor)
+        .code_lenses
+        .iter()
+        .map(|lens| lens.lens.command.as_ref().unwrap().title.clone())
+        .collect();
+    assert_eq!(titles, ["1 reference", "2 references", "Run", "Debug"]);
+
+    // The lenses of the first line go below it, the others above their lines, sharing a
+    // virtual line per line.
+    assert_eq!(line_rows(&app), [0, 2, 4, 6]);
+
+    // Inserting a line moves the lenses along with their lines.
+    send_keys(&tx, "o<esc>")?;
+    run_until(&mut app, &mut input, |app| {
+        line_rows(app) == [0, 2, 3, 5, 7]
+    })
+    .await?;
+
+    send_keys(&tx, "gel<space>l<ret>")?;
+    run_until(&mut app, &mut input, |_| {
+        read_log(&dir, REQUEST_LOG) == "run\n"
+    })
+    .await?;
+
+    test_key_sequence(&mut app, Some("<esc>:qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use helix_term::application::Application;
+use helix_view::{doc, editor::Severity};
 
 use super::*;
 
@@ -6,6 +7,7 @@
 mod movement;
 mod reverse_selection_contents;
 mod rotate_selection_contents;
+mod substitute;
 mod write;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -274,6 +276,67 @@ async fn test_multi_selection_shell_commands() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pipe_commands() -> anyhow::Result<()> {
+    // pipe-to
+    test((
+        indoc! {"\
+            #[|lorem]# ipsum
+            #(|dolor)# sit
+            "},
+        ":pipe-to cat<ret>",
+        indoc! {"\
+            #[ |]#ipsum
+            #( |)#sit
+            "},
+    ))
+    .await?;
+
+    // The `<A-|>` binding keeps the selections, unlike `:pipe-to`.
+    test((
+        indoc! {"\
+            #[|lorem]# ipsum
+            #(|dolor)# sit
+            "},
+        "<A-|>cat<ret>",
+        indoc! {"\
+            #[|lorem]# ipsum
+            #(|dolor)# sit
+            "},
+    ))
+    .await?;
+
+    // pipe-from
+    test((
+        indoc! {"\
+            #[|lorem]#
+            "},
+        ":pipe-from echo foo<ret>",
+        indoc! {"\
+            #[|foo]#lorem
+            "},
+    ))
+    .await?;
+
+    // A failing command reports its stderr and leaves the text alone.
+    for command in ["pipe", "pipe-to", "pipe-from"] {
+        test_key_sequence(
+            &mut AppBuilder::new().with_input_text("#[|lorem]#\n").build()?,
+            Some(&format!(":{command} echo oops <gt>&2; exit 1<ret>")),
+            Some(&|app| {
+                let (status, &severity) = app.editor.get_status().unwrap();
+                assert_eq!(severity, Severity::Error);
+                assert_eq!(status, "Shell command failed: oops");
+                assert_eq!(doc!(app.editor).text(), "lorem\n");
+            }),
+            false,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_undo_redo() -> anyhow::Result<()> {
     // A jumplist selection is created at a point which is undone.
@@ -844,3 +907,166 @@ async fn global_search_with_multibyte_chars() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_search_wrap_warning() -> anyhow::Result<()> {
+    let mut app = helpers::AppBuilder::new()
+        .with_input_text("#[o|]#ne two one")
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some("/one<ret>"),
+                Some(&|app| {
+                    assert_eq!(None, app.editor.get_status());
+                }),
+            ),
+            (
+                Some("n"),
+                Some(&|app| {
+                    let (status, &severity) = app.editor.get_status().unwrap();
+                    assert_eq!("Search hit bottom, continuing at top", status);
+                    assert_eq!(helix_view::editor::Severity::Warning, severity);
+                }),
+            ),
+            (
+                Some("N"),
+                Some(&|app| {
+                    let (status, _) = app.editor.get_status().unwrap();
+                    assert_eq!("Search hit top, continuing at bottom", status);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// The ranges of the search matches highlighted in the current view.
+fn search_match_ranges(app: &Application) -> Option<Vec<std::ops::Range<usize>>> {
+    let regex = app.editor.search_highlight.as_ref()?;
+    let (view, doc) = helix_view::current_ref!(app.editor);
+    let overlay = helix_term::ui::EditorView::search_match_highlights(
+        doc,
+        regex,
+        doc.view_offset(view.id).anchor,
+        view.inner_height() as u16,
+        &app.editor.theme,
+    )?;
+    match overlay {
+        helix_core::syntax::OverlayHighlights::Homogeneous { ranges, .. } => Some(ranges),
+        _ => None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_search_highlight_all_matches() -> anyhow::Result<()> {
+    let mut config = helpers::test_config();
+    config.editor.search.highlight_all_matches = true;
+    let mut app = helpers::AppBuilder::new()
+        .with_config(config)
+        .with_input_text("#[o|]#ne two one")
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some("/o<ret>"),
+                Some(&|app| {
+                    assert_eq!(Some(vec![0..1, 6..7, 8..9]), search_match_ranges(app));
+                }),
+            ),
+            (
+                Some("/one<esc>"),
+                Some(&|app| {
+                    assert!(app.editor.search_highlight.is_none());
+                }),
+            ),
+            (
+                Some("/one<ret>n"),
+                Some(&|app| {
+                    assert_eq!(Some(vec![0..3, 8..11]), search_match_ranges(app));
+                }),
+            ),
+            (
+                Some("i"),
+                Some(&|app| {
+                    assert!(app.editor.search_highlight.is_none());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    // Matches are not highlighted unless enabled.
+    test_key_sequence(
+        &mut helpers::AppBuilder::new()
+            .with_input_text("#[o|]#ne two one")
+            .build()?,
+        Some("/one<ret>"),
+        Some(&|app| {
+            assert!(app.editor.search_highlight.is_none());
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_set_language_id() -> anyhow::Result<()> {
+    test_key_sequences(
+        &mut helpers::AppBuilder::new().build()?,
+        vec![
+            (
+                Some(":lang html<ret>:set-language-id jinja2<ret>"),
+                Some(&|app| {
+                    let doc = helix_view::doc!(app.editor);
+                    assert_eq!(Some("html"), doc.language_name());
+                    assert_eq!(Some("jinja2"), doc.language_id());
+                }),
+            ),
+            (
+                Some(":set-language-id --reset<ret>"),
+                Some(&|app| {
+                    assert_eq!(Some("html"), helix_view::doc!(app.editor).language_id());
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_align_cursors() -> anyhow::Result<()> {
+    test((
+        "#[a|]#bc\n#(abcde|)#f\n#(a|)#\n",
+        ":align<ret>",
+        "abc#[\n|]#abcd#(e|)#f\na#(\n|)#",
+    ))
+    .await?;
+    test((
+        "ab#[c|]#\nabcd#(e|)#f\n",
+        ":align min<ret>",
+        "ab#[c|]#\nab#(c|)#def\n",
+    ))
+    .await?;
+    test((
+        "#[a|]#bc\n#(a|)#bcdef\n",
+        ":align 3<ret>",
+        "ab#[c|]#\nab#(c|)#def\n",
+    ))
+    .await?;
+
+    Ok(())
+}
@@ -279,6 +279,37 @@ async fn insert_newline_continue_line_comment() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_insert_at_line_start_end_multiline_selection() -> anyhow::Result<()> {
+    test((
+        indoc! {"\
+            #[one
+              two
+            thr|]#ee
+            "},
+        "I- <esc>",
+        indoc! {"\
+            - #(o|)#ne
+              - #(t|)#wo
+            - #[t|]#hree
+            "},
+    ))
+    .await?;
+
+    test((
+        indoc! {"\
+            #[one
+            two
+            |]#three
+            "},
+        "A;<esc>",
+        "one;#(\n|)#two;#[\n|]#three\n",
+    ))
+    .await?;
+
+    Ok(())
+}
+
 /// NOTE: Language is set to markdown to check if the indentation is correct for the new line
 #[tokio::test(flavor = "multi_thread")]
 async fn test_open_above() -> anyhow::Result<()> {
@@ -583,3 +614,16 @@ async fn test_jump_undo_redo() -> anyhow::Result<()> {
     .await?;
     Ok(())
 }
+
+// Everything typed in one insert session is a single revision, so one undo removes it.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_undo_insert_session() -> anyhow::Result<()> {
+    test(("#[|]#", "ihello<esc>u", "#[\n|]#")).await?;
+    test(("#[|]#", "ihello world<ret>again<esc>u", "#[\n|]#")).await?;
+    // Auto-pairs and deletions are part of the session.
+    test(("#[|]#", "ifoo(bar<backspace>z<esc>u", "#[\n|]#")).await?;
+    // The session ends when leaving insert mode.
+    test(("#[|]#", "ihello<esc>a world<esc>u", "hello#[|\n]#")).await?;
+
+    Ok(())
+}
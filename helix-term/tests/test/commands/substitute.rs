@@ -0,0 +1,292 @@
+use helix_term::application::Application;
+use helix_view::doc;
+
+use super::*;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_on_current_line() -> anyhow::Result<()> {
+    test((
+        indoc! {"\
+            #[o|]#ne foo foo
+            foo"},
+        ":s/foo/bar<ret>",
+        indoc! {"\
+            #[o|]#ne bar foo
+            foo"},
+    ))
+    .await?;
+    test((
+        indoc! {"\
+            #[o|]#ne foo foo
+            foo"},
+        ":s/foo/bar/g<ret>",
+        indoc! {"\
+            #[o|]#ne bar bar
+            foo"},
+    ))
+    .await?;
+    // The argument can be separated from the command name.
+    test((
+        indoc! {"\
+            #[o|]#ne foo foo
+            foo"},
+        ":substitute /foo/bar/g<ret>",
+        indoc! {"\
+            #[o|]#ne bar bar
+            foo"},
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_on_selected_lines() -> anyhow::Result<()> {
+    test((
+        indoc! {"\
+            #[one foo foo
+            two f|]#oo foo
+            three foo foo"},
+        ":s/foo/bar<ret>",
+        indoc! {"\
+            #[one bar foo
+            two |]#bar foo
+            three foo foo"},
+    ))
+    .await?;
+    test((
+        indoc! {"\
+            #[one foo foo
+            two f|]#oo foo
+            three foo foo"},
+        ":s/foo/bar/g<ret>",
+        indoc! {"\
+            #[one bar bar
+            two |]#bar bar
+            three foo foo"},
+    ))
+    .await?;
+    // Each line is only substituted once when multiple selections are on it.
+    test((
+        indoc! {"\
+            #[o|]#ne #(f|)#oo foo
+            two foo foo
+            #(t|)#hree foo foo"},
+        ":s/foo/bar<ret>",
+        indoc! {"\
+            #[o|]#ne #(b|)#ar foo
+            two foo foo
+            #(t|)#hree bar foo"},
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_case_insensitive() -> anyhow::Result<()> {
+    test((
+        "#[o|]#ne Foo FOO foo",
+        ":s/foo/bar/i<ret>",
+        "#[o|]#ne bar FOO foo",
+    ))
+    .await?;
+    test((
+        "#[o|]#ne Foo FOO foo",
+        ":s/foo/bar/gi<ret>",
+        "#[o|]#ne bar bar bar",
+    ))
+    .await?;
+    test((
+        "#[o|]#ne Foo FOO foo",
+        ":s/foo/bar/g<ret>",
+        "#[o|]#ne Foo FOO bar",
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_backreferences() -> anyhow::Result<()> {
+    test((
+        "#[a|]# = b, c = d",
+        r":s/(\w) = (\w)/\2 = \1/g<ret>",
+        "#[b|]# = a, d = c",
+    ))
+    .await?;
+    test(("#[o|]#ne two", r":s/\w+/[&]/g<ret>", "#[[|]#one] [two]")).await?;
+    // Escaped delimiters, ampersands and backslashes are inserted literally.
+    test(("#[o|]#ne", r":s/one/a\/b\&c\\d<ret>", r"#[a|]#/b&c\d")).await?;
+    test((
+        "#[o|]#ne two",
+        r":s/(one) (two)/\1\n\2<ret>",
+        indoc! {"\
+            #[o|]#ne
+            two"},
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_last_search() -> anyhow::Result<()> {
+    test((
+        "#[o|]#ne foo foo",
+        "/foo<ret>:s//bar/g<ret>",
+        "one #[bar|]# bar",
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_single_undo_step() -> anyhow::Result<()> {
+    test((
+        indoc! {"\
+            #[one foo foo
+            two f|]#oo foo"},
+        ":s/foo/bar/g<ret>u",
+        indoc! {"\
+            #[one foo foo
+            two f|]#oo foo"},
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_errors() -> anyhow::Result<()> {
+    for command in [
+        ":s/foo/bar/x<ret>",
+        ":s/baz/bar<ret>",
+        r":s/foo/\1<ret>",
+        ":s/(/bar<ret>",
+        ":s//bar<ret>",
+    ] {
+        test_key_sequence(
+            &mut AppBuilder::new().with_input_text("#[o|]#ne foo").build()?,
+            Some(command),
+            Some(&|app| {
+                assert!(app.editor.is_err(), "{command} didn't fail");
+                assert_eq!(doc!(app.editor).text(), "one foo");
+            }),
+            false,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Runs `command` and then answers each confirmation prompt in turn.
+async fn test_confirm(
+    input: &str,
+    command: &str,
+    answers: &[&str],
+    output: &str,
+) -> anyhow::Result<()> {
+    let mut app = AppBuilder::new().with_input_text(input).build()?;
+    let check = |app: &Application| {
+        assert!(!app.editor.is_err(), "{:?}", app.editor.get_status());
+        assert_eq!(doc!(app.editor).text(), output);
+    };
+    let (last_answer, answers) = answers.split_last().unwrap();
+    let mut inputs: Vec<(Option<&str>, Option<&dyn Fn(&Application)>)> =
+        vec![(Some(command), None)];
+    inputs.extend(answers.iter().map(|&answer| (Some(answer), None)));
+    inputs.push((Some(last_answer), Some(&check)));
+    test_key_sequences(&mut app, inputs, false).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_with_confirmation() -> anyhow::Result<()> {
+    let input = indoc! {"\
+        #[one foo foo
+        two f|]#oo foo
+        three foo foo
+        "};
+    test_confirm(
+        input,
+        ":s/foo/bar/gc<ret>",
+        &["y<ret>", "n<ret>", "<ret>", "y<ret>"],
+        "one bar foo\ntwo foo bar\nthree foo foo\n",
+    )
+    .await?;
+    test_confirm(
+        input,
+        ":s/foo/bar/c<ret>",
+        &["n<ret>", "y<ret>"],
+        "one foo foo\ntwo bar foo\nthree foo foo\n",
+    )
+    .await?;
+    // `a` replaces the remaining matches.
+    test_confirm(
+        input,
+        ":s/foo/bar/gc<ret>",
+        &["n<ret>", "a<ret>"],
+        "one foo bar\ntwo bar bar\nthree foo foo\n",
+    )
+    .await?;
+    // `q` and escape keep the replacements accepted so far.
+    test_confirm(
+        input,
+        ":s/foo/bar/gc<ret>",
+        &["y<ret>", "q<ret>"],
+        "one bar foo\ntwo foo foo\nthree foo foo\n",
+    )
+    .await?;
+    test_confirm(
+        input,
+        ":s/FOO/bar/gci<ret>",
+        &["n<ret>", "y<ret>", "<esc>"],
+        "one foo bar\ntwo foo foo\nthree foo foo\n",
+    )
+    .await?;
+    test_confirm(
+        input,
+        r":s/(\w+) (foo)/\2 \1/ci<ret>",
+        &["y<ret>", "y<ret>"],
+        "foo one foo\nfoo two foo\nthree foo foo\n",
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn substitute_with_confirmation_single_undo_step() -> anyhow::Result<()> {
+    let input = indoc! {"\
+        #[one foo foo
+        two f|]#oo foo
+        "};
+    let mut app = AppBuilder::new().with_input_text(input).build()?;
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some(":s/foo/bar/gc<ret>"), None),
+            (Some("y<ret>"), None),
+            (Some("y<ret>"), None),
+            (Some("n<ret>"), None),
+            (
+                Some("y<ret>"),
+                Some(&|app| {
+                    assert_eq!(doc!(app.editor).text(), "one bar bar\ntwo foo bar\n");
+                }),
+            ),
+            (
+                Some("u"),
+                Some(&|app| {
+                    assert_eq!(doc!(app.editor).text(), "one foo foo\ntwo foo foo\n");
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use helix_view::editor::LspConfig;
+use serde_json::json;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::*;
+
+const REQUEST_LOG: &str = "completion.log";
+
+/// Not a test: runs as the language server of [mock_language_server_loader], logging the
+/// column of each completion request.
+#[test]
+fn mock_language_server() {
+    mock_server(
+        |_| json!({ "completionProvider": {} }),
+        |dir, method, message| match method {
+            "textDocument/completion" => {
+                let character = &message["params"]["position"]["character"];
+                write_log(dir, REQUEST_LOG, &character.to_string());
+                Some(json!([]))
+            }
+            _ => None,
+        },
+    );
+}
+
+fn requested_columns(dir: &std::path::Path) -> Vec<String> {
+    read_log(dir, REQUEST_LOG)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trigger_completion_on_delete() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = helix_stdx::path::canonicalize(tmp.path());
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "hello\n")?;
+
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        ..Default::default()
+    };
+    config.editor.completion_trigger_on_delete = true;
+    config.editor.completion_timeout = std::time::Duration::from_millis(5);
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(&file, None)
+        .with_lang_loader(mock_language_server_loader(
+            "rust",
+            "test::completion_on_delete::mock_language_server",
+            &dir,
+        ))
+        .build()?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 1)).await?;
+
+    // Entering insert mode after `hello` requests completions for it.
+    send_keys(&tx, "A")?;
+    run_until(&mut app, &mut input, |_| {
+        requested_columns(&dir).contains(&"5".to_string())
+    })
+    .await?;
+
+    // Deleting backwards requests completions for `hell`.
+    send_keys(&tx, "<backspace>")?;
+    run_until(&mut app, &mut input, |_| {
+        requested_columns(&dir).contains(&"4".to_string())
+    })
+    .await?;
+
+    test_key_sequence(&mut app, Some("<esc>:qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use helix_view::editor::LspConfig;
+use serde_json::json;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::*;
+
+const RESOLVE_LOG: &str = "resolve.log";
+
+/// Not a test: runs as the language server of [mock_language_server_loader].
+#[test]
+fn mock_language_server() {
+    mock_server(
+        |_| json!({ "completionProvider": { "resolveProvider": true } }),
+        |dir, method, message| match method {
+            // The documentation is left to be resolved.
+            "textDocument/completion" => Some(json!([
+                { "label": "foo" },
+                { "label": "fox" },
+                { "label": "fog" },
+            ])),
+            "completionItem/resolve" => {
+                let label = message["params"]["label"].as_str().unwrap();
+                write_log(dir, RESOLVE_LOG, label);
+                let mut item = message["params"].clone();
+                item["detail"] = json!(format!("fn {label}()"));
+                item["documentation"] = json!(format!("Documentation of {label}"));
+                Some(item)
+            }
+            _ => None,
+        },
+    );
+}
+
+fn resolved(dir: &std::path::Path) -> Vec<String> {
+    read_log(dir, RESOLVE_LOG)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_resolve_selected_completion_item() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = helix_stdx::path::canonicalize(tmp.path());
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "fo\n")?;
+
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        ..Default::default()
+    };
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(&file, None)
+        .with_lang_loader(mock_language_server_loader(
+            "rust",
+            "test::completion_resolve::mock_language_server",
+            &dir,
+        ))
+        .build()?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 1)).await?;
+
+    // Items are only resolved once they are selected.
+    send_keys(&tx, "A<C-x>")?;
+    run_until(&mut app, &mut input, |app| app.screen().contains("fox")).await?;
+    assert!(resolved(&dir).is_empty());
+
+    send_keys(&tx, "<C-n>")?;
+    run_until(&mut app, &mut input, |app| {
+        resolved(&dir)
+            .first()
+            .is_some_and(|label| app.screen().contains(&format!("Documentation of {label}")))
+    })
+    .await?;
+    let first = resolved(&dir).remove(0);
+    assert!(app.screen().contains(&format!("fn {first}()")));
+
+    // Moving on before the debounce skips the item in between.
+    send_keys(&tx, "<C-n><C-n>")?;
+    run_until(&mut app, &mut input, |app| {
+        let resolved = resolved(&dir);
+        resolved.len() == 2
+            && app
+                .screen()
+                .contains(&format!("Documentation of {}", resolved[1]))
+    })
+    .await?;
+    assert_eq!(resolved(&dir).len(), 2);
+
+    test_key_sequence(&mut app, Some("<esc>:qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
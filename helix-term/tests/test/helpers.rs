@@ -1,15 +1,17 @@
 use std::{
-    io::{Read, Write},
+    io::{BufRead, Read, Write},
     mem::replace,
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::Duration,
 };
 
-use anyhow::bail;
+use anyhow::{bail, Context};
 use helix_core::{diagnostic::Severity, test, Selection, Transaction};
 use helix_term::{application::Application, args::Args, config::Config, keymap::merge_keys};
 use helix_view::{current_ref, doc, editor::LspConfig, input::parse_macro, Editor};
+use serde_json::{json, Value};
 use tempfile::NamedTempFile;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 #[cfg(windows)]
@@ -233,6 +235,174 @@ pub fn test_syntax_loader(overrides: Option<String>) -> helix_core::syntax::Load
     helix_core::syntax::Loader::new(lang.try_into().unwrap()).unwrap()
 }
 
+/// Set for the mock language servers of [mock_language_server_loader] to the directory they
+/// take their files from and log their messages to.
+pub const MOCK_LANGUAGE_SERVER_DIR: &str = "HELIX_MOCK_LANGUAGE_SERVER_DIR";
+
+/// A language config loader using a mock language server for `language`.
+///
+/// The server runs the `server` test of this test binary, which calls [mock_server].
+pub fn mock_language_server_loader(
+    language: &str,
+    server: &str,
+    dir: &Path,
+) -> helix_core::syntax::Loader {
+    mock_language_servers_loader(language, &[("mock", server, dir)])
+}
+
+/// Like [mock_language_server_loader], with several mock language servers for `language`, given
+/// by their name, test and the directory set as their `MOCK_LANGUAGE_SERVER_DIR`.
+pub fn mock_language_servers_loader(
+    language: &str,
+    servers: &[(&str, &str, &Path)],
+) -> helix_core::syntax::Loader {
+    let command = std::env::current_exe().unwrap();
+    let names: Vec<_> = servers.iter().map(|(name, _, _)| *name).collect();
+    let mut overrides = format!(
+        r#"
+[[language]]
+name = {}
+language-servers = {}
+"#,
+        toml::Value::from(language),
+        toml::Value::from(names),
+    );
+    for (name, server, dir) in servers {
+        let args = [
+            *server,
+            "--exact",
+            "--nocapture",
+            "--quiet",
+            "--test-threads=1",
+        ];
+        overrides.push_str(&format!(
+            r#"
+[language-server.{name}]
+command = {}
+args = {}
+environment = {{ {MOCK_LANGUAGE_SERVER_DIR} = {} }}
+"#,
+            toml::Value::from(command.to_str().unwrap()),
+            toml::Value::from(args.to_vec()),
+            toml::Value::from(dir.to_str().unwrap()),
+        ));
+    }
+    test_syntax_loader(Some(overrides))
+}
+
+fn read_lsp_message(reader: &mut impl BufRead) -> Option<Value> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        if line == "\r\n" {
+            break;
+        }
+        if let Some(len) = line.trim().strip_prefix("Content-Length: ") {
+            content_length = len.parse().ok();
+        }
+    }
+    let mut content = vec![0; content_length?];
+    reader.read_exact(&mut content).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// Responds to `request` with `result`. Notifications are not responded to.
+fn write_lsp_response(writer: &mut impl Write, request: &Value, result: Value) {
+    let Some(id) = request.get("id") else {
+        return;
+    };
+    let response = json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{response}",
+        response.len()
+    )
+    .unwrap();
+    writer.flush().unwrap();
+}
+
+/// Acts as a language server if this test binary was started by [mock_language_server_loader],
+/// and returns right away otherwise.
+///
+/// `initialize` is answered with the result of `capabilities` and `exit` stops the server. Other
+/// messages are passed to `respond` along with the `MOCK_LANGUAGE_SERVER_DIR` and their method.
+/// Requests are answered with its result, or `null` for `None`.
+pub fn mock_server(
+    capabilities: impl FnOnce(&Path) -> Value,
+    mut respond: impl FnMut(&Path, &str, &Value) -> Option<Value>,
+) {
+    let Some(dir) = std::env::var_os(MOCK_LANGUAGE_SERVER_DIR).map(PathBuf::from) else {
+        return;
+    };
+    let mut capabilities = Some(capabilities);
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+
+    while let Some(message) = read_lsp_message(&mut stdin) {
+        let method = message["method"].as_str().unwrap_or_default();
+        let result = match method {
+            "initialize" => {
+                let capabilities = capabilities.take().expect("initialized twice")(&dir);
+                Some(json!({ "capabilities": capabilities }))
+            }
+            "exit" => return,
+            _ => respond(&dir, method, &message),
+        };
+        write_lsp_response(&mut stdout, &message, result.unwrap_or(Value::Null));
+    }
+}
+
+/// Appends `line` to the log `name` in `dir`, for the tests to check with [read_log] what a mock
+/// language server was sent.
+pub fn write_log(dir: &Path, name: &str, line: &str) {
+    let mut log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(name))
+        .unwrap();
+    writeln!(log, "{line}").unwrap();
+}
+
+/// The log `name` in `dir` written with [write_log], empty if nothing was logged yet.
+pub fn read_log(dir: &Path, name: &str) -> String {
+    std::fs::read_to_string(dir.join(name)).unwrap_or_default()
+}
+
+/// Whether there are `count` language servers, which are all initialized.
+pub fn all_initialized(app: &Application, count: usize) -> bool {
+    let clients: Vec<_> = app.editor.language_servers.iter_clients().collect();
+    clients.len() == count && clients.iter().all(|client| client.is_initialized())
+}
+
+pub fn send_keys(tx: &UnboundedSender<std::io::Result<Event>>, keys: &str) -> anyhow::Result<()> {
+    for key_event in parse_macro(keys)? {
+        tx.send(Ok(Event::Key(KeyEvent::from(key_event))))?;
+    }
+    Ok(())
+}
+
+/// Runs the event loop until `done` returns true, as language server responses arrive
+/// asynchronously.
+pub async fn run_until(
+    app: &mut Application,
+    input: &mut UnboundedReceiverStream<std::io::Result<Event>>,
+    done: impl Fn(&Application) -> bool,
+) -> anyhow::Result<()> {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !done(app) {
+            // The idle timer is cleared once it fires, reset it to not wait for input forever.
+            app.editor.reset_idle_timer();
+            app.event_loop_until_idle(input).await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .context("timed out waiting for the language server")
+}
+
 /// Use this for very simple test cases where there is one input
 /// document, selection, and sequence of key presses, and you just
 /// want to verify the resulting document and selection.
@@ -294,6 +464,8 @@ pub fn test_editor_config() -> helix_view::editor::Config {
             enable: false,
             ..Default::default()
         },
+        // Tests mustn't read or write the session file of the user.
+        project_sessions: false,
         ..Default::default()
     }
 }
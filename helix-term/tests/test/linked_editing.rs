@@ -0,0 +1,102 @@
+use helix_term::application::Application;
+use helix_view::{doc, editor::LspConfig};
+use serde_json::json;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::*;
+
+const REQUEST_LOG: &str = "requests.log";
+
+/// Not a test: runs as the language server of [mock_language_server_loader].
+#[test]
+fn mock_language_server() {
+    let range = |start, end| {
+        json!({
+            "start": { "line": 0, "character": start },
+            "end": { "line": 0, "character": end },
+        })
+    };
+    mock_server(
+        |_| json!({ "linkedEditingRangeProvider": true }),
+        |dir, method, _| match method {
+            // The names of the tags of `<div>text</div>`.
+            "textDocument/linkedEditingRange" => {
+                write_log(dir, REQUEST_LOG, method);
+                Some(json!({ "ranges": [range(1, 4), range(11, 14)] }))
+            }
+            _ => None,
+        },
+    );
+}
+
+fn text(app: &Application) -> String {
+    doc!(app.editor).text().to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_linked_editing_ranges() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = helix_stdx::path::canonicalize(tmp.path());
+    let file = dir.join("index.html");
+    std::fs::write(&file, "<div>text</div>\n")?;
+
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        ..Default::default()
+    };
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(&file, None)
+        .with_lang_loader(mock_language_server_loader(
+            "html",
+            "test::linked_editing::mock_language_server",
+            &dir,
+        ))
+        .build()?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 1)).await?;
+
+    // Appending to the name of the opening tag requests the linked ranges.
+    send_keys(&tx, "gg3la")?;
+    run_until(&mut app, &mut input, |app| {
+        doc!(app.editor).linked_ranges.len() == 2
+    })
+    .await?;
+
+    send_keys(&tx, "x")?;
+    run_until(&mut app, &mut input, |app| {
+        text(app) == "<divx>text</divx>\n"
+    })
+    .await?;
+    send_keys(&tx, "<backspace>")?;
+    run_until(&mut app, &mut input, |app| text(app) == "<div>text</div>\n").await?;
+
+    // Leaving insert mode clears the linked ranges.
+    send_keys(&tx, "<esc>")?;
+    run_until(&mut app, &mut input, |app| {
+        doc!(app.editor).linked_ranges.is_empty()
+    })
+    .await?;
+
+    // A character that can't be part of a tag name ends linked editing.
+    send_keys(&tx, "a")?;
+    run_until(&mut app, &mut input, |app| {
+        doc!(app.editor).linked_ranges.len() == 2
+    })
+    .await?;
+    send_keys(&tx, " ")?;
+    run_until(&mut app, &mut input, |app| {
+        doc!(app.editor).linked_ranges.is_empty()
+    })
+    .await?;
+    assert_eq!(text(&app), "<div >text</div>\n");
+
+    assert_eq!(read_log(&dir, REQUEST_LOG).lines().count(), 2);
+
+    test_key_sequence(&mut app, Some("<esc>:qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use helix_view::editor::LspConfig;
+use serde_json::json;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::*;
+
+const NOTIFICATION_LOG: &str = "notifications.log";
+
+/// Not a test: runs as the language servers of [mock_language_servers_loader].
+#[test]
+fn mock_language_server() {
+    mock_server(
+        |_| json!({}),
+        |dir, method, message| {
+            if let "textDocument/didOpen" | "textDocument/didClose" = method {
+                let document = &message["params"]["textDocument"];
+                let uri = document["uri"].as_str().unwrap();
+                let file_name = uri.rsplit('/').next().unwrap();
+                let line = match document["languageId"].as_str() {
+                    Some(language_id) => format!("{method} {file_name} {language_id}"),
+                    None => format!("{method} {file_name}"),
+                };
+                write_log(dir, NOTIFICATION_LOG, &line);
+            }
+            None
+        },
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_broadcast_to_all_language_servers() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = helix_stdx::path::canonicalize(tmp.path());
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "fn main() {}\n")?;
+    let dirs = [dir.join("first"), dir.join("second")];
+    for dir in &dirs {
+        std::fs::create_dir(dir)?;
+    }
+
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        ..Default::default()
+    };
+    let server = "test::lsp_broadcast::mock_language_server";
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(&file, None)
+        .with_lang_loader(mock_language_servers_loader(
+            "rust",
+            &[("first", server, &dirs[0]), ("second", server, &dirs[1])],
+        ))
+        .build()?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| {
+        all_initialized(app, 2)
+            && dirs
+                .iter()
+                .all(|dir| read_log(dir, NOTIFICATION_LOG) == "textDocument/didOpen main.rs rust\n")
+    })
+    .await?;
+
+    // Both servers reopen the document with the new language ID.
+    send_keys(&tx, ":set-language-id rust-script<ret>")?;
+    run_until(&mut app, &mut input, |_| {
+        dirs.iter().all(|dir| {
+            read_log(dir, NOTIFICATION_LOG).ends_with(
+                "textDocument/didClose main.rs\ntextDocument/didOpen main.rs rust-script\n",
+            )
+        })
+    })
+    .await?;
+
+    // Both servers close the document under its old path.
+    send_keys(&tx, &format!(":move {}<ret>", dir.join("lib.rs").display()))?;
+    run_until(&mut app, &mut input, |_| {
+        dirs.iter().all(|dir| {
+            let notifications = read_log(dir, NOTIFICATION_LOG);
+            notifications
+                .matches("textDocument/didClose main.rs\n")
+                .count()
+                == 2
+                && notifications.contains("textDocument/didOpen lib.rs")
+        })
+    })
+    .await?;
+
+    test_key_sequence(&mut app, Some("<esc>:qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use helix_view::editor::LspConfig;
+use serde_json::json;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::*;
+
+const CAPABILITIES: &str = "capabilities.json";
+const REQUEST_LOG: &str = "requests.log";
+
+/// Not a test: runs as the language servers of [mock_language_servers_loader], advertising the
+/// capabilities in their directory.
+#[test]
+fn mock_language_server() {
+    mock_server(
+        |dir| {
+            let capabilities = std::fs::read_to_string(dir.join(CAPABILITIES)).unwrap();
+            serde_json::from_str(&capabilities).unwrap()
+        },
+        |dir, method, message| {
+            // Notifications aren't logged.
+            message.get("id")?;
+            write_log(dir, REQUEST_LOG, method);
+            match method {
+                "textDocument/hover" => Some(json!({ "contents": "fn main()" })),
+                _ => None,
+            }
+        },
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_route_hover_to_capable_server() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = helix_stdx::path::canonicalize(tmp.path());
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "fn main() {}\n")?;
+    // The server without hover support comes first.
+    let linter = dir.join("linter");
+    let analyzer = dir.join("analyzer");
+    for (dir, capabilities) in [
+        (&linter, json!({ "documentFormattingProvider": true })),
+        (&analyzer, json!({ "hoverProvider": true })),
+    ] {
+        std::fs::create_dir(dir)?;
+        std::fs::write(dir.join(CAPABILITIES), capabilities.to_string())?;
+    }
+
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        ..Default::default()
+    };
+    let server = "test::lsp_routing::mock_language_server";
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(&file, None)
+        .with_lang_loader(mock_language_servers_loader(
+            "rust",
+            &[("linter", server, &linter), ("analyzer", server, &analyzer)],
+        ))
+        .build()?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 2)).await?;
+
+    send_keys(&tx, "<space>k")?;
+    run_until(&mut app, &mut input, |_| {
+        read_log(&analyzer, REQUEST_LOG).contains("textDocument/hover")
+    })
+    .await?;
+    assert_status_not_error(&app.editor);
+    assert!(!read_log(&linter, REQUEST_LOG).contains("textDocument/hover"));
+
+    test_key_sequence(&mut app, Some("<esc>:qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
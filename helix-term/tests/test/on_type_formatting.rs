@@ -0,0 +1,68 @@
+use helix_term::application::Application;
+use helix_view::{doc, editor::LspConfig};
+use serde_json::json;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::*;
+
+/// Not a test: runs as the language server of [mock_language_server_loader].
+#[test]
+fn mock_language_server() {
+    mock_server(
+        |_| json!({ "documentOnTypeFormattingProvider": { "firstTriggerCharacter": ";" } }),
+        |_, method, _| match method {
+            // Puts spaces around the `=` of `let x=1;`.
+            "textDocument/onTypeFormatting" => Some(json!([{
+                "range": {
+                    "start": { "line": 0, "character": 5 },
+                    "end": { "line": 0, "character": 6 },
+                },
+                "newText": " = ",
+            }])),
+            _ => None,
+        },
+    );
+}
+
+fn text(app: &Application) -> String {
+    doc!(app.editor).text().to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_format_on_trigger_character() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = helix_stdx::path::canonicalize(tmp.path());
+    let file = dir.join("main.rs");
+    std::fs::write(&file, "let x=1\n")?;
+
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        on_type_formatting: true,
+        ..Default::default()
+    };
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(&file, None)
+        .with_lang_loader(mock_language_server_loader(
+            "rust",
+            "test::on_type_formatting::mock_language_server",
+            &dir,
+        ))
+        .build()?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 1)).await?;
+
+    send_keys(&tx, "A;")?;
+    run_until(&mut app, &mut input, |app| text(app) == "let x = 1;\n").await?;
+
+    // The formatting is undone together with the typed character.
+    send_keys(&tx, "<esc>u")?;
+    run_until(&mut app, &mut input, |app| text(app) == "let x=1\n").await?;
+
+    test_key_sequence(&mut app, Some(":qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
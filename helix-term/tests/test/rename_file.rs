@@ -0,0 +1,318 @@
+use std::path::Path;
+
+use helix_core::diagnostic::Severity;
+use helix_lsp::lsp::Url;
+use helix_stdx::path;
+use helix_term::{application::Application, ui::completers};
+use helix_view::{doc, editor::LspConfig};
+use serde_json::{json, Value};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::*;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rename_file_with_unsaved_changes() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let source_file = dir.path().join("file.txt");
+    let target_file = dir.path().join("renamed.txt");
+    std::fs::write(&source_file, "hello\n")?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(&source_file, None)
+        .build()?;
+
+    test_key_sequence(
+        &mut app,
+        Some("iworld <esc>:rename-file renamed.txt<ret>"),
+        Some(&|app| {
+            assert_eq!(&Severity::Error, app.editor.get_status().unwrap().1);
+            assert_eq!(doc!(app.editor).path(), Some(&source_file));
+        }),
+        false,
+    )
+    .await?;
+
+    assert!(source_file.is_file());
+    assert!(!target_file.exists());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rename_file() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir = path::canonicalize(dir.path());
+    std::fs::create_dir(dir.join("sub"))?;
+    let source_file = dir.join("sub").join("file.txt");
+    std::fs::write(&source_file, "hello\n")?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(&source_file, None)
+        .build()?;
+
+    // Relative paths are resolved against the directory of the file.
+    let target_file = dir.join("sub").join("renamed.txt");
+    // Existing files are only replaced after confirming.
+    let existing_file = dir.join("existing.txt");
+    std::fs::write(&existing_file, "existing\n")?;
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(":rename-file renamed.txt<ret>"),
+                Some(&|app| {
+                    assert_status_not_error(&app.editor);
+                    let doc = doc!(app.editor);
+                    assert_eq!(doc.path(), Some(&target_file));
+                    assert!(doc.display_name().ends_with("renamed.txt"));
+                    assert!(!source_file.exists());
+                    assert_eq!(std::fs::read_to_string(&target_file).unwrap(), "hello\n");
+                }),
+            ),
+            (Some(":rename-file ../existing.txt<ret>"), None),
+            (
+                Some("n<ret>"),
+                Some(&|app| {
+                    assert_eq!(doc!(app.editor).path(), Some(&target_file));
+                    assert_eq!(
+                        std::fs::read_to_string(&existing_file).unwrap(),
+                        "existing\n"
+                    );
+                }),
+            ),
+            (Some(":rename-file ../existing.txt<ret>"), None),
+            (
+                Some("y<ret>"),
+                Some(&|app| {
+                    assert_status_not_error(&app.editor);
+                    assert_eq!(doc!(app.editor).path(), Some(&existing_file));
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    assert!(!target_file.exists());
+    assert_eq!(std::fs::read_to_string(&existing_file)?, "hello\n");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rename_file_completes_relative_to_file() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir(dir.path().join("sub"))?;
+    let source_file = dir.path().join("sub").join("file.txt");
+    std::fs::write(&source_file, "hello\n")?;
+    std::fs::write(dir.path().join("sub").join("other.txt"), "")?;
+    std::fs::write(dir.path().join("top.txt"), "")?;
+
+    let app = helpers::AppBuilder::new()
+        .with_file(&source_file, None)
+        .build()?;
+
+    let complete = |input| {
+        completers::document_relative_filename(&app.editor, input)
+            .into_iter()
+            .map(|(range, span)| (range.start, span.content.into_owned()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(complete("oth"), [(0, "other.txt".to_string())]);
+    assert_eq!(complete("../to"), [(3, "top.txt".to_string())]);
+
+    Ok(())
+}
+
+const REQUEST_LOG: &str = "requests.log";
+
+/// Not a test: runs as the language server of [mock_language_server_loader].
+///
+/// Logs the file operation requests with whether the file was already moved and their parameters.
+#[test]
+fn mock_language_server() {
+    let range = |line, start, end| {
+        json!({
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        })
+    };
+    let moved = |params: &Value| {
+        let exists = |key: &str| {
+            let uri = Url::parse(params["files"][0][key].as_str().unwrap()).unwrap();
+            uri.to_file_path().unwrap().exists()
+        };
+        !exists("oldUri") && exists("newUri")
+    };
+    mock_server(
+        |_| {
+            let filters = json!({ "filters": [{ "pattern": { "glob": "**/*.rs" } }] });
+            json!({
+                "workspace": {
+                    "fileOperations": { "willRename": filters, "didRename": filters },
+                },
+            })
+        },
+        |dir, method, message| {
+            let params = &message["params"];
+            if let "workspace/willRenameFiles" | "workspace/didRenameFiles" = method {
+                write_log(
+                    dir,
+                    REQUEST_LOG,
+                    &format!("{method} {} {params}", moved(params)),
+                );
+            }
+            let uri = |name: &str| Url::from_file_path(dir.join(name)).unwrap().to_string();
+            // Renaming `util.rs` updates the module declaration and adds a comment to the renamed
+            // file itself, which is only found under its old path before the rename.
+            if method != "workspace/willRenameFiles"
+                || params["files"][0]["oldUri"] != uri("util.rs")
+            {
+                return None;
+            }
+            Some(json!({
+                "changes": {
+                    uri("lib.rs"): [{ "range": range(0, 4, 8), "newText": "helpers" }],
+                    uri("util.rs"): [{ "range": range(0, 0, 0), "newText": "// helpers\n" }],
+                },
+            }))
+        },
+    );
+}
+
+/// The logged requests as their method, whether the file was already moved and their parameters.
+fn requests(dir: &Path) -> Vec<(String, bool, Value)> {
+    read_log(dir, REQUEST_LOG)
+        .lines()
+        .map(|line| {
+            let mut parts = line.splitn(3, ' ');
+            let method = parts.next().unwrap().to_string();
+            let moved = parts.next().unwrap() == "true";
+            let params = serde_json::from_str(parts.next().unwrap()).unwrap();
+            (method, moved, params)
+        })
+        .collect()
+}
+
+/// An application editing `file` in `dir` with the mock language server.
+fn mock_app(dir: &Path, file: &str) -> anyhow::Result<Application> {
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        ..Default::default()
+    };
+    AppBuilder::new()
+        .with_config(config)
+        .with_file(dir.join(file), None)
+        .with_lang_loader(mock_language_server_loader(
+            "rust",
+            "test::rename_file::mock_language_server",
+            dir,
+        ))
+        .build()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rename_file_notifies_language_servers() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = path::canonicalize(tmp.path());
+    std::fs::write(dir.join("main.rs"), "fn main() {}\n")?;
+    let mut app = mock_app(&dir, "main.rs")?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 1)).await?;
+
+    send_keys(&tx, ":rename-file renamed.rs<ret>")?;
+    run_until(&mut app, &mut input, |_| requests(&dir).len() == 2).await?;
+
+    let url = |name| Url::from_file_path(dir.join(name)).unwrap();
+    let params = json!({
+        "files": [{ "oldUri": url("main.rs"), "newUri": url("renamed.rs") }],
+    });
+    assert_eq!(
+        requests(&dir),
+        [
+            (
+                "workspace/willRenameFiles".to_string(),
+                false,
+                params.clone()
+            ),
+            ("workspace/didRenameFiles".to_string(), true, params),
+        ]
+    );
+
+    test_key_sequence(&mut app, Some(":qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rename_file_applies_will_rename_edits() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = path::canonicalize(tmp.path());
+    std::fs::write(dir.join("lib.rs"), "mod util;\n")?;
+    std::fs::write(dir.join("util.rs"), "pub fn foo() {}\n")?;
+    let mut app = mock_app(&dir, "util.rs")?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 1)).await?;
+
+    send_keys(&tx, ":rename-file helpers.rs<ret>")?;
+    run_until(&mut app, &mut input, |_| requests(&dir).len() == 2).await?;
+
+    // The server was asked before the file was moved and told after.
+    let requests: Vec<_> = requests(&dir)
+        .into_iter()
+        .map(|(method, moved, _)| (method, moved))
+        .collect();
+    assert_eq!(
+        requests,
+        [
+            ("workspace/willRenameFiles".to_string(), false),
+            ("workspace/didRenameFiles".to_string(), true),
+        ]
+    );
+    assert!(!dir.join("util.rs").exists());
+
+    // The edit of the renamed file was applied to its buffer before the rename.
+    let doc = doc!(app.editor);
+    assert_eq!(doc.path(), Some(&dir.join("helpers.rs")));
+    assert_eq!(doc.text().to_string(), "// helpers\npub fn foo() {}\n");
+    assert!(app.editor.document_by_path(dir.join("util.rs")).is_none());
+    let lib = app
+        .editor
+        .document_by_path(dir.join("lib.rs"))
+        .expect("lib.rs is opened by the workspace edit");
+    assert_eq!(lib.text().to_string(), "mod helpers;\n");
+
+    test_key_sequence(&mut app, Some(":qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
+
+/// Servers which aren't interested in the file aren't asked.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rename_file_without_interest() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = path::canonicalize(tmp.path());
+    std::fs::write(dir.join("notes.txt"), "text\n")?;
+    let mut app = mock_app(&dir, "lib.rs")?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 1)).await?;
+
+    send_keys(
+        &tx,
+        &format!(":open {}<ret>", dir.join("notes.txt").display()),
+    )?;
+    send_keys(&tx, ":rename-file todo.txt<ret>")?;
+    run_until(&mut app, &mut input, |_| dir.join("todo.txt").exists()).await?;
+    assert_eq!(requests(&dir), []);
+
+    test_key_sequence(&mut app, Some(":qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
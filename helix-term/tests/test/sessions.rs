@@ -0,0 +1,63 @@
+use super::*;
+
+use helix_core::Range;
+use helix_stdx::path;
+use helix_view::{current_ref, doc, doc_mut, editor::Action, view};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_session_restore_layout_and_selections() -> anyhow::Result<()> {
+    let file1 = temp_file_with_contents("one\ntwo\nthree\n")?;
+    let file2 = temp_file_with_contents("hello\n")?;
+    let file3 = tempfile::NamedTempFile::new()?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file1.path(), None)
+        .build()?;
+
+    let editor = &mut app.editor;
+    let view_id = view!(editor).id;
+    doc_mut!(editor).set_selection(view_id, Range::new(4, 8).into());
+    editor.open(file2.path(), Action::VerticalSplit)?;
+    editor.open(file3.path(), Action::HorizontalSplit)?;
+    assert_eq!(3, editor.tree.traverse().count());
+
+    let session = editor.capture_session(helix_stdx::env::current_working_dir());
+    assert_eq!(3, session.documents.len());
+    assert_eq!(2, session.focus);
+
+    let file3_path = path::normalize(file3.path());
+    file3.close()?;
+    let others: Vec<_> = editor
+        .tree
+        .traverse()
+        .map(|(view_id, _)| view_id)
+        .filter(|&view_id| view_id != editor.tree.focus)
+        .collect();
+    for view_id in others {
+        editor.close(view_id);
+    }
+
+    let missing = editor.restore_session(session);
+    assert_eq!(vec![file3_path], missing);
+    // The view of the missing file is dropped and its split collapses.
+    assert_eq!(2, editor.tree.traverse().count());
+
+    let (view, doc) = current_ref!(editor);
+    assert_eq!(Some(&path::normalize(file1.path())), doc.path());
+    assert_eq!(Range::new(4, 8), doc.selection(view.id).primary());
+
+    let other = editor
+        .tree
+        .traverse()
+        .find(|(view_id, _)| *view_id != view.id)
+        .map(|(_, view)| view.doc)
+        .unwrap();
+    assert_eq!(
+        Some(&path::normalize(file2.path())),
+        doc!(editor, &other).path()
+    );
+
+    test_key_sequence(&mut app, Some(":qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use super::*;
+
+use helix_stdx::path;
+use helix_term::{application::Application, ui::EditorView};
+use helix_view::{doc, graphics::Rect};
+use tui::buffer::Buffer;
+
+fn current_path(app: &Application) -> Option<std::path::PathBuf> {
+    doc!(app.editor).path().cloned()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tab_open_switch_close() -> anyhow::Result<()> {
+    let file1 = tempfile::NamedTempFile::new()?;
+    let file2 = tempfile::NamedTempFile::new()?;
+
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file1.path(), None)
+        .build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(&format!(":tabopen {}<ret>", file2.path().to_string_lossy())),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert_eq!(2, app.editor.tabs.len());
+                    assert_eq!(1, app.editor.active_tab);
+                    assert_eq!(1, app.editor.tree.traverse().count());
+                    assert_eq!(Some(path::normalize(file2.path())), current_path(app));
+                }),
+            ),
+            (
+                // Splits only affect the current tab.
+                Some(":vsplit<ret>"),
+                Some(&|app| {
+                    assert_eq!(2, app.editor.tree.traverse().count());
+                    assert_eq!(3, app.editor.tree.views().count());
+                }),
+            ),
+            (
+                Some(":tabprev<ret>"),
+                Some(&|app| {
+                    assert_eq!(0, app.editor.active_tab);
+                    assert_eq!(1, app.editor.tree.traverse().count());
+                    assert_eq!(Some(path::normalize(file1.path())), current_path(app));
+                }),
+            ),
+            (
+                // Switching wraps around.
+                Some(":tabprev<ret>"),
+                Some(&|app| {
+                    assert_eq!(1, app.editor.active_tab);
+                    assert_eq!(2, app.editor.tree.traverse().count());
+                }),
+            ),
+            (
+                Some(":tabclose<ret>"),
+                Some(&|app| {
+                    helpers::assert_status_not_error(&app.editor);
+                    assert_eq!(1, app.editor.tabs.len());
+                    assert_eq!(0, app.editor.active_tab);
+                    assert_eq!(1, app.editor.tree.views().count());
+                    // Buffers shown in the closed tab stay open.
+                    assert_eq!(2, app.editor.documents().count());
+                    assert_eq!(Some(path::normalize(file1.path())), current_path(app));
+                }),
+            ),
+            (
+                Some(":tabclose<ret>"),
+                Some(&|app| {
+                    let (msg, severity) = app.editor.get_status().unwrap();
+                    assert_eq!(&helix_view::editor::Severity::Error, severity);
+                    assert_eq!("'tabclose': Cannot close the last tab", msg);
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tab_quit_last_view_closes_tab() -> anyhow::Result<()> {
+    let mut app = helpers::AppBuilder::new().build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(":tabopen<ret>:tabopen<ret>:tabprev<ret>"),
+                Some(&|app| {
+                    assert_eq!(3, app.editor.tabs.len());
+                    assert_eq!(1, app.editor.active_tab);
+                }),
+            ),
+            (
+                Some(":q<ret>"),
+                Some(&|app| {
+                    assert_eq!(2, app.editor.tabs.len());
+                    assert_eq!(1, app.editor.active_tab);
+                    assert!(!app.editor.tree.is_empty());
+                }),
+            ),
+            (
+                Some(":qa<ret>"),
+                Some(&|app| {
+                    assert!(app.editor.tree.is_empty());
+                }),
+            ),
+        ],
+        true,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tabline_render() -> anyhow::Result<()> {
+    let file = tempfile::NamedTempFile::new()?;
+    let mut app = helpers::AppBuilder::new().build()?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (
+                Some(&format!(":tabopen {}<ret>", file.path().to_string_lossy())),
+                Some(&|app| {
+                    let area = Rect::new(0, 0, 80, 1);
+                    let mut surface = Buffer::empty(area);
+                    EditorView::render_tabline(&app.editor, area, &mut surface);
+
+                    let line: String = (0..area.width)
+                        .map(|x| surface.get(x, 0).unwrap().symbol.as_str())
+                        .collect();
+                    let file_name = file.path().file_name().unwrap().to_string_lossy();
+                    assert_eq!(format!(" 1: [scratch]  2: {file_name}"), line.trim_end());
+
+                    let theme = &app.editor.theme;
+                    let active = theme
+                        .try_get("ui.bufferline.active")
+                        .unwrap_or_else(|| theme.get("ui.statusline.active"));
+                    let second_tab = " 1: [scratch] ".len() as u16;
+                    assert_eq!(active.fg, surface.get(second_tab, 0).unwrap().fg.into());
+                }),
+            ),
+            (Some(":qa!<ret>"), None),
+        ],
+        true,
+    )
+    .await?;
+
+    Ok(())
+}
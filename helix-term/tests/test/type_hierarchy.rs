@@ -0,0 +1,177 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use helix_term::application::Application;
+use helix_view::{current_ref, editor::LspConfig};
+use serde_json::{json, Value};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+#[cfg(windows)]
+use crossterm::event::Event;
+#[cfg(not(windows))]
+use termina::event::Event;
+
+use super::*;
+
+const REQUEST_LOG: &str = "requests.log";
+
+/// A type hierarchy of `Dog`, which implements `Animal` and is extended by `Puppy`.
+const TYPES: [(&str, &str); 3] = [
+    ("Animal", "trait Animal {}\n"),
+    ("Dog", "struct Dog;\n"),
+    ("Puppy", "struct Puppy;\n"),
+];
+
+/// Only `Dog` is in a Rust file, which the hierarchy is requested from. The other types are in
+/// files without a language so that their previews don't depend on the Rust grammar.
+fn type_file(dir: &Path, name: &str) -> PathBuf {
+    let extension = if name == "Dog" { "rs" } else { "txt" };
+    dir.join(format!("{}.{extension}", name.to_lowercase()))
+}
+
+fn type_item(dir: &Path, name: &str) -> Value {
+    let (_, text) = TYPES.iter().find(|(n, _)| *n == name).unwrap();
+    let start = text.find(name).unwrap();
+    let uri = helix_lsp::lsp::Url::from_file_path(type_file(dir, name)).unwrap();
+    json!({
+        "name": name,
+        "kind": 23,
+        "uri": uri,
+        "range": {
+            "start": { "line": 0, "character": 0 },
+            "end": { "line": 0, "character": text.trim_end().len() },
+        },
+        "selectionRange": {
+            "start": { "line": 0, "character": start },
+            "end": { "line": 0, "character": start + name.len() },
+        },
+    })
+}
+
+/// Not a test: runs as the language server of [mock_language_server_loader].
+#[test]
+fn mock_language_server() {
+    mock_server(
+        |_| json!({ "typeHierarchyProvider": true }),
+        |dir, method, message| {
+            let item = message["params"]["item"]["name"].as_str();
+            let result = match (method, item) {
+                ("textDocument/prepareTypeHierarchy", _) => json!([type_item(dir, "Dog")]),
+                ("typeHierarchy/supertypes", Some("Dog")) => json!([type_item(dir, "Animal")]),
+                ("typeHierarchy/subtypes", Some("Dog")) => json!([type_item(dir, "Puppy")]),
+                ("typeHierarchy/supertypes" | "typeHierarchy/subtypes", _) => json!([]),
+                _ => return None,
+            };
+            write_log(
+                dir,
+                REQUEST_LOG,
+                &format!("{method} {}", item.unwrap_or_default()),
+            );
+            Some(result)
+        },
+    );
+}
+
+fn requests(dir: &Path) -> Vec<String> {
+    read_log(dir, REQUEST_LOG)
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+/// Settles pending callbacks, such as the children of the picker root being added.
+async fn settle(
+    app: &mut Application,
+    input: &mut UnboundedReceiverStream<std::io::Result<Event>>,
+) {
+    for _ in 0..5 {
+        app.editor.reset_idle_timer();
+        app.event_loop_until_idle(input).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+fn current_path(app: &Application) -> Option<PathBuf> {
+    let (_, doc) = current_ref!(app.editor);
+    doc.path().cloned()
+}
+
+fn cursor(app: &Application) -> usize {
+    let (view, doc) = current_ref!(app.editor);
+    doc.selection(view.id)
+        .primary()
+        .cursor(doc.text().slice(..))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_type_hierarchy_pickers() -> anyhow::Result<()> {
+    let tmp = tempfile::tempdir()?;
+    let dir = helix_stdx::path::canonicalize(tmp.path());
+    for (name, text) in TYPES {
+        std::fs::write(type_file(&dir, name), text)?;
+    }
+
+    let mut config = test_config();
+    config.editor.lsp = LspConfig {
+        enable: true,
+        ..Default::default()
+    };
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_file(type_file(&dir, "Dog"), None)
+        .with_lang_loader(mock_language_server_loader(
+            "rust",
+            "test::type_hierarchy::mock_language_server",
+            &dir,
+        ))
+        .build()?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut input = UnboundedReceiverStream::new(rx);
+    run_until(&mut app, &mut input, |app| all_initialized(app, 1)).await?;
+
+    // The picker opens with the prepared item as its root and requests its supertypes right
+    // away. Selecting the supertype opens its file at the name of the type.
+    send_keys(&tx, "gg<space>th")?;
+    run_until(&mut app, &mut input, |_| requests(&dir).len() == 2).await?;
+    settle(&mut app, &mut input).await;
+    send_keys(&tx, "<down><ret>")?;
+    let animal = type_file(&dir, "Animal");
+    run_until(&mut app, &mut input, |app| {
+        current_path(app).as_ref() == Some(&animal)
+    })
+    .await?;
+    assert_eq!(cursor(&app), "trait ".len());
+
+    send_keys(&tx, "<C-o>gg<space>ts")?;
+    run_until(&mut app, &mut input, |_| requests(&dir).len() == 4).await?;
+    settle(&mut app, &mut input).await;
+    // Expanding the subtype requests its own subtypes.
+    send_keys(&tx, "<down><tab>")?;
+    run_until(&mut app, &mut input, |_| requests(&dir).len() == 5).await?;
+    settle(&mut app, &mut input).await;
+    send_keys(&tx, "<ret>")?;
+    let puppy = type_file(&dir, "Puppy");
+    run_until(&mut app, &mut input, |app| {
+        current_path(app).as_ref() == Some(&puppy)
+    })
+    .await?;
+    assert_eq!(cursor(&app), "struct ".len());
+
+    assert_eq!(
+        requests(&dir),
+        [
+            "textDocument/prepareTypeHierarchy ",
+            "typeHierarchy/supertypes Dog",
+            "textDocument/prepareTypeHierarchy ",
+            "typeHierarchy/subtypes Dog",
+            "typeHierarchy/subtypes Puppy",
+        ]
+    );
+
+    test_key_sequence(&mut app, Some(":qa!<ret>"), None, true).await?;
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use crate::{backend::Backend, buffer::Cell, terminal::Config};
+use crate::{
+    backend::{Backend, ColorDepth},
+    buffer::Cell,
+    terminal::Config,
+};
 use crossterm::{
     cursor::{Hide, MoveTo, SetCursorStyle, Show},
     event::{
@@ -53,6 +57,8 @@ fn reset_cursor_approach(terminfo: TermInfo) -> String {
 struct Capabilities {
     /// Support for undercurled, underdashed, etc.
     has_extended_underlines: bool,
+    /// Support for the blinking cursor styles of DECSCUSR.
+    has_cursor_blink: bool,
     /// Support for resetting the cursor style back to normal.
     reset_cursor_command: String,
 }
@@ -61,6 +67,7 @@ impl Default for Capabilities {
     fn default() -> Self {
         Self {
             has_extended_underlines: false,
+            has_cursor_blink: false,
             reset_cursor_command: "\x1B[0 q".to_string(),
         }
     }
@@ -75,6 +82,8 @@ pub fn from_env_or_default(config: &Config) -> Self {
         match termini::TermInfo::from_env() {
             Err(_) => Capabilities {
                 has_extended_underlines: config.force_enable_extended_underlines,
+                // Windows Terminal supports DECSCUSR but doesn't ship a terminfo entry.
+                has_cursor_blink: std::env::var_os("WT_SESSION").is_some(),
                 ..Capabilities::default()
             },
             Ok(t) => Capabilities {
@@ -86,6 +95,8 @@ pub fn from_env_or_default(config: &Config) -> Self {
                     || t.extended_cap("Su").is_some()
                     || vte_version() >= Some(5102)
                     || matches!(term_program().as_deref(), Some("WezTerm")),
+                // Ss: the terminfo extension for setting the cursor style with DECSCUSR
+                has_cursor_blink: t.extended_cap("Ss").is_some() || vte_version().is_some(),
                 reset_cursor_command: reset_cursor_approach(t),
             },
         }
@@ -293,11 +304,15 @@ fn hide_cursor(&mut self) -> io::Result<()> {
     }
 
     fn show_cursor(&mut self, kind: CursorKind) -> io::Result<()> {
-        let shape = match kind {
-            CursorKind::Block => SetCursorStyle::SteadyBlock,
-            CursorKind::Bar => SetCursorStyle::SteadyBar,
-            CursorKind::Underline => SetCursorStyle::SteadyUnderScore,
-            CursorKind::Hidden => unreachable!(),
+        let blink = self.config.cursor_blink && self.capabilities.has_cursor_blink;
+        let shape = match (kind, blink) {
+            (CursorKind::Block, false) => SetCursorStyle::SteadyBlock,
+            (CursorKind::Block, true) => SetCursorStyle::BlinkingBlock,
+            (CursorKind::Bar, false) => SetCursorStyle::SteadyBar,
+            (CursorKind::Bar, true) => SetCursorStyle::BlinkingBar,
+            (CursorKind::Underline, false) => SetCursorStyle::SteadyUnderScore,
+            (CursorKind::Underline, true) => SetCursorStyle::BlinkingUnderScore,
+            (CursorKind::Hidden, _) => unreachable!(),
         };
         execute!(self.buffer, Show, shape)
     }
@@ -321,8 +336,8 @@ fn flush(&mut self) -> io::Result<()> {
         self.buffer.flush()
     }
 
-    fn supports_true_color(&self) -> bool {
-        false
+    fn color_depth(&self) -> ColorDepth {
+        ColorDepth::from_env()
     }
 
     fn get_theme_mode(&self) -> Option<helix_view::theme::Mode> {
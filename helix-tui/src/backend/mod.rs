@@ -1,10 +1,10 @@
 //! Provides interface for controlling the terminal
 
-use std::io;
+use std::{ffi::OsString, io};
 
 use crate::{buffer::Cell, terminal::Config};
 
-use helix_view::graphics::{CursorKind, Rect};
+use helix_view::graphics::{Color, CursorKind, Rect};
 
 #[cfg(all(feature = "termina", not(windows)))]
 mod termina;
@@ -43,6 +43,147 @@ fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
     fn size(&self) -> Result<Rect, io::Error>;
     /// Flushes the terminal buffer
     fn flush(&mut self) -> Result<(), io::Error>;
-    fn supports_true_color(&self) -> bool;
+    /// The colors the terminal can display
+    fn color_depth(&self) -> ColorDepth;
+    fn supports_true_color(&self) -> bool {
+        self.color_depth() == ColorDepth::TrueColor
+    }
     fn get_theme_mode(&self) -> Option<helix_view::theme::Mode>;
 }
+
+/// The colors a terminal can display.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// The 256 colors of the xterm palette. RGB colors are approximated by the closest color of
+    /// the palette.
+    #[default]
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+/// The levels of each channel in the 6x6x6 color cube of the xterm palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Detects the color depth of the terminal from the environment, see [ColorDepth::detect].
+    #[cfg(windows)]
+    pub fn from_env() -> Self {
+        Self::TrueColor
+    }
+
+    /// Detects the color depth of the terminal from the environment, see [ColorDepth::detect].
+    #[cfg(not(windows))]
+    pub fn from_env() -> Self {
+        let terminfo = termini::TermInfo::from_env().ok();
+        Self::detect(|name| std::env::var_os(name), terminfo.as_ref())
+    }
+
+    /// Detects the color depth from the environment variables looked up with `var` and the
+    /// terminfo entry of the terminal: `$COLORTERM` is checked for `truecolor` or `24bit` first,
+    /// then terminfo for the `RGB` and `Tc` capabilities. Falls back to [ColorDepth::Ansi256].
+    pub fn detect(
+        var: impl Fn(&str) -> Option<OsString>,
+        terminfo: Option<&termini::TermInfo>,
+    ) -> Self {
+        if var("COLORTERM").is_some_and(|v| v == "truecolor" || v == "24bit")
+            || var("WSL_DISTRO_NAME").is_some()
+        {
+            return Self::TrueColor;
+        }
+
+        let true_color = terminfo.is_some_and(|t| {
+            t.extended_cap("RGB").is_some()
+                || t.extended_cap("Tc").is_some()
+                || (t.extended_cap("setrgbf").is_some() && t.extended_cap("setrgbb").is_some())
+        });
+        if true_color {
+            Self::TrueColor
+        } else {
+            Self::Ansi256
+        }
+    }
+
+    /// Converts `color` to a color the terminal can display.
+    pub fn adapt(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(ansi256(r, g, b)),
+            _ => color,
+        }
+    }
+}
+
+/// The index of the color of the xterm palette closest to the given RGB color, out of its color
+/// cube and grayscale ramp.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|&(a, b)| (a as i32 - b as i32).pow(2))
+            .sum::<i32>()
+    };
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - channel as i32).abs())
+            .unwrap()
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+    // The grayscale ramp runs from 8 to 238 in steps of 10.
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+    let gray = (gray_level, gray_level, gray_level);
+
+    if distance(gray) < distance(cube) {
+        232 + gray_index
+    } else {
+        16 + (36 * ri + 6 * gi + bi) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_color_depth() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        assert_eq!(
+            ColorDepth::detect(env(&[("COLORTERM", "truecolor")]), None),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::detect(env(&[("COLORTERM", "24bit")]), None),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::detect(env(&[("COLORTERM", "yes")]), None),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(ColorDepth::detect(env(&[]), None), ColorDepth::Ansi256);
+    }
+
+    #[test]
+    fn adapt_rgb_colors() {
+        assert_eq!(
+            ColorDepth::TrueColor.adapt(Color::Rgb(1, 2, 3)),
+            Color::Rgb(1, 2, 3)
+        );
+        let ansi256 = |r, g, b| ColorDepth::Ansi256.adapt(Color::Rgb(r, g, b));
+        assert_eq!(ansi256(0, 0, 0), Color::Indexed(16));
+        assert_eq!(ansi256(255, 255, 255), Color::Indexed(231));
+        assert_eq!(ansi256(255, 0, 0), Color::Indexed(196));
+        assert_eq!(ansi256(95, 135, 175), Color::Indexed(67));
+        assert_eq!(ansi256(128, 128, 128), Color::Indexed(244));
+        assert_eq!(ansi256(30, 30, 30), Color::Indexed(234));
+        assert_eq!(ColorDepth::Ansi256.adapt(Color::Red), Color::Red);
+    }
+}
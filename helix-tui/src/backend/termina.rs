@@ -16,7 +16,7 @@
 
 use crate::{buffer::Cell, terminal::Config};
 
-use super::Backend;
+use super::{Backend, ColorDepth};
 
 // These macros are helpers to set/unset modes like bracketed paste or enter/exit the alternate
 // screen.
@@ -52,6 +52,8 @@ struct Capabilities {
     synchronized_output: bool,
     true_color: bool,
     extended_underlines: bool,
+    /// Support for the blinking cursor styles of DECSCUSR.
+    cursor_blink: bool,
     theme_mode: Option<theme::Mode>,
 }
 
@@ -130,7 +132,10 @@ fn detect_capabilities(
 
         terminal.enter_raw_mode()?;
 
-        let mut capabilities = Capabilities::default();
+        let mut capabilities = Capabilities {
+            true_color: ColorDepth::from_env() == ColorDepth::TrueColor,
+            ..Capabilities::default()
+        };
         let start = Instant::now();
 
         capabilities.kitty_keyboard = match config.kitty_keyboard_protocol {
@@ -194,7 +199,7 @@ fn detect_capabilities(
                         value: dcs::DcsResponse::GraphicRendition(sgrs),
                         ..
                     }) => {
-                        capabilities.true_color =
+                        capabilities.true_color |=
                             sgrs.contains(&csi::Sgr::Background(TEST_COLOR.into()));
                         capabilities.extended_underlines =
                             sgrs.contains(&csi::Sgr::UnderlineColor(TEST_COLOR.into()));
@@ -213,6 +218,7 @@ fn detect_capabilities(
         }
 
         capabilities.extended_underlines |= config.force_enable_extended_underlines;
+        capabilities.cursor_blink = vte_version().is_some();
 
         let mut reset_cursor_command =
             Csi::Cursor(csi::Cursor::CursorStyle(CursorStyle::Default)).to_string();
@@ -223,6 +229,8 @@ fn detect_capabilities(
                 // HACK: once WezTerm can support DECRQSS/DECRPSS for SGR we can remove this line.
                 // <https://github.com/wezterm/wezterm/pull/6856>
                 || matches!(term_program().as_deref(), Some("WezTerm"));
+            // `Ss` is the terminfo extension for setting the cursor style with DECSCUSR.
+            capabilities.cursor_blink |= t.extended_cap("Ss").is_some();
 
             if let Some(termini::Value::Utf8String(se_str)) = t.extended_cap("Se") {
                 reset_cursor_command.push_str(se_str);
@@ -436,6 +444,7 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
         let mut underline_style = UnderlineStyle::Reset;
         let mut modifier = Modifier::empty();
         let mut last_pos: Option<(u16, u16)> = None;
+        let color_depth = self.color_depth();
         for (x, y, cell) in content {
             // Move the cursor if the previous location was not (x - 1, y)
             if !matches!(last_pos, Some(p) if x == p.0 + 1 && y == p.1) {
@@ -452,11 +461,11 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
 
             let mut attributes = SgrAttributes::default();
             if cell.fg != fg {
-                attributes.foreground = Some(cell.fg.into());
+                attributes.foreground = Some(color_depth.adapt(cell.fg).into());
                 fg = cell.fg;
             }
             if cell.bg != bg {
-                attributes.background = Some(cell.bg.into());
+                attributes.background = Some(color_depth.adapt(cell.bg).into());
                 bg = cell.bg;
             }
             if cell.modifier != modifier {
@@ -472,7 +481,9 @@ fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
                     write!(
                         self.terminal,
                         "{}",
-                        Csi::Sgr(csi::Sgr::UnderlineColor(cell.underline_color.into()))
+                        Csi::Sgr(csi::Sgr::UnderlineColor(
+                            color_depth.adapt(cell.underline_color).into()
+                        ))
                     )?;
                     underline_color = cell.underline_color;
                 }
@@ -518,12 +529,10 @@ fn hide_cursor(&mut self) -> io::Result<()> {
     }
 
     fn show_cursor(&mut self, kind: CursorKind) -> io::Result<()> {
-        let style = match kind {
-            CursorKind::Block => CursorStyle::SteadyBlock,
-            CursorKind::Bar => CursorStyle::SteadyBar,
-            CursorKind::Underline => CursorStyle::SteadyUnderline,
-            CursorKind::Hidden => unreachable!(),
-        };
+        let style = cursor_style(
+            kind,
+            self.config.cursor_blink && self.capabilities.cursor_blink,
+        );
         write!(
             self.terminal,
             "{}{}",
@@ -563,8 +572,12 @@ fn flush(&mut self) -> io::Result<()> {
         self.terminal.flush()
     }
 
-    fn supports_true_color(&self) -> bool {
-        self.capabilities.true_color
+    fn color_depth(&self) -> ColorDepth {
+        if self.capabilities.true_color || self.config.force_true_color {
+            ColorDepth::TrueColor
+        } else {
+            ColorDepth::Ansi256
+        }
     }
 
     fn get_theme_mode(&self) -> Option<theme::Mode> {
@@ -593,6 +606,18 @@ fn drop(&mut self) {
     }
 }
 
+fn cursor_style(kind: CursorKind, blink: bool) -> CursorStyle {
+    match (kind, blink) {
+        (CursorKind::Block, false) => CursorStyle::SteadyBlock,
+        (CursorKind::Block, true) => CursorStyle::BlinkingBlock,
+        (CursorKind::Bar, false) => CursorStyle::SteadyBar,
+        (CursorKind::Bar, true) => CursorStyle::BlinkingBar,
+        (CursorKind::Underline, false) => CursorStyle::SteadyUnderline,
+        (CursorKind::Underline, true) => CursorStyle::BlinkingUnderline,
+        (CursorKind::Hidden, _) => unreachable!(),
+    }
+}
+
 fn diff_modifiers(from: Modifier, to: Modifier) -> SgrModifiers {
     let mut modifiers = SgrModifiers::default();
 
@@ -647,3 +672,31 @@ fn diff_modifiers(from: Modifier, to: Modifier) -> SgrModifiers {
 
     modifiers
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blinking_cursor_style() {
+        let escape = |kind, blink| {
+            Csi::Cursor(csi::Cursor::CursorStyle(cursor_style(kind, blink))).to_string()
+        };
+        assert_eq!(escape(CursorKind::Bar, false), "\x1b[6 q");
+        assert_eq!(escape(CursorKind::Bar, true), "\x1b[5 q");
+        assert_eq!(escape(CursorKind::Underline, true), "\x1b[3 q");
+        assert_eq!(escape(CursorKind::Block, true), "\x1b[1 q");
+    }
+
+    #[test]
+    fn true_color_escapes() {
+        let colorterm = |name: &str| (name == "COLORTERM").then(|| "truecolor".into());
+        let color_depth = ColorDepth::detect(colorterm, None);
+        let foreground = |color_depth: ColorDepth| {
+            let color = color_depth.adapt(Color::Rgb(59, 34, 76));
+            Csi::Sgr(csi::Sgr::Foreground(color.into())).to_string()
+        };
+        assert_eq!(foreground(color_depth), "\x1b[38;2;59;34;76m");
+        assert_eq!(foreground(ColorDepth::Ansi256), "\x1b[38;5;237m");
+    }
+}
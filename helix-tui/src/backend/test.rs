@@ -1,5 +1,5 @@
 use crate::{
-    backend::Backend,
+    backend::{Backend, ColorDepth},
     buffer::{Buffer, Cell},
     terminal::Config,
 };
@@ -157,8 +157,8 @@ fn flush(&mut self) -> Result<(), io::Error> {
         Ok(())
     }
 
-    fn supports_true_color(&self) -> bool {
-        false
+    fn color_depth(&self) -> ColorDepth {
+        ColorDepth::Ansi256
     }
 
     fn get_theme_mode(&self) -> Option<helix_view::theme::Mode> {
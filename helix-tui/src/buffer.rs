@@ -2,6 +2,7 @@
 use crate::text::{Span, Spans};
 use helix_core::unicode::width::UnicodeWidthStr;
 use std::cmp::min;
+use unicode_bidi::{Level, ParagraphBidiInfo};
 use unicode_segmentation::UnicodeSegmentation;
 
 use helix_view::graphics::{Color, Modifier, Rect, Style, UnderlineStyle};
@@ -596,6 +597,63 @@ pub fn set_style(&mut self, area: Rect, style: Style) {
         }
     }
 
+    /// Reorder the cells of the line at `area` from logical to visual order using the Unicode
+    /// bidirectional algorithm, so that right-to-left text like Hebrew or Arabic reads correctly.
+    /// The line itself is laid out left-to-right.
+    ///
+    /// Returns the new column of every cell of the line relative to `area.x`, or `None` if the
+    /// line contains no right-to-left text and was left unchanged.
+    pub fn reorder_bidi_line(&mut self, area: Rect) -> Option<Vec<u16>> {
+        let area = area.intersection(self.area);
+        if area.width == 0 || area.height == 0 {
+            return None;
+        }
+        let start = self.index_of(area.x, area.y);
+        let cells = &self.content[start..start + area.width as usize];
+
+        // (byte offset in `text`, first cell, width) of every grapheme. Multi-width graphemes
+        // are followed by the cells they hide, which move together with them.
+        let mut graphemes = Vec::new();
+        let mut text = String::new();
+        let mut col = 0;
+        while col < cells.len() {
+            let symbol = match cells[col].symbol.as_str() {
+                "" => " ",
+                symbol => symbol,
+            };
+            let width = symbol.width().clamp(1, cells.len() - col);
+            graphemes.push((text.len(), col, width));
+            text.push_str(symbol);
+            col += width;
+        }
+
+        let bidi = ParagraphBidiInfo::new(&text, Some(Level::ltr()));
+        if !bidi.has_rtl() {
+            return None;
+        }
+        let (levels, runs) = bidi.visual_runs(0..text.len());
+        let mut reordered = Vec::with_capacity(cells.len());
+        let mut columns = vec![0; cells.len()];
+        for run in runs {
+            let first = graphemes.partition_point(|&(offset, _, _)| offset < run.start);
+            let last = graphemes.partition_point(|&(offset, _, _)| offset < run.end);
+            let run_graphemes = &graphemes[first..last];
+            let mut place = |&(_, col, width): &(usize, usize, usize)| {
+                for col in col..col + width {
+                    columns[col] = reordered.len() as u16;
+                    reordered.push(cells[col].clone());
+                }
+            };
+            if levels[run.start].is_rtl() {
+                run_graphemes.iter().rev().for_each(&mut place);
+            } else {
+                run_graphemes.iter().for_each(&mut place);
+            }
+        }
+        self.content[start..start + area.width as usize].clone_from_slice(&reordered);
+        Some(columns)
+    }
+
     /// Resize the buffer so that the mapped area matches the given area and that the buffer
     /// length is equal to area.width * area.height
     pub fn resize(&mut self, area: Rect) {
@@ -1020,4 +1078,35 @@ fn buffer_merge3() {
         };
         assert_eq!(one, merged);
     }
+
+    #[test]
+    fn reorder_bidi_line() {
+        let area = Rect::new(0, 0, 16, 2);
+        let mut buffer = Buffer::empty(area);
+        // "Shalom Olam" between two latin words.
+        buffer.set_string(0, 0, "a שלום עולם b", Style::default());
+        buffer.set_string(0, 1, "a 世界 b", Style::default());
+
+        let columns = buffer.reorder_bidi_line(Rect::new(0, 0, 16, 1)).unwrap();
+        assert_eq!(
+            buffer,
+            Buffer::with_lines(vec!["a םלוע םולש b   ", "a 世界 b        "])
+        );
+        assert_eq!(columns[..13], [0, 1, 10, 9, 8, 7, 6, 5, 4, 3, 2, 11, 12]);
+
+        // Lines without right-to-left text are left unchanged, including wide graphemes.
+        assert_eq!(buffer.reorder_bidi_line(Rect::new(0, 1, 16, 1)), None);
+        assert_eq!(buffer[(2, 1)].symbol, "世");
+
+        // Wide graphemes keep their width within right-to-left runs.
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 1));
+        buffer.set_string(0, 0, "א！ב", Style::default());
+        buffer.reorder_bidi_line(*buffer.area()).unwrap();
+        let symbols: Vec<_> = buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol.as_str())
+            .collect();
+        assert_eq!(symbols, ["ב", "！", " ", "א", " ", " "]);
+    }
 }
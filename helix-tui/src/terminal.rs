@@ -26,6 +26,9 @@ pub struct Config {
     pub enable_mouse_capture: bool,
     pub force_enable_extended_underlines: bool,
     pub kitty_keyboard_protocol: KittyKeyboardProtocolConfig,
+    pub cursor_blink: bool,
+    /// Emit RGB colors as is even if the terminal isn't detected to support them.
+    pub force_true_color: bool,
 }
 
 impl From<&EditorConfig> for Config {
@@ -34,6 +37,8 @@ fn from(config: &EditorConfig) -> Self {
             enable_mouse_capture: config.mouse,
             force_enable_extended_underlines: config.undercurl,
             kitty_keyboard_protocol: config.kitty_keyboard_protocol,
+            cursor_blink: config.cursor_shape.blink,
+            force_true_color: config.true_color,
         }
     }
 }
@@ -17,7 +17,7 @@
 };
 use gix::{Commit, ObjectId, Repository, ThreadSafeRepository};
 
-use crate::FileChange;
+use crate::{FileChange, VcsSummary};
 
 #[cfg(test)]
 mod test;
@@ -79,11 +79,42 @@ pub fn get_current_head_name(file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
     Ok(Arc::new(ArcSwap::from_pointee(name.into_boxed_str())))
 }
 
+pub fn get_vcs_summary(repo_dir: &Path) -> Result<VcsSummary> {
+    // Opened at the exact path rather than discovered upwards, so that bare repositories and
+    // worktrees resolve their own HEAD.
+    let trust = gix::sec::Trust::from_path_ownership(repo_dir)?;
+    let options = open_trust_options().into_value_by_level(trust);
+    let repo = ThreadSafeRepository::open_opts(repo_dir, options)?.to_thread_local();
+    let head = match repo.head_name()? {
+        Some(name) => name.shorten().to_string(),
+        None => repo.head_id()?.to_hex_with_len(7).to_string(),
+    };
+    // Bare repositories have no working tree to be dirty, and an unborn HEAD has no tree to
+    // compare against.
+    let dirty = repo.workdir().is_some() && repo.is_dirty().unwrap_or(false);
+    Ok(VcsSummary { head, dirty })
+}
+
 pub fn for_each_changed_file(cwd: &Path, f: impl Fn(Result<FileChange>) -> bool) -> Result<()> {
     status(&open_repo(cwd)?.to_thread_local(), f)
 }
 
 fn open_repo(path: &Path) -> Result<ThreadSafeRepository> {
+    let open_options = gix::discover::upwards::Options {
+        dot_git_only: true,
+        ..Default::default()
+    };
+
+    let res = ThreadSafeRepository::discover_with_environment_overrides_opts(
+        path,
+        open_options,
+        open_trust_options(),
+    )?;
+
+    Ok(res)
+}
+
+fn open_trust_options() -> gix::sec::trust::Mapping<gix::open::Options> {
     // custom open options
     let mut git_open_opts_map = gix::sec::trust::Mapping::<gix::open::Options>::default();
 
@@ -110,19 +141,7 @@ fn open_repo(path: &Path) -> Result<ThreadSafeRepository> {
         config,
         ..gix::open::Permissions::default_for_level(gix::sec::Trust::Full)
     });
-
-    let open_options = gix::discover::upwards::Options {
-        dot_git_only: true,
-        ..Default::default()
-    };
-
-    let res = ThreadSafeRepository::discover_with_environment_overrides_opts(
-        path,
-        open_options,
-        git_open_opts_map,
-    )?;
-
-    Ok(res)
+    git_open_opts_map
 }
 
 /// Emulates the result of running `git status` from the command line.
@@ -150,3 +150,47 @@ fn symlink_to_git_repo() {
     assert_eq!(git::get_diff_base(&file_link).unwrap(), contents);
     assert_eq!(git::get_diff_base(&file).unwrap(), contents);
 }
+
+#[test]
+fn vcs_summary() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+
+    let summary = git::get_vcs_summary(temp_git.path()).unwrap();
+    assert_eq!(summary.head, "main");
+    assert!(!summary.dirty);
+
+    File::create(&file).unwrap().write_all(b"bar").unwrap();
+    assert!(git::get_vcs_summary(temp_git.path()).unwrap().dirty);
+
+    exec_git_cmd("checkout --detach", temp_git.path());
+    let summary = git::get_vcs_summary(temp_git.path()).unwrap();
+    assert_eq!(summary.head.len(), 7);
+}
+
+#[test]
+fn vcs_summary_of_worktree() {
+    let temp_git = empty_git_repo();
+    File::create(temp_git.path().join("file.txt")).unwrap();
+    create_commit(temp_git.path(), true);
+    let worktree = temp_git.path().join("worktree");
+    exec_git_cmd("worktree add -b feature worktree", temp_git.path());
+
+    let summary = git::get_vcs_summary(&worktree).unwrap();
+    assert_eq!(summary.head, "feature");
+    assert_eq!(git::get_vcs_summary(temp_git.path()).unwrap().head, "main");
+}
+
+#[test]
+fn vcs_summary_of_bare_repo() {
+    let temp_git = empty_git_repo();
+    File::create(temp_git.path().join("file.txt")).unwrap();
+    create_commit(temp_git.path(), true);
+    exec_git_cmd("clone --bare . bare.git", temp_git.path());
+
+    let summary = git::get_vcs_summary(&temp_git.path().join("bare.git")).unwrap();
+    assert_eq!(summary.head, "main");
+    assert!(!summary.dirty);
+}
@@ -18,7 +18,7 @@
 
 mod status;
 
-pub use status::FileChange;
+pub use status::{FileChange, VcsSummary};
 
 /// Contains all active diff providers. Diff providers are compiled in via features. Currently
 /// only `git` is supported.
@@ -57,6 +57,21 @@ pub fn get_current_head_name(&self, file: &Path) -> Option<Arc<ArcSwap<Box<str>>
             })
     }
 
+    /// Get the checked out branch of the repository at `repo_dir` and whether it has changes.
+    /// This reads the working tree, so it shouldn't be called on the main thread.
+    pub fn project_vcs_summary(&self, repo_dir: &Path) -> Option<VcsSummary> {
+        self.providers
+            .iter()
+            .find_map(|provider| match provider.get_vcs_summary(repo_dir) {
+                Ok(res) => Some(res),
+                Err(err) => {
+                    log::debug!("{err:#?}");
+                    log::debug!("failed to obtain vcs summary for {}", repo_dir.display());
+                    None
+                }
+            })
+    }
+
     /// Fire-and-forget changed file iteration. Runs everything in a background task. Keeps
     /// iteration until `on_change` returns `false`.
     pub fn for_each_changed_file(
@@ -118,6 +133,14 @@ fn get_current_head_name(&self, file: &Path) -> Result<Arc<ArcSwap<Box<str>>>> {
         }
     }
 
+    fn get_vcs_summary(&self, repo_dir: &Path) -> Result<VcsSummary> {
+        match self {
+            #[cfg(feature = "git")]
+            Self::Git => git::get_vcs_summary(repo_dir),
+            Self::None => bail!("No diff support compiled in"),
+        }
+    }
+
     fn for_each_changed_file(
         &self,
         cwd: &Path,
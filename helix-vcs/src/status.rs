@@ -28,3 +28,12 @@ pub fn path(&self) -> &Path {
         }
     }
 }
+
+/// The state of a repository shown next to it, for example in the project picker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VcsSummary {
+    /// The name of the checked out branch, or the short hash of the commit if HEAD is detached.
+    pub head: String,
+    /// Whether tracked files have changes which aren't committed.
+    pub dirty: bool,
+}
@@ -0,0 +1,67 @@
+use helix_core::text_annotations::LineAnnotation;
+use helix_core::{Position, RopeSlice};
+
+use crate::document::CodeLens;
+use crate::Document;
+
+/// The code lenses shown in the virtual line after `doc_line`: those of the next line, since
+/// lenses are shown above the line they apply to. Lenses of the first line have no line above
+/// them, so they are shown after it instead.
+pub fn code_lenses_after_line<'a>(
+    text: RopeSlice,
+    code_lenses: &'a [CodeLens],
+    doc_line: usize,
+) -> &'a [CodeLens] {
+    // The last line has no next line.
+    if doc_line + 1 >= text.len_lines() {
+        return &[];
+    }
+    let start = if doc_line == 0 {
+        0
+    } else {
+        text.line_to_char(doc_line + 1)
+    };
+    let end = if doc_line + 2 < text.len_lines() {
+        text.line_to_char(doc_line + 2)
+    } else {
+        text.len_chars() + 1
+    };
+    let first = code_lenses.partition_point(|lens| lens.char_idx < start);
+    let last = code_lenses.partition_point(|lens| lens.char_idx < end);
+    &code_lenses[first..last]
+}
+
+/// Reserves a virtual line above each line with code lenses.
+pub struct CodeLensLines<'a> {
+    text: RopeSlice<'a>,
+    code_lenses: &'a [CodeLens],
+}
+
+impl<'a> CodeLensLines<'a> {
+    #[allow(clippy::new_ret_no_self)]
+    pub(crate) fn new(doc: &'a Document) -> Box<dyn LineAnnotation + 'a> {
+        Box::new(CodeLensLines {
+            text: doc.text().slice(..),
+            code_lenses: &doc.code_lenses,
+        })
+    }
+}
+
+impl LineAnnotation for CodeLensLines<'_> {
+    fn insert_virtual_lines(
+        &mut self,
+        line_end_char_idx: usize,
+        _line_end_visual_pos: Position,
+        doc_line: usize,
+    ) -> Position {
+        // Soft wrapped lines end in the middle of the document line, the lenses go after all of it.
+        let is_line_end = doc_line + 1 < self.text.len_lines()
+            && self.text.line_to_char(doc_line + 1) == line_end_char_idx;
+        if is_line_end && !code_lenses_after_line(self.text, self.code_lenses, doc_line).is_empty()
+        {
+            Position::new(1, 0)
+        } else {
+            Position::new(0, 0)
+        }
+    }
+}
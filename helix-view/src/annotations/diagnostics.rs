@@ -203,7 +203,9 @@ fn process_anchor_impl(
             if diag.range.start != grapheme.char_idx {
                 break;
             }
-            self.stack.push((diag, anchor_col as u16));
+            if !self.doc.is_duplicate_diagnostic(self.idx) {
+                self.stack.push((diag, anchor_col as u16));
+            }
             self.idx += 1;
         }
         false
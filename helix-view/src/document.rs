@@ -13,7 +13,7 @@
 use helix_core::syntax::config::LanguageServerFeature;
 use helix_core::text_annotations::{InlineAnnotation, Overlay};
 use helix_event::TaskController;
-use helix_lsp::util::lsp_pos_to_pos;
+use helix_lsp::util::lsp_range_to_char_range;
 use helix_stdx::faccess::{copy_metadata, readonly};
 use helix_vcs::{DiffHandle, DiffProviderRegistry};
 use once_cell::sync::OnceCell;
@@ -28,6 +28,7 @@
 use std::fmt::Display;
 use std::future::Future;
 use std::io;
+use std::num::NonZeroU8;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Weak};
@@ -37,8 +38,8 @@
     editor_config::EditorConfig,
     encoding,
     history::{History, State, UndoKind},
-    indent::{auto_detect_indent_style, IndentStyle},
-    line_ending::auto_detect_line_ending,
+    indent::{auto_detect_indent_style, IndentStyle, MAX_INDENT},
+    line_ending::DetectedLineEnding,
     syntax::{self, config::LanguageConfiguration},
     ChangeSet, Diagnostic, LineEnding, Range, Rope, RopeBuilder, Selection, Syntax, Transaction,
 };
@@ -144,6 +145,9 @@ pub struct Document {
     selections: HashMap<ViewId, Selection>,
     view_data: HashMap<ViewId, ViewData>,
     pub active_snippet: Option<ActiveSnippet>,
+    /// Ranges reported by `textDocument/linkedEditingRange` that are edited together while in
+    /// insert mode, such as the names of an opening and a closing tag.
+    pub linked_ranges: Vec<Range>,
 
     /// Inlay hints annotations for the document, by view.
     ///
@@ -156,6 +160,9 @@ pub struct Document {
 
     path: Option<PathBuf>,
     relative_path: OnceCell<Option<PathBuf>>,
+    /// The registered project containing the document, see
+    /// [Editor::document_project_root](crate::Editor::document_project_root).
+    pub(crate) project: OnceCell<Option<DocumentProject>>,
     encoding: &'static encoding::Encoding,
     has_bom: bool,
 
@@ -164,6 +171,7 @@ pub struct Document {
     /// Current indent style.
     pub indent_style: IndentStyle,
     editor_config: EditorConfig,
+    modeline: Modeline,
 
     /// The document's default line ending.
     pub line_ending: LineEnding,
@@ -171,6 +179,9 @@ pub struct Document {
     pub syntax: Option<Syntax>,
     /// Corresponding language scope name. Usually `source.<lang>`.
     pub language: Option<Arc<LanguageConfiguration>>,
+    /// The language ID sent to language servers in place of the one of `language`, see
+    /// [Self::language_id].
+    language_id: Option<String>,
 
     /// Pending changes since last history commit.
     changes: ChangeSet,
@@ -213,6 +224,10 @@ pub struct Document {
     pub color_swatch_controller: TaskController,
     pub pull_diagnostic_controller: TaskController,
 
+    /// The code lenses of the document, sorted by position.
+    pub code_lenses: Vec<CodeLens>,
+    pub code_lens_controller: TaskController,
+
     // NOTE: this field should eventually go away - we should use the Editor's syn_loader instead
     // of storing a copy on every doc. Then we can remove the surrounding `Arc` and use the
     // `ArcSwap` directly.
@@ -226,6 +241,15 @@ pub struct DocumentColorSwatches {
     pub color_swatches_padding: Vec<InlineAnnotation>,
 }
 
+/// A code lens of a language server, shown above the line it applies to.
+#[derive(Debug, Clone)]
+pub struct CodeLens {
+    /// The position of the start of the lens' range, kept up to date with changes to the text.
+    pub char_idx: usize,
+    pub lens: lsp::CodeLens,
+    pub language_server_id: LanguageServerId,
+}
+
 /// Inlay hints for a single `(Document, View)` combo.
 ///
 /// There are `*_inlay_hints` field for each kind of hints an LSP can send since we offer the
@@ -293,6 +317,104 @@ pub struct DocumentInlayHintsId {
     pub last_line: usize,
 }
 
+/// The number of lines at the start and at the end of a document searched for a modeline.
+const MODELINE_LINES: usize = 5;
+/// The indent width of `indent_style=space` when `indent_width` isn't given.
+const DEFAULT_SPACES: u8 = 4;
+
+/// Settings for a single document given by a comment such as
+/// `# helix: indent_style=space; indent_width=2`, see [Document::parse_modeline].
+///
+/// They take precedence over `.editorconfig` files and the detected settings.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Modeline {
+    pub indent_style: Option<IndentStyle>,
+    /// The width of a level of indentation, and of a tab character.
+    pub indent_width: Option<NonZeroU8>,
+    pub line_ending: Option<LineEnding>,
+    /// The name of the language of the document, as in `languages.toml`.
+    pub language: Option<String>,
+}
+
+impl Modeline {
+    /// Parses the `key=value` pairs following `helix:` in a line, if it's a modeline.
+    ///
+    /// The marker has to follow a comment token such as `#` or `//`. Malformed and unknown
+    /// pairs are skipped.
+    fn parse_line(&mut self, line: &str) {
+        let Some((prefix, pairs)) = line.split_once("helix:") else {
+            return;
+        };
+        let prefix = prefix.trim();
+        if prefix.is_empty() || prefix.chars().any(char::is_alphanumeric) {
+            return;
+        }
+        // Leave out the end of block comments such as `/* helix: ... */`.
+        let pairs = pairs.trim_end();
+        let pairs = ["*/", "-->", "*)"]
+            .iter()
+            .find_map(|suffix| pairs.strip_suffix(suffix))
+            .unwrap_or(pairs);
+
+        for pair in pairs
+            .split(';')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let Some((key, value)) = pair.split_once('=') else {
+                log::warn!("Ignoring malformed modeline setting '{pair}'");
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let valid = match key {
+                "indent_style" => match value {
+                    "tab" | "tabs" => {
+                        self.indent_style = Some(IndentStyle::Tabs);
+                        true
+                    }
+                    "space" | "spaces" => {
+                        let width = self.indent_width.map_or(DEFAULT_SPACES, NonZeroU8::get);
+                        self.indent_style = Some(IndentStyle::Spaces(width));
+                        true
+                    }
+                    _ => false,
+                },
+                "indent_width" => match value.parse::<NonZeroU8>() {
+                    Ok(width) if width.get() <= MAX_INDENT => {
+                        self.indent_width = Some(width);
+                        if let Some(IndentStyle::Spaces(spaces)) = &mut self.indent_style {
+                            *spaces = width.get();
+                        }
+                        true
+                    }
+                    _ => false,
+                },
+                "line_ending" => match value {
+                    "lf" => Some(LineEnding::LF),
+                    "crlf" => Some(LineEnding::Crlf),
+                    #[cfg(feature = "unicode-lines")]
+                    "cr" => Some(LineEnding::CR),
+                    _ => None,
+                }
+                .map(|line_ending| self.line_ending = Some(line_ending))
+                .is_some(),
+                "language" if !value.is_empty() => {
+                    self.language = Some(value.to_string());
+                    true
+                }
+                "language" => false,
+                _ => {
+                    log::warn!("Ignoring unknown modeline setting '{key}'");
+                    continue;
+                }
+            };
+            if !valid {
+                log::warn!("Ignoring invalid value '{value}' of modeline setting '{key}'");
+            }
+        }
+    }
+}
+
 use std::{fmt, mem};
 impl fmt::Debug for Document {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -336,6 +458,67 @@ pub(crate) fn clear_doc_relative_paths(&mut self) {
             doc.relative_path.take();
         }
     }
+
+    /// Drops the projects resolved for each document so that they are looked up again on next
+    /// use.
+    pub fn refresh_projects(&mut self) {
+        for doc in self.documents.values_mut() {
+            doc.project.take();
+        }
+    }
+
+    /// Refreshes the projects of the documents if the registered projects changed since the last
+    /// call, see [crate::projects::SharedProjects::generation]. Called before rendering so that
+    /// the statusline follows changes made by background tasks and commands alike.
+    pub fn sync_projects(&mut self) {
+        let generation = self.projects.generation();
+        if generation != self.projects_generation {
+            self.projects_generation = generation;
+            self.refresh_projects();
+        }
+    }
+
+    /// The name of the registered project containing `doc`, if any.
+    ///
+    /// The result is cached per document, see [Self::refresh_projects].
+    pub fn document_project_name<'a>(&self, doc: &'a Document) -> Option<&'a str> {
+        self.document_project(doc)?.name.as_deref()
+    }
+
+    /// The root of the innermost registered project containing `doc`, if any.
+    ///
+    /// The result is cached per document, see [Self::refresh_projects].
+    pub fn document_project_root<'a>(&self, doc: &'a Document) -> Option<&'a Path> {
+        Some(&self.document_project(doc)?.root)
+    }
+
+    fn document_project<'a>(&self, doc: &'a Document) -> Option<&'a DocumentProject> {
+        doc.project
+            .get_or_init(|| {
+                let path = doc.path()?;
+                self.projects
+                    .read(|store| {
+                        helix_loader::projects::find_project_for_path(store.projects(), path).map(
+                            |project| DocumentProject {
+                                root: project.path.clone(),
+                                name: project.display_name().map(str::to_string),
+                            },
+                        )
+                    })
+                    .unwrap_or_else(|err| {
+                        log::warn!("Failed to load projects: {err}");
+                        None
+                    })
+            })
+            .as_ref()
+    }
+}
+
+/// The registered project containing a document, cached in [Document::project].
+#[derive(Debug)]
+pub(crate) struct DocumentProject {
+    root: PathBuf,
+    name: Option<String>,
 }
 
 enum Encoder {
@@ -697,8 +880,10 @@ pub fn from(
         Self {
             id: DocumentId::default(),
             active_snippet: None,
+            linked_ranges: Vec::new(),
             path: None,
             relative_path: OnceCell::new(),
+            project: OnceCell::new(),
             encoding,
             has_bom,
             text,
@@ -708,10 +893,12 @@ pub fn from(
             view_data: Default::default(),
             indent_style: DEFAULT_INDENT,
             editor_config: EditorConfig::default(),
+            modeline: Modeline::default(),
             line_ending,
             restore_cursor: false,
             syntax: None,
             language: None,
+            language_id: None,
             changes,
             old_state,
             diagnostics: Vec::new(),
@@ -730,6 +917,8 @@ pub fn from(
             jump_labels: HashMap::new(),
             color_swatches: None,
             color_swatch_controller: TaskController::new(),
+            code_lenses: Vec::new(),
+            code_lens_controller: TaskController::new(),
             syn_loader,
             previous_diagnostic_id: None,
             pull_diagnostic_controller: TaskController::new(),
@@ -781,6 +970,7 @@ pub fn open(
 
         let loader = syn_loader.load();
         let mut doc = Self::from(rope, Some((encoding, has_bom)), config, syn_loader);
+        doc.modeline = Self::parse_modeline(doc.text());
 
         // set the path and try detecting the language
         doc.set_path(Some(path));
@@ -927,11 +1117,8 @@ pub fn format(
                     Default::default()
                 })
                 .unwrap_or_default();
-            Ok(helix_lsp::util::generate_transaction_from_edits(
-                &text,
-                edits,
-                offset_encoding,
-            ))
+            helix_lsp::util::generate_transaction_from_edits(&text, edits, offset_encoding)
+                .map_err(FormatterError::InvalidEdits)
         };
         Some(fut.boxed())
     }
@@ -1149,6 +1336,12 @@ pub fn detect_language_config(
         &self,
         loader: &syntax::Loader,
     ) -> Option<Arc<syntax::config::LanguageConfiguration>> {
+        if let Some(name) = &self.modeline.language {
+            match loader.language_for_name(name.as_str()) {
+                Some(language) => return Some(loader.language(language).config().clone()),
+                None => log::warn!("Ignoring unknown language '{name}' of the modeline"),
+            }
+        }
         let language = loader
             .language_for_filename(self.path.as_ref()?)
             .or_else(|| loader.language_for_shebang(self.text().slice(..)))?;
@@ -1158,9 +1351,17 @@ pub fn detect_language_config(
 
     /// Detect the indentation used in the file, or otherwise defaults to the language indentation
     /// configured in `languages.toml`, with a fallback to tabs if it isn't specified. Line ending
-    /// is likewise auto-detected, and will remain unchanged if no line endings were detected.
+    /// is likewise auto-detected by majority, and will remain unchanged if no line endings were
+    /// detected or if several line endings are used equally often.
+    ///
+    /// Settings of the [modeline](Self::parse_modeline) and of `.editorconfig` files take
+    /// precedence over the detected ones.
     pub fn detect_indent_and_line_ending(&mut self) {
-        self.indent_style = if let Some(indent_style) = self.editor_config.indent_style {
+        self.indent_style = if let Some(indent_style) = self
+            .modeline
+            .indent_style
+            .or(self.editor_config.indent_style)
+        {
             indent_style
         } else {
             auto_detect_indent_style(&self.text).unwrap_or_else(|| {
@@ -1169,15 +1370,42 @@ pub fn detect_indent_and_line_ending(&mut self) {
                     .map_or(DEFAULT_INDENT, |config| IndentStyle::from_str(&config.unit))
             })
         };
+        if let (Some(width), IndentStyle::Spaces(spaces)) =
+            (self.modeline.indent_width, &mut self.indent_style)
+        {
+            *spaces = width.get();
+        }
         if let Some(line_ending) = self
-            .editor_config
+            .modeline
             .line_ending
-            .or_else(|| auto_detect_line_ending(&self.text))
+            .or(self.editor_config.line_ending)
+            .or_else(|| LineEnding::detect(&self.text).and_then(DetectedLineEnding::majority))
         {
             self.line_ending = line_ending;
         }
     }
 
+    /// Parses the settings of a `helix:` comment in the first or last five lines of `text`,
+    /// for example `# helix: indent_style=space; indent_width=2`.
+    ///
+    /// The settings are `indent_style` (`tab` or `space`), `indent_width`, `line_ending` (`lf`
+    /// or `crlf`) and `language`. When several lines are modelines the later ones win.
+    pub fn parse_modeline(text: &Rope) -> Modeline {
+        let len_lines = text.len_lines();
+        let first = 0..len_lines.min(MODELINE_LINES);
+        let last = len_lines.saturating_sub(MODELINE_LINES).max(first.end)..len_lines;
+
+        let mut modeline = Modeline::default();
+        for line in first.chain(last) {
+            let line = text.line(line);
+            // Modelines are short, there is no need to copy long lines.
+            if line.len_chars() <= 256 {
+                modeline.parse_line(&Cow::from(line));
+            }
+        }
+        modeline
+    }
+
     pub fn detect_editor_config(&mut self) {
         if self.config.load().editor_config {
             if let Some(path) = self.path.as_ref() {
@@ -1243,6 +1471,7 @@ pub fn reload(
         self.append_changes_to_history(view);
         self.reset_modified();
         self.pickup_last_saved_time();
+        self.modeline = Self::parse_modeline(self.text());
         self.detect_indent_and_line_ending();
 
         match provider_registry.get_diff_base(&path) {
@@ -1279,6 +1508,7 @@ pub fn set_path(&mut self, path: Option<&Path>) {
         // `take` to remove any prior relative path that may have existed.
         // This will get set in `relative_path()`.
         self.relative_path.take();
+        self.project.take();
 
         // if parent doesn't exist we still want to open the document
         // and error out when document is saved
@@ -1796,15 +2026,32 @@ pub fn language_name(&self) -> Option<&str> {
             .map(|language| language.language_id.as_str())
     }
 
-    /// Language ID for the document. Either the `language-id`,
-    /// or the document language name if no `language-id` has been specified.
+    /// Language ID for the document. Either the ID set with [Self::set_language_id], the
+    /// `language-id`, or the document language name if no `language-id` has been specified.
     pub fn language_id(&self) -> Option<&str> {
+        if let Some(language_id) = &self.language_id {
+            return Some(language_id);
+        }
         self.language_config()?
             .language_server_language_id
             .as_deref()
             .or_else(|| self.language_name())
     }
 
+    /// The language ID set with [Self::set_language_id], if any.
+    pub fn language_id_override(&self) -> Option<&str> {
+        self.language_id.as_deref()
+    }
+
+    /// Overrides the language ID reported to language servers, independent of the language
+    /// used for highlighting. `None` restores the ID of the document's language.
+    ///
+    /// Use [Editor::set_language_id](crate::Editor::set_language_id) to also let the language
+    /// servers of the document know.
+    pub fn set_language_id(&mut self, language_id: Option<String>) {
+        self.language_id = language_id;
+    }
+
     /// Corresponding [`LanguageConfiguration`].
     pub fn language_config(&self) -> Option<&LanguageConfiguration> {
         self.language.as_deref()
@@ -1904,8 +2151,9 @@ pub fn syntax(&self) -> Option<&Syntax> {
 
     /// The width that the tab character is rendered at
     pub fn tab_width(&self) -> usize {
-        self.editor_config
-            .tab_width
+        self.modeline
+            .indent_width
+            .or(self.editor_config.tab_width)
             .map(|n| n.get() as usize)
             .unwrap_or_else(|| {
                 self.language_config()
@@ -2041,17 +2289,9 @@ pub fn lsp_diagnostic_to_diagnostic(
         use helix_core::diagnostic::{Range, Severity::*};
 
         // TODO: convert inside server
-        let start =
-            if let Some(start) = lsp_pos_to_pos(text, diagnostic.range.start, offset_encoding) {
-                start
-            } else {
-                log::warn!("lsp position out of bounds - {:?}", diagnostic);
-                return None;
-            };
-
-        let end = if let Some(end) = lsp_pos_to_pos(text, diagnostic.range.end, offset_encoding) {
-            end
-        } else {
+        let Some(std::ops::Range { start, end }) =
+            lsp_range_to_char_range(diagnostic.range, text, offset_encoding)
+        else {
             log::warn!("lsp position out of bounds - {:?}", diagnostic);
             return None;
         };
@@ -2124,6 +2364,32 @@ pub fn diagnostics(&self) -> &[Diagnostic] {
         &self.diagnostics
     }
 
+    /// Whether the diagnostic at `idx` duplicates an earlier one of another provider, with the
+    /// same range, severity and message. Language servers running the same linter report the
+    /// same diagnostics, which are shown once.
+    pub fn is_duplicate_diagnostic(&self, idx: usize) -> bool {
+        let diagnostic = &self.diagnostics[idx];
+        // The diagnostics are sorted by range, so those with the same range are adjacent.
+        self.diagnostics[..idx]
+            .iter()
+            .rev()
+            .take_while(|other| other.range == diagnostic.range)
+            .any(|other| {
+                other.provider != diagnostic.provider
+                    && other.severity == diagnostic.severity
+                    && other.message == diagnostic.message
+            })
+    }
+
+    /// The diagnostics without the duplicates of [Self::is_duplicate_diagnostic].
+    pub fn unique_diagnostics(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .enumerate()
+            .filter(|&(idx, _)| !self.is_duplicate_diagnostic(idx))
+            .map(|(_, diagnostic)| diagnostic)
+    }
+
     pub fn replace_diagnostics(
         &mut self,
         diagnostics: impl IntoIterator<Item = Diagnostic>,
@@ -2170,6 +2436,9 @@ pub fn clear_diagnostics_for_language_server(&mut self, id: LanguageServerId) {
     /// language config with auto pairs configured, returns that;
     /// otherwise, falls back to the global auto pairs config. If the global
     /// config is false, then ignore language settings.
+    ///
+    /// With a syntax tree the language is the one of the injection layer under
+    /// the primary selection, otherwise it is the document's language.
     pub fn auto_pairs<'a>(
         &'a self,
         editor: &'a Editor,
@@ -2187,16 +2456,19 @@ pub fn auto_pairs<'a>(
             }
         }
 
-        self.syntax
-            .as_ref()
-            .and_then(|syntax| {
+        let lang_config = match self.syntax.as_ref() {
+            Some(syntax) => {
                 let selection = self.selection(view.id).primary();
                 let (start, end) = selection.into_byte_range(self.text().slice(..));
                 let layer = syntax.layer_for_byte_range(start as u32, end as u32);
 
-                let lang_config = loader.language(syntax.layer(layer).language).config();
-                lang_config.auto_pairs.as_ref()
-            })
+                Some(&**loader.language(syntax.layer(layer).language).config())
+            }
+            None => self.language_config(),
+        };
+
+        lang_config
+            .and_then(|config| config.auto_pairs.as_ref())
             .or(global_config)
     }
 
@@ -2322,6 +2594,8 @@ pub enum FormatterError {
     WaitForOutputFailed,
     InvalidUtf8Output,
     NonZeroExitStatus(Option<String>),
+    /// The text edits returned by the language server can't be applied.
+    InvalidEdits(helix_lsp::util::TextEditError),
 }
 
 impl std::error::Error for FormatterError {}
@@ -2339,6 +2613,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             Self::NonZeroExitStatus(None) => {
                 write!(f, "Formatter exited with non zero exit status")
             }
+            Self::InvalidEdits(err) => write!(f, "Invalid formatting edits: {err}"),
         }
     }
 }
@@ -2507,6 +2782,75 @@ fn changeset_to_changes() {
         );
     }
 
+    #[test]
+    fn deduplicate_diagnostics() {
+        use helix_core::diagnostic::{DiagnosticProvider, Range, Severity};
+
+        let mut doc = Document::from(
+            Rope::from("let x = 1;\n"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        let provider = |name: &str| DiagnosticProvider::Lsp {
+            server_id: Default::default(),
+            identifier: Some(name.into()),
+        };
+        let diagnostic = |provider: &DiagnosticProvider, start, message: &str| Diagnostic {
+            range: Range { start, end: 5 },
+            ends_at_word: false,
+            starts_at_word: false,
+            zero_width: false,
+            line: 0,
+            message: message.to_string(),
+            severity: Some(Severity::Warning),
+            code: None,
+            provider: provider.clone(),
+            tags: Vec::new(),
+            source: None,
+            data: None,
+        };
+        let (first, second) = (provider("first"), provider("second"));
+        doc.replace_diagnostics(
+            [
+                diagnostic(&first, 4, "unused variable"),
+                diagnostic(&first, 4, "unused variable"),
+                diagnostic(&first, 0, "let"),
+            ],
+            &[],
+            Some(&first),
+        );
+        doc.replace_diagnostics(
+            [
+                diagnostic(&second, 4, "unused variable"),
+                diagnostic(&second, 4, "unused"),
+                diagnostic(&second, 2, "let"),
+            ],
+            &[],
+            Some(&second),
+        );
+
+        // Only the duplicates of other providers are left out.
+        let unique: Vec<_> = doc
+            .unique_diagnostics()
+            .map(|diagnostic| (diagnostic.range.start, diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(
+            unique,
+            [
+                (0, "let"),
+                (2, "let"),
+                (4, "unused variable"),
+                (4, "unused variable"),
+                (4, "unused"),
+            ]
+        );
+
+        // The diagnostics of the other provider remain when one provider clears its own.
+        doc.replace_diagnostics([], &[], Some(&first));
+        assert_eq!(doc.unique_diagnostics().count(), 3);
+    }
+
     #[test]
     fn test_line_ending() {
         assert_eq!(
@@ -2569,6 +2913,124 @@ fn $name() {
         };
     }
 
+    /// Decodes `bytes` with encoding detection and encodes the result again.
+    fn roundtrip(bytes: &[u8]) -> (String, &'static Encoding, Vec<u8>) {
+        let (text, encoding, has_bom) = from_reader(&mut &bytes[..], None).unwrap();
+        let mut buf: Vec<u8> = Vec::new();
+        helix_lsp::block_on(to_writer(&mut buf, (encoding, has_bom), &text)).unwrap();
+        (text.to_string(), encoding, buf)
+    }
+
+    #[test]
+    fn roundtrip_latin1() {
+        let text = "Le cœur déçu mais l'âme plutôt naïve, Louÿs rêva de crapaüter en canoë \
+                    au delà des îles, près du mälström où brûlent les novæ.\n";
+        let (bytes, _, unmappable) = encoding::WINDOWS_1252.encode(text);
+        assert!(!unmappable);
+
+        let (decoded, encoding, encoded) = roundtrip(&bytes);
+        assert_eq!(encoding, encoding::WINDOWS_1252);
+        assert_eq!(decoded, text);
+        assert_eq!(encoded, &bytes[..]);
+    }
+
+    #[test]
+    fn roundtrip_utf16le() {
+        let text = "hello, 世界 🦀\r\n";
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+
+        let (decoded, encoding, encoded) = roundtrip(&bytes);
+        assert_eq!(encoding, encoding::UTF_16LE);
+        assert_eq!(decoded, text);
+        assert_eq!(encoded, bytes);
+    }
+
+    fn modeline(text: &str) -> Modeline {
+        Document::parse_modeline(&Rope::from(text))
+    }
+
+    #[test]
+    fn parse_valid_modelines() {
+        assert_eq!(
+            modeline("# helix: indent_style=space; indent_width=2\nimport os\n"),
+            Modeline {
+                indent_style: Some(IndentStyle::Spaces(2)),
+                indent_width: NonZeroU8::new(2),
+                ..Default::default()
+            }
+        );
+        // The width applies whatever the order of the settings.
+        assert_eq!(
+            modeline("/* helix: indent_width=3 ; indent_style=space */").indent_style,
+            Some(IndentStyle::Spaces(3))
+        );
+        assert_eq!(
+            modeline("// helix: indent_style=space").indent_style,
+            Some(IndentStyle::Spaces(4))
+        );
+        assert_eq!(
+            modeline(
+                "all:\n\tmake\n\n# helix: indent_style=tab; line_ending=crlf; language=make;\n"
+            ),
+            Modeline {
+                indent_style: Some(IndentStyle::Tabs),
+                line_ending: Some(LineEnding::Crlf),
+                language: Some("make".to_string()),
+                ..Default::default()
+            }
+        );
+        // Later modelines win.
+        let text = "-- helix: indent_width=2\n\n\n\n\n\n\n-- helix: indent_width=8\n";
+        assert_eq!(modeline(text).indent_width, NonZeroU8::new(8));
+    }
+
+    #[test]
+    fn parse_malformed_modelines() {
+        for text in [
+            "",
+            "helix: indent_style=tab",
+            "let helix: indent_style=tab",
+            "# helix indent_style=tab",
+            "# helix: indent_style",
+            "# helix: indent_style=tabulation; indent_width=0; line_ending=lfcr; language=",
+            "# helix: indent_width=17",
+            "# helix: tab_width=2; = ;;",
+            // Only the first and last five lines are searched.
+            "\n\n\n\n\n# helix: indent_style=tab\n\n\n\n\n",
+        ] {
+            assert_eq!(modeline(text), Modeline::default(), "{text:?}");
+        }
+        // Valid settings are kept.
+        assert_eq!(
+            modeline("# helix: indent_style=tabs; indent_width=x; foo").indent_style,
+            Some(IndentStyle::Tabs)
+        );
+    }
+
+    #[test]
+    fn modeline_overrides_detection() {
+        let mut doc = Document::from(
+            Rope::from("# helix: indent_style=tab; indent_width=2\nfoo:\n    bar\n"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        doc.detect_indent_and_line_ending();
+        assert_eq!(doc.indent_style, IndentStyle::Spaces(4));
+
+        doc.modeline = Document::parse_modeline(doc.text());
+        doc.detect_indent_and_line_ending();
+        assert_eq!(doc.indent_style, IndentStyle::Tabs);
+        assert_eq!(doc.tab_width(), 2);
+        assert_eq!(doc.indent_width(), 2);
+
+        // The width alone resizes the detected indentation.
+        doc.modeline.indent_style = None;
+        doc.detect_indent_and_line_ending();
+        assert_eq!(doc.indent_style, IndentStyle::Spaces(2));
+    }
+
     decode!(big5_decode, "big5");
     encode!(big5_encode, "big5");
     decode!(euc_kr_decode, "euc_kr", "EUC-KR");
@@ -349,12 +349,22 @@ pub struct Config {
         deserialize_with = "deserialize_duration_millis"
     )]
     pub completion_timeout: Duration,
+    /// Time in milliseconds after which highlight, indent and text object queries are
+    /// cancelled, or 0 to never cancel them. Defaults to 50ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub query_timeout: Duration,
     /// Whether to insert the completion suggestion on hover. Defaults to true.
     pub preview_completion_insert: bool,
     pub completion_trigger_len: u8,
     /// Whether to instruct the LSP to replace the entire word when applying a completion
     /// or to only insert new text
     pub completion_replace: bool,
+    /// Whether deleting backwards within a word requests completions for the shortened word,
+    /// like typing does. Defaults to false.
+    pub completion_trigger_on_delete: bool,
     /// `true` if helix should automatically add a line comment token if you're currently in a comment
     /// and press `enter`.
     pub continue_comments: bool,
@@ -381,6 +391,8 @@ pub struct Config {
     pub whitespace: WhitespaceConfig,
     /// Persistently display open buffers along the top
     pub bufferline: BufferLine,
+    /// Display a tab bar listing the open tabs along the top. Defaults to `false`.
+    pub tabs: bool,
     /// Vertical indent width guides.
     pub indent_guides: IndentGuidesConfig,
     /// Whether to color modes with different colors. Defaults to `false`.
@@ -406,6 +418,8 @@ pub struct Config {
     pub smart_tab: Option<SmartTabConfig>,
     /// Draw border around popups.
     pub popup_border: PopupBorderConfig,
+    /// How `:zen-mode` hides the UI around the text.
+    pub zen_mode: ZenModeConfig,
     /// Which indent heuristic to use when a new line is inserted
     #[serde(default)]
     pub indent_heuristic: IndentationHeuristic,
@@ -425,12 +439,87 @@ pub struct Config {
     pub editor_config: bool,
     /// Whether to render rainbow colors for matching brackets. Defaults to `false`.
     pub rainbow_brackets: bool,
+    /// Whether to display right-to-left text like Hebrew or Arabic in visual order. Defaults to
+    /// `false`.
+    pub bidi: bool,
     /// Whether to enable Kitty Keyboard Protocol
     pub kitty_keyboard_protocol: KittyKeyboardProtocolConfig,
     pub buffer_picker: BufferPickerConfig,
     /// Root directories to scan for git repositories with :project-scan command.
     /// Defaults to empty (will use home directory).
     pub project_scan_roots: Vec<PathBuf>,
+    /// How many repositories `:project-scan` registers at most per root. Defaults to 1000, and
+    /// 0 doesn't limit them.
+    pub project_scan_max_repos: usize,
+    /// Whether to register the repository of each opened file in the projects list.
+    /// Defaults to `false`.
+    pub project_auto_register: bool,
+    /// Glob patterns of repository paths which are never registered automatically.
+    pub project_auto_register_exclude: Vec<String>,
+    /// Whether to register the projects opened by name from the roots of
+    /// `$HELIX_PROJECT_PATHS`, see [helix_loader::projects::ambient_roots]. Defaults to `false`.
+    pub project_register_ambient: bool,
+    /// Whether to save the open buffers and splits of a project on exit and restore them when
+    /// the project is opened again. Defaults to `true`.
+    pub project_sessions: bool,
+    /// Whether to restore the session of the project on startup without asking first. Only
+    /// applies with `project_sessions`. Defaults to `false`.
+    pub restore_session: bool,
+    /// Whether to jump to the file and cursor position focused when last leaving a project when
+    /// switching to it, unless its session is restored. Defaults to `true`.
+    pub project_restore_last_file: bool,
+    /// Whether to remember the files opened and written in the current project for
+    /// `:recent-files`. Defaults to `false`.
+    pub project_recent_files: bool,
+    /// Whether to show the recent projects picker when started without arguments outside of a
+    /// registered project. Defaults to `false`.
+    pub project_startup_picker: bool,
+    /// Projects which weren't accessed for this many days are archived on startup. Defaults to
+    /// 0, which never archives projects.
+    pub project_retention_days: u64,
+    /// The order of the entries in the projects file. Defaults to the project path.
+    pub projects_file_order: helix_loader::projects::ProjectsFileOrder,
+    /// The profile whose projects file is used, see [helix_loader::projects::profile_file_path].
+    /// `$HELIX_PROJECT_PROFILE` takes precedence. Defaults to the default profile.
+    pub project_profile: Option<String>,
+    /// A read-only projects file, such as `/etc/helix/projects.toml`, whose projects are listed
+    /// along with the user's. Defaults to none.
+    pub system_projects_file: Option<PathBuf>,
+    /// Commands run when switching between projects.
+    pub project_hooks: ProjectHooksConfig,
+    /// Short names shown in place of directories in displayed paths, keyed by the name. The
+    /// home directory is always shown as `~`.
+    pub path_aliases: BTreeMap<String, PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ProjectHooksConfig {
+    /// Shell command run in the project root after switching to a project which doesn't have
+    /// its own `on_open` hook.
+    pub on_open: Option<String>,
+    /// Shell command run in the project root when switching away from a project which doesn't
+    /// have its own `on_close` hook.
+    pub on_close: Option<String>,
+    /// Time in milliseconds after which a hook is killed. Defaults to 10s.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub timeout: Duration,
+    /// Whether to run the hooks defined in the system projects file. Defaults to `false`.
+    pub trust_system_projects: bool,
+}
+
+impl Default for ProjectHooksConfig {
+    fn default() -> Self {
+        Self {
+            on_open: None,
+            on_close: None,
+            timeout: Duration::from_secs(10),
+            trust_system_projects: false,
+        }
+    }
 }
 
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, Clone, Copy)]
@@ -484,6 +573,26 @@ fn default() -> Self {
     }
 }
 
+/// The layout of views in zen mode, which hides the gutters, inlay hints, inline diagnostics,
+/// tab bar and bufferline, see [Editor::zen_mode].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ZenModeConfig {
+    /// Columns left empty on both sides of the text, which centers it. Defaults to 0.
+    pub padding: usize,
+    /// Whether the statusline is hidden too. Defaults to true.
+    pub hide_statusline: bool,
+}
+
+impl Default for ZenModeConfig {
+    fn default() -> Self {
+        Self {
+            padding: 0,
+            hide_statusline: true,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
 pub struct TerminalConfig {
@@ -557,6 +666,10 @@ pub struct LspConfig {
     pub inlay_hints_length_limit: Option<NonZeroU8>,
     /// Display document color swatches
     pub display_color_swatches: bool,
+    /// Display code lenses above the lines they apply to
+    pub code_lens: bool,
+    /// Apply the edits of language servers which format the document as you type
+    pub on_type_formatting: bool,
     /// Whether to enable snippet support
     pub snippets: bool,
     /// Whether to include declaration in the goto reference query
@@ -576,6 +689,8 @@ fn default() -> Self {
             snippets: true,
             goto_reference_include_declaration: true,
             display_color_swatches: true,
+            code_lens: false,
+            on_type_formatting: false,
         }
     }
 }
@@ -585,8 +700,10 @@ fn default() -> Self {
 pub struct SearchConfig {
     /// Smart case: Case insensitive searching unless pattern contains upper case characters. Defaults to true.
     pub smart_case: bool,
-    /// Whether the search should wrap after depleting the matches. Default to true.
+    /// Whether the search and `]g`/`[g` should wrap after depleting the matches. Default to true.
     pub wrap_around: bool,
+    /// Whether all matches of the active search are highlighted. Defaults to false.
+    pub highlight_all_matches: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -718,12 +835,22 @@ pub enum StatusLineElement {
 
     /// The base of current working directory
     CurrentWorkingDirectory,
+
+    /// The name of the registered project containing the document
+    ProjectName,
+
+    /// The syntax scopes around the primary cursor, like `impl Foo > function bar`
+    SymbolsPath,
 }
 
 // Cursor shape is read and used on every rendered frame and so needs
 // to be fast. Therefore we avoid a hashmap and use an enum indexed array.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct CursorShapeConfig([CursorKind; 3]);
+pub struct CursorShapeConfig {
+    shapes: [CursorKind; 3],
+    /// Whether the cursor blinks when it is drawn by the terminal. Defaults to `false`.
+    pub blink: bool,
+}
 
 impl CursorShapeConfig {
     pub fn from_mode(&self, mode: Mode) -> CursorKind {
@@ -736,13 +863,24 @@ fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let m = HashMap::<Mode, CursorKind>::deserialize(deserializer)?;
-        let into_cursor = |mode: Mode| m.get(&mode).copied().unwrap_or_default();
-        Ok(CursorShapeConfig([
-            into_cursor(Mode::Normal),
-            into_cursor(Mode::Select),
-            into_cursor(Mode::Insert),
-        ]))
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct RawCursorShapeConfig {
+            #[serde(default)]
+            normal: CursorKind,
+            #[serde(default)]
+            select: CursorKind,
+            #[serde(default)]
+            insert: CursorKind,
+            #[serde(default)]
+            blink: bool,
+        }
+
+        let raw = RawCursorShapeConfig::deserialize(deserializer)?;
+        Ok(CursorShapeConfig {
+            shapes: [raw.normal, raw.select, raw.insert],
+            blink: raw.blink,
+        })
     }
 }
 
@@ -751,11 +889,12 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let mut map = serializer.serialize_map(Some(self.len()))?;
+        let mut map = serializer.serialize_map(Some(self.len() + 1))?;
         let modes = [Mode::Normal, Mode::Select, Mode::Insert];
         for mode in modes {
             map.serialize_entry(&mode, &self.from_mode(mode))?;
         }
+        map.serialize_entry("blink", &self.blink)?;
         map.end()
     }
 }
@@ -764,13 +903,16 @@ impl std::ops::Deref for CursorShapeConfig {
     type Target = [CursorKind; 3];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.shapes
     }
 }
 
 impl Default for CursorShapeConfig {
     fn default() -> Self {
-        Self([CursorKind::Block; 3])
+        Self {
+            shapes: [CursorKind::Block; 3],
+            blink: false,
+        }
     }
 }
 
@@ -1108,6 +1250,7 @@ fn default() -> Self {
             auto_save: AutoSave::default(),
             idle_timeout: Duration::from_millis(250),
             completion_timeout: Duration::from_millis(250),
+            query_timeout: syntax::DEFAULT_QUERY_TIMEOUT,
             preview_completion_insert: true,
             completion_trigger_len: 2,
             auto_info: true,
@@ -1123,6 +1266,7 @@ fn default() -> Self {
             rulers: Vec::new(),
             whitespace: WhitespaceConfig::default(),
             bufferline: BufferLine::default(),
+            tabs: false,
             indent_guides: IndentGuidesConfig::default(),
             color_modes: false,
             soft_wrap: SoftWrap {
@@ -1131,6 +1275,7 @@ fn default() -> Self {
             },
             text_width: 80,
             completion_replace: false,
+            completion_trigger_on_delete: false,
             continue_comments: true,
             workspace_lsp_roots: Vec::new(),
             default_line_ending: LineEndingConfig::default(),
@@ -1140,6 +1285,7 @@ fn default() -> Self {
             trim_trailing_whitespace: false,
             smart_tab: Some(SmartTabConfig::default()),
             popup_border: PopupBorderConfig::None,
+            zen_mode: ZenModeConfig::default(),
             indent_heuristic: IndentationHeuristic::default(),
             jump_label_alphabet: ('a'..='z').collect(),
             inline_diagnostics: InlineDiagnosticsConfig::default(),
@@ -1147,10 +1293,42 @@ fn default() -> Self {
             clipboard_provider: ClipboardProvider::default(),
             editor_config: true,
             rainbow_brackets: false,
+            bidi: false,
             kitty_keyboard_protocol: Default::default(),
             buffer_picker: BufferPickerConfig::default(),
-            project_scan_roots: vec![PathBuf::from("/home/SENSETIME/liyanan/lyn/src/github.com/vscode/helix")],
-        }
+            project_scan_roots: Vec::new(),
+            project_scan_max_repos: 1000,
+            project_auto_register: false,
+            project_auto_register_exclude: vec!["/tmp/**".into(), "/nix/store/**".into()],
+            project_register_ambient: false,
+            project_sessions: true,
+            restore_session: false,
+            project_restore_last_file: true,
+            project_recent_files: false,
+            project_startup_picker: false,
+            project_retention_days: 0,
+            projects_file_order: Default::default(),
+            project_profile: None,
+            system_projects_file: None,
+            project_hooks: ProjectHooksConfig::default(),
+            path_aliases: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// The directories shortened by [helix_core::path::shorten_path] with their aliases: the
+    /// home directory and the configured `path-aliases`.
+    pub fn path_aliases(&self) -> Vec<(PathBuf, &str)> {
+        let home = helix_stdx::path::home_dir().ok().map(|home| (home, "~"));
+        home.into_iter()
+            .chain(self.path_aliases.iter().map(|(alias, dir)| {
+                (
+                    helix_stdx::path::expand_tilde(dir.as_path()).into_owned(),
+                    alias.as_str(),
+                )
+            }))
+            .collect()
     }
 }
 
@@ -1159,6 +1337,7 @@ fn default() -> Self {
         Self {
             wrap_around: true,
             smart_case: true,
+            highlight_all_matches: false,
         }
     }
 }
@@ -1176,6 +1355,26 @@ pub struct Breakpoint {
     pub log_message: Option<String>,
 }
 
+/// A named window layout. Each tab has its own tree of splits.
+#[derive(Debug)]
+pub struct Tab {
+    pub name: Option<String>,
+    /// The root container of the tab's window layout in [Editor::tree].
+    pub root: ViewId,
+    /// The view that was focused when the tab was last active.
+    focus: ViewId,
+}
+
+impl Tab {
+    fn new(root: ViewId, name: Option<String>) -> Self {
+        Self {
+            name,
+            root,
+            focus: root,
+        }
+    }
+}
+
 use futures_util::stream::{Flatten, Once};
 
 type Diagnostics = BTreeMap<Uri, Vec<(lsp::Diagnostic, DiagnosticProvider)>>;
@@ -1183,7 +1382,10 @@ pub struct Breakpoint {
 pub struct Editor {
     /// Current editing mode.
     pub mode: Mode,
+    /// The window layouts of all tabs. Only the layout of the active tab is shown.
     pub tree: Tree,
+    pub tabs: Vec<Tab>,
+    pub active_tab: usize,
     pub next_document_id: DocumentId,
     pub documents: BTreeMap<DocumentId, Document>,
 
@@ -1218,6 +1420,9 @@ pub struct Editor {
     /// restored when the preview is aborted, or added to the jumplist when it is
     /// confirmed.
     pub last_selection: Option<Selection>,
+    /// The regex of the active search, whose matches are highlighted if
+    /// `search.highlight-all-matches` is enabled. Cleared when entering insert mode.
+    pub search_highlight: Option<helix_stdx::rope::Regex>,
 
     pub status_msg: Option<(Cow<'static, str>, Severity)>,
     pub autoinfo: Option<Info>,
@@ -1230,11 +1435,19 @@ pub struct Editor {
     last_motion: Option<Motion>,
     pub last_completion: Option<CompleteAction>,
     last_cwd: Option<PathBuf>,
+    /// The registered projects, shared by the features which list or record projects.
+    pub projects: crate::projects::SharedProjects,
+    /// The generation of the projects when the documents' projects were last refreshed.
+    pub(crate) projects_generation: u64,
 
     pub exit_code: i32,
 
     pub config_events: (UnboundedSender<ConfigEvent>, UnboundedReceiver<ConfigEvent>),
     pub needs_redraw: bool,
+    /// Whether `:zen-mode` is on, hiding the UI around the text as configured by
+    /// `editor.zen-mode`. The UI is shown while zen mode is suspended, see [Editor::update_zen_mode].
+    pub zen_mode: bool,
+    zen_mode_suspended: bool,
     /// Cached position of the cursor calculated during rendering.
     /// The content of `cursor_cache` is returned by `Editor::cursor` if
     /// set to `Some(_)`. The value will be cleared after it's used.
@@ -1269,6 +1482,9 @@ pub enum EditorEvent {
 pub enum ConfigEvent {
     Refresh,
     Update(Box<Config>),
+    /// The working directory moved to another workspace: reload the workspace
+    /// configuration without reporting it in the statusline.
+    WorkspaceChanged,
 }
 
 enum ThemeAction {
@@ -1327,13 +1543,19 @@ pub fn new(
         let language_servers = helix_lsp::Registry::new(syn_loader.clone());
         let conf = config.load();
         let auto_pairs = (&conf.auto_pairs).into();
+        syntax::set_query_timeout(conf.query_timeout);
 
         // HAXX: offset the render area height by 1 to account for prompt/commandline
         area.height -= 1;
 
+        let tree = Tree::new(area);
+        let tabs = vec![Tab::new(tree.root(), None)];
+
         Self {
             mode: Mode::Normal,
-            tree: Tree::new(area),
+            tree,
+            tabs,
+            active_tab: 0,
             next_document_id: DocumentId::default(),
             documents: BTreeMap::new(),
             saves: HashMap::new(),
@@ -1353,6 +1575,7 @@ pub fn new(
             theme_loader,
             last_theme: None,
             last_selection: None,
+            search_highlight: None,
             registers: Registers::new(Box::new(arc_swap::access::Map::new(
                 Arc::clone(&config),
                 |config: &Config| &config.clipboard_provider,
@@ -1364,11 +1587,15 @@ pub fn new(
             last_motion: None,
             last_completion: None,
             last_cwd: None,
+            projects: Default::default(),
+            projects_generation: 0,
             config,
             auto_pairs,
             exit_code: 0,
             config_events: unbounded_channel(),
             needs_redraw: false,
+            zen_mode: false,
+            zen_mode_suspended: false,
             handlers,
             mouse_down_range: None,
             cursor_cache: CursorCache::default(),
@@ -1412,6 +1639,7 @@ pub fn config(&self) -> DynGuard<Config> {
     pub fn refresh_config(&mut self, old_config: &Config) {
         let config = self.config();
         self.auto_pairs = (&config.auto_pairs).into();
+        syntax::set_query_timeout(config.query_timeout);
         self.reset_idle_timer();
         self._refresh();
         helix_event::dispatch(crate::events::ConfigDidChange {
@@ -1524,6 +1752,17 @@ pub fn language_server_by_id(
             .map(|client| &**client)
     }
 
+    /// Calls `f` with each language server of the document `doc_id`, for sending the same
+    /// notification to all of them.
+    pub fn broadcast_to_lsp_clients(&self, doc_id: DocumentId, f: impl Fn(&helix_lsp::Client)) {
+        let Some(doc) = self.documents.get(&doc_id) else {
+            return;
+        };
+        for language_server in doc.language_servers() {
+            f(language_server);
+        }
+    }
+
     /// Refreshes the language server for a given document
     pub fn refresh_language_servers(&mut self, doc_id: DocumentId) {
         self.launch_language_servers(doc_id)
@@ -1586,20 +1825,20 @@ pub fn move_path(&mut self, old_path: &Path, new_path: &Path) -> io::Result<()>
     }
 
     pub fn set_doc_path(&mut self, doc_id: DocumentId, path: &Path) {
-        let doc = doc_mut!(self, &doc_id);
-        let old_path = doc.path();
+        let doc = doc!(self, &doc_id);
 
-        if let Some(old_path) = old_path {
+        if let Some(old_path) = doc.path() {
             // sanity check, should not occur but some callers (like an LSP) may
             // create bogus calls
             if old_path == path {
                 return;
             }
             // if we are open in LSPs send did_close notification
-            for language_server in doc.language_servers() {
+            self.broadcast_to_lsp_clients(doc_id, |language_server| {
                 language_server.text_document_did_close(doc.identifier());
-            }
+            });
         }
+        let doc = doc_mut!(self, &doc_id);
         // we need to clear the list of language servers here so that
         // refresh_doc_language/refresh_language_servers doesn't resend
         // text_document_did_close. Since we called `text_document_did_close`
@@ -1623,6 +1862,32 @@ pub fn refresh_doc_language(&mut self, doc_id: DocumentId) {
         doc.reset_all_inlay_hints();
     }
 
+    /// Overrides the language ID of a document, see [Document::set_language_id], and reopens
+    /// the document in its language servers so that they pick up the new ID.
+    pub fn set_language_id(&mut self, doc_id: DocumentId, language_id: Option<String>) {
+        let Some(doc) = self.documents.get_mut(&doc_id) else {
+            return;
+        };
+        if doc.language_id_override() == language_id.as_deref() {
+            return;
+        }
+        doc.set_language_id(language_id);
+        let doc = doc!(self, &doc_id);
+        let Some(url) = doc.url() else {
+            return;
+        };
+        let language_id = doc.language_id().map(ToOwned::to_owned).unwrap_or_default();
+        self.broadcast_to_lsp_clients(doc_id, |language_server| {
+            language_server.text_document_did_close(doc.identifier());
+            language_server.text_document_did_open(
+                url.clone(),
+                doc.version(),
+                doc.text(),
+                language_id.clone(),
+            );
+        });
+    }
+
     /// Launch a language server for a given document
     fn launch_language_servers(&mut self, doc_id: DocumentId) {
         if !self.config().lsp.enable {
@@ -1725,6 +1990,26 @@ fn _refresh(&mut self) {
         }
     }
 
+    /// Whether zen mode currently hides the UI, which it doesn't while suspended.
+    pub fn zen_mode_active(&self) -> bool {
+        self.zen_mode && !self.zen_mode_suspended
+    }
+
+    /// Applies zen mode to the layout of the views, showing the UI while `suspended`, for example
+    /// while a picker is open. This is done before every render so that it covers new views.
+    pub fn update_zen_mode(&mut self, suspended: bool) {
+        self.zen_mode_suspended = suspended;
+        let config = self.config();
+        let zen = self.zen_mode_active().then(|| config.zen_mode.clone());
+        for (view, _) in self.tree.views_mut() {
+            if view.zen != zen {
+                view.zen = zen.clone();
+                let doc = doc_mut!(self, &view.doc);
+                view.ensure_cursor_in_view(doc, config.scrolloff);
+            }
+        }
+    }
+
     fn replace_document_in_view(&mut self, current_view: ViewId, doc_id: DocumentId) {
         let scrolloff = self.config().scrolloff;
         let view = self.tree.get_mut(current_view);
@@ -1950,7 +2235,121 @@ pub fn close(&mut self, id: ViewId) {
             doc.remove_view(id);
         }
         self.tree.remove(id);
+        self.remove_empty_tabs();
+        self._refresh();
+    }
+
+    /// Removes tabs which no longer have any views. The last tab is kept even if it is empty.
+    fn remove_empty_tabs(&mut self) {
+        let mut index = 0;
+        while self.tabs.len() > 1 && index < self.tabs.len() {
+            if !self.tree.is_layout_empty(self.tabs[index].root) {
+                index += 1;
+                continue;
+            }
+            if index == self.active_tab {
+                let next = if index + 1 < self.tabs.len() {
+                    index + 1
+                } else {
+                    index - 1
+                };
+                self.set_active_tab(next);
+            }
+            let tab = self.tabs.remove(index);
+            self.tree.remove_root(tab.root);
+            if index < self.active_tab {
+                self.active_tab -= 1;
+            }
+        }
+    }
+
+    /// Switches the tree to the layout of the tab at `index` without touching the current view.
+    fn set_active_tab(&mut self, index: usize) {
+        self.tabs[self.active_tab].focus = self.tree.focus;
+        self.active_tab = index;
+        let tab = &self.tabs[index];
+        self.tree.set_root(tab.root, tab.focus);
+    }
+
+    /// Returns the focused view of the tab at `index`, or the tab's root container if the tab has
+    /// no views.
+    pub fn tab_focus(&self, index: usize) -> ViewId {
+        if index == self.active_tab {
+            self.tree.focus
+        } else {
+            let tab = &self.tabs[index];
+            self.tree.layout_focus(tab.root, tab.focus)
+        }
+    }
+
+    /// Opens a new tab after the current one showing the document `doc_id` and focuses it.
+    pub fn new_tab(&mut self, doc_id: DocumentId, name: Option<String>) {
+        self.enter_normal_mode();
+        let (view, doc) = current!(self);
+        doc.append_changes_to_history(view);
+
+        let root = self.tree.insert_root();
+        let index = self.active_tab + 1;
+        self.tabs.insert(index, Tab::new(root, name));
+        self.set_active_tab(index);
+
+        let view = View::new(doc_id, self.config().gutters.clone());
+        let view_id = self.tree.insert(view);
+        let doc = doc_mut!(self, &doc_id);
+        doc.ensure_view_init(view_id);
+        doc.mark_as_focused();
+        self._refresh();
+    }
+
+    /// Focuses the tab at `index`.
+    pub fn focus_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+
+        self.enter_normal_mode();
+        let (view, doc) = current!(self);
+        doc.append_changes_to_history(view);
+
+        self.set_active_tab(index);
+        doc_mut!(self).mark_as_focused();
+        self._refresh();
+    }
+
+    /// Focuses the next tab, wrapping around to the first tab.
+    pub fn focus_next_tab(&mut self) {
+        self.focus_tab((self.active_tab + 1) % self.tabs.len());
+    }
+
+    /// Focuses the previous tab, wrapping around to the last tab.
+    pub fn focus_prev_tab(&mut self) {
+        let len = self.tabs.len();
+        self.focus_tab((self.active_tab + len - 1) % len);
+    }
+
+    /// Closes all views of the current tab and focuses the next tab.
+    ///
+    /// Documents shown in the tab are kept open. Returns `false` if this is the last tab.
+    pub fn close_tab(&mut self) -> bool {
+        if self.tabs.len() == 1 {
+            return false;
+        }
+
+        self.enter_normal_mode();
+        let (view, doc) = current!(self);
+        doc.append_changes_to_history(view);
+
+        let views: Vec<_> = self.tree.traverse().map(|(id, _)| id).collect();
+        for id in views {
+            for doc in self.documents_mut() {
+                doc.remove_view(id);
+            }
+            self.tree.remove(id);
+        }
+        self.remove_empty_tabs();
+        doc_mut!(self).mark_as_focused();
         self._refresh();
+        true
     }
 
     pub fn close_document(&mut self, doc_id: DocumentId, force: bool) -> Result<(), CloseError> {
@@ -2384,6 +2783,7 @@ pub fn get_synced_view_id(&mut self, id: DocumentId) -> ViewId {
     pub fn set_cwd(&mut self, path: &Path) -> std::io::Result<()> {
         self.last_cwd = helix_stdx::env::set_current_working_dir(path)?;
         self.clear_doc_relative_paths();
+        self.refresh_projects();
         Ok(())
     }
 
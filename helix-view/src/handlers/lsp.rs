@@ -10,7 +10,7 @@
 use helix_core::diagnostic::DiagnosticProvider;
 use helix_core::Uri;
 use helix_event::register_hook;
-use helix_lsp::util::generate_transaction_from_edits;
+use helix_lsp::util::{generate_transaction_from_edits, TextEditError};
 use helix_lsp::{lsp, LanguageServerId, OffsetEncoding};
 
 use super::Handlers;
@@ -51,8 +51,7 @@ pub enum ApplyEditErrorKind {
     FileNotFound,
     InvalidUrl(helix_core::uri::UrlConversionError),
     IoError(std::io::Error),
-    // TODO: check edits before applying and propagate failure
-    // InvalidEdit,
+    InvalidEdit(TextEditError),
 }
 
 impl From<std::io::Error> for ApplyEditErrorKind {
@@ -74,6 +73,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             ApplyEditErrorKind::FileNotFound => f.write_str("file not found"),
             ApplyEditErrorKind::InvalidUrl(err) => f.write_str(&format!("{err}")),
             ApplyEditErrorKind::IoError(err) => f.write_str(&format!("{err}")),
+            ApplyEditErrorKind::InvalidEdit(err) => f.write_str(&format!("{err}")),
         }
     }
 }
@@ -123,7 +123,16 @@ fn apply_text_edits(
         let view_id = self.get_synced_view_id(doc_id);
         let doc = doc_mut!(self, &doc_id);
 
-        let transaction = generate_transaction_from_edits(doc.text(), text_edits, offset_encoding);
+        let transaction =
+            match generate_transaction_from_edits(doc.text(), text_edits, offset_encoding) {
+                Ok(transaction) => transaction,
+                Err(err) => {
+                    let err_msg = format!("invalid workspace edit for {path:?}: {err}");
+                    log::error!("{err_msg}");
+                    self.set_error(err_msg);
+                    return Err(ApplyEditErrorKind::InvalidEdit(err));
+                }
+            };
         let view = view_mut!(self, view_id);
         doc.apply(&transaction, view.id);
         doc.append_changes_to_history(view);
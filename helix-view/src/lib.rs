@@ -13,7 +13,9 @@
 pub mod info;
 pub mod input;
 pub mod keyboard;
+pub mod projects;
 pub mod register;
+pub mod session;
 pub mod theme;
 pub mod tree;
 pub mod view;
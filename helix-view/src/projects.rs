@@ -0,0 +1,104 @@
+//! The registered projects shared by the editor's features.
+//!
+//! The projects file is read once into a [ProjectsStore] held by the [Editor](crate::Editor)
+//! rather than by each feature that lists, matches or records projects.
+
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Arc, Mutex};
+
+use helix_loader::projects::{Project, ProjectsError, ProjectsStore};
+
+type Result<T> = std::result::Result<T, ProjectsError>;
+
+/// A handle to the [ProjectsStore] of the editor, loaded on first use.
+///
+/// Handles are cheap to clone so that background tasks can use the same store.
+#[derive(Debug, Default, Clone)]
+pub struct SharedProjects {
+    store: Arc<Mutex<Option<ProjectsStore>>>,
+    /// The generation of the store after it was last used, see [Self::generation].
+    generation: Arc<AtomicU64>,
+}
+
+impl SharedProjects {
+    /// Applies `f` to the store, loading it first if needed.
+    pub fn with_store<T>(&self, f: impl FnOnce(&mut ProjectsStore) -> Result<T>) -> Result<T> {
+        let mut store = self.store.lock().unwrap();
+        let file_path = helix_loader::projects::projects_file_path();
+        // Switch to the projects file of another profile when the config changes.
+        if let Some(old) = store
+            .as_mut()
+            .filter(|store| store.file_path() != file_path)
+        {
+            old.flush()?;
+            *store = None;
+        }
+        let store = match &mut *store {
+            Some(store) => store,
+            None => store.insert(ProjectsStore::open()?),
+        };
+        let result = f(store);
+        self.generation
+            .store(store.generation(), atomic::Ordering::Relaxed);
+        result
+    }
+
+    /// The [generation](ProjectsStore::generation) of the store, without waiting for a
+    /// background task using it.
+    ///
+    /// Components showing projects compare it with the generation they were built from, see
+    /// [Self::changed_since], and refresh themselves when they are next rendered.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Whether the projects changed after `generation`, see [Self::generation].
+    pub fn changed_since(&self, generation: u64) -> bool {
+        self.generation() > generation
+    }
+
+    /// Calls `f` with the store after rereading the projects file if it changed on disk.
+    pub fn read<T>(&self, f: impl FnOnce(&ProjectsStore) -> T) -> Result<T> {
+        self.with_store(|store| {
+            store.revalidate()?;
+            Ok(f(store))
+        })
+    }
+
+    /// The registered projects, like [helix_loader::projects::list_projects].
+    pub fn list(&self, include_archived: bool) -> Result<Vec<Project>> {
+        self.read(|store| {
+            store
+                .projects()
+                .iter()
+                .filter(|project| include_archived || !project.archived)
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Applies `f` to the up to date store and writes the result right away, for changes made
+    /// explicitly by the user.
+    pub fn update_now<T>(&self, f: impl FnOnce(&mut ProjectsStore) -> T) -> Result<T> {
+        self.with_store(|store| {
+            store.flush()?;
+            let result = f(store);
+            store.flush()?;
+            Ok(result)
+        })
+    }
+
+    /// Applies `f` to the up to date projects list, see [ProjectsStore::update].
+    pub fn update<T>(&self, f: impl FnOnce(&mut Vec<Project>) -> (bool, T)) -> Result<T> {
+        self.with_store(|store| store.update(f))
+    }
+
+    /// Writes the pending changes, such as project accesses. Called when the editor exits.
+    pub fn flush(&self) {
+        if let Some(store) = self.store.lock().unwrap().as_mut() {
+            if let Err(err) = store.flush() {
+                log::warn!("Failed to save projects: {err}");
+            }
+        }
+    }
+}
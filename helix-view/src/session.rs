@@ -0,0 +1,359 @@
+//! Capturing and restoring the open documents and window layout of a project.
+//!
+//! Sessions are stored by [helix_loader::session]. Only the layout of the active tab is part of
+//! a session.
+
+use std::path::{Path, PathBuf};
+
+use helix_core::{coords_at_pos, pos_at_coords, Position, Range, Selection};
+use helix_loader::session::{
+    Session, SessionDocument, SessionLayout, SessionRange, SplitDirection,
+};
+
+use crate::{editor::Action, tree::Layout, DocumentId, Editor, ViewId};
+
+impl Editor {
+    /// Captures the documents with a path, their selections and the layout of the active tab.
+    ///
+    /// Documents shown in the active tab come first, followed by the remaining documents in the
+    /// order they were opened.
+    pub fn capture_session(&self, project: PathBuf) -> Session {
+        let mut session = Session::new(project);
+        session.working_directory = Some(helix_stdx::env::current_working_dir());
+        let mut documents: Vec<(DocumentId, ViewId)> = Vec::new();
+        let mut views = Vec::new();
+        session.layout = self.capture_layout(self.tree.root(), &mut documents, &mut views);
+        session.focus = views
+            .iter()
+            .position(|&view| view == self.tree.focus)
+            .unwrap_or(0);
+
+        let shown = documents.len();
+        for doc in self.documents() {
+            if doc.path().is_none() || documents.iter().any(|&(id, _)| id == doc.id()) {
+                continue;
+            }
+            let Some(&view) = doc.selections().keys().next() else {
+                continue;
+            };
+            documents.push((doc.id(), view));
+        }
+
+        session.documents = documents
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (doc_id, view))| {
+                let doc = self.document(doc_id)?;
+                // Documents of the layout use the selection of their first view.
+                let selection = if i < shown {
+                    doc.selection(view)
+                } else {
+                    doc.selections().get(&view)?
+                };
+                Some(SessionDocument {
+                    path: doc.path()?.clone(),
+                    selection: selection
+                        .ranges()
+                        .iter()
+                        .map(|range| SessionRange {
+                            anchor: range.anchor,
+                            head: range.head,
+                        })
+                        .collect(),
+                    primary: selection.primary_index(),
+                    language_id: doc.language_id_override().map(ToOwned::to_owned),
+                })
+            })
+            .collect();
+        session
+    }
+
+    fn capture_layout(
+        &self,
+        node: ViewId,
+        documents: &mut Vec<(DocumentId, ViewId)>,
+        views: &mut Vec<ViewId>,
+    ) -> Option<SessionLayout> {
+        let Some((layout, children)) = self.tree.container(node) else {
+            let view = self.tree.get(node);
+            // Scratch buffers can't be restored.
+            self.document(view.doc)?.path()?;
+            let document = match documents.iter().position(|&(id, _)| id == view.doc) {
+                Some(index) => index,
+                None => {
+                    documents.push((view.doc, node));
+                    documents.len() - 1
+                }
+            };
+            views.push(node);
+            return Some(SessionLayout::View { document });
+        };
+
+        let mut children: Vec<_> = children
+            .iter()
+            .filter_map(|&child| self.capture_layout(child, documents, views))
+            .collect();
+        match children.len() {
+            0 => None,
+            1 => children.pop(),
+            _ => Some(SessionLayout::Split {
+                direction: match layout {
+                    Layout::Horizontal => SplitDirection::Horizontal,
+                    Layout::Vertical => SplitDirection::Vertical,
+                },
+                children,
+            }),
+        }
+    }
+
+    /// Opens the documents of `session` and replaces the layout of the active tab with the
+    /// layout of the session.
+    ///
+    /// Documents that no longer exist are skipped and their paths are returned. If none of the
+    /// documents of the layout exist the layout of the active tab is left unchanged.
+    pub fn restore_session(&mut self, session: Session) -> Vec<PathBuf> {
+        let mut missing = Vec::new();
+        let documents: Vec<_> = session
+            .documents
+            .iter()
+            .map(|document| {
+                if !document.path.exists() {
+                    missing.push(document.path.clone());
+                    return None;
+                }
+                match self.open(&document.path, Action::Load) {
+                    Ok(doc_id) => Some(doc_id),
+                    Err(err) => {
+                        log::warn!(
+                            "Failed to restore {} from session: {err}",
+                            document.path.display()
+                        );
+                        missing.push(document.path.clone());
+                        None
+                    }
+                }
+            })
+            .collect();
+        let doc_id = |index: usize| documents.get(index).copied().flatten();
+
+        if let Some(layout) = session.layout {
+            let saved_views = layout.views();
+            let focus = match saved_views.get(session.focus) {
+                Some(&document) if doc_id(document).is_some() => saved_views[..session.focus]
+                    .iter()
+                    .filter(|&&document| doc_id(document).is_some())
+                    .count(),
+                _ => 0,
+            };
+
+            if let Some(layout) = layout.retain(&|document| doc_id(document).is_some()) {
+                let others: Vec<_> = self
+                    .tree
+                    .traverse()
+                    .map(|(view_id, _)| view_id)
+                    .filter(|&view_id| view_id != self.tree.focus)
+                    .collect();
+                for view_id in others {
+                    self.close(view_id);
+                }
+
+                let first = layout.views()[0];
+                self.switch(doc_id(first).unwrap(), Action::Replace);
+                let mut views = Vec::new();
+                self.restore_layout(&layout, self.tree.focus, &doc_id, &mut views);
+                self.focus(views.get(focus).copied().unwrap_or(views[0]));
+            }
+        }
+
+        for (document, doc_id) in session.documents.iter().zip(documents) {
+            let Some(doc_id) = doc_id else {
+                continue;
+            };
+            if document.language_id.is_some() {
+                self.set_language_id(doc_id, document.language_id.clone());
+            }
+            let Some(doc) = self.documents.get_mut(&doc_id) else {
+                continue;
+            };
+            let Some(selection) = restored_selection(document, doc.text().len_chars()) else {
+                continue;
+            };
+            let views: Vec<_> = doc.selections().keys().copied().collect();
+            for view_id in views {
+                doc.set_selection(view_id, selection.clone());
+            }
+        }
+        let views: Vec<_> = self.tree.traverse().map(|(view_id, _)| view_id).collect();
+        for view_id in views {
+            self.ensure_cursor_in_view(view_id);
+        }
+
+        missing
+    }
+
+    /// Recreates `layout` in place of `view_id`, which already shows the first document of
+    /// `layout`. The IDs of the created views are appended to `views` in layout order.
+    fn restore_layout(
+        &mut self,
+        layout: &SessionLayout,
+        view_id: ViewId,
+        doc_id: &impl Fn(usize) -> Option<DocumentId>,
+        views: &mut Vec<ViewId>,
+    ) {
+        let SessionLayout::Split {
+            direction,
+            children,
+        } = layout
+        else {
+            views.push(view_id);
+            return;
+        };
+
+        let action = match direction {
+            SplitDirection::Horizontal => Action::HorizontalSplit,
+            SplitDirection::Vertical => Action::VerticalSplit,
+        };
+        // Split the view once per child first so that every child has its own view, then
+        // recreate the layouts of the children within their views.
+        let mut child_views = vec![view_id];
+        for child in &children[1..] {
+            let Some(doc) = doc_id(child.views()[0]) else {
+                continue;
+            };
+            self.tree.focus = *child_views.last().unwrap();
+            self.switch(doc, action);
+            child_views.push(self.tree.focus);
+        }
+        for (child, &child_view) in children.iter().zip(&child_views) {
+            self.restore_layout(child, child_view, doc_id, views);
+        }
+    }
+
+    /// Saves the session of the current project if project sessions are enabled.
+    pub fn save_project_session(&self) {
+        if !self.config().project_sessions {
+            return;
+        }
+        let (project, _) = helix_loader::find_workspace();
+        let session = self.capture_session(project.clone());
+        if let Err(err) = helix_loader::session::save_session(&project, &session) {
+            log::error!("Failed to save session: {err}");
+        }
+    }
+
+    /// The saved session of the current project, if project sessions are enabled.
+    pub fn project_session(&self) -> Option<Session> {
+        if !self.config().project_sessions {
+            return None;
+        }
+        let (project, _) = helix_loader::find_workspace();
+        helix_loader::session::load_session(&project)
+    }
+
+    /// Restores `session`, the session of the current project from [Editor::project_session].
+    ///
+    /// The working directory of the session is restored if it is within the project. Sets a
+    /// status message listing the files of the session which no longer exist.
+    pub fn restore_project_session(&mut self, session: Session) {
+        if let Some(cwd) = session
+            .working_directory
+            .as_deref()
+            .filter(|cwd| cwd.starts_with(&session.project) && cwd.is_dir())
+        {
+            if let Err(err) = self.set_cwd(cwd) {
+                log::warn!(
+                    "Failed to restore working directory {}: {err}",
+                    cwd.display()
+                );
+            }
+        }
+        let missing = self.restore_session(session);
+        if !missing.is_empty() {
+            let cwd = helix_stdx::env::current_working_dir();
+            let paths: Vec<_> = missing
+                .iter()
+                .map(|path| display_path(path, &cwd))
+                .collect();
+            self.set_warning(format!(
+                "Skipped missing files from session: {}",
+                paths.join(", ")
+            ));
+        }
+    }
+
+    /// Remembers the focused file and its cursor position as the last file of the current
+    /// project, see [helix_loader::project_state].
+    pub fn save_project_last_file(&self) {
+        if !self.config().project_restore_last_file {
+            return;
+        }
+        let (view, doc) = current_ref!(self);
+        let Some(path) = doc.path() else {
+            return;
+        };
+        let text = doc.text().slice(..);
+        let position = coords_at_pos(text, doc.selection(view.id).primary().cursor(text));
+        let (project, _) = helix_loader::find_workspace();
+        if let Err(err) = helix_loader::project_state::save_last_file(
+            &project,
+            path,
+            position.row + 1,
+            position.col + 1,
+        ) {
+            log::error!("Failed to save the last file of the project: {err}");
+        }
+    }
+
+    /// Opens the last file of the current project in the focused view and moves the cursor to
+    /// where it was. Returns whether the file was opened, which is skipped if it no longer
+    /// exists.
+    pub fn restore_project_last_file(&mut self) -> bool {
+        if !self.config().project_restore_last_file {
+            return false;
+        }
+        let (project, _) = helix_loader::find_workspace();
+        let state = helix_loader::project_state::load_project_state(&project);
+        let Some(path) = state
+            .last_file
+            .map(|path| project.join(path))
+            .filter(|path| path.is_file())
+        else {
+            return false;
+        };
+        if let Err(err) = self.open(&path, Action::Replace) {
+            log::warn!("Failed to open {}: {err}", path.display());
+            return false;
+        }
+        let scrolloff = self.config().scrolloff;
+        let (view, doc) = current!(self);
+        if let Some((line, column)) = state.last_position {
+            // The file may have become shorter since it was left.
+            let position = Position::new(line.saturating_sub(1), column.saturating_sub(1));
+            let pos = pos_at_coords(doc.text().slice(..), position, true);
+            doc.set_selection(view.id, Selection::point(pos));
+        }
+        view.ensure_cursor_in_view_center(doc, scrolloff);
+        true
+    }
+}
+
+fn display_path(path: &Path, cwd: &Path) -> String {
+    path.strip_prefix(cwd)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Converts the saved selection of `document`, clamping it to the length of the text.
+fn restored_selection(document: &SessionDocument, len_chars: usize) -> Option<Selection> {
+    if document.selection.is_empty() {
+        return None;
+    }
+    let ranges = document
+        .selection
+        .iter()
+        .map(|range| Range::new(range.anchor.min(len_chars), range.head.min(len_chars)))
+        .collect();
+    let primary = document.primary.min(document.selection.len() - 1);
+    Some(Selection::new(ranges, primary))
+}
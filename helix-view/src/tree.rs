@@ -103,6 +103,84 @@ pub fn new(area: Rect) -> Self {
         }
     }
 
+    /// The root container of the active layout.
+    pub fn root(&self) -> ViewId {
+        self.root
+    }
+
+    /// Inserts a new, empty layout root container.
+    ///
+    /// Views of every layout share the same node storage so that view IDs are unique across
+    /// layouts, but only the active layout is laid out and traversed. Use [Self::set_root] to
+    /// activate the new layout.
+    pub fn insert_root(&mut self) -> ViewId {
+        let root = self.nodes.insert(Node::container(Layout::Vertical));
+        // root is it's own parent
+        self.nodes[root].parent = root;
+        root
+    }
+
+    /// Makes the layout rooted at `root` the active layout.
+    ///
+    /// `focus` is focused if it is a view within the layout, otherwise the first view of the
+    /// layout is focused.
+    pub fn set_root(&mut self, root: ViewId, focus: ViewId) {
+        self.root = root;
+        self.focus = self.layout_focus(root, focus);
+        self.recalculate();
+    }
+
+    /// Returns `focus` if it is a view within the layout rooted at `root`, otherwise the first
+    /// view of the layout. Returns `root` if the layout is empty.
+    pub fn layout_focus(&self, root: ViewId, focus: ViewId) -> ViewId {
+        if self.try_get(focus).is_some() && self.layout_root(focus) == root {
+            focus
+        } else {
+            self.traverse_layout(root).next().map_or(root, |(id, _)| id)
+        }
+    }
+
+    /// Removes an inactive layout root and all of the nodes inside of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `root` is the active layout.
+    pub fn remove_root(&mut self, root: ViewId) {
+        assert_ne!(root, self.root, "cannot remove the active layout");
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            if let Some(Node {
+                content: Content::Container(container),
+                ..
+            }) = self.nodes.remove(id)
+            {
+                stack.extend(container.children);
+            }
+        }
+    }
+
+    /// Finds the root container of the layout that contains the node `id`.
+    pub fn layout_root(&self, mut id: ViewId) -> ViewId {
+        loop {
+            let parent = self.nodes[id].parent;
+            if parent == id {
+                return id;
+            }
+            id = parent;
+        }
+    }
+
+    /// Returns `true` if the layout rooted at `root` has no views.
+    pub fn is_layout_empty(&self, root: ViewId) -> bool {
+        match &self.nodes[root] {
+            Node {
+                content: Content::Container(container),
+                ..
+            } => container.children.is_empty(),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn insert(&mut self, view: View) -> ViewId {
         let focus = self.focus;
         let parent = self.nodes[focus].parent;
@@ -214,6 +292,18 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
         node
     }
 
+    /// Returns the layout and children of the container `index`, or `None` if `index` is not a
+    /// container.
+    pub fn container(&self, index: ViewId) -> Option<(Layout, &[ViewId])> {
+        match self.nodes.get(index) {
+            Some(Node {
+                content: Content::Container(container),
+                ..
+            }) => Some((container.layout, &container.children)),
+            _ => None,
+        }
+    }
+
     /// Get a mutable reference to a [Container] by index.
     /// # Panics
     /// Panics if `index` is not in self.nodes, or if the node's content is not a [Content::Container].
@@ -254,7 +344,8 @@ pub fn remove(&mut self, index: ViewId) {
         }
 
         let parent = self.nodes[index].parent;
-        let parent_is_root = parent == self.root;
+        // The view may belong to an inactive layout so check for any root container.
+        let parent_is_root = self.nodes[parent].parent == parent;
 
         self.remove_or_replace(index, None);
 
@@ -269,6 +360,8 @@ pub fn remove(&mut self, index: ViewId) {
         self.recalculate()
     }
 
+    /// Iterates over all views of every layout. Use [Self::traverse] to iterate over the views of
+    /// the active layout.
     pub fn views(&self) -> impl Iterator<Item = (&View, bool)> {
         let focus = self.focus;
         self.nodes.iter().filter_map(move |(key, node)| match node {
@@ -334,13 +427,7 @@ pub fn contains(&self, index: ViewId) -> bool {
     }
 
     pub fn is_empty(&self) -> bool {
-        match &self.nodes[self.root] {
-            Node {
-                content: Content::Container(container),
-                ..
-            } => container.children.is_empty(),
-            _ => unreachable!(),
-        }
+        self.is_layout_empty(self.root)
     }
 
     pub fn resize(&mut self, area: Rect) -> bool {
@@ -442,7 +529,12 @@ pub fn recalculate(&mut self) {
     }
 
     pub fn traverse(&self) -> Traverse<'_> {
-        Traverse::new(self)
+        Traverse::new(self, self.root)
+    }
+
+    /// Traverses the views of the layout rooted at `root`, whether or not it is active.
+    pub fn traverse_layout(&self, root: ViewId) -> Traverse<'_> {
+        Traverse::new(self, root)
     }
 
     // Finds the split in the given direction if it exists
@@ -678,10 +770,10 @@ pub struct Traverse<'a> {
 }
 
 impl<'a> Traverse<'a> {
-    fn new(tree: &'a Tree) -> Self {
+    fn new(tree: &'a Tree, root: ViewId) -> Self {
         Self {
             tree,
-            stack: vec![tree.root],
+            stack: vec![root],
         }
     }
 }
@@ -966,4 +1058,49 @@ fn vsplit_gap_rounding() {
                 .collect::<Vec<_>>()
         );
     }
+
+    #[test]
+    fn layouts_have_distinct_views() {
+        let mut tree = Tree::new(Rect::new(0, 0, 180, 80));
+        let first_root = tree.root();
+        let l0 = tree.insert(View::new(DocumentId::default(), GutterConfig::default()));
+        let l1 = tree.split(
+            View::new(DocumentId::default(), GutterConfig::default()),
+            Layout::Vertical,
+        );
+
+        let second_root = tree.insert_root();
+        assert!(tree.is_layout_empty(second_root));
+        tree.set_root(second_root, l1);
+        assert!(tree.is_empty());
+        assert_eq!(tree.focus, second_root);
+
+        let r0 = tree.insert(View::new(DocumentId::default(), GutterConfig::default()));
+        assert_ne!(r0, l0);
+        assert_ne!(r0, l1);
+        assert_eq!(tree.layout_root(r0), second_root);
+        assert_eq!(tree.layout_root(l1), first_root);
+        assert_eq!(tree.traverse().map(|(id, _)| id).collect::<Vec<_>>(), [r0]);
+        assert_eq!(tree.views().count(), 3);
+
+        // Closing a view in an inactive layout keeps that layout's root intact.
+        tree.remove(l0);
+        assert_eq!(tree.focus, r0);
+        assert_eq!(
+            tree.traverse_layout(first_root)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            [l1]
+        );
+
+        // The previous focus is restored when it still exists in the layout.
+        tree.set_root(first_root, l0);
+        assert_eq!(tree.focus, l1);
+        assert_eq!(tree.get(l1).area, Rect::new(0, 0, 180, 80));
+
+        tree.remove_root(second_root);
+        assert!(!tree.contains(second_root));
+        assert!(!tree.contains(r0));
+        assert_eq!(tree.views().count(), 1);
+    }
 }
@@ -1,8 +1,8 @@
 use crate::{
     align_view,
-    annotations::diagnostics::InlineDiagnostics,
+    annotations::{code_lens::CodeLensLines, diagnostics::InlineDiagnostics},
     document::{DocumentColorSwatches, DocumentInlayHints},
-    editor::{GutterConfig, GutterType},
+    editor::{GutterConfig, GutterType, ZenModeConfig},
     graphics::Rect,
     handlers::diagnostics::DiagnosticsHandler,
     Align, Document, DocumentId, Theme, ViewId,
@@ -141,6 +141,9 @@ pub struct View {
     pub object_selections: Vec<Selection>,
     /// all gutter-related configuration settings, used primarily for gutter rendering
     pub gutters: GutterConfig,
+    /// The zen mode layout of the view while zen mode is active, see
+    /// [crate::Editor::update_zen_mode].
+    pub zen: Option<ZenModeConfig>,
     /// A mapping between documents and the last history revision the view was updated at.
     /// Changes between documents and views are synced lazily when switching windows. This
     /// mapping keeps track of the last applied history revision so that only new changes
@@ -177,6 +180,7 @@ pub fn new(doc: DocumentId, gutters: GutterConfig) -> Self {
             last_modified_docs: [None, None],
             object_selections: Vec::new(),
             gutters,
+            zen: None,
             doc_revisions: HashMap::new(),
             diagnostics_handler: DiagnosticsHandler::new(),
         }
@@ -190,25 +194,51 @@ pub fn add_to_history(&mut self, id: DocumentId) {
     }
 
     pub fn inner_area(&self, doc: &Document) -> Rect {
-        self.area.clip_left(self.gutter_offset(doc)).clip_bottom(1) // -1 for statusline
+        let padding = self.zen_padding();
+        self.area
+            .clip_left(self.gutter_offset(doc) + padding)
+            .clip_right(padding)
+            .clip_bottom(self.statusline_height())
     }
 
     pub fn inner_height(&self) -> usize {
-        self.area.clip_bottom(1).height.into() // -1 for statusline
+        self.area
+            .clip_bottom(self.statusline_height())
+            .height
+            .into()
     }
 
     pub fn inner_width(&self, doc: &Document) -> u16 {
-        self.area.clip_left(self.gutter_offset(doc)).width
+        self.inner_area(doc).width
     }
 
+    /// The height of the statusline, which zen mode may hide.
+    pub fn statusline_height(&self) -> u16 {
+        match &self.zen {
+            Some(zen) if zen.hide_statusline => 0,
+            _ => 1,
+        }
+    }
+
+    /// The columns left empty on both sides of the text in zen mode, leaving at least one column
+    /// for the text.
+    fn zen_padding(&self) -> u16 {
+        let padding = self.zen.as_ref().map_or(0, |zen| zen.padding);
+        let max = self.area.width.saturating_sub(1) / 2;
+        u16::try_from(padding).unwrap_or(u16::MAX).min(max)
+    }
+
+    /// The gutters of the view, none in zen mode.
     pub fn gutters(&self) -> &[GutterType] {
+        if self.zen.is_some() {
+            return &[];
+        }
         &self.gutters.layout
     }
 
     pub fn gutter_offset(&self, doc: &Document) -> u16 {
         let total_width = self
-            .gutters
-            .layout
+            .gutters()
             .iter()
             .map(|gutter| gutter.width(self, doc) as u16)
             .sum();
@@ -456,7 +486,7 @@ pub fn text_annotations<'a>(
             other_inlay_hints,
             padding_before_inlay_hints,
             padding_after_inlay_hints,
-        }) = doc.inlay_hints.get(&self.id)
+        }) = doc.inlay_hints.get(&self.id).filter(|_| self.zen.is_none())
         {
             let type_style = theme.and_then(|t| t.find_highlight("ui.virtual.inlay-hint.type"));
             let parameter_style =
@@ -510,6 +540,10 @@ pub fn text_annotations<'a>(
             ));
         }
 
+        if doc.config.load().lsp.code_lens && !doc.code_lenses.is_empty() {
+            text_annotations.add_line_annotation(CodeLensLines::new(doc));
+        }
+
         text_annotations
     }
 
@@ -934,6 +968,44 @@ fn test_text_pos_at_screen_coords_without_any_gutters() {
         );
     }
 
+    #[test]
+    fn test_zen_mode_layout() {
+        let mut view = View::new(DocumentId::default(), GutterConfig::default());
+        view.area = Rect::new(40, 40, 40, 40);
+        let doc = Document::from(
+            Rope::from_str("abc\n\tdef"),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+            Arc::new(ArcSwap::from_pointee(syntax::Loader::default())),
+        );
+        assert_eq!(
+            view.inner_area(&doc),
+            Rect::new(
+                40 + DEFAULT_GUTTER_OFFSET,
+                40,
+                40 - DEFAULT_GUTTER_OFFSET,
+                39
+            )
+        );
+
+        view.zen = Some(ZenModeConfig {
+            padding: 5,
+            hide_statusline: true,
+        });
+        assert!(view.gutters().is_empty());
+        assert_eq!(view.inner_area(&doc), Rect::new(45, 40, 30, 40));
+        assert_eq!(view.inner_height(), 40);
+        assert_eq!(view.inner_width(&doc), 30);
+
+        // The padding leaves at least one column for the text.
+        view.zen = Some(ZenModeConfig {
+            padding: 100,
+            hide_statusline: false,
+        });
+        assert_eq!(view.inner_area(&doc), Rect::new(59, 40, 2, 39));
+        assert_eq!(view.inner_height(), 39);
+    }
+
     #[test]
     fn test_text_pos_at_screen_coords_cjk() {
         let mut view = View::new(DocumentId::default(), GutterConfig::default());
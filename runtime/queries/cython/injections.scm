@@ -8,11 +8,5 @@
   (#eq? @_re "re")
   (#set! injection.language "regex"))
 
-((binary_operator
-  left: (string
-    (string_content) @injection.content)
-  operator: "%")
-  (#set! injection.language "printf"))
-
 ((comment) @injection.content
   (#set! injection.language "comment"))
@@ -24,3 +24,21 @@
 
 (macro_definition
   name: (identifier) @definition.macro)
+
+; Scopes shown in the statusline breadcrumb
+
+[
+  (mod_item)
+  (impl_item)
+  (trait_item)
+  (struct_item)
+  (enum_item)
+  (union_item)
+  (function_item)
+  (closure_expression)
+  (if_expression)
+  (match_expression)
+  (for_expression)
+  (while_expression)
+  (loop_expression)
+] @scope.symbol